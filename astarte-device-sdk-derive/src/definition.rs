@@ -0,0 +1,571 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Derives the `AstarteInterface` trait, generating the interface definition from a struct.
+
+use std::str::FromStr;
+
+use astarte_interfaces::Interface;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use serde_json::{json, Map, Value};
+use syn::{
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+    Expr, GenericArgument, Generics, PathArguments, Type,
+};
+
+use crate::{
+    case::RenameRule, parse_attribute_list, parse_bool_lit, parse_name_value_attrs, parse_str_lit,
+};
+
+/// Handle for the `#[astarte_interface(..)]` attribute on the struct.
+///
+/// ### Example
+///
+/// ```no_compile
+/// #[derive(AstarteInterface)]
+/// #[astarte_interface(name = "com.example.Sensor", major = 1, minor = 0, path = "/sensor")]
+/// struct Sensor {
+///     temperature: f64,
+/// }
+/// ```
+#[derive(Debug, Default)]
+struct InterfaceAttrs {
+    name: Option<String>,
+    major: Option<i32>,
+    minor: Option<i32>,
+    interface_type: Option<String>,
+    ownership: Option<String>,
+    aggregation: Option<String>,
+    path: Option<String>,
+    rename_all: Option<RenameRule>,
+    reliability: Option<String>,
+    retention: Option<String>,
+    expiry: Option<u64>,
+    explicit_timestamp: Option<bool>,
+}
+
+impl InterfaceAttrs {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            name: other.name.or(self.name),
+            major: other.major.or(self.major),
+            minor: other.minor.or(self.minor),
+            interface_type: other.interface_type.or(self.interface_type),
+            ownership: other.ownership.or(self.ownership),
+            aggregation: other.aggregation.or(self.aggregation),
+            path: other.path.or(self.path),
+            rename_all: other.rename_all.or(self.rename_all),
+            reliability: other.reliability.or(self.reliability),
+            retention: other.retention.or(self.retention),
+            expiry: other.expiry.or(self.expiry),
+            explicit_timestamp: other.explicit_timestamp.or(self.explicit_timestamp),
+        }
+    }
+
+    /// Returns true if an option only valid for datastreams is set.
+    fn has_datastream_options(&self) -> bool {
+        self.reliability.is_some()
+            || self.retention.is_some()
+            || self.expiry.is_some()
+            || self.explicit_timestamp.is_some()
+    }
+}
+
+impl Parse for InterfaceAttrs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = parse_name_value_attrs(input)?;
+
+        let mut str_attr = |name: &str| {
+            attrs
+                .remove(name)
+                .map(|expr| parse_str_lit(&expr))
+                .transpose()
+        };
+
+        let name = str_attr("name")?;
+        let interface_type = str_attr("interface_type")?;
+        let ownership = str_attr("ownership")?;
+        let aggregation = str_attr("aggregation")?;
+        let path = str_attr("path")?;
+        let reliability = str_attr("reliability")?;
+        let retention = str_attr("retention")?;
+
+        let major = attrs
+            .remove("major")
+            .map(|expr| parse_int_lit(&expr))
+            .transpose()?;
+        let minor = attrs
+            .remove("minor")
+            .map(|expr| parse_int_lit(&expr))
+            .transpose()?;
+        let expiry = attrs
+            .remove("expiry")
+            .map(|expr| parse_int_lit(&expr))
+            .transpose()?;
+
+        let explicit_timestamp = attrs
+            .remove("explicit_timestamp")
+            .map(|expr| parse_bool_lit(&expr))
+            .transpose()?;
+
+        let rename_all = attrs
+            .remove("rename_all")
+            .map(|expr| {
+                parse_str_lit(&expr).and_then(|rename| {
+                    RenameRule::from_str(&rename)
+                        .map_err(|_| syn::Error::new(expr.span(), "invalid rename rule"))
+                })
+            })
+            .transpose()?;
+
+        if let Some((_, expr)) = attrs.iter().next() {
+            return Err(syn::Error::new(expr.span(), "unrecognized attribute"));
+        }
+
+        Ok(Self {
+            name,
+            major,
+            minor,
+            interface_type,
+            ownership,
+            aggregation,
+            path,
+            rename_all,
+            reliability,
+            retention,
+            expiry,
+            explicit_timestamp,
+        })
+    }
+}
+
+/// Handle for the `#[astarte_interface(..)]` attribute on the fields.
+///
+/// ### Example
+///
+/// ```no_compile
+/// #[derive(AstarteInterface)]
+/// #[astarte_interface(name = "com.example.Sensor", major = 1)]
+/// struct Sensor {
+///     #[astarte_interface(rename = "temp", mapping_type = "double")]
+///     temperature: Celsius,
+/// }
+/// ```
+#[derive(Debug, Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+    mapping_type: Option<String>,
+}
+
+impl FieldAttrs {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            rename: other.rename.or(self.rename),
+            mapping_type: other.mapping_type.or(self.mapping_type),
+        }
+    }
+}
+
+impl Parse for FieldAttrs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = parse_name_value_attrs(input)?;
+
+        let rename = attrs
+            .remove("rename")
+            .map(|expr| parse_str_lit(&expr))
+            .transpose()?;
+        let mapping_type = attrs
+            .remove("mapping_type")
+            .map(|expr| parse_str_lit(&expr))
+            .transpose()?;
+
+        if let Some((_, expr)) = attrs.iter().next() {
+            return Err(syn::Error::new(expr.span(), "unrecognized attribute"));
+        }
+
+        Ok(Self {
+            rename,
+            mapping_type,
+        })
+    }
+}
+
+/// Parses a [`syn::Lit::Int`] into a number.
+fn parse_int_lit<N>(expr: &Expr) -> syn::Result<N>
+where
+    N: FromStr,
+    N::Err: std::fmt::Display,
+{
+    match expr {
+        Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => lit.base10_parse(),
+        _ => Err(syn::Error::new(
+            expr.span(),
+            "expression must be an integer literal",
+        )),
+    }
+}
+
+/// Field of the struct, mapped to an endpoint of the interface.
+struct MappingField {
+    endpoint: String,
+    mapping_type: String,
+    optional: bool,
+    span: proc_macro2::Span,
+}
+
+/// Handle for the `#[derive(AstarteInterface)]` derive macro.
+pub(crate) struct InterfaceDerive {
+    name: Ident,
+    generics: Generics,
+    interface_name: String,
+    json: String,
+}
+
+impl InterfaceDerive {
+    pub(crate) fn quote(&self) -> TokenStream {
+        let name = &self.name;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let interface_name = &self.interface_name;
+        let json = &self.json;
+
+        quote! {
+            impl #impl_generics astarte_device_sdk::introspection::AstarteInterface for #name #ty_generics #where_clause {
+                const INTERFACE_NAME: &'static str = #interface_name;
+                const INTERFACE_JSON: &'static str = #json;
+            }
+        }
+    }
+
+    /// Builds the JSON definition of the interface.
+    fn definition(attrs: &InterfaceAttrs, fields: &[MappingField]) -> Result<Value, String> {
+        let properties = match attrs.interface_type.as_deref() {
+            None | Some("datastream") => false,
+            Some("properties") => true,
+            Some(other) => {
+                return Err(format!(
+                    "invalid interface_type {other}, expected datastream or properties"
+                ))
+            }
+        };
+
+        if properties && attrs.has_datastream_options() {
+            return Err(
+                "reliability, retention, expiry and explicit_timestamp are only valid for datastreams"
+                    .to_string(),
+            );
+        }
+
+        let mappings = fields
+            .iter()
+            .map(|field| {
+                let mut mapping = Map::new();
+                mapping.insert("endpoint".to_string(), json!(field.endpoint));
+                mapping.insert("type".to_string(), json!(field.mapping_type));
+
+                if let Some(reliability) = &attrs.reliability {
+                    mapping.insert("reliability".to_string(), json!(reliability));
+                }
+                if let Some(retention) = &attrs.retention {
+                    mapping.insert("retention".to_string(), json!(retention));
+                }
+                if let Some(expiry) = attrs.expiry {
+                    mapping.insert("expiry".to_string(), json!(expiry));
+                }
+                if let Some(explicit_timestamp) = attrs.explicit_timestamp {
+                    mapping.insert("explicit_timestamp".to_string(), json!(explicit_timestamp));
+                }
+                if properties && field.optional {
+                    mapping.insert("allow_unset".to_string(), json!(true));
+                }
+
+                Value::Object(mapping)
+            })
+            .collect::<Vec<_>>();
+
+        let mut interface = Map::new();
+        interface.insert("interface_name".to_string(), json!(attrs.name));
+        interface.insert("version_major".to_string(), json!(attrs.major.unwrap_or(0)));
+        interface.insert("version_minor".to_string(), json!(attrs.minor.unwrap_or(0)));
+        interface.insert(
+            "type".to_string(),
+            json!(attrs.interface_type.as_deref().unwrap_or("datastream")),
+        );
+        interface.insert(
+            "ownership".to_string(),
+            json!(attrs.ownership.as_deref().unwrap_or("device")),
+        );
+        if let Some(aggregation) = &attrs.aggregation {
+            interface.insert("aggregation".to_string(), json!(aggregation));
+        }
+        interface.insert("mappings".to_string(), Value::Array(mappings));
+
+        Ok(Value::Object(interface))
+    }
+
+    /// Returns the mapping type of a field, and if it's wrapped in an [`Option`].
+    fn mapping_type(ty: &Type) -> Option<(&'static str, bool)> {
+        if let Some(inner) = generic_arg(ty, "Option") {
+            return Self::mapping_type(inner)
+                .filter(|(_, optional)| !optional)
+                .map(|(mapping_type, _)| (mapping_type, true));
+        }
+
+        if let Some(inner) = generic_arg(ty, "Vec") {
+            let mapping_type = match type_name(inner)?.as_str() {
+                "u8" => "binaryblob",
+                "f64" => "doublearray",
+                "i32" => "integerarray",
+                "bool" => "booleanarray",
+                "i64" => "longintegerarray",
+                "String" => "stringarray",
+                "DateTime" => "datetimearray",
+                "Vec" if generic_arg(inner, "Vec").and_then(type_name).as_deref() == Some("u8") => {
+                    "binaryblobarray"
+                }
+                _ => return None,
+            };
+
+            return Some((mapping_type, false));
+        }
+
+        let mapping_type = match type_name(ty)?.as_str() {
+            "f64" => "double",
+            "i32" => "integer",
+            "bool" => "boolean",
+            "i64" => "longinteger",
+            "String" => "string",
+            "DateTime" => "datetime",
+            _ => return None,
+        };
+
+        Some((mapping_type, false))
+    }
+}
+
+/// Returns the name of the last segment of a type path.
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the generic argument of a type with the given name, like `Vec<T>`.
+pub(crate) fn generic_arg<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let segment = path.path.segments.last().filter(|s| s.ident == name)?;
+
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+impl Parse for InterfaceDerive {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ast = syn::DeriveInput::parse(input)?;
+
+        let attrs = ast
+            .attrs
+            .iter()
+            .filter_map(|a| parse_attribute_list::<InterfaceAttrs>(a, "astarte_interface"))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .reduce(|first, second| first.merge(second))
+            .unwrap_or_default();
+
+        let Some(interface_name) = attrs.name.clone() else {
+            return Err(syn::Error::new(
+                ast.ident.span(),
+                r#"missing the interface name, add `#[astarte_interface(name = "...")]`"#,
+            ));
+        };
+
+        let syn::Data::Struct(ref st) = ast.data else {
+            return Err(syn::Error::new(ast.span(), "a named struct is required"));
+        };
+        let syn::Fields::Named(ref fields_named) = st.fields else {
+            return Err(syn::Error::new(ast.span(), "a named struct is required"));
+        };
+
+        let rename_rule = attrs.rename_all.unwrap_or_default();
+        let path = attrs.path.as_deref().unwrap_or_default();
+        let properties = attrs.interface_type.as_deref() == Some("properties");
+
+        let fields = fields_named
+            .named
+            .iter()
+            .map(|field| {
+                let field_attrs = field
+                    .attrs
+                    .iter()
+                    .filter_map(|a| parse_attribute_list::<FieldAttrs>(a, "astarte_interface"))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .into_iter()
+                    .reduce(|first, second| first.merge(second))
+                    .unwrap_or_default();
+
+                let ident = field
+                    .ident
+                    .as_ref()
+                    .ok_or_else(|| syn::Error::new(field.span(), "field is not an ident"))?;
+
+                let name = field_attrs.rename.unwrap_or_else(|| {
+                    let name = ident.to_string();
+                    let name = name.strip_prefix("r#").unwrap_or(&name);
+
+                    rename_rule.apply_to_field(name).into_owned()
+                });
+
+                let inferred = Self::mapping_type(&field.ty);
+                let optional = inferred.is_some_and(|(_, optional)| optional);
+                let mapping_type = match (field_attrs.mapping_type, inferred) {
+                    (Some(mapping_type), _) => mapping_type,
+                    (None, Some((mapping_type, _))) => mapping_type.to_string(),
+                    (None, None) => {
+                        return Err(syn::Error::new(
+                            field.ty.span(),
+                            r#"couldn't infer the mapping type, add `#[astarte_interface(mapping_type = "...")]`"#,
+                        ));
+                    }
+                };
+
+                if optional && !properties {
+                    return Err(syn::Error::new(
+                        field.ty.span(),
+                        r#"optional fields are only allowed with `interface_type = "properties"`"#,
+                    ));
+                }
+
+                Ok(MappingField {
+                    endpoint: format!("{path}/{name}"),
+                    mapping_type,
+                    optional,
+                    span: field.span(),
+                })
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        if let Some(field) = fields.iter().find(|field| field.endpoint.ends_with('/')) {
+            return Err(syn::Error::new(
+                field.span,
+                "the endpoint must not be empty",
+            ));
+        }
+
+        let json = Self::definition(&attrs, &fields)
+            .map_err(|err| syn::Error::new(ast.ident.span(), err))?;
+
+        let interface = Interface::from_str(&json.to_string()).map_err(|err| {
+            syn::Error::new(ast.ident.span(), format!("invalid interface: {err}"))
+        })?;
+
+        let json = serde_json::to_string_pretty(&interface).map_err(|err| {
+            syn::Error::new(
+                ast.ident.span(),
+                format!("couldn't serialize the interface: {err}"),
+            )
+        })?;
+
+        Ok(Self {
+            name: ast.ident,
+            generics: ast.generics,
+            interface_name,
+            json,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    #[test]
+    fn should_infer_mapping_types() {
+        let cases: [(Type, Option<(&str, bool)>); 7] = [
+            (parse_quote!(f64), Some(("double", false))),
+            (parse_quote!(std::string::String), Some(("string", false))),
+            (parse_quote!(Vec<u8>), Some(("binaryblob", false))),
+            (parse_quote!(Vec<Vec<u8>>), Some(("binaryblobarray", false))),
+            (
+                parse_quote!(Option<chrono::DateTime<chrono::Utc>>),
+                Some(("datetime", true)),
+            ),
+            (parse_quote!(Option<Option<i32>>), None),
+            (parse_quote!(u32), None),
+        ];
+
+        for (ty, exp) in cases {
+            assert_eq!(InterfaceDerive::mapping_type(&ty), exp);
+        }
+    }
+
+    #[test]
+    fn should_generate_a_valid_interface() {
+        let derive: InterfaceDerive = parse_quote! {
+            #[astarte_interface(name = "com.example.Sensor", major = 1, aggregation = "object")]
+            #[astarte_interface(path = "/%{sensor_id}", reliability = "guaranteed", explicit_timestamp = true)]
+            struct Sensor {
+                temperature: f64,
+                #[astarte_interface(rename = "label")]
+                name: String,
+            }
+        };
+
+        let interface = Interface::from_str(&derive.json).unwrap();
+
+        assert_eq!(interface.interface_name(), "com.example.Sensor");
+        assert_eq!(interface.version_major(), 1);
+        assert_eq!(interface.version_minor(), 0);
+        assert!(interface.is_datastream_object());
+        assert!(derive.json.contains(r#""endpoint": "/%{sensor_id}/label""#));
+    }
+
+    #[test]
+    fn should_reject_invalid_interfaces() {
+        let res = syn::parse2::<InterfaceDerive>(quote! {
+            #[astarte_interface(name = "com.example.Sensor")]
+            struct Sensor {
+                temperature: f64,
+            }
+        });
+        assert!(res.is_err(), "the version 0.0 is invalid");
+
+        let res = syn::parse2::<InterfaceDerive>(quote! {
+            #[astarte_interface(name = "com.example.Sensor", major = 1)]
+            struct Sensor {
+                temperature: Option<f64>,
+            }
+        });
+        assert!(res.is_err(), "optional field on a datastream");
+    }
+}
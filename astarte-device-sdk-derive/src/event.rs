@@ -31,7 +31,6 @@ use syn::{
 
 use crate::{
     case::RenameRule, parse_attribute_list, parse_bool_lit, parse_name_value_attrs, parse_str_lit,
-    parse_struct_fields,
 };
 
 #[derive(Debug, Default)]
@@ -180,13 +179,22 @@ impl FromEventDerive {
             FromEventAggregation::Individual { variants } => self.quote_indv(variants),
             FromEventAggregation::Object { fields, path } => self.quote_obj(path, fields),
             FromEventAggregation::Property { variants } => self.quote_property(variants),
+            FromEventAggregation::Dispatch { variants } => self.quote_dispatch(variants),
         }
     }
 
-    pub(crate) fn quote_obj(&self, path: &str, fields: &[Ident]) -> proc_macro2::TokenStream {
+    pub(crate) fn quote_obj(&self, path: &str, fields: &[ObjectField]) -> proc_macro2::TokenStream {
         let rename_rule = self.rename_rule.unwrap_or_default();
         let (impl_generics, ty_generics, where_clause) = &self.generics.split_for_impl();
-        let fields_val = fields.iter().map(|i| {
+        let fields_val = fields.iter().map(|field| {
+            let i = &field.ident;
+
+            if let Some(param) = &field.path_param {
+                return quote_spanned! {i.span() =>
+                    let #i = params.parse(#param)?;
+                };
+            }
+
             let name = i.to_string();
             let name = rename_rule.apply_to_field(&name);
             quote_spanned! {i.span() =>
@@ -200,7 +208,12 @@ impl FromEventDerive {
                     .try_into()?;
             }
         });
-        let fields = fields.iter();
+        let params = fields.iter().any(|field| field.path_param.is_some()).then(|| {
+            quote! {
+                let params = astarte_device_sdk::event::PathParams::new(&endpoint, &event.path)?;
+            }
+        });
+        let fields = fields.iter().map(|field| &field.ident);
         let interface = &self.interface;
         let name = &self.name;
 
@@ -208,6 +221,10 @@ impl FromEventDerive {
             impl #impl_generics astarte_device_sdk::FromEvent for #name #ty_generics #where_clause {
                 type Err = astarte_device_sdk::event::FromEventError;
 
+                fn interface_name() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some(#interface)
+                }
+
                 fn from_event(event: astarte_device_sdk::DeviceEvent) -> ::std::result::Result<Self, Self::Err> {
                     use astarte_device_sdk::Value;
                     use astarte_device_sdk::error::{AggregationError, InterfaceTypeError};
@@ -253,6 +270,8 @@ impl FromEventDerive {
                         },
                     };
 
+                    #params
+
                     #(#fields_val)*
 
                     Ok(Self{#(#fields),*})
@@ -322,6 +341,10 @@ impl FromEventDerive {
             impl #impl_generics astarte_device_sdk::FromEvent for #name #ty_generics #where_clause {
                 type Err = astarte_device_sdk::event::FromEventError;
 
+                fn interface_name() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some(#interface)
+                }
+
                 fn from_event(event: astarte_device_sdk::DeviceEvent) -> ::std::result::Result<Self, Self::Err> {
                     use astarte_device_sdk::{AstarteData, Value};
                     use astarte_device_sdk::error::{AggregationError, InterfaceTypeError};
@@ -420,6 +443,10 @@ impl FromEventDerive {
             impl #impl_generics astarte_device_sdk::FromEvent for #name #ty_generics #where_clause {
                 type Err = astarte_device_sdk::event::FromEventError;
 
+                fn interface_name() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some(#interface)
+                }
+
                 fn from_event(event: astarte_device_sdk::DeviceEvent) -> ::std::result::Result<Self, Self::Err> {
                     use astarte_device_sdk::{AstarteData, Value};
                     use astarte_device_sdk::error::{AggregationError, InterfaceTypeError};
@@ -453,6 +480,214 @@ impl FromEventDerive {
             }
         }
     }
+
+    fn quote_dispatch(&self, variants: &[DispatchVariant]) -> proc_macro2::TokenStream {
+        let (impl_generics, ty_generics, where_clause) = &self.generics.split_for_impl();
+
+        let name = &self.name;
+
+        let mut interfaces: Vec<&str> = Vec::new();
+        for variant in variants {
+            if let DispatchKind::Mapping { interface, .. } = &variant.kind {
+                if !interfaces.contains(&interface.as_str()) {
+                    interfaces.push(interface);
+                }
+            }
+        }
+
+        let arms = variants.iter().filter_map(|v| {
+            let DispatchKind::Mapping {
+                interface,
+                path,
+                kind,
+            } = &v.kind
+            else {
+                return None;
+            };
+
+            let variant = &v.name;
+
+            let convert = match kind {
+                MappingKind::Individual => quote! {
+                    return match event.data {
+                        Value::Individual{data, ..} => {
+                            data.try_into().map(#name::#variant).map_err(FromEventError::from)
+                        },
+                        Value::Object{..} => {
+                            Err(FromEventError::Aggregation(AggregationError::new(
+                                event.interface,
+                                event.path,
+                                Aggregation::Individual,
+                                Aggregation::Object,
+                            )))
+                        },
+                        Value::Property(_) => {
+                            Err(FromEventError::InterfaceType(InterfaceTypeError::with_path(
+                                event.interface,
+                                event.path,
+                                InterfaceType::Datastream,
+                                InterfaceType::Properties,
+                            )))
+                        },
+                    };
+                },
+                MappingKind::Property { allow_unset } => {
+                    let (set, unset) = if *allow_unset {
+                        (
+                            quote! { Some(value) },
+                            quote! { Ok(#name::#variant(None)) },
+                        )
+                    } else {
+                        (
+                            quote! { value },
+                            quote! {
+                                Err(FromEventError::Unset {
+                                    interface: #interface,
+                                    endpoint: event.path,
+                                })
+                            },
+                        )
+                    };
+
+                    quote! {
+                        return match event.data {
+                            Value::Individual{..} | Value::Object{..} => {
+                                Err(FromEventError::InterfaceType(InterfaceTypeError::with_path(
+                                    event.interface,
+                                    event.path,
+                                    InterfaceType::Properties,
+                                    InterfaceType::Datastream,
+                                )))
+                            },
+                            Value::Property(Some(prop)) => {
+                                prop.try_into()
+                                    .map(|value| #name::#variant(#set))
+                                    .map_err(FromEventError::from)
+                            },
+                            Value::Property(None) => #unset,
+                        };
+                    }
+                }
+                MappingKind::Object { fields, rename_rule } => {
+                    let fields_val = fields.iter().map(|field| {
+                        let i = &field.ident;
+
+                        if let Some(param) = &field.path_param {
+                            return quote_spanned! {i.span() =>
+                                let #i = params.parse(#param)?;
+                            };
+                        }
+
+                        let name = i.to_string();
+                        let name = rename_rule.apply_to_field(&name);
+                        quote_spanned! {i.span() =>
+                            let #i = object
+                                .remove(#name)
+                                .ok_or(FromEventError::MissingField {
+                                    interface: #interface,
+                                    base_path: #path,
+                                    path: #name,
+                                })?
+                                .try_into()?;
+                        }
+                    });
+                    let params = fields.iter().any(|field| field.path_param.is_some()).then(|| {
+                        quote! {
+                            let params = astarte_device_sdk::event::PathParams::new(&endpoint, &event.path)?;
+                        }
+                    });
+                    let fields = fields.iter().map(|field| &field.ident);
+
+                    quote! {
+                        let mut object = match event.data {
+                            Value::Object{data, ..} => data,
+                            Value::Individual{..} => {
+                                return Err(FromEventError::Aggregation(AggregationError::new(
+                                    event.interface,
+                                    event.path,
+                                    Aggregation::Object,
+                                    Aggregation::Individual,
+                                )));
+                            },
+                            Value::Property(_) => {
+                                return Err(FromEventError::InterfaceType(InterfaceTypeError::with_path(
+                                    event.interface,
+                                    event.path,
+                                    InterfaceType::Datastream,
+                                    InterfaceType::Properties,
+                                )));
+                            },
+                        };
+
+                        #params
+
+                        #(#fields_val)*
+
+                        return Ok(#name::#variant { #(#fields),* });
+                    }
+                }
+            };
+
+            Some(quote! {
+                if event.interface == #interface {
+                    let endpoint: Endpoint<&str> = Endpoint::try_from(#path)?;
+
+                    if endpoint.eq_mapping(&path) {
+                        #convert
+                    }
+                }
+            })
+        });
+
+        let fallback = match variants
+            .iter()
+            .find(|v| matches!(v.kind, DispatchKind::Fallback))
+        {
+            Some(v) => {
+                let variant = &v.name;
+
+                quote! { Ok(#name::#variant(event)) }
+            }
+            None => quote! {
+                match INTERFACES.iter().copied().find(|interface| *interface == event.interface) {
+                    Some(interface) => Err(FromEventError::Path {
+                        interface,
+                        base_path: event.path,
+                    }),
+                    None => Err(FromEventError::Interface(event.interface)),
+                }
+            },
+        };
+
+        quote! {
+            impl #impl_generics astarte_device_sdk::FromEvent for #name #ty_generics #where_clause {
+                type Err = astarte_device_sdk::event::FromEventError;
+
+                fn interface_name() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::None
+                }
+
+                #[allow(unused_imports)]
+                fn from_event(event: astarte_device_sdk::DeviceEvent) -> ::std::result::Result<Self, Self::Err> {
+                    use astarte_device_sdk::Value;
+                    use astarte_device_sdk::error::{AggregationError, InterfaceTypeError};
+                    use astarte_device_sdk::event::FromEventError;
+                    use astarte_device_sdk::astarte_interfaces::MappingPath;
+                    use astarte_device_sdk::astarte_interfaces::mapping::endpoint::Endpoint;
+                    use astarte_device_sdk::astarte_interfaces::schema::{Aggregation, InterfaceType};
+
+                    #[allow(dead_code)]
+                    const INTERFACES: &[&str] = &[ #(#interfaces),* ];
+
+                    let path = MappingPath::try_from(event.path.as_str())?;
+
+                    #(#arms)*
+
+                    #fallback
+                }
+            }
+        }
+    }
 }
 
 impl Parse for FromEventDerive {
@@ -466,13 +701,41 @@ impl Parse for FromEventDerive {
             .filter_map(|a| parse_attribute_list::<FromEventAttrs>(a, "from_event"))
             .collect::<Result<Vec<_>, _>>()?
             .into_iter()
-            .reduce(|first, other| first.merge(other))
-            .ok_or_else(|| {
-                syn::Error::new(
+            .reduce(|first, other| first.merge(other));
+
+        // An enum without the interface dispatches the events of the interfaces of its variants
+        if matches!(ast.data, syn::Data::Enum(_))
+            && attrs
+                .as_ref()
+                .map_or(true, |attrs| attrs.interface.is_none())
+        {
+            let attrs = attrs.unwrap_or_default();
+
+            if attrs.path.is_some() || attrs.aggregation.is_some() || attrs.interface_type.is_some()
+            {
+                return Err(syn::Error::new(
                     ast.span(),
-                    r#"missing attributes #[from_event(interface = "..", ...)]"#,
-                )
-            })?;
+                    "the path, aggregation and interface_type must be set on the variants",
+                ));
+            }
+
+            let variants = DispatchVariant::parse_variants(&ast, attrs.rename_rule)?;
+
+            return Ok(Self {
+                interface: String::new(),
+                rename_rule: attrs.rename_rule,
+                name: ast.ident,
+                generics: ast.generics,
+                inner: FromEventAggregation::Dispatch { variants },
+            });
+        }
+
+        let attrs = attrs.ok_or_else(|| {
+            syn::Error::new(
+                ast.span(),
+                r#"missing attributes #[from_event(interface = "..", ...)]"#,
+            )
+        })?;
 
         let interface = attrs.interface.ok_or_else(|| {
             syn::Error::new(
@@ -497,7 +760,7 @@ impl Parse for FromEventDerive {
                     )
                 })?;
 
-                let fields = parse_struct_fields(&ast)?;
+                let fields = ObjectField::parse_fields(&ast)?;
 
                 FromEventAggregation::Object { fields, path }
             }
@@ -527,9 +790,19 @@ impl Parse for FromEventDerive {
 }
 
 enum FromEventAggregation {
-    Individual { variants: Vec<IndividualMapping> },
-    Object { fields: Vec<Ident>, path: String },
-    Property { variants: Vec<IndividualMapping> },
+    Individual {
+        variants: Vec<IndividualMapping>,
+    },
+    Object {
+        fields: Vec<ObjectField>,
+        path: String,
+    },
+    Property {
+        variants: Vec<IndividualMapping>,
+    },
+    Dispatch {
+        variants: Vec<DispatchVariant>,
+    },
 }
 
 impl FromEventAggregation {
@@ -546,6 +819,78 @@ impl FromEventAggregation {
     }
 }
 
+/// Field of a struct for an object interface to derive FromEvent.
+#[derive(Debug)]
+pub(crate) struct ObjectField {
+    ident: Ident,
+    /// Name of the path parameter to bind the field to, instead of an object field.
+    path_param: Option<String>,
+}
+
+impl ObjectField {
+    /// Parses the fields of the struct
+    fn parse_fields(ast: &syn::DeriveInput) -> syn::Result<Vec<Self>> {
+        let syn::Data::Struct(ref st) = ast.data else {
+            return Err(syn::Error::new(ast.span(), "a named struct is required"));
+        };
+        let syn::Fields::Named(ref fields_named) = st.fields else {
+            return Err(syn::Error::new(ast.span(), "a named struct is required"));
+        };
+
+        Self::parse_named(fields_named)
+    }
+
+    /// Parses the named fields of a struct or enum variant
+    fn parse_named(fields_named: &syn::FieldsNamed) -> syn::Result<Vec<Self>> {
+        fields_named
+            .named
+            .iter()
+            .map(|field| {
+                let ident = field
+                    .ident
+                    .clone()
+                    .ok_or_else(|| syn::Error::new(field.span(), "field is not an ident"))?;
+
+                let attrs = field
+                    .attrs
+                    .iter()
+                    .filter_map(|attr| parse_attribute_list::<FieldAttr>(attr, "from_event"))
+                    .next_back()
+                    .transpose()?;
+
+                Ok(Self {
+                    ident,
+                    path_param: attrs.and_then(|attrs| attrs.path_param),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Attributes for a field of an object.
+#[derive(Debug)]
+struct FieldAttr {
+    /// Name of the path parameter in the object path.
+    path_param: Option<String>,
+}
+
+impl Parse for FieldAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = parse_name_value_attrs(input)?;
+
+        let path_param = attrs
+            .remove("path_param")
+            .map(|expr| parse_str_lit(&expr))
+            .transpose()?;
+
+        if let Some((_, expr)) = attrs.iter().next() {
+            return Err(syn::Error::new(expr.span(), "unrecognized attribute"));
+        }
+
+        Ok(Self { path_param })
+    }
+}
+
 /// Enum variant for an individual interface to derive FromEvent.
 #[derive(Debug)]
 struct IndividualMapping {
@@ -635,5 +980,267 @@ impl Parse for MappingAttr {
     }
 }
 
+/// Attributes of a variant of an enum dispatching the events of multiple interfaces.
+///
+/// ```no_compile
+/// #[derive(FromEvent)]
+/// enum Commands {
+///     #[from_event(interface = "com.example.Led", path = "/led/enable")]
+///     LedEnable(bool),
+///     #[from_event(interface = "com.example.Config", path = "/threshold", interface_type = "properties", allow_unset = true)]
+///     Threshold(Option<f64>),
+///     #[from_event(fallback = true)]
+///     Unknown(DeviceEvent),
+/// }
+/// ```
+#[derive(Debug, Default)]
+struct VariantAttrs {
+    interface: Option<String>,
+    path: Option<String>,
+    rename_rule: Option<RenameRule>,
+    aggregation: Option<Aggregation>,
+    interface_type: Option<InterfaceType>,
+    allow_unset: Option<bool>,
+    fallback: Option<bool>,
+}
+
+impl VariantAttrs {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            interface: other.interface.or(self.interface),
+            path: other.path.or(self.path),
+            rename_rule: other.rename_rule.or(self.rename_rule),
+            aggregation: other.aggregation.or(self.aggregation),
+            interface_type: other.interface_type.or(self.interface_type),
+            allow_unset: other.allow_unset.or(self.allow_unset),
+            fallback: other.fallback.or(self.fallback),
+        }
+    }
+}
+
+impl Parse for VariantAttrs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = parse_name_value_attrs(input)?;
+
+        let interface = attrs
+            .remove("interface")
+            .map(|expr| parse_str_lit(&expr))
+            .transpose()?;
+
+        let path = attrs
+            .remove("path")
+            .map(|expr| parse_str_lit(&expr))
+            .transpose()?;
+
+        let rename_rule = attrs
+            .remove("rename_all")
+            .map(|expr| {
+                parse_str_lit(&expr).and_then(|rename| {
+                    RenameRule::from_str(&rename)
+                        .map_err(|_| syn::Error::new(expr.span(), "invalid rename rule"))
+                })
+            })
+            .transpose()?;
+
+        let aggregation = attrs
+            .remove("aggregation")
+            .map(Aggregation::try_from)
+            .transpose()?;
+
+        let interface_type = attrs
+            .remove("interface_type")
+            .map(InterfaceType::try_from)
+            .transpose()?;
+
+        let allow_unset = attrs
+            .remove("allow_unset")
+            .as_ref()
+            .map(parse_bool_lit)
+            .transpose()?;
+
+        let fallback = attrs
+            .remove("fallback")
+            .as_ref()
+            .map(parse_bool_lit)
+            .transpose()?;
+
+        if let Some((_, expr)) = attrs.iter().next() {
+            return Err(syn::Error::new(expr.span(), "unrecognized attribute"));
+        }
+
+        Ok(Self {
+            interface,
+            path,
+            rename_rule,
+            aggregation,
+            interface_type,
+            allow_unset,
+            fallback,
+        })
+    }
+}
+
+/// Variant of an enum dispatching the events of multiple interfaces.
+struct DispatchVariant {
+    name: Ident,
+    kind: DispatchKind,
+}
+
+enum DispatchKind {
+    /// Variant receiving the events on a mapping of an interface.
+    Mapping {
+        interface: String,
+        path: String,
+        kind: MappingKind,
+    },
+    /// Variant receiving the [`DeviceEvent`] not matching the other variants.
+    Fallback,
+}
+
+enum MappingKind {
+    Individual,
+    Property {
+        allow_unset: bool,
+    },
+    Object {
+        fields: Vec<ObjectField>,
+        rename_rule: RenameRule,
+    },
+}
+
+impl DispatchVariant {
+    /// Parses the variants of the enum
+    fn parse_variants(
+        ast: &syn::DeriveInput,
+        rename_rule: Option<RenameRule>,
+    ) -> syn::Result<Vec<Self>> {
+        let syn::Data::Enum(data) = &ast.data else {
+            return Err(syn::Error::new(ast.span(), "an enum is required"));
+        };
+
+        let variants = data
+            .variants
+            .iter()
+            .map(|variant| Self::parse(variant, rename_rule))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let mut fallbacks = variants
+            .iter()
+            .filter(|v| matches!(v.kind, DispatchKind::Fallback));
+        if let Some(second) = fallbacks.nth(1) {
+            return Err(syn::Error::new(
+                second.name.span(),
+                "only one fallback variant is allowed",
+            ));
+        }
+
+        Ok(variants)
+    }
+
+    fn parse(variant: &Variant, rename_rule: Option<RenameRule>) -> syn::Result<Self> {
+        let name = variant.ident.clone();
+
+        let attrs = variant
+            .attrs
+            .iter()
+            .filter_map(|attr| parse_attribute_list::<VariantAttrs>(attr, "from_event"))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .reduce(|first, other| first.merge(other))
+            .unwrap_or_default();
+
+        let single_field =
+            matches!(&variant.fields, syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1);
+
+        if attrs.fallback.unwrap_or_default() {
+            if attrs.interface.is_some() || attrs.path.is_some() {
+                return Err(syn::Error::new(
+                    variant.span(),
+                    "the fallback variant can't have an interface or path",
+                ));
+            }
+
+            if !single_field {
+                return Err(syn::Error::new(
+                    variant.span(),
+                    "the fallback variant must have a single DeviceEvent field",
+                ));
+            }
+
+            return Ok(Self {
+                name,
+                kind: DispatchKind::Fallback,
+            });
+        }
+
+        let interface = attrs.interface.ok_or_else(|| {
+            syn::Error::new(
+                variant.span(),
+                r#"missing interface attribute #[from_event(interface = "..")] on the variant"#,
+            )
+        })?;
+        let path = attrs.path.ok_or_else(|| {
+            syn::Error::new(
+                variant.span(),
+                r#"missing path attribute #[from_event(path = "..")] on the variant"#,
+            )
+        })?;
+
+        let kind = match (
+            attrs.aggregation.unwrap_or_default(),
+            attrs.interface_type.unwrap_or_default(),
+        ) {
+            (Aggregation::Individual, InterfaceType::Datastream) => {
+                if attrs.allow_unset.is_some() {
+                    return Err(syn::Error::new(
+                        variant.span(),
+                        r#"the attribute allow_unset is only usable with `interface_type = "properties"`"#,
+                    ));
+                }
+
+                MappingKind::Individual
+            }
+            (Aggregation::Individual, InterfaceType::Properties) => MappingKind::Property {
+                allow_unset: attrs.allow_unset.unwrap_or_default(),
+            },
+            (Aggregation::Object, InterfaceType::Datastream) => {
+                let syn::Fields::Named(fields) = &variant.fields else {
+                    return Err(syn::Error::new(
+                        variant.span(),
+                        "the object variant must have named fields",
+                    ));
+                };
+
+                MappingKind::Object {
+                    fields: ObjectField::parse_named(fields)?,
+                    rename_rule: attrs.rename_rule.or(rename_rule).unwrap_or_default(),
+                }
+            }
+            (Aggregation::Object, InterfaceType::Properties) => {
+                return Err(syn::Error::new(
+                    variant.span(),
+                    "object properties are not supported",
+                ));
+            }
+        };
+
+        if !matches!(kind, MappingKind::Object { .. }) && !single_field {
+            return Err(syn::Error::new(
+                variant.span(),
+                "the variant must have a single unnamed field",
+            ));
+        }
+
+        Ok(Self {
+            name,
+            kind: DispatchKind::Mapping {
+                interface,
+                path,
+                kind,
+            },
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {}
@@ -0,0 +1,1098 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generates the typed API of an interface from its JSON definition.
+
+use std::{collections::HashSet, path::PathBuf, str::FromStr};
+
+use astarte_interfaces::{
+    interface::InterfaceTypeAggregation,
+    mapping::endpoint::{Endpoint, Level},
+    schema::{MappingType, Ownership},
+    DatastreamIndividualMapping, DatastreamObject, Interface, InterfaceMapping, PropertiesMapping,
+    Schema,
+};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    LitStr, Token,
+};
+
+use crate::case::RenameRule;
+
+/// Parses the `astarte_interface!` macro input.
+///
+/// ```no_compile
+/// astarte_interface!("interfaces/com.example.Sensor.json");
+/// astarte_interface!(sensor = "interfaces/com.example.Sensor.json");
+/// ```
+pub(crate) struct InterfaceMacro {
+    module: Ident,
+    file: String,
+    interface: Interface,
+    span: Span,
+}
+
+impl Parse for InterfaceMacro {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let module = if input.peek(syn::Ident) {
+            let module: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+
+            Some(module)
+        } else {
+            None
+        };
+
+        let lit: LitStr = input.parse()?;
+        let span = lit.span();
+
+        let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+            .map_err(|_| syn::Error::new(span, "CARGO_MANIFEST_DIR is not set"))?;
+        let file = PathBuf::from(manifest_dir).join(lit.value());
+
+        let json = std::fs::read_to_string(&file).map_err(|err| {
+            syn::Error::new(
+                span,
+                format!("couldn't read the interface {}: {err}", file.display()),
+            )
+        })?;
+
+        let interface = Interface::from_str(&json)
+            .map_err(|err| syn::Error::new(span, format!("invalid interface: {err}")))?;
+
+        let module = match module {
+            Some(module) => module,
+            None => {
+                let name = interface
+                    .interface_name()
+                    .rsplit('.')
+                    .next()
+                    .unwrap_or_default();
+
+                ident(&snake_case(name), span)?
+            }
+        };
+
+        let file = file
+            .to_str()
+            .ok_or_else(|| syn::Error::new(span, "the interface path must be valid UTF-8"))?
+            .to_string();
+
+        Ok(Self {
+            module,
+            file,
+            interface,
+            span,
+        })
+    }
+}
+
+impl InterfaceMacro {
+    pub(crate) fn quote(&self) -> TokenStream {
+        self.try_quote()
+            .unwrap_or_else(syn::Error::into_compile_error)
+    }
+
+    fn try_quote(&self) -> syn::Result<TokenStream> {
+        let module = &self.module;
+        let file = &self.file;
+        let name = self.interface.interface_name();
+        let major = self.interface.version_major();
+        let minor = self.interface.version_minor();
+        let doc = format!("Typed API generated from the `{name}` interface.");
+
+        let items = match (self.interface.inner(), self.interface.ownership()) {
+            (InterfaceTypeAggregation::DatastreamIndividual(interface), Ownership::Device) => {
+                let mappings = self.mappings(interface)?;
+
+                quote_sender(&individual_senders(&mappings), &[])
+            }
+            (InterfaceTypeAggregation::DatastreamIndividual(interface), Ownership::Server) => {
+                let mappings = self.mappings(interface)?;
+
+                quote_individual_event(&mappings)
+            }
+            (InterfaceTypeAggregation::DatastreamObject(interface), ownership) => {
+                let object = self.object(interface)?;
+
+                match ownership {
+                    Ownership::Device => object.quote_device(),
+                    Ownership::Server => object.quote_server(),
+                }
+            }
+            (InterfaceTypeAggregation::Properties(interface), Ownership::Device) => {
+                let mappings = self.mappings(interface)?;
+
+                quote_sender(&property_setters(&mappings), &property_getters(&mappings))
+            }
+            (InterfaceTypeAggregation::Properties(interface), Ownership::Server) => {
+                let mappings = self.mappings(interface)?;
+                let getters = property_getters(&mappings);
+                let event = quote_property_event(&mappings);
+
+                quote! {
+                    /// Typed access to the properties of the interface.
+                    #[derive(Debug, Clone)]
+                    pub struct Properties<C> {
+                        client: C,
+                    }
+
+                    impl<C> Properties<C> {
+                        /// Wraps the client to access the interface.
+                        pub fn new(client: C) -> Self {
+                            Self { client }
+                        }
+
+                        /// Returns the wrapped client.
+                        pub fn into_inner(self) -> C {
+                            self.client
+                        }
+                    }
+
+                    impl<C> Properties<C>
+                    where
+                        C: astarte_device_sdk::properties::PropAccess,
+                    {
+                        #(#getters)*
+                    }
+
+                    #event
+                }
+            }
+        };
+
+        Ok(quote! {
+            #[doc = #doc]
+            pub mod #module {
+                /// Name of the interface.
+                pub const INTERFACE_NAME: &str = #name;
+                /// Major version of the interface.
+                pub const VERSION_MAJOR: i32 = #major;
+                /// Minor version of the interface.
+                pub const VERSION_MINOR: i32 = #minor;
+                /// JSON definition of the interface.
+                pub const INTERFACE: &str = include_str!(#file);
+
+                /// Returns the interface, to add it to the device introspection.
+                pub fn interface() -> astarte_device_sdk::astarte_interfaces::Interface {
+                    <astarte_device_sdk::astarte_interfaces::Interface as ::std::str::FromStr>::from_str(INTERFACE)
+                        .expect("the interface was validated at compile time")
+                }
+
+                #items
+            }
+        })
+    }
+
+    fn mappings<S>(&self, interface: &S) -> syn::Result<Vec<Mapping>>
+    where
+        S: Schema,
+        S::Mapping: MappingExt,
+    {
+        let mut names = HashSet::new();
+
+        interface
+            .iter_mappings()
+            .map(|mapping| {
+                let mapping = Mapping::new(mapping, self.span)?;
+
+                if !names.insert(mapping.name.to_string()) {
+                    return Err(syn::Error::new(
+                        self.span,
+                        format!(
+                            "the endpoint {} generates the duplicated name {}",
+                            mapping.endpoint, mapping.name
+                        ),
+                    ));
+                }
+
+                Ok(mapping)
+            })
+            .collect()
+    }
+
+    fn object(&self, interface: &DatastreamObject) -> syn::Result<Object> {
+        let mut fields = Vec::with_capacity(interface.mappings_len());
+        let mut path = None;
+
+        for mapping in interface.iter_mappings() {
+            let endpoint = mapping.endpoint();
+            let levels: Vec<_> = endpoint.iter().collect();
+
+            let Some((Level::Simple(key), base)) = levels.split_last() else {
+                return Err(syn::Error::new(
+                    self.span,
+                    format!(
+                        "the last level of the object endpoint {endpoint} must not be a parameter"
+                    ),
+                ));
+            };
+
+            path.get_or_insert_with(|| Path::new(base.iter().copied(), self.span));
+
+            fields.push(Field {
+                ident: ident(&snake_case(key), self.span)?,
+                key: key.clone(),
+                mapping_type: mapping.mapping_type(),
+            });
+        }
+
+        let path = path
+            .transpose()?
+            .ok_or_else(|| syn::Error::new(self.span, "the object interface has no mappings"))?;
+
+        let mut names: HashSet<String> = path.params.iter().map(|p| p.ident.to_string()).collect();
+        for field in &fields {
+            if !names.insert(field.ident.to_string()) {
+                return Err(syn::Error::new(
+                    self.span,
+                    format!("the field {} is duplicated", field.ident),
+                ));
+            }
+        }
+
+        Ok(Object {
+            path,
+            fields,
+            explicit_timestamp: interface.explicit_timestamp(),
+        })
+    }
+}
+
+/// Access the mapping attributes that depend on the interface type.
+trait MappingExt: InterfaceMapping {
+    fn explicit_timestamp(&self) -> bool {
+        false
+    }
+
+    fn allow_unset(&self) -> bool {
+        false
+    }
+}
+
+impl MappingExt for DatastreamIndividualMapping {
+    fn explicit_timestamp(&self) -> bool {
+        self.explicit_timestamp()
+    }
+}
+
+impl MappingExt for PropertiesMapping {
+    fn allow_unset(&self) -> bool {
+        self.allow_unset()
+    }
+}
+
+/// Parameter of an endpoint, passed as a string.
+struct Param {
+    name: String,
+    ident: Ident,
+}
+
+/// Path of a mapping, with the parameters to format it.
+struct Path {
+    endpoint: String,
+    format: String,
+    params: Vec<Param>,
+}
+
+impl Path {
+    fn new<'a, I>(levels: I, span: Span) -> syn::Result<Self>
+    where
+        I: IntoIterator<Item = &'a Level<String>>,
+    {
+        let mut endpoint = String::new();
+        let mut format = String::new();
+        let mut params = Vec::new();
+
+        for level in levels {
+            match level {
+                Level::Simple(level) => {
+                    endpoint.push('/');
+                    endpoint.push_str(level);
+                    format.push('/');
+                    format.push_str(level);
+                }
+                Level::Parameter(name) => {
+                    endpoint.push_str(&format!("/%{{{name}}}"));
+                    format.push_str("/{}");
+                    params.push(Param {
+                        name: name.clone(),
+                        ident: ident(&snake_case(name), span)?,
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            endpoint,
+            format,
+            params,
+        })
+    }
+
+    /// Arguments of the function for the parameters.
+    fn args(&self) -> impl Iterator<Item = TokenStream> + '_ {
+        self.params.iter().map(|Param { ident, .. }| {
+            quote! { #ident: &str, }
+        })
+    }
+
+    /// Expression that builds the path.
+    fn expr(&self) -> TokenStream {
+        if self.params.is_empty() {
+            let endpoint = &self.endpoint;
+
+            return quote! { #endpoint };
+        }
+
+        let format = &self.format;
+        let params = self.params.iter().map(|p| &p.ident);
+
+        quote! { ::std::format!(#format, #(#params),*) }
+    }
+
+    /// Statements that extract the parameters from the event path.
+    fn extract(&self) -> TokenStream {
+        if self.params.is_empty() {
+            return TokenStream::new();
+        }
+
+        let params = self.params.iter().map(|Param { name, ident }| {
+            quote! {
+                let #ident: ::std::string::String = params.parse(#name)?;
+            }
+        });
+
+        quote! {
+            let params = astarte_device_sdk::event::PathParams::new(&endpoint, &event.path)?;
+            #(#params)*
+        }
+    }
+
+    fn idents(&self) -> impl Iterator<Item = &Ident> + '_ {
+        self.params.iter().map(|p| &p.ident)
+    }
+}
+
+/// Individual mapping of a datastream or property.
+struct Mapping {
+    name: Ident,
+    variant: Ident,
+    endpoint: String,
+    path: Path,
+    mapping_type: MappingType,
+    explicit_timestamp: bool,
+    allow_unset: bool,
+}
+
+impl Mapping {
+    fn new<M>(mapping: &M, span: Span) -> syn::Result<Self>
+    where
+        M: MappingExt,
+    {
+        let endpoint: &Endpoint<String> = mapping.endpoint();
+
+        let name = endpoint
+            .iter()
+            .filter_map(|level| match level {
+                Level::Simple(level) => Some(snake_case(level)),
+                Level::Parameter(_) => None,
+            })
+            .collect::<Vec<_>>()
+            .join("_");
+        let name = if name.is_empty() {
+            "value".to_string()
+        } else {
+            name
+        };
+
+        let variant = RenameRule::PascalCase.apply_to_field(&name);
+
+        Ok(Self {
+            name: ident(&name, span)?,
+            variant: ident(&variant, span)?,
+            endpoint: endpoint.to_string(),
+            path: Path::new(endpoint.iter(), span)?,
+            mapping_type: mapping.mapping_type(),
+            explicit_timestamp: mapping.explicit_timestamp(),
+            allow_unset: mapping.allow_unset(),
+        })
+    }
+}
+
+/// Field of an object datastream.
+struct Field {
+    ident: Ident,
+    key: String,
+    mapping_type: MappingType,
+}
+
+/// Object datastream with the common path of the mappings.
+struct Object {
+    path: Path,
+    fields: Vec<Field>,
+    explicit_timestamp: bool,
+}
+
+impl Object {
+    fn quote_device(&self) -> TokenStream {
+        let capacity = self.fields.len();
+        let fields = self.fields.iter().map(|field| {
+            let ident = &field.ident;
+            let ty = rust_type(field.mapping_type);
+
+            quote! { pub #ident: #ty, }
+        });
+        let inserts = self.fields.iter().map(|field| {
+            let ident = &field.ident;
+            let key = &field.key;
+            let data = into_data(field.mapping_type, &quote! { value.#ident });
+
+            quote! {
+                object.insert(#key.to_string(), #data);
+            }
+        });
+
+        let args = self.path.args();
+        let path = self.path.expr();
+        let doc = format!("Sends the object on the `{}` path.", self.path.endpoint);
+        let send = if self.explicit_timestamp {
+            quote! {
+                #[doc = #doc]
+                pub async fn send(
+                    &mut self,
+                    #(#args)*
+                    data: Object,
+                    timestamp: astarte_device_sdk::chrono::DateTime<astarte_device_sdk::chrono::Utc>,
+                ) -> ::std::result::Result<(), astarte_device_sdk::Error> {
+                    let path = #path;
+                    let data = astarte_device_sdk::aggregate::AstarteObject::try_from(data)?;
+
+                    astarte_device_sdk::Client::send_object_with_timestamp(&mut self.client, INTERFACE_NAME, &path, data, timestamp).await
+                }
+            }
+        } else {
+            quote! {
+                #[doc = #doc]
+                pub async fn send(
+                    &mut self,
+                    #(#args)*
+                    data: Object,
+                ) -> ::std::result::Result<(), astarte_device_sdk::Error> {
+                    let path = #path;
+                    let data = astarte_device_sdk::aggregate::AstarteObject::try_from(data)?;
+
+                    astarte_device_sdk::Client::send_object(&mut self.client, INTERFACE_NAME, &path, data).await
+                }
+            }
+        };
+
+        let sender = quote_sender(&[send], &[]);
+
+        quote! {
+            /// Data sent on the object interface.
+            #[derive(Debug, Clone, PartialEq)]
+            pub struct Object {
+                #(#fields)*
+            }
+
+            impl ::std::convert::TryFrom<Object> for astarte_device_sdk::aggregate::AstarteObject {
+                type Error = astarte_device_sdk::Error;
+
+                fn try_from(value: Object) -> ::std::result::Result<Self, Self::Error> {
+                    let mut object = Self::with_capacity(#capacity);
+                    #(#inserts)*
+                    Ok(object)
+                }
+            }
+
+            #sender
+        }
+    }
+
+    fn quote_server(&self) -> TokenStream {
+        let params = self.path.idents();
+        let fields = self.fields.iter().map(|field| {
+            let ident = &field.ident;
+            let ty = rust_type(field.mapping_type);
+
+            quote! { pub #ident: #ty, }
+        });
+        let fields_val = self.fields.iter().map(|field| {
+            let ident = &field.ident;
+            let key = &field.key;
+
+            quote! {
+                let #ident = object
+                    .remove(#key)
+                    .ok_or(FromEventError::MissingField {
+                        interface: INTERFACE_NAME,
+                        base_path: BASE_PATH,
+                        path: #key,
+                    })?
+                    .try_into()?;
+            }
+        });
+        let idents = self
+            .path
+            .idents()
+            .chain(self.fields.iter().map(|field| &field.ident));
+        let base_path = &self.path.endpoint;
+        let extract = self.path.extract();
+
+        quote! {
+            /// Event received on the object interface.
+            #[derive(Debug, Clone, PartialEq)]
+            pub struct Event {
+                #(
+                    #[allow(missing_docs)]
+                    pub #params: ::std::string::String,
+                )*
+                #(
+                    #[allow(missing_docs)]
+                    #fields
+                )*
+            }
+
+            impl astarte_device_sdk::FromEvent for Event {
+                type Err = astarte_device_sdk::event::FromEventError;
+
+                fn interface_name() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some(INTERFACE_NAME)
+                }
+
+                fn from_event(event: astarte_device_sdk::DeviceEvent) -> ::std::result::Result<Self, Self::Err> {
+                    use astarte_device_sdk::Value;
+                    use astarte_device_sdk::error::{AggregationError, InterfaceTypeError};
+                    use astarte_device_sdk::event::FromEventError;
+                    use astarte_device_sdk::astarte_interfaces::MappingPath;
+                    use astarte_device_sdk::astarte_interfaces::mapping::endpoint::Endpoint;
+                    use astarte_device_sdk::astarte_interfaces::schema::{Aggregation, InterfaceType};
+
+                    const BASE_PATH: &str = #base_path;
+
+                    if event.interface != INTERFACE_NAME {
+                        return Err(FromEventError::Interface(event.interface));
+                    }
+
+                    let endpoint: Endpoint<&str> = Endpoint::try_from(BASE_PATH)?;
+                    let path = MappingPath::try_from(event.path.as_str())?;
+
+                    if !endpoint.eq_mapping(&path) {
+                        return Err(FromEventError::Path {
+                            interface: INTERFACE_NAME,
+                            base_path: event.path.clone(),
+                        });
+                    }
+
+                    #extract
+
+                    let mut object = match event.data {
+                        Value::Object { data, .. } => data,
+                        Value::Individual { .. } => {
+                            return Err(FromEventError::Aggregation(AggregationError::new(
+                                event.interface,
+                                event.path,
+                                Aggregation::Object,
+                                Aggregation::Individual,
+                            )));
+                        }
+                        Value::Property(_) => {
+                            return Err(FromEventError::InterfaceType(InterfaceTypeError::with_path(
+                                event.interface,
+                                event.path,
+                                InterfaceType::Datastream,
+                                InterfaceType::Properties,
+                            )));
+                        }
+                    };
+
+                    #(#fields_val)*
+
+                    Ok(Self { #(#idents),* })
+                }
+            }
+        }
+    }
+}
+
+/// Typed sender wrapping a client, with the methods of the interface.
+fn quote_sender(send: &[TokenStream], get: &[TokenStream]) -> TokenStream {
+    let getters = (!get.is_empty()).then(|| {
+        quote! {
+            impl<C> Sender<C>
+            where
+                C: astarte_device_sdk::properties::PropAccess,
+            {
+                #(#get)*
+            }
+        }
+    });
+
+    quote! {
+        /// Typed sender for the interface, wrapping a client.
+        #[derive(Debug, Clone)]
+        pub struct Sender<C> {
+            client: C,
+        }
+
+        impl<C> Sender<C> {
+            /// Wraps the client to send on the interface.
+            pub fn new(client: C) -> Self {
+                Self { client }
+            }
+
+            /// Returns the wrapped client.
+            pub fn into_inner(self) -> C {
+                self.client
+            }
+        }
+
+        impl<C> Sender<C>
+        where
+            C: astarte_device_sdk::Client,
+        {
+            #(#send)*
+        }
+
+        #getters
+    }
+}
+
+fn individual_senders(mappings: &[Mapping]) -> Vec<TokenStream> {
+    mappings
+        .iter()
+        .map(|mapping| {
+            let name = format_ident!("send_{}", mapping.name);
+            let args = mapping.path.args();
+            let path = mapping.path.expr();
+            let ty = rust_type(mapping.mapping_type);
+            let data = into_data(mapping.mapping_type, &quote! { value });
+            let doc = format!("Sends the data on the `{}` endpoint.", mapping.endpoint);
+
+            if mapping.explicit_timestamp {
+                quote! {
+                    #[doc = #doc]
+                    pub async fn #name(
+                        &mut self,
+                        #(#args)*
+                        value: #ty,
+                        timestamp: astarte_device_sdk::chrono::DateTime<astarte_device_sdk::chrono::Utc>,
+                    ) -> ::std::result::Result<(), astarte_device_sdk::Error> {
+                        let path = #path;
+                        let data = #data;
+
+                        astarte_device_sdk::Client::send_individual_with_timestamp(&mut self.client, INTERFACE_NAME, &path, data, timestamp).await
+                    }
+                }
+            } else {
+                quote! {
+                    #[doc = #doc]
+                    pub async fn #name(
+                        &mut self,
+                        #(#args)*
+                        value: #ty,
+                    ) -> ::std::result::Result<(), astarte_device_sdk::Error> {
+                        let path = #path;
+                        let data = #data;
+
+                        astarte_device_sdk::Client::send_individual(&mut self.client, INTERFACE_NAME, &path, data).await
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+fn property_setters(mappings: &[Mapping]) -> Vec<TokenStream> {
+    mappings
+        .iter()
+        .map(|mapping| {
+            let set = format_ident!("set_{}", mapping.name);
+            let args = mapping.path.args();
+            let path = mapping.path.expr();
+            let ty = rust_type(mapping.mapping_type);
+            let data = into_data(mapping.mapping_type, &quote! { value });
+            let doc = format!("Sets the property on the `{}` endpoint.", mapping.endpoint);
+
+            let unset = mapping.allow_unset.then(|| {
+                let unset = format_ident!("unset_{}", mapping.name);
+                let args = mapping.path.args();
+                let doc = format!("Unsets the property on the `{}` endpoint.", mapping.endpoint);
+
+                quote! {
+                    #[doc = #doc]
+                    pub async fn #unset(
+                        &mut self,
+                        #(#args)*
+                    ) -> ::std::result::Result<(), astarte_device_sdk::Error> {
+                        let path = #path;
+
+                        astarte_device_sdk::Client::unset_property(&mut self.client, INTERFACE_NAME, &path).await
+                    }
+                }
+            });
+
+            quote! {
+                #[doc = #doc]
+                pub async fn #set(
+                    &mut self,
+                    #(#args)*
+                    value: #ty,
+                ) -> ::std::result::Result<(), astarte_device_sdk::Error> {
+                    let path = #path;
+                    let data = #data;
+
+                    astarte_device_sdk::Client::set_property(&mut self.client, INTERFACE_NAME, &path, data).await
+                }
+
+                #unset
+            }
+        })
+        .collect()
+}
+
+fn property_getters(mappings: &[Mapping]) -> Vec<TokenStream> {
+    mappings
+        .iter()
+        .map(|mapping| {
+            let name = &mapping.name;
+            let args = mapping.path.args();
+            let path = mapping.path.expr();
+            let ty = rust_type(mapping.mapping_type);
+            let doc = format!(
+                "Returns the stored value of the property on the `{}` endpoint.",
+                mapping.endpoint
+            );
+
+            quote! {
+                #[doc = #doc]
+                pub async fn #name(
+                    &self,
+                    #(#args)*
+                ) -> ::std::result::Result<::std::option::Option<#ty>, astarte_device_sdk::Error> {
+                    let path = #path;
+
+                    let value = astarte_device_sdk::properties::PropAccess::property(&self.client, INTERFACE_NAME, &path).await?;
+
+                    value
+                        .map(<#ty as ::std::convert::TryFrom<astarte_device_sdk::AstarteData>>::try_from)
+                        .transpose()
+                        .map_err(astarte_device_sdk::Error::from)
+                }
+            }
+        })
+        .collect()
+}
+
+/// Variants of the event enum, with the parameters and the value.
+fn event_variants<'a>(
+    mappings: &'a [Mapping],
+    unset: bool,
+) -> impl Iterator<Item = TokenStream> + 'a {
+    mappings.iter().map(move |mapping| {
+        let variant = &mapping.variant;
+        let params = mapping.path.idents();
+        let ty = rust_type(mapping.mapping_type);
+        let ty = if unset && mapping.allow_unset {
+            quote! { ::std::option::Option<#ty> }
+        } else {
+            ty
+        };
+        let doc = format!("Data received on the `{}` endpoint.", mapping.endpoint);
+
+        quote! {
+            #[doc = #doc]
+            #[allow(missing_docs)]
+            #variant {
+                #(#params: ::std::string::String,)*
+                value: #ty,
+            },
+        }
+    })
+}
+
+fn quote_event(variants: impl Iterator<Item = TokenStream>, arms: Vec<TokenStream>) -> TokenStream {
+    quote! {
+        /// Event received on the interface.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Event {
+            #(#variants)*
+        }
+
+        impl astarte_device_sdk::FromEvent for Event {
+            type Err = astarte_device_sdk::event::FromEventError;
+
+            fn interface_name() -> ::std::option::Option<&'static str> {
+                ::std::option::Option::Some(INTERFACE_NAME)
+            }
+
+            fn from_event(event: astarte_device_sdk::DeviceEvent) -> ::std::result::Result<Self, Self::Err> {
+                use astarte_device_sdk::Value;
+                use astarte_device_sdk::error::{AggregationError, InterfaceTypeError};
+                use astarte_device_sdk::event::FromEventError;
+                use astarte_device_sdk::astarte_interfaces::MappingPath;
+                use astarte_device_sdk::astarte_interfaces::mapping::endpoint::Endpoint;
+                use astarte_device_sdk::astarte_interfaces::schema::{Aggregation, InterfaceType};
+
+                if event.interface != INTERFACE_NAME {
+                    return Err(FromEventError::Interface(event.interface));
+                }
+
+                let path = MappingPath::try_from(event.path.as_str())?;
+
+                #(#arms)*
+
+                Err(FromEventError::Path {
+                    interface: INTERFACE_NAME,
+                    base_path: event.path.clone(),
+                })
+            }
+        }
+    }
+}
+
+fn quote_individual_event(mappings: &[Mapping]) -> TokenStream {
+    let arms = mappings
+        .iter()
+        .map(|mapping| {
+            let variant = &mapping.variant;
+            let endpoint = &mapping.endpoint;
+            let extract = mapping.path.extract();
+            let params = mapping.path.idents();
+
+            quote! {
+                let endpoint: Endpoint<&str> = Endpoint::try_from(#endpoint)?;
+                if endpoint.eq_mapping(&path) {
+                    #extract
+
+                    let data = match event.data {
+                        Value::Individual { data, .. } => data,
+                        Value::Object { .. } => {
+                            return Err(FromEventError::Aggregation(AggregationError::new(
+                                event.interface,
+                                event.path,
+                                Aggregation::Individual,
+                                Aggregation::Object,
+                            )));
+                        }
+                        Value::Property(_) => {
+                            return Err(FromEventError::InterfaceType(InterfaceTypeError::with_path(
+                                event.interface,
+                                event.path,
+                                InterfaceType::Datastream,
+                                InterfaceType::Properties,
+                            )));
+                        }
+                    };
+
+                    return Ok(Self::#variant {
+                        #(#params,)*
+                        value: data.try_into()?,
+                    });
+                }
+            }
+        })
+        .collect();
+
+    quote_event(event_variants(mappings, false), arms)
+}
+
+fn quote_property_event(mappings: &[Mapping]) -> TokenStream {
+    let arms = mappings
+        .iter()
+        .map(|mapping| {
+            let variant = &mapping.variant;
+            let endpoint = &mapping.endpoint;
+            let extract = mapping.path.extract();
+            let params: Vec<_> = mapping.path.idents().collect();
+
+            let (set, unset) = if mapping.allow_unset {
+                (
+                    quote! { ::std::option::Option::Some(data.try_into()?) },
+                    quote! {
+                        Ok(Self::#variant {
+                            #(#params,)*
+                            value: ::std::option::Option::None,
+                        })
+                    },
+                )
+            } else {
+                (
+                    quote! { data.try_into()? },
+                    quote! {
+                        Err(FromEventError::Unset {
+                            interface: INTERFACE_NAME,
+                            endpoint: event.path,
+                        })
+                    },
+                )
+            };
+
+            quote! {
+                let endpoint: Endpoint<&str> = Endpoint::try_from(#endpoint)?;
+                if endpoint.eq_mapping(&path) {
+                    #extract
+
+                    return match event.data {
+                        Value::Property(::std::option::Option::Some(data)) => Ok(Self::#variant {
+                            #(#params,)*
+                            value: #set,
+                        }),
+                        Value::Property(::std::option::Option::None) => #unset,
+                        Value::Individual { .. } | Value::Object { .. } => {
+                            Err(FromEventError::InterfaceType(InterfaceTypeError::with_path(
+                                event.interface,
+                                event.path,
+                                InterfaceType::Properties,
+                                InterfaceType::Datastream,
+                            )))
+                        }
+                    };
+                }
+            }
+        })
+        .collect();
+
+    quote_event(event_variants(mappings, true), arms)
+}
+
+/// Rust type of the mapping.
+fn rust_type(mapping_type: MappingType) -> TokenStream {
+    let date_time = quote! {
+        astarte_device_sdk::chrono::DateTime<astarte_device_sdk::chrono::Utc>
+    };
+
+    match mapping_type {
+        MappingType::Double => quote! { f64 },
+        MappingType::Integer => quote! { i32 },
+        MappingType::Boolean => quote! { bool },
+        MappingType::LongInteger => quote! { i64 },
+        MappingType::String => quote! { ::std::string::String },
+        MappingType::BinaryBlob => quote! { ::std::vec::Vec<u8> },
+        MappingType::DateTime => date_time,
+        MappingType::DoubleArray => quote! { ::std::vec::Vec<f64> },
+        MappingType::IntegerArray => quote! { ::std::vec::Vec<i32> },
+        MappingType::BooleanArray => quote! { ::std::vec::Vec<bool> },
+        MappingType::LongIntegerArray => quote! { ::std::vec::Vec<i64> },
+        MappingType::StringArray => quote! { ::std::vec::Vec<::std::string::String> },
+        MappingType::BinaryBlobArray => quote! { ::std::vec::Vec<::std::vec::Vec<u8>> },
+        MappingType::DateTimeArray => quote! { ::std::vec::Vec<#date_time> },
+    }
+}
+
+/// Converts the value into the `AstarteData`, only the floats can fail.
+fn into_data(mapping_type: MappingType, value: &TokenStream) -> TokenStream {
+    match mapping_type {
+        MappingType::Double | MappingType::DoubleArray => {
+            quote! { astarte_device_sdk::AstarteData::try_from(#value)? }
+        }
+        _ => quote! { astarte_device_sdk::AstarteData::from(#value) },
+    }
+}
+
+/// Converts a camelCase or PascalCase name in snake_case.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len());
+    let mut prev_lower = false;
+
+    for ch in name.chars() {
+        if ch == '-' {
+            snake.push('_');
+            prev_lower = false;
+
+            continue;
+        }
+
+        if ch.is_ascii_uppercase() && prev_lower {
+            snake.push('_');
+        }
+
+        prev_lower = ch.is_ascii_lowercase() || ch.is_ascii_digit();
+        snake.push(ch.to_ascii_lowercase());
+    }
+
+    snake
+}
+
+/// Creates an identifier, using a raw identifier for the keywords.
+fn ident(name: &str, span: Span) -> syn::Result<Ident> {
+    if let Ok(ident) = syn::parse_str::<Ident>(name) {
+        return Ok(Ident::new(&ident.to_string(), span));
+    }
+
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+
+    if !valid || matches!(name, "_" | "self" | "Self" | "super" | "crate") {
+        return Err(syn::Error::new(
+            span,
+            format!("couldn't generate an identifier from {name:?}"),
+        ));
+    }
+
+    Ok(Ident::new_raw(name, span))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_to_snake_case() {
+        for (name, exp) in [
+            ("Sensor", "sensor"),
+            ("DeviceDatastream", "device_datastream"),
+            ("individual-datastream", "individual_datastream"),
+            ("sensorId", "sensor_id"),
+            ("value_1", "value_1"),
+            ("v2Value", "v2_value"),
+        ] {
+            assert_eq!(snake_case(name), exp);
+        }
+    }
+
+    #[test]
+    fn should_create_identifiers() {
+        let span = Span::call_site();
+
+        assert_eq!(ident("value", span).unwrap().to_string(), "value");
+        assert_eq!(ident("type", span).unwrap().to_string(), "r#type");
+        assert!(ident("1value", span).is_err());
+        assert!(ident("self", span).is_err());
+    }
+
+    #[test]
+    fn should_format_the_path() {
+        let endpoint = Endpoint::<String>::try_from("/%{sensorId}/value").unwrap();
+        let path = Path::new(endpoint.iter(), Span::call_site()).unwrap();
+
+        assert_eq!(path.endpoint, "/%{sensorId}/value");
+        assert_eq!(path.format, "/{}/value");
+        assert_eq!(path.params.len(), 1);
+        assert_eq!(path.params[0].name, "sensorId");
+        assert_eq!(path.params[0].ident.to_string(), "sensor_id");
+    }
+}
@@ -32,10 +32,19 @@ use syn::{
     Attribute, Expr, GenericParam, Generics, MetaNameValue, Token,
 };
 
-use crate::{case::RenameRule, event::FromEventDerive};
+use crate::{
+    case::RenameRule,
+    definition::{generic_arg, InterfaceDerive},
+    event::FromEventDerive,
+    interface::InterfaceMacro,
+    properties::PropertiesDerive,
+};
 
 mod case;
+mod definition;
 mod event;
+mod interface;
+mod properties;
 
 /// Handle for the `#[astarte_object(..)]` attribute.
 ///
@@ -85,6 +94,89 @@ impl Parse for ObjectAttributes {
     }
 }
 
+/// Handle for the `#[astarte_object(..)]` attribute on the fields.
+///
+/// ### Example
+///
+/// ```no_compile
+/// #[derive(IntoAstarteObject)]
+/// struct Foo {
+///     #[astarte_object(rename = "value")]
+///     bar: String,
+///     #[astarte_object(skip)]
+///     cache: Vec<u8>,
+///     #[astarte_object(with = to_astarte_data)]
+///     level: Level,
+///     #[astarte_object(flatten)]
+///     nested: Nested,
+/// }
+/// ```
+#[derive(Debug, Default)]
+struct FieldAttributes {
+    /// Name of the field in the object, it overrides the `rename_all` rule.
+    rename: Option<String>,
+    /// Don't include the field in the object.
+    skip: Option<bool>,
+    /// Function converting the field into an `AstarteData`.
+    with: Option<syn::Path>,
+    /// Include the fields of the nested object.
+    flatten: Option<bool>,
+}
+
+impl FieldAttributes {
+    /// Merge the Astarte attributes from the other struct into self.
+    fn merge(self, other: Self) -> Self {
+        Self {
+            rename: other.rename.or(self.rename),
+            skip: other.skip.or(self.skip),
+            with: other.with.or(self.with),
+            flatten: other.flatten.or(self.flatten),
+        }
+    }
+}
+
+impl Parse for FieldAttributes {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+
+        for meta in Punctuated::<syn::Meta, Token![,]>::parse_terminated(input)? {
+            let name = meta
+                .path()
+                .get_ident()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+
+            match (name.as_str(), &meta) {
+                ("skip", syn::Meta::Path(_)) => attrs.skip = Some(true),
+                ("skip", syn::Meta::NameValue(v)) => attrs.skip = Some(parse_bool_lit(&v.value)?),
+                ("flatten", syn::Meta::Path(_)) => attrs.flatten = Some(true),
+                ("flatten", syn::Meta::NameValue(v)) => {
+                    attrs.flatten = Some(parse_bool_lit(&v.value)?)
+                }
+                ("rename", syn::Meta::NameValue(v)) => {
+                    attrs.rename = Some(parse_str_lit(&v.value)?)
+                }
+                ("with", syn::Meta::NameValue(v)) => attrs.with = Some(parse_path(&v.value)?),
+                _ => return Err(syn::Error::new(meta.span(), "unrecognized attribute")),
+            }
+        }
+
+        Ok(attrs)
+    }
+}
+
+/// Parses a path, like `path::to::fn` or `"path::to::fn"`, into a [`syn::Path`].
+fn parse_path(expr: &Expr) -> syn::Result<syn::Path> {
+    match expr {
+        Expr::Path(path) => Ok(path.path.clone()),
+        Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(lit),
+            ..
+        }) => lit.parse(),
+        _ => Err(syn::Error::new(expr.span(), "expression must be a path")),
+    }
+}
+
 /// Parses the content of a [`syn::MetaList`] as a list of [`syn::MetaNameValue`].
 ///
 /// Will convert a list of `#[attr(name = "string",..)]` into an [`HashMap<String, string>`]
@@ -145,7 +237,7 @@ fn parse_bool_lit(expr: &Expr) -> syn::Result<bool> {
 struct ObjectDerive {
     name: Ident,
     attrs: ObjectAttributes,
-    fields: Vec<Ident>,
+    fields: Vec<ObjectField>,
     generics: Generics,
 }
 
@@ -155,18 +247,18 @@ impl ObjectDerive {
 
         let name = &self.name;
         let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
-        let capacity = self.fields.len();
-        let fields = self.fields.iter().map(|i| {
-            let name = i.to_string();
-            let name = rename_rule.apply_to_field(&name);
-            quote_spanned! {i.span() =>
-                // TODO *Temporarily* ignore this new lint will be fixed in a new pr
-                #[allow(unknown_lints)]
-                #[allow(clippy::unnecessary_fallible_conversions)]
-                let v: astarte_device_sdk::types::AstarteData = ::std::convert::TryInto::try_into(value.#i)?;
-                object.insert(#name.to_string(), v);
-            }
-        });
+        let capacity = self
+            .fields
+            .iter()
+            .filter(|field| !field.is_skipped() && !field.is_flattened())
+            .count();
+        // The keys of the flattened objects could overlap with the other fields
+        let checked = self.fields.iter().any(ObjectField::is_flattened);
+        let fields = self
+            .fields
+            .iter()
+            .filter(|field| !field.is_skipped())
+            .map(|field| field.quote(rename_rule, checked));
 
         quote! {
             impl #impl_generics ::std::convert::TryFrom<#name #ty_generics> for astarte_device_sdk::aggregate::AstarteObject #where_clause {
@@ -207,7 +299,7 @@ impl Parse for ObjectDerive {
             .reduce(|first, second| first.merge(second))
             .unwrap_or_default();
 
-        let fields = parse_struct_fields(&ast)?;
+        let fields = ObjectField::parse_fields(&ast)?;
 
         let name = ast.ident;
 
@@ -222,27 +314,144 @@ impl Parse for ObjectDerive {
     }
 }
 
-/// Parses the fields of a struct
-fn parse_struct_fields(ast: &syn::DeriveInput) -> Result<Vec<Ident>, syn::Error> {
-    let syn::Data::Struct(ref st) = ast.data else {
-        return Err(syn::Error::new(ast.span(), "a named struct is required"));
-    };
-    let syn::Fields::Named(ref fields_named) = st.fields else {
-        return Err(syn::Error::new(ast.span(), "a nemed struct is required"));
-    };
-
-    let fields = fields_named
-        .named
-        .iter()
-        .map(|field| {
-            field
-                .ident
-                .clone()
-                .ok_or_else(|| syn::Error::new(field.span(), "field is not an ident"))
-        })
-        .collect::<Result<_, _>>()?;
+/// Field of a struct converted into an object.
+struct ObjectField {
+    ident: Ident,
+    attrs: FieldAttributes,
+    /// The field is an [`Option`], omitted from the object when [`None`].
+    optional: bool,
+}
+
+impl ObjectField {
+    fn is_skipped(&self) -> bool {
+        self.attrs.skip.unwrap_or_default()
+    }
+
+    fn is_flattened(&self) -> bool {
+        self.attrs.flatten.unwrap_or_default()
+    }
+
+    /// Quotes the insertion of the field in the object.
+    ///
+    /// If checked, inserting a key already in the object returns an error instead of overwriting
+    /// it.
+    fn quote(&self, rename_rule: RenameRule, checked: bool) -> proc_macro2::TokenStream {
+        let i = &self.ident;
+
+        let check = |key: proc_macro2::TokenStream| {
+            checked.then(|| {
+                quote! {
+                    if object.get(&#key).is_some() {
+                        return ::std::result::Result::Err(
+                            astarte_device_sdk::error::Error::DuplicateObjectKey(
+                                ::std::string::ToString::to_string(&#key),
+                            ),
+                        );
+                    }
+                }
+            })
+        };
+
+        let insert = if self.is_flattened() {
+            let check = check(quote! { key });
+
+            quote_spanned! {i.span() =>
+                let nested: astarte_device_sdk::aggregate::AstarteObject = ::std::convert::TryFrom::try_from(v)?;
+                for (key, v) in nested.into_key_values() {
+                    #check
+                    object.insert(key, v);
+                }
+            }
+        } else {
+            let name = self.attrs.rename.clone().unwrap_or_else(|| {
+                let name = i.to_string();
+                let name = name.strip_prefix("r#").unwrap_or(&name);
+
+                rename_rule.apply_to_field(name).into_owned()
+            });
+
+            let convert = match &self.attrs.with {
+                Some(with) => quote_spanned! {with.span() =>
+                    let v: astarte_device_sdk::types::AstarteData = #with(v)?;
+                },
+                None => quote_spanned! {i.span() =>
+                    // TODO *Temporarily* ignore this new lint will be fixed in a new pr
+                    #[allow(unknown_lints)]
+                    #[allow(clippy::unnecessary_fallible_conversions)]
+                    let v: astarte_device_sdk::types::AstarteData = ::std::convert::TryInto::try_into(v)?;
+                },
+            };
+
+            let check = check(quote! { #name });
+
+            quote! {
+                #convert
+                #check
+                object.insert(#name.to_string(), v);
+            }
+        };
+
+        // The custom conversion receives the whole field, option included
+        if self.optional && self.attrs.with.is_none() {
+            quote! {
+                if let ::std::option::Option::Some(v) = value.#i {
+                    #insert
+                }
+            }
+        } else {
+            quote! {
+                {
+                    let v = value.#i;
+                    #insert
+                }
+            }
+        }
+    }
 
-    Ok(fields)
+    /// Parses the fields of a struct
+    fn parse_fields(ast: &syn::DeriveInput) -> syn::Result<Vec<Self>> {
+        let syn::Data::Struct(ref st) = ast.data else {
+            return Err(syn::Error::new(ast.span(), "a named struct is required"));
+        };
+        let syn::Fields::Named(ref fields_named) = st.fields else {
+            return Err(syn::Error::new(ast.span(), "a nemed struct is required"));
+        };
+
+        fields_named
+            .named
+            .iter()
+            .map(|field| {
+                let ident = field
+                    .ident
+                    .clone()
+                    .ok_or_else(|| syn::Error::new(field.span(), "field is not an ident"))?;
+
+                let attrs = field
+                    .attrs
+                    .iter()
+                    .filter_map(|a| parse_attribute_list::<FieldAttributes>(a, "astarte_object"))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .into_iter()
+                    .reduce(|first, second| first.merge(second))
+                    .unwrap_or_default();
+
+                if attrs.flatten.unwrap_or_default()
+                    && (attrs.rename.is_some() || attrs.with.is_some())
+                {
+                    return Err(syn::Error::new(
+                        field.span(),
+                        "a flattened field can't be renamed or converted with a function",
+                    ));
+                }
+
+                Ok(Self {
+                    ident,
+                    attrs,
+                    optional: generic_arg(&field.ty, "Option").is_some(),
+                })
+            })
+            .collect()
+    }
 }
 
 /// Parse the `#[name(..)]` attribute.
@@ -287,6 +496,27 @@ where
 ///     bar: String
 /// }
 /// ```
+///
+/// The fields can be renamed, skipped, converted with a custom function returning a
+/// `Result<AstarteData, E>` or flattened from a nested struct. The [`Option`] fields are omitted
+/// from the object when [`None`]. The conversion fails if a flattened key is already in the
+/// object.
+///
+/// ```no_compile
+/// #[derive(IntoAstarteObject)]
+/// #[astarte_object(rename_all = "camelCase")]
+/// struct Foo {
+///     #[astarte_object(rename = "value")]
+///     bar: String,
+///     #[astarte_object(skip)]
+///     cache: Vec<u8>,
+///     #[astarte_object(with = level_to_data)]
+///     level: Level,
+///     #[astarte_object(flatten)]
+///     position: Position,
+///     description: Option<String>,
+/// }
+/// ```
 #[proc_macro_derive(IntoAstarteObject, attributes(astarte_object))]
 pub fn astarte_aggregate_derive(input: TokenStream) -> TokenStream {
     // Construct a representation of Rust code as a syntax tree
@@ -324,6 +554,18 @@ pub fn astarte_aggregate_derive(input: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// The fields of an object can be bound to the parameters of the path.
+///
+/// ```no_compile
+/// #[derive(FromEvent)]
+/// #[from_event(interface = "com.example.Foo", path = "/%{sensor_id}", aggregation = "object")]
+/// struct Foo {
+///     #[from_event(path_param = "sensor_id")]
+///     sensor_id: u32,
+///     bar: String
+/// }
+/// ```
+///
 ///
 /// To derive the trait it for a property.
 ///
@@ -337,6 +579,28 @@ pub fn astarte_aggregate_derive(input: TokenStream) -> TokenStream {
 ///     Temperature(Option<f64>),
 /// }
 /// ```
+///
+/// An enum without the interface dispatches the events of multiple interfaces, each variant
+/// specifies its own interface and path. The events not matching any variant are returned in the
+/// fallback variant, or as an error if there is none.
+///
+/// ```no_compile
+/// #[derive(FromEvent)]
+/// enum Commands {
+///     #[from_event(interface = "com.example.Led", path = "/%{led_id}/enable")]
+///     LedEnable(bool),
+///     #[from_event(interface = "com.example.Config", path = "/threshold", interface_type = "properties", allow_unset = true)]
+///     Threshold(Option<f64>),
+///     #[from_event(interface = "com.example.Move", path = "/%{motor_id}", aggregation = "object")]
+///     Move {
+///         #[from_event(path_param = "motor_id")]
+///         motor: u32,
+///         speed: i32,
+///     },
+///     #[from_event(fallback = true)]
+///     Unknown(DeviceEvent),
+/// }
+/// ```
 #[proc_macro_derive(FromEvent, attributes(from_event, mapping))]
 pub fn from_event_derive(input: TokenStream) -> TokenStream {
     // Construct a representation of Rust code as a syntax tree
@@ -346,3 +610,91 @@ pub fn from_event_derive(input: TokenStream) -> TokenStream {
     // Build the trait implementation
     from_event.quote().into()
 }
+
+/// Derive macro `#[derive(AstarteInterface)]` to generate the interface definition from a struct.
+///
+/// Each field is a mapping of the interface, with the endpoint prefixed by the `path` and the type
+/// inferred from the field type. Optional fields of a property allow the unset. The interface is
+/// validated at compile time.
+///
+/// The attributes on the struct are `name`, `major`, `minor`, `interface_type`, `ownership`,
+/// `aggregation`, `path` and `rename_all`, and for the datastreams `reliability`, `retention`,
+/// `expiry` and `explicit_timestamp`. The fields accept `rename` and `mapping_type`.
+///
+/// ### Example
+///
+/// ```no_compile
+/// #[derive(AstarteInterface)]
+/// #[astarte_interface(name = "com.example.Sensor", major = 1, minor = 0, aggregation = "object")]
+/// #[astarte_interface(path = "/%{sensor_id}", reliability = "guaranteed", explicit_timestamp = true)]
+/// struct Sensor {
+///     temperature: f64,
+///     #[astarte_interface(rename = "label")]
+///     name: String,
+/// }
+///
+/// let builder = DeviceBuilder::new().interface(Sensor::interface()?)?;
+/// ```
+#[proc_macro_derive(AstarteInterface, attributes(astarte_interface))]
+pub fn astarte_interface_derive(input: TokenStream) -> TokenStream {
+    let interface = parse_macro_input!(input as InterfaceDerive);
+
+    interface.quote().into()
+}
+
+/// Derive macro `#[derive(AstarteProperties)]` to implement the AstarteProperties trait.
+///
+/// Each field is mapped to the property with the path prefixed by the `path`, a field with an
+/// [`Option`] type is unset when [`None`]. The `set_all` only sends the fields that are different
+/// from the stored ones and the `load` rebuilds the struct from the stored properties.
+///
+/// ### Example
+///
+/// ```no_compile
+/// #[derive(AstarteProperties)]
+/// #[astarte_properties(interface = "com.example.Config", path = "/sensor", rename_all = "camelCase")]
+/// struct Config {
+///     sampling_period: i32,
+///     #[astarte_properties(rename = "label")]
+///     name: Option<String>,
+/// }
+///
+/// config.set_all(&client).await?;
+///
+/// let config = Config::load(&client).await?;
+/// ```
+#[proc_macro_derive(AstarteProperties, attributes(astarte_properties))]
+pub fn astarte_properties_derive(input: TokenStream) -> TokenStream {
+    let properties = parse_macro_input!(input as PropertiesDerive);
+
+    properties.quote().into()
+}
+
+/// Macro `astarte_interface!` to generate the typed API of an interface from its JSON definition.
+///
+/// The path is relative to the `CARGO_MANIFEST_DIR` and the interface is validated at compile
+/// time. The macro generates a module named after the last segment of the interface name, or the
+/// given name, containing:
+///
+/// - a `Sender` wrapping a client for the device owned interfaces, with a method for each mapping
+///   and the getters for the properties;
+/// - an `Event` implementing `FromEvent` for the server owned interfaces, and a `Properties`
+///   wrapping a client for the server properties.
+///
+/// ### Example
+///
+/// ```no_compile
+/// astarte_interface!("interfaces/com.example.Sensor.json");
+/// astarte_interface!(commands = "interfaces/com.example.Commands.json");
+///
+/// let mut sender = sensor::Sender::new(client.clone());
+/// sender.send_temperature("1", 24.5).await?;
+///
+/// let event = commands::Event::from_event(event)?;
+/// ```
+#[proc_macro]
+pub fn astarte_interface(input: TokenStream) -> TokenStream {
+    let interface = parse_macro_input!(input as InterfaceMacro);
+
+    interface.quote().into()
+}
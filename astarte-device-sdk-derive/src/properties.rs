@@ -0,0 +1,416 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Derives the `AstarteProperties` trait.
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+    Generics, Type,
+};
+
+use crate::{
+    case::RenameRule, definition::generic_arg, parse_attribute_list, parse_name_value_attrs,
+    parse_str_lit,
+};
+
+/// Handle for the `#[astarte_properties(..)]` attribute on the struct.
+///
+/// ### Example
+///
+/// ```no_compile
+/// #[derive(AstarteProperties)]
+/// #[astarte_properties(interface = "com.example.Config", path = "/sensor", rename_all = "camelCase")]
+/// struct Config {
+///     sampling_period: i32,
+/// }
+/// ```
+#[derive(Debug, Default)]
+struct PropertiesAttrs {
+    interface: Option<String>,
+    path: Option<String>,
+    rename_all: Option<RenameRule>,
+}
+
+impl PropertiesAttrs {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            interface: other.interface.or(self.interface),
+            path: other.path.or(self.path),
+            rename_all: other.rename_all.or(self.rename_all),
+        }
+    }
+}
+
+impl Parse for PropertiesAttrs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = parse_name_value_attrs(input)?;
+
+        let interface = attrs
+            .remove("interface")
+            .map(|expr| parse_str_lit(&expr))
+            .transpose()?;
+
+        let path = attrs
+            .remove("path")
+            .map(|expr| parse_str_lit(&expr))
+            .transpose()?;
+
+        let rename_all = attrs
+            .remove("rename_all")
+            .map(|expr| {
+                parse_str_lit(&expr).and_then(|rename| {
+                    RenameRule::from_str(&rename)
+                        .map_err(|_| syn::Error::new(expr.span(), "invalid rename rule"))
+                })
+            })
+            .transpose()?;
+
+        if let Some((_, expr)) = attrs.iter().next() {
+            return Err(syn::Error::new(expr.span(), "unrecognized attribute"));
+        }
+
+        Ok(Self {
+            interface,
+            path,
+            rename_all,
+        })
+    }
+}
+
+/// Handle for the `#[astarte_properties(..)]` attribute on the fields.
+#[derive(Debug, Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+}
+
+impl FieldAttrs {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            rename: other.rename.or(self.rename),
+        }
+    }
+}
+
+impl Parse for FieldAttrs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = parse_name_value_attrs(input)?;
+
+        let rename = attrs
+            .remove("rename")
+            .map(|expr| parse_str_lit(&expr))
+            .transpose()?;
+
+        if let Some((_, expr)) = attrs.iter().next() {
+            return Err(syn::Error::new(expr.span(), "unrecognized attribute"));
+        }
+
+        Ok(Self { rename })
+    }
+}
+
+/// Field mapped to a property path.
+struct PropertyField {
+    ident: Ident,
+    path: String,
+    /// Inner type of an [`Option`] field, that can be unset.
+    optional: Option<Type>,
+}
+
+/// Handle for the `#[derive(AstarteProperties)]` derive macro.
+///
+/// ### Example
+///
+/// ```no_compile
+/// #[derive(AstarteProperties)]
+/// #[astarte_properties(interface = "com.example.Config", path = "/sensor")]
+/// struct Config {
+///     name: String,
+///     threshold: Option<f64>,
+/// }
+/// ```
+pub(crate) struct PropertiesDerive {
+    name: Ident,
+    interface: String,
+    fields: Vec<PropertyField>,
+    generics: Generics,
+}
+
+impl PropertiesDerive {
+    pub(crate) fn quote(&self) -> TokenStream {
+        let name = &self.name;
+        let interface = &self.interface;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+
+        let changes = (0..self.fields.len())
+            .map(|i| format_ident!("__change_{i}"))
+            .collect::<Vec<_>>();
+
+        // Converts the field and compares it with the stored value, returning the change to send
+        let prepare_fields = self.fields.iter().zip(&changes).map(|(field, change)| {
+            let ident = &field.ident;
+            let path = &field.path;
+
+            let convert = |value: TokenStream| {
+                quote_spanned! {ident.span() =>
+                    // TODO *Temporarily* ignore this new lint will be fixed in a new pr
+                    #[allow(unknown_lints)]
+                    #[allow(clippy::unnecessary_fallible_conversions)]
+                    let data: astarte_device_sdk::types::AstarteData = ::std::convert::TryInto::try_into(#value)?;
+                }
+            };
+
+            let change_value = if field.optional.is_some() {
+                let convert = convert(quote! { ::std::clone::Clone::clone(value) });
+
+                quote! {
+                    match &self.#ident {
+                        ::std::option::Option::Some(value) => {
+                            #convert
+
+                            (stored.as_ref() != ::std::option::Option::Some(&data))
+                                .then_some(::std::option::Option::Some(data))
+                        }
+                        ::std::option::Option::None if stored.is_some() => {
+                            ::std::option::Option::Some(::std::option::Option::None)
+                        }
+                        ::std::option::Option::None => ::std::option::Option::None,
+                    }
+                }
+            } else {
+                let convert = convert(quote! { ::std::clone::Clone::clone(&self.#ident) });
+
+                quote! {
+                    #convert
+
+                    (stored.as_ref() != ::std::option::Option::Some(&data))
+                        .then_some(::std::option::Option::Some(data))
+                }
+            };
+
+            quote! {
+                let #change: ::std::option::Option<::std::option::Option<astarte_device_sdk::types::AstarteData>> = {
+                    let stored = astarte_device_sdk::properties::PropAccess::property(&client, #interface, #path).await?;
+
+                    #change_value
+                };
+            }
+        });
+
+        let unset_fields = self.fields.iter().zip(&changes).map(|(field, change)| {
+            let path = &field.path;
+
+            quote! {
+                if let ::std::option::Option::Some(::std::option::Option::None) = #change {
+                    astarte_device_sdk::Client::unset_property(&mut client, #interface, #path).await?;
+                }
+            }
+        });
+
+        let set_fields = self.fields.iter().zip(&changes).map(|(field, change)| {
+            let path = &field.path;
+
+            quote! {
+                if let ::std::option::Option::Some(::std::option::Option::Some(data)) = #change {
+                    astarte_device_sdk::Client::set_property(&mut client, #interface, #path, data).await?;
+                }
+            }
+        });
+
+        let load_fields = self.fields.iter().map(|field| {
+            let ident = &field.ident;
+            let path = &field.path;
+
+            match &field.optional {
+                Some(inner) => quote! {
+                    let #ident = astarte_device_sdk::properties::PropAccess::property(client, #interface, #path)
+                        .await?
+                        .map(<#inner as ::std::convert::TryFrom<astarte_device_sdk::types::AstarteData>>::try_from)
+                        .transpose()?;
+                },
+                None => quote! {
+                    let #ident = astarte_device_sdk::properties::PropAccess::property(client, #interface, #path)
+                        .await?
+                        .ok_or_else(|| astarte_device_sdk::properties::PropertiesError::Missing {
+                            interface: #interface.to_string(),
+                            path: #path.to_string(),
+                        })?;
+                    let #ident = ::std::convert::TryInto::try_into(#ident)?;
+                },
+            }
+        });
+
+        let idents = self.fields.iter().map(|field| &field.ident);
+
+        quote! {
+            impl #impl_generics astarte_device_sdk::properties::AstarteProperties for #name #ty_generics #where_clause {
+                const INTERFACE_NAME: &'static str = #interface;
+
+                async fn set_all<C>(&self, client: &C) -> ::std::result::Result<(), astarte_device_sdk::Error>
+                where
+                    C: astarte_device_sdk::Client + astarte_device_sdk::properties::PropAccess + Send + Sync,
+                {
+                    let mut client = ::std::clone::Clone::clone(client);
+
+                    // Convert all the fields and read the stored values before sending anything
+                    #(#prepare_fields)*
+
+                    #(#unset_fields)*
+
+                    #(#set_fields)*
+
+                    Ok(())
+                }
+
+                async fn load<C>(client: &C) -> ::std::result::Result<Self, astarte_device_sdk::Error>
+                where
+                    C: astarte_device_sdk::properties::PropAccess + Sync,
+                {
+                    #(#load_fields)*
+
+                    Ok(Self { #(#idents),* })
+                }
+            }
+        }
+    }
+}
+
+impl Parse for PropertiesDerive {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ast = syn::DeriveInput::parse(input)?;
+
+        let attrs = ast
+            .attrs
+            .iter()
+            .filter_map(|a| parse_attribute_list::<PropertiesAttrs>(a, "astarte_properties"))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .reduce(|first, second| first.merge(second))
+            .unwrap_or_default();
+
+        let Some(interface) = attrs.interface else {
+            return Err(syn::Error::new(
+                ast.ident.span(),
+                r#"missing the interface name, add `#[astarte_properties(interface = "...")]`"#,
+            ));
+        };
+
+        let path = attrs.path.unwrap_or_default();
+        if path.contains("%{") {
+            return Err(syn::Error::new(
+                ast.ident.span(),
+                "the path must not contain parameters, use the value of the parameter",
+            ));
+        }
+
+        let syn::Data::Struct(ref st) = ast.data else {
+            return Err(syn::Error::new(ast.span(), "a named struct is required"));
+        };
+        let syn::Fields::Named(ref fields_named) = st.fields else {
+            return Err(syn::Error::new(ast.span(), "a named struct is required"));
+        };
+
+        let rename_rule = attrs.rename_all.unwrap_or_default();
+
+        let fields = fields_named
+            .named
+            .iter()
+            .map(|field| {
+                let field_attrs = field
+                    .attrs
+                    .iter()
+                    .filter_map(|a| parse_attribute_list::<FieldAttrs>(a, "astarte_properties"))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .into_iter()
+                    .reduce(|first, second| first.merge(second))
+                    .unwrap_or_default();
+
+                let ident = field
+                    .ident
+                    .clone()
+                    .ok_or_else(|| syn::Error::new(field.span(), "field is not an ident"))?;
+
+                let name = field_attrs.rename.unwrap_or_else(|| {
+                    let name = ident.to_string();
+                    let name = name.strip_prefix("r#").unwrap_or(&name);
+
+                    rename_rule.apply_to_field(name).into_owned()
+                });
+
+                Ok(PropertyField {
+                    ident,
+                    path: format!("{path}/{name}"),
+                    optional: generic_arg(&field.ty, "Option").cloned(),
+                })
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            name: ast.ident,
+            interface,
+            fields,
+            generics: ast.generics,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    #[test]
+    fn should_map_the_fields_to_paths() {
+        let derive: PropertiesDerive = parse_quote! {
+            #[astarte_properties(interface = "com.example.Config", path = "/sensor")]
+            #[astarte_properties(rename_all = "camelCase")]
+            struct Config {
+                sampling_period: i32,
+                #[astarte_properties(rename = "label")]
+                name: Option<String>,
+            }
+        };
+
+        let fields: Vec<_> = derive
+            .fields
+            .iter()
+            .map(|field| (field.path.as_str(), field.optional.is_some()))
+            .collect();
+
+        assert_eq!(derive.interface, "com.example.Config");
+        assert_eq!(
+            fields,
+            [("/sensor/samplingPeriod", false), ("/sensor/label", true)]
+        );
+    }
+
+    #[test]
+    fn should_reject_path_parameters() {
+        let res = syn::parse2::<PropertiesDerive>(quote! {
+            #[astarte_properties(interface = "com.example.Config", path = "/%{sensor_id}")]
+            struct Config {
+                name: String,
+            }
+        });
+
+        assert!(res.is_err());
+    }
+}
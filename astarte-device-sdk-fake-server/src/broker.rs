@@ -0,0 +1,419 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! MQTT broker speaking the Astarte MQTT v1 protocol with a single device per connection.
+
+use std::{io, sync::Arc};
+
+use astarte_device_sdk::rumqttc::{
+    self,
+    tokio_rustls::{server::TlsStream, TlsAcceptor},
+    Codec, ConnAck, ConnectReturnCode, Packet, PubAck, PubComp, PubRec, PubRel, Publish, QoS,
+    SubAck, SubscribeReasonCode, UnsubAck,
+};
+use futures::{SinkExt, StreamExt};
+use rustls::{
+    pki_types::CertificateDer, server::WebPkiClientVerifier, RootCertStore, ServerConfig,
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    task::JoinSet,
+};
+use tokio_util::codec::Framed;
+use tracing::{debug, error, warn};
+
+use crate::{
+    ca::FakeCa,
+    device::{decode_properties, encode_properties, IntrospectionEntry, Message},
+    state::{Outgoing, Shared},
+    FakeServerError,
+};
+
+/// Maximum size of the MQTT packets.
+const MAX_PACKET_SIZE: usize = 10 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+enum BrokerError {
+    #[error("couldn't accept the TLS connection")]
+    Tls(#[from] io::Error),
+    #[error("couldn't read or write the packet")]
+    Mqtt(#[from] rumqttc::Error),
+    #[error("unexpected packet {0:?}")]
+    Unexpected(Packet),
+}
+
+/// Creates the TLS acceptor, requiring a client certificate issued by the CA.
+pub(crate) fn tls_acceptor(ca: &FakeCa) -> Result<TlsAcceptor, FakeServerError> {
+    let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+
+    let mut roots = RootCertStore::empty();
+    roots.add(ca.der())?;
+
+    let verifier =
+        WebPkiClientVerifier::builder_with_provider(Arc::new(roots), Arc::clone(&provider))
+            .build()?;
+
+    let (chain, key) = ca.server_cert()?;
+
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(chain, key)?;
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Accepts the connections, they are closed when the task is aborted.
+pub(crate) async fn serve(listener: TcpListener, acceptor: TlsAcceptor, shared: Arc<Shared>) {
+    let mut connections = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            res = listener.accept() => {
+                let (stream, addr) = match res {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        error!(error = %err, "couldn't accept the connection");
+
+                        continue;
+                    }
+                };
+
+                let acceptor = acceptor.clone();
+                let shared = Arc::clone(&shared);
+
+                connections.spawn(async move {
+                    if let Err(err) = handle(stream, acceptor, shared).await {
+                        debug!(%addr, error = %err, "connection closed with an error");
+                    }
+                });
+            }
+            Some(res) = connections.join_next() => {
+                if let Err(err) = res {
+                    error!(error = %err, "connection task panicked");
+                }
+            }
+        }
+    }
+}
+
+/// Returns the id of the device if the client id and certificate are valid.
+fn authorize(shared: &Shared, client_id: &str, cert: Option<&CertificateDer>) -> Option<String> {
+    let (realm, device_id) = client_id.split_once('/')?;
+
+    if realm != shared.realm {
+        return None;
+    }
+
+    let cert = cert?;
+
+    shared
+        .read(device_id, |device| device.issued.contains(cert))?
+        .then(|| device_id.to_string())
+}
+
+async fn handle(
+    stream: TcpStream,
+    acceptor: TlsAcceptor,
+    shared: Arc<Shared>,
+) -> Result<(), BrokerError> {
+    let tls = acceptor.accept(stream).await?;
+
+    let cert = tls
+        .get_ref()
+        .1
+        .peer_certificates()
+        .and_then(|certs| certs.first())
+        .map(|cert| cert.clone().into_owned());
+
+    let mut framed = Framed::new(
+        tls,
+        Codec {
+            max_incoming_size: MAX_PACKET_SIZE,
+            max_outgoing_size: MAX_PACKET_SIZE,
+        },
+    );
+
+    let connect = match framed.next().await.transpose()? {
+        Some(Packet::Connect(connect)) => connect,
+        Some(packet) => return Err(BrokerError::Unexpected(packet)),
+        None => return Ok(()),
+    };
+
+    let Some(device_id) = authorize(&shared, &connect.client_id, cert.as_ref()) else {
+        warn!(client_id = connect.client_id, "device not authorized");
+
+        framed
+            .send(Packet::ConnAck(ConnAck::new(
+                ConnectReturnCode::NotAuthorized,
+                false,
+            )))
+            .await?;
+
+        return Ok(());
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let Some(id) = shared.connect(&device_id, tx) else {
+        return Ok(());
+    };
+
+    debug!(device_id, "device connected");
+
+    let mut connection = Connection {
+        shared,
+        device_id,
+        client_id: connect.client_id,
+        framed,
+        pkid: 0,
+    };
+
+    let res = connection.run(&mut rx).await;
+
+    debug!(device_id = connection.device_id, "device disconnected");
+
+    connection.shared.disconnect(&connection.device_id, id);
+
+    res
+}
+
+struct Connection {
+    shared: Arc<Shared>,
+    device_id: String,
+    client_id: String,
+    framed: Framed<TlsStream<TcpStream>, Codec>,
+    pkid: u16,
+}
+
+impl Connection {
+    async fn run(&mut self, rx: &mut mpsc::UnboundedReceiver<Outgoing>) -> Result<(), BrokerError> {
+        // The session is never persisted, so the device always performs the full handshake
+        self.send(Packet::ConnAck(ConnAck::new(
+            ConnectReturnCode::Success,
+            false,
+        )))
+        .await?;
+
+        loop {
+            tokio::select! {
+                packet = self.framed.next() => {
+                    let Some(packet) = packet.transpose()? else {
+                        return Ok(());
+                    };
+
+                    if !self.handle_packet(packet).await? {
+                        return Ok(());
+                    }
+                }
+                outgoing = rx.recv() => {
+                    let Some(outgoing) = outgoing else {
+                        debug!(device_id = self.device_id, "connection closed by the server");
+
+                        return Ok(());
+                    };
+
+                    self.publish(outgoing).await?;
+                }
+            }
+        }
+    }
+
+    async fn send(&mut self, packet: Packet) -> Result<(), BrokerError> {
+        self.framed.send(packet).await.map_err(BrokerError::from)
+    }
+
+    /// Sends a publish to the device with QoS 1.
+    async fn publish(&mut self, outgoing: Outgoing) -> Result<(), BrokerError> {
+        // Packet ids are non zero
+        self.pkid = self.pkid.wrapping_add(1).max(1);
+
+        let mut publish = Publish::new(
+            format!("{}{}", self.client_id, outgoing.topic),
+            QoS::AtLeastOnce,
+            outgoing.payload,
+        );
+        publish.pkid = self.pkid;
+
+        self.send(Packet::Publish(publish)).await
+    }
+
+    /// Handles a packet of the device, returns `false` if it disconnected.
+    async fn handle_packet(&mut self, packet: Packet) -> Result<bool, BrokerError> {
+        match packet {
+            Packet::Publish(publish) => {
+                let replies = self.handle_publish(&publish);
+
+                match publish.qos {
+                    QoS::AtMostOnce => {}
+                    QoS::AtLeastOnce => {
+                        self.send(Packet::PubAck(PubAck::new(publish.pkid))).await?
+                    }
+                    QoS::ExactlyOnce => {
+                        self.send(Packet::PubRec(PubRec::new(publish.pkid))).await?
+                    }
+                }
+
+                for reply in replies {
+                    self.publish(reply).await?;
+                }
+            }
+            Packet::PubRel(rel) => self.send(Packet::PubComp(PubComp::new(rel.pkid))).await?,
+            Packet::PubRec(rec) => self.send(Packet::PubRel(PubRel::new(rec.pkid))).await?,
+            Packet::PubAck(_) | Packet::PubComp(_) => {}
+            Packet::Subscribe(subscribe) => {
+                let codes = subscribe
+                    .filters
+                    .iter()
+                    .map(|filter| SubscribeReasonCode::Success(filter.qos))
+                    .collect();
+
+                self.shared.update(&self.device_id, |device| {
+                    let subscriptions = &mut device.state.subscriptions;
+
+                    for filter in subscribe.filters {
+                        if !subscriptions.contains(&filter.path) {
+                            subscriptions.push(filter.path);
+                        }
+                    }
+                });
+
+                self.send(Packet::SubAck(SubAck::new(subscribe.pkid, codes)))
+                    .await?;
+            }
+            Packet::Unsubscribe(unsubscribe) => {
+                self.shared.update(&self.device_id, |device| {
+                    device
+                        .state
+                        .subscriptions
+                        .retain(|filter| !unsubscribe.topics.contains(filter));
+                });
+
+                self.send(Packet::UnsubAck(UnsubAck::new(unsubscribe.pkid)))
+                    .await?;
+            }
+            Packet::PingReq => self.send(Packet::PingResp).await?,
+            Packet::Disconnect => return Ok(false),
+            packet @ (Packet::Connect(_)
+            | Packet::ConnAck(_)
+            | Packet::SubAck(_)
+            | Packet::UnsubAck(_)
+            | Packet::PingResp) => return Err(BrokerError::Unexpected(packet)),
+        }
+
+        Ok(true)
+    }
+
+    /// Records the publish, returning the messages to send back to the device.
+    fn handle_publish(&self, publish: &Publish) -> Vec<Outgoing> {
+        let Some(topic) = publish.topic.strip_prefix(&self.client_id) else {
+            warn!(
+                topic = publish.topic,
+                "publish on the topic of another device"
+            );
+
+            return Vec::new();
+        };
+
+        match topic {
+            "" => {
+                let introspection = std::str::from_utf8(&publish.payload)
+                    .ok()
+                    .and_then(IntrospectionEntry::parse_list);
+
+                let Some(introspection) = introspection else {
+                    warn!(device_id = self.device_id, "invalid introspection");
+
+                    return Vec::new();
+                };
+
+                self.shared.update(&self.device_id, |device| {
+                    device.state.introspection = introspection;
+                });
+            }
+            "/control/emptyCache" => {
+                return self
+                    .shared
+                    .update(&self.device_id, |device| {
+                        device.state.empty_cache += 1;
+
+                        consumer_properties(device.server_properties.iter())
+                    })
+                    .unwrap_or_default();
+            }
+            "/control/producer/properties" => match decode_properties(&publish.payload) {
+                Ok(properties) => {
+                    self.shared.update(&self.device_id, |device| {
+                        device.state.device_properties = Some(properties);
+                    });
+                }
+                Err(err) => {
+                    warn!(device_id = self.device_id, error = %err, "invalid producer properties");
+                }
+            },
+            topic => {
+                let Some((interface, path)) = topic
+                    .strip_prefix('/')
+                    .and_then(|topic| topic.split_once('/'))
+                else {
+                    warn!(topic, "publish on an invalid topic");
+
+                    return Vec::new();
+                };
+
+                let message = Message {
+                    interface: interface.to_string(),
+                    path: format!("/{path}"),
+                    qos: publish.qos,
+                    payload: publish.payload.to_vec(),
+                };
+
+                self.shared.update(&self.device_id, |device| {
+                    device.state.messages.push(message);
+                });
+            }
+        }
+
+        Vec::new()
+    }
+}
+
+/// Returns the list of server properties followed by their values, sent after the `emptyCache`.
+fn consumer_properties<'a>(
+    properties: impl Iterator<Item = (&'a (String, String), &'a Vec<u8>)> + Clone,
+) -> Vec<Outgoing> {
+    let list = match encode_properties(properties.clone().map(|(key, _)| key)) {
+        Ok(list) => list,
+        Err(err) => {
+            error!(error = %err, "couldn't encode the consumer properties");
+
+            return Vec::new();
+        }
+    };
+
+    std::iter::once(Outgoing {
+        topic: "/control/consumer/properties".to_string(),
+        payload: list,
+    })
+    .chain(properties.map(|((interface, path), payload)| Outgoing {
+        topic: format!("/{interface}{path}"),
+        payload: payload.clone(),
+    }))
+    .collect()
+}
@@ -0,0 +1,96 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Certificate authority issuing the broker and device certificates.
+
+use rcgen::{
+    BasicConstraints, CertificateParams, CertificateSigningRequestParams, DistinguishedName,
+    DnType, ExtendedKeyUsagePurpose, IsCa, KeyPair, KeyUsagePurpose,
+};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+
+/// Self signed CA of the fake server.
+pub(crate) struct FakeCa {
+    cert: rcgen::Certificate,
+    key: KeyPair,
+}
+
+impl FakeCa {
+    pub(crate) fn generate() -> Result<Self, rcgen::Error> {
+        let mut params = CertificateParams::default();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.key_usages = vec![
+            KeyUsagePurpose::KeyCertSign,
+            KeyUsagePurpose::CrlSign,
+            KeyUsagePurpose::DigitalSignature,
+        ];
+        params.distinguished_name = distinguished_name("Fake Astarte CA");
+
+        let key = KeyPair::generate()?;
+        let cert = params.self_signed(&key)?;
+
+        Ok(Self { cert, key })
+    }
+
+    /// PEM of the CA certificate, to be trusted by the device.
+    pub(crate) fn pem(&self) -> String {
+        self.cert.pem()
+    }
+
+    pub(crate) fn der(&self) -> CertificateDer<'static> {
+        self.cert.der().clone()
+    }
+
+    /// Issues the certificate of the broker and pairing API for `localhost`.
+    pub(crate) fn server_cert(
+        &self,
+    ) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), rcgen::Error> {
+        let mut params =
+            CertificateParams::new(vec!["localhost".to_string(), "127.0.0.1".to_string()])?;
+        params.distinguished_name = distinguished_name("localhost");
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+
+        let key = KeyPair::generate()?;
+        let cert = params.signed_by(&key, &self.cert, &self.key)?;
+
+        let key = PrivatePkcs8KeyDer::from(key.serialize_der());
+
+        Ok((vec![cert.der().clone(), self.der()], key.into()))
+    }
+
+    /// Signs the CSR of a device, returning the client certificate.
+    pub(crate) fn sign_csr(&self, csr: &str) -> Result<rcgen::Certificate, rcgen::Error> {
+        let mut csr = CertificateSigningRequestParams::from_pem(csr)?;
+        csr.params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+
+        csr.signed_by(&self.cert, &self.key)
+    }
+}
+
+impl std::fmt::Debug for FakeCa {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FakeCa").finish_non_exhaustive()
+    }
+}
+
+fn distinguished_name(common_name: &str) -> DistinguishedName {
+    let mut name = DistinguishedName::new();
+    name.push(DnType::CommonName, common_name);
+
+    name
+}
@@ -0,0 +1,274 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! State of a device as received by the fake server.
+
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+};
+
+use astarte_device_sdk::rumqttc::QoS;
+use bson::{Bson, Document};
+use chrono::{DateTime, Utc};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+/// Interface in the introspection of a device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntrospectionEntry {
+    /// Name of the interface.
+    pub name: String,
+    /// Major version of the interface.
+    pub major: i32,
+    /// Minor version of the interface.
+    pub minor: i32,
+}
+
+impl IntrospectionEntry {
+    /// Parses the introspection payload `name:major:minor;...` sent by the device.
+    pub(crate) fn parse_list(payload: &str) -> Option<Vec<Self>> {
+        if payload.is_empty() {
+            return Some(Vec::new());
+        }
+
+        payload
+            .split(';')
+            .map(|entry| {
+                let mut parts = entry.split(':');
+
+                let name = parts.next()?.to_string();
+                let major = parts.next()?.parse().ok()?;
+                let minor = parts.next()?.parse().ok()?;
+
+                if parts.next().is_some() {
+                    return None;
+                }
+
+                Some(Self { name, major, minor })
+            })
+            .collect()
+    }
+}
+
+/// Message published by the device on one of its interfaces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    /// Name of the interface.
+    pub interface: String,
+    /// Path of the mapping, or of the object for an object aggregate.
+    pub path: String,
+    /// QoS used by the device, from the reliability of the mapping.
+    pub qos: QoS,
+    /// Raw BSON payload, empty when a property is unset.
+    pub payload: Vec<u8>,
+}
+
+impl Message {
+    fn document(&self) -> Result<Option<Document>, bson::de::Error> {
+        if self.payload.is_empty() {
+            return Ok(None);
+        }
+
+        bson::from_slice(&self.payload).map(Some)
+    }
+
+    /// Decodes the value of the message.
+    ///
+    /// An object aggregate is returned as a [`Bson::Document`], while an unset property is
+    /// returned as [`None`].
+    pub fn value(&self) -> Result<Option<Bson>, bson::de::Error> {
+        self.document()
+            .map(|doc| doc.and_then(|mut doc| doc.remove("v")))
+    }
+
+    /// Decodes the explicit timestamp of the message, if any.
+    pub fn timestamp(&self) -> Result<Option<DateTime<Utc>>, bson::de::Error> {
+        let timestamp = self.document()?.and_then(|doc| match doc.get("t") {
+            Some(Bson::DateTime(t)) => Some(t.to_chrono()),
+            _ => None,
+        });
+
+        Ok(timestamp)
+    }
+}
+
+/// Everything the fake server received from a device.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct DeviceState {
+    /// Credentials secret of the registered device.
+    pub credentials_secret: String,
+    /// PEM of the certificates issued to the device.
+    pub certificates: Vec<String>,
+    /// Whether the device is connected to the broker.
+    pub connected: bool,
+    /// Number of MQTT connections of the device.
+    pub connections: usize,
+    /// Last introspection sent by the device.
+    pub introspection: Vec<IntrospectionEntry>,
+    /// Number of `emptyCache` messages received.
+    pub empty_cache: usize,
+    /// Topic filters the device subscribed to.
+    pub subscriptions: Vec<String>,
+    /// Last list of device properties sent on `control/producer/properties`.
+    pub device_properties: Option<Vec<String>>,
+    /// Messages published on the interfaces, in order.
+    pub messages: Vec<Message>,
+}
+
+impl DeviceState {
+    pub(crate) fn new(credentials_secret: String) -> Self {
+        Self {
+            credentials_secret,
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if the interface is in the introspection.
+    pub fn has_interface(&self, name: &str) -> bool {
+        self.introspection.iter().any(|entry| entry.name == name)
+    }
+
+    /// Returns the messages published on an interface.
+    pub fn messages_on<'a>(&'a self, interface: &'a str) -> impl Iterator<Item = &'a Message> {
+        self.messages
+            .iter()
+            .filter(move |msg| msg.interface == interface)
+    }
+
+    /// Returns the last value published on an interface and path.
+    pub fn last_value(&self, interface: &str, path: &str) -> Option<&Message> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|msg| msg.interface == interface && msg.path == path)
+    }
+}
+
+/// Server owned properties set on a device, by interface and path.
+pub(crate) type ServerProperties = BTreeMap<(String, String), Vec<u8>>;
+
+/// Encodes the payload of the `control/consumer/properties` and `control/producer/properties`.
+///
+/// The payload is the size of the uncompressed list in big endian, followed by the zlib of the
+/// `interface/path` joined by `;`.
+pub(crate) fn encode_properties<'a>(
+    properties: impl IntoIterator<Item = &'a (String, String)>,
+) -> std::io::Result<Vec<u8>> {
+    let list = properties
+        .into_iter()
+        .map(|(interface, path)| format!("{interface}{path}"))
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let size = u32::try_from(list.len())
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+
+    let mut encoder = ZlibEncoder::new(size.to_be_bytes().to_vec(), Compression::default());
+    encoder.write_all(list.as_bytes())?;
+
+    encoder.finish()
+}
+
+/// Decodes the payload of the properties list, see [`encode_properties`].
+pub(crate) fn decode_properties(payload: &[u8]) -> std::io::Result<Vec<String>> {
+    let Some(data) = payload.get(4..) else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "missing the size of the properties",
+        ));
+    };
+
+    let mut list = String::new();
+    ZlibDecoder::new(data).read_to_string(&mut list)?;
+
+    if list.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    Ok(list.split(';').map(str::to_string).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_introspection() {
+        let entries =
+            IntrospectionEntry::parse_list("com.example.Foo:0:1;com.example.Bar:1:0").unwrap();
+
+        assert_eq!(
+            entries,
+            [
+                IntrospectionEntry {
+                    name: "com.example.Foo".to_string(),
+                    major: 0,
+                    minor: 1,
+                },
+                IntrospectionEntry {
+                    name: "com.example.Bar".to_string(),
+                    major: 1,
+                    minor: 0,
+                },
+            ]
+        );
+
+        assert_eq!(IntrospectionEntry::parse_list("").unwrap(), []);
+        assert!(IntrospectionEntry::parse_list("com.example.Foo:0").is_none());
+    }
+
+    #[test]
+    fn should_encode_and_decode_properties() {
+        let props = [
+            ("com.example.Foo".to_string(), "/a".to_string()),
+            ("com.example.Bar".to_string(), "/b/c".to_string()),
+        ];
+
+        let payload = encode_properties(&props).unwrap();
+        assert_eq!(payload[..4], 37u32.to_be_bytes());
+
+        let decoded = decode_properties(&payload).unwrap();
+        assert_eq!(decoded, ["com.example.Foo/a", "com.example.Bar/b/c"]);
+
+        let empty = encode_properties(&[]).unwrap();
+        assert_eq!(decode_properties(&empty).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn should_decode_message_value() {
+        let payload = bson::to_vec(&bson::doc! {"v": 42i32}).unwrap();
+
+        let msg = Message {
+            interface: "com.example.Foo".to_string(),
+            path: "/a".to_string(),
+            qos: QoS::AtLeastOnce,
+            payload,
+        };
+        assert_eq!(msg.value().unwrap(), Some(Bson::Int32(42)));
+        assert_eq!(msg.timestamp().unwrap(), None);
+
+        let unset = Message {
+            payload: Vec::new(),
+            ..msg
+        };
+        assert_eq!(unset.value().unwrap(), None);
+    }
+}
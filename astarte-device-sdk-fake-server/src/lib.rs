@@ -0,0 +1,403 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! In-process fake Astarte cluster to test devices offline.
+//!
+//! The [`FakeServer`] serves a local pairing API, that registers the devices and issues their
+//! certificates, and an MQTT broker following the Astarte MQTT v1 protocol. A device built with
+//! the [`DeviceBuilder`](astarte_device_sdk::builder::DeviceBuilder) and the [`MqttConfig`]
+//! returned by the server goes through the full registration and connection flow.
+//!
+//! Everything the server received from a device is recorded in its [`DeviceState`], and the
+//! server can publish data and set properties on the device.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use astarte_device_sdk::{builder::DeviceBuilder, prelude::*};
+//! use astarte_device_sdk_fake_server::FakeServer;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! rustls::crypto::aws_lc_rs::default_provider()
+//!     .install_default()
+//!     .map_err(|_| "couldn't install default crypto provider")?;
+//!
+//! let server = FakeServer::start("test").await?;
+//!
+//! let (client, connection) = DeviceBuilder::new()
+//!     .interface_directory("interfaces")?
+//!     .store_dir("store")
+//!     .await?
+//!     .connection(server.mqtt_config("device_id"))
+//!     .build()
+//!     .await?;
+//!
+//! tokio::spawn(connection.handle_events());
+//!
+//! let state = server
+//!     .wait_for("device_id", Duration::from_secs(5), |state| state.empty_cache > 0)
+//!     .await?;
+//!
+//! assert!(state.has_interface("com.example.Sensor"));
+//! # Ok(())
+//! # }
+//! ```
+
+#![warn(missing_docs)]
+
+use std::{io, net::Ipv4Addr, sync::Arc, time::Duration};
+
+use astarte_device_sdk::{aggregate::AstarteObject, transport::mqtt::MqttConfig, AstarteData};
+use bson::{Bson, Document};
+use chrono::{DateTime, Utc};
+use tokio::{net::TcpListener, task::JoinHandle};
+use tracing::error;
+
+use crate::{
+    ca::FakeCa,
+    state::{Outgoing, Shared},
+};
+
+pub use self::device::{DeviceState, IntrospectionEntry, Message};
+
+mod broker;
+mod ca;
+mod device;
+mod pairing;
+mod state;
+
+/// Error returned by the [`FakeServer`].
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum FakeServerError {
+    /// Couldn't bind the listeners.
+    #[error("couldn't bind the listener")]
+    Io(#[from] io::Error),
+    /// Couldn't generate the certificates.
+    #[error("couldn't generate the certificates")]
+    Certificate(#[from] rcgen::Error),
+    /// Couldn't configure TLS for the broker.
+    #[error("couldn't configure TLS")]
+    Tls(#[from] rustls::Error),
+    /// Couldn't configure the verifier of the client certificates.
+    #[error("couldn't configure the client certificate verifier")]
+    Verifier(#[from] rustls::server::VerifierBuilderError),
+    /// Couldn't encode the BSON payload.
+    #[error("couldn't encode the payload")]
+    Encode(#[from] bson::ser::Error),
+    /// The device is not registered.
+    #[error("device {0} is not registered")]
+    NotRegistered(String),
+    /// The device is not connected to the broker.
+    #[error("device {0} is not connected")]
+    NotConnected(String),
+    /// The condition on the device wasn't met in time.
+    #[error("timed out waiting for device {0}")]
+    Timeout(String),
+}
+
+/// Fake Astarte cluster with a pairing API and an MQTT broker.
+///
+/// The server is stopped when dropped.
+#[derive(Debug)]
+pub struct FakeServer {
+    shared: Arc<Shared>,
+    ca: Arc<FakeCa>,
+    pairing_url: String,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl FakeServer {
+    /// Starts the server for the realm on random local ports.
+    pub async fn start(realm: impl Into<String>) -> Result<Self, FakeServerError> {
+        let ca = Arc::new(FakeCa::generate()?);
+
+        let broker = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let pairing = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+
+        let broker_url = format!("mqtts://localhost:{}/", broker.local_addr()?.port());
+        let pairing_url = format!("http://{}", pairing.local_addr()?);
+
+        let shared = Arc::new(Shared::new(realm.into(), random_token(), broker_url));
+
+        let acceptor = broker::tls_acceptor(&ca)?;
+        let router = pairing::router(Arc::clone(&shared), Arc::clone(&ca));
+
+        let tasks = vec![
+            tokio::spawn(broker::serve(broker, acceptor, Arc::clone(&shared))),
+            tokio::spawn(async move {
+                if let Err(err) = axum::serve(pairing, router).await {
+                    error!(error = %err, "pairing API stopped");
+                }
+            }),
+        ];
+
+        Ok(Self {
+            shared,
+            ca,
+            pairing_url,
+            tasks,
+        })
+    }
+
+    /// Realm of the devices.
+    pub fn realm(&self) -> &str {
+        &self.shared.realm
+    }
+
+    /// URL of the pairing API.
+    pub fn pairing_url(&self) -> &str {
+        &self.pairing_url
+    }
+
+    /// URL of the MQTT broker returned to the devices.
+    pub fn broker_url(&self) -> &str {
+        &self.shared.broker_url
+    }
+
+    /// Pairing token accepted to register the devices.
+    pub fn pairing_token(&self) -> &str {
+        &self.shared.pairing_token
+    }
+
+    /// PEM of the CA of the broker and device certificates.
+    pub fn ca_pem(&self) -> String {
+        self.ca.pem()
+    }
+
+    /// Registers a device, returning its credentials secret.
+    ///
+    /// Registering the device again generates a new secret.
+    pub fn register_device(&self, device_id: &str) -> String {
+        self.shared.register(device_id)
+    }
+
+    /// Configuration to register and connect a device with the pairing token.
+    ///
+    /// The device requires a writable directory to store the credentials secret.
+    pub fn mqtt_config(&self, device_id: &str) -> MqttConfig {
+        let mut config = MqttConfig::with_pairing_token(
+            self.realm(),
+            device_id,
+            self.pairing_token(),
+            self.pairing_url(),
+        );
+        config.ca_certificates(self.ca_pem());
+
+        config
+    }
+
+    /// Configuration to connect a registered device with its credentials secret.
+    pub fn mqtt_config_with_secret(&self, device_id: &str, secret: &str) -> MqttConfig {
+        let mut config =
+            MqttConfig::with_credential_secret(self.realm(), device_id, secret, self.pairing_url());
+        config.ca_certificates(self.ca_pem());
+
+        config
+    }
+
+    /// Returns a snapshot of what the server received from the device.
+    pub fn device(&self, device_id: &str) -> Option<DeviceState> {
+        self.shared.read(device_id, |device| device.state.clone())
+    }
+
+    /// Waits until the predicate on the state of the device is true, returning the state.
+    pub async fn wait_for<F>(
+        &self,
+        device_id: &str,
+        timeout: Duration,
+        mut predicate: F,
+    ) -> Result<DeviceState, FakeServerError>
+    where
+        F: FnMut(&DeviceState) -> bool,
+    {
+        let mut changed = self.shared.subscribe();
+
+        let wait = async {
+            loop {
+                let state = self.shared.read(device_id, |device| {
+                    predicate(&device.state).then(|| device.state.clone())
+                });
+
+                if let Some(Some(state)) = state {
+                    return state;
+                }
+
+                // The sender is owned by the server, so it's never closed while waiting
+                let _ = changed.changed().await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| FakeServerError::Timeout(device_id.to_string()))
+    }
+
+    /// Publishes an individual value on a server owned datastream.
+    pub fn send_individual(
+        &self,
+        device_id: &str,
+        interface: &str,
+        path: &str,
+        data: AstarteData,
+        timestamp: Option<DateTime<Utc>>,
+    ) -> Result<(), FakeServerError> {
+        let payload = encode_payload(data.into(), timestamp)?;
+
+        self.send(device_id, interface, path, payload)
+    }
+
+    /// Publishes an object on a server owned datastream.
+    pub fn send_object(
+        &self,
+        device_id: &str,
+        interface: &str,
+        path: &str,
+        data: AstarteObject,
+        timestamp: Option<DateTime<Utc>>,
+    ) -> Result<(), FakeServerError> {
+        let doc = data
+            .into_key_values()
+            .map(|(key, value)| (key, Bson::from(value)))
+            .collect::<Document>();
+
+        let payload = encode_payload(doc.into(), timestamp)?;
+
+        self.send(device_id, interface, path, payload)
+    }
+
+    fn send(
+        &self,
+        device_id: &str,
+        interface: &str,
+        path: &str,
+        payload: Vec<u8>,
+    ) -> Result<(), FakeServerError> {
+        let sent = self
+            .shared
+            .read(device_id, |device| {
+                device.send(Outgoing {
+                    topic: format!("/{interface}{path}"),
+                    payload,
+                })
+            })
+            .ok_or_else(|| FakeServerError::NotRegistered(device_id.to_string()))?;
+
+        if !sent {
+            return Err(FakeServerError::NotConnected(device_id.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Sets a server owned property.
+    ///
+    /// The property is published if the device is connected, and sent again after each
+    /// `emptyCache` of the device.
+    pub fn set_property(
+        &self,
+        device_id: &str,
+        interface: &str,
+        path: &str,
+        data: AstarteData,
+    ) -> Result<(), FakeServerError> {
+        let payload = encode_payload(data.into(), None)?;
+
+        self.update_property(device_id, interface, path, Some(payload))
+    }
+
+    /// Unsets a server owned property.
+    pub fn unset_property(
+        &self,
+        device_id: &str,
+        interface: &str,
+        path: &str,
+    ) -> Result<(), FakeServerError> {
+        self.update_property(device_id, interface, path, None)
+    }
+
+    fn update_property(
+        &self,
+        device_id: &str,
+        interface: &str,
+        path: &str,
+        payload: Option<Vec<u8>>,
+    ) -> Result<(), FakeServerError> {
+        self.shared
+            .update(device_id, |device| {
+                let key = (interface.to_string(), path.to_string());
+
+                let payload = match payload {
+                    Some(payload) => {
+                        device.server_properties.insert(key, payload.clone());
+
+                        payload
+                    }
+                    None => {
+                        device.server_properties.remove(&key);
+
+                        Vec::new()
+                    }
+                };
+
+                device.send(Outgoing {
+                    topic: format!("/{interface}{path}"),
+                    payload,
+                });
+            })
+            .ok_or_else(|| FakeServerError::NotRegistered(device_id.to_string()))
+    }
+
+    /// Closes the MQTT connection of the device, returns `false` if it wasn't connected.
+    ///
+    /// The device will reconnect with its reconnect policy.
+    pub fn disconnect(&self, device_id: &str) -> bool {
+        self.shared.close(device_id)
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Encodes the BSON payload `{"v": value, "t": timestamp}` of a publish.
+fn encode_payload(
+    value: Bson,
+    timestamp: Option<DateTime<Utc>>,
+) -> Result<Vec<u8>, bson::ser::Error> {
+    let mut doc = Document::new();
+    doc.insert("v", value);
+
+    if let Some(timestamp) = timestamp {
+        doc.insert("t", bson::DateTime::from_chrono(timestamp));
+    }
+
+    bson::to_vec(&doc)
+}
+
+fn random_token() -> String {
+    let mut token = String::with_capacity(32);
+    token.extend(std::iter::repeat_with(fastrand::alphanumeric).take(32));
+
+    token
+}
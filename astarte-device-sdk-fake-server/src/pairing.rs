@@ -0,0 +1,231 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Routes of the Astarte pairing API.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{debug, error};
+
+use crate::{ca::FakeCa, state::Shared};
+
+#[derive(Debug, Clone)]
+struct Pairing {
+    shared: Arc<Shared>,
+    ca: Arc<FakeCa>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApiData<T> {
+    data: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct HwId {
+    hw_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Csr {
+    csr: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientCrt {
+    client_crt: String,
+}
+
+pub(crate) fn router(shared: Arc<Shared>, ca: Arc<FakeCa>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/v1/:realm/agent/devices", post(register))
+        .route("/v1/:realm/devices/:device_id", get(device_info))
+        .route(
+            "/v1/:realm/devices/:device_id/protocols/astarte_mqtt_v1/credentials",
+            post(create_certificate),
+        )
+        .route(
+            "/v1/:realm/devices/:device_id/protocols/astarte_mqtt_v1/credentials/verify",
+            post(verify_certificate),
+        )
+        .with_state(Pairing { shared, ca })
+}
+
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+fn error(status: StatusCode, detail: &str) -> Response {
+    (status, Json(json!({"errors": {"detail": detail}}))).into_response()
+}
+
+impl Pairing {
+    /// Checks the realm and the credentials secret of the device.
+    fn authorize(
+        &self,
+        headers: &HeaderMap,
+        realm: &str,
+        device_id: &str,
+    ) -> Result<(), (StatusCode, &'static str)> {
+        if realm != self.shared.realm {
+            return Err((StatusCode::NOT_FOUND, "realm not found"));
+        }
+
+        let authorized =
+            bearer(headers).is_some_and(|secret| self.shared.is_authorized(device_id, secret));
+
+        if !authorized {
+            debug!(device_id, "invalid credentials secret");
+
+            return Err((StatusCode::UNAUTHORIZED, "invalid credentials secret"));
+        }
+
+        Ok(())
+    }
+}
+
+async fn health() -> StatusCode {
+    StatusCode::OK
+}
+
+async fn register(
+    State(pairing): State<Pairing>,
+    Path(realm): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<ApiData<HwId>>,
+) -> Response {
+    if realm != pairing.shared.realm {
+        return error(StatusCode::NOT_FOUND, "realm not found");
+    }
+
+    if bearer(&headers) != Some(pairing.shared.pairing_token.as_str()) {
+        return error(StatusCode::UNAUTHORIZED, "invalid pairing token");
+    }
+
+    let credentials_secret = pairing.shared.register(&body.data.hw_id);
+
+    debug!(device_id = body.data.hw_id, "device registered");
+
+    (
+        StatusCode::CREATED,
+        Json(json!({"data": {"credentials_secret": credentials_secret}})),
+    )
+        .into_response()
+}
+
+async fn device_info(
+    State(pairing): State<Pairing>,
+    Path((realm, device_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err((status, detail)) = pairing.authorize(&headers, &realm, &device_id) {
+        return error(status, detail);
+    }
+
+    let status = pairing
+        .shared
+        .read(&device_id, |device| {
+            if device.state.connections > 0 {
+                "confirmed"
+            } else {
+                "pending"
+            }
+        })
+        .unwrap_or("pending");
+
+    Json(json!({
+        "data": {
+            "version": "1.2.0",
+            "status": status,
+            "protocols": {
+                "astarte_mqtt_v1": {
+                    "broker_url": pairing.shared.broker_url,
+                }
+            }
+        }
+    }))
+    .into_response()
+}
+
+async fn create_certificate(
+    State(pairing): State<Pairing>,
+    Path((realm, device_id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(body): Json<ApiData<Csr>>,
+) -> Response {
+    if let Err((status, detail)) = pairing.authorize(&headers, &realm, &device_id) {
+        return error(status, detail);
+    }
+
+    let client_crt = match pairing.ca.sign_csr(&body.data.csr) {
+        Ok(crt) => crt,
+        Err(err) => {
+            error!(device_id, error = %err, "couldn't sign the CSR");
+
+            return error(StatusCode::UNPROCESSABLE_ENTITY, "invalid CSR");
+        }
+    };
+
+    pairing.shared.update(&device_id, |device| {
+        device.state.certificates.push(client_crt.pem());
+        device.issued.push(client_crt.der().clone());
+    });
+
+    (
+        StatusCode::CREATED,
+        Json(json!({"data": {"client_crt": client_crt.pem()}})),
+    )
+        .into_response()
+}
+
+async fn verify_certificate(
+    State(pairing): State<Pairing>,
+    Path((realm, device_id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(body): Json<ApiData<ClientCrt>>,
+) -> Response {
+    if let Err((status, detail)) = pairing.authorize(&headers, &realm, &device_id) {
+        return error(status, detail);
+    }
+
+    let valid = pairing
+        .shared
+        .read(&device_id, |device| {
+            device
+                .state
+                .certificates
+                .iter()
+                .any(|crt| crt.trim() == body.data.client_crt.trim())
+        })
+        .unwrap_or(false);
+
+    Json(json!({"data": {"valid": valid}})).into_response()
+}
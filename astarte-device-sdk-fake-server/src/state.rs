@@ -0,0 +1,209 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! State shared between the pairing API, the broker and the [`FakeServer`](crate::FakeServer).
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use rustls::pki_types::CertificateDer;
+use tokio::sync::{mpsc, watch};
+use tracing::warn;
+
+use crate::device::{DeviceState, ServerProperties};
+
+/// Publish sent by the server to a connected device.
+#[derive(Debug, Clone)]
+pub(crate) struct Outgoing {
+    /// Topic after the client id, starting with a `/`.
+    pub(crate) topic: String,
+    pub(crate) payload: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Device {
+    pub(crate) state: DeviceState,
+    pub(crate) server_properties: ServerProperties,
+    /// Certificates issued to the device, accepted by the broker.
+    pub(crate) issued: Vec<CertificateDer<'static>>,
+    connection: Option<(u64, mpsc::UnboundedSender<Outgoing>)>,
+}
+
+impl Device {
+    fn new(credentials_secret: String) -> Self {
+        Self {
+            state: DeviceState::new(credentials_secret),
+            server_properties: ServerProperties::new(),
+            issued: Vec::new(),
+            connection: None,
+        }
+    }
+
+    /// Sends a publish to the device, returns `false` if it isn't connected.
+    pub(crate) fn send(&self, outgoing: Outgoing) -> bool {
+        self.connection
+            .as_ref()
+            .is_some_and(|(_, tx)| tx.send(outgoing).is_ok())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Shared {
+    pub(crate) realm: String,
+    pub(crate) pairing_token: String,
+    pub(crate) broker_url: String,
+    devices: Mutex<HashMap<String, Device>>,
+    next_connection: Mutex<u64>,
+    changed: watch::Sender<()>,
+}
+
+impl Shared {
+    pub(crate) fn new(realm: String, pairing_token: String, broker_url: String) -> Self {
+        Self {
+            realm,
+            pairing_token,
+            broker_url,
+            devices: Mutex::new(HashMap::new()),
+            next_connection: Mutex::new(0),
+            changed: watch::Sender::new(()),
+        }
+    }
+
+    fn devices(&self) -> MutexGuard<'_, HashMap<String, Device>> {
+        // The state is still consistent if a test panicked while holding the lock
+        self.devices
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+    }
+
+    /// Registers the device, replacing the credentials secret if already registered.
+    pub(crate) fn register(&self, device_id: &str) -> String {
+        let secret = random_secret();
+
+        self.devices()
+            .entry(device_id.to_string())
+            .and_modify(|device| device.state.credentials_secret.clone_from(&secret))
+            .or_insert_with(|| Device::new(secret.clone()));
+
+        self.notify();
+
+        secret
+    }
+
+    /// Checks the credentials secret of a registered device.
+    pub(crate) fn is_authorized(&self, device_id: &str, secret: &str) -> bool {
+        self.devices()
+            .get(device_id)
+            .is_some_and(|device| device.state.credentials_secret == secret)
+    }
+
+    /// Reads the device, if registered.
+    pub(crate) fn read<F, T>(&self, device_id: &str, f: F) -> Option<T>
+    where
+        F: FnOnce(&Device) -> T,
+    {
+        self.devices().get(device_id).map(f)
+    }
+
+    /// Updates the device, if registered, and wakes up the waiters.
+    pub(crate) fn update<F, T>(&self, device_id: &str, f: F) -> Option<T>
+    where
+        F: FnOnce(&mut Device) -> T,
+    {
+        let res = self.devices().get_mut(device_id).map(f);
+
+        if res.is_some() {
+            self.notify();
+        }
+
+        res
+    }
+
+    /// Marks the device as connected, returning the id of the connection.
+    ///
+    /// A previous connection of the same device is closed.
+    pub(crate) fn connect(
+        &self,
+        device_id: &str,
+        tx: mpsc::UnboundedSender<Outgoing>,
+    ) -> Option<u64> {
+        let id = {
+            let mut next = self
+                .next_connection
+                .lock()
+                .unwrap_or_else(|poison| poison.into_inner());
+            *next += 1;
+            *next
+        };
+
+        self.update(device_id, |device| {
+            if device.connection.replace((id, tx)).is_some() {
+                warn!(
+                    device_id,
+                    "device connected again, closing the old connection"
+                );
+            }
+
+            device.state.connected = true;
+            device.state.connections += 1;
+
+            id
+        })
+    }
+
+    /// Marks the device as disconnected, if the connection is still the current one.
+    pub(crate) fn disconnect(&self, device_id: &str, connection: u64) {
+        self.update(device_id, |device| {
+            if device
+                .connection
+                .as_ref()
+                .is_some_and(|(id, _)| *id == connection)
+            {
+                device.connection = None;
+                device.state.connected = false;
+            }
+        });
+    }
+
+    /// Closes the connection of the device, returns `false` if it wasn't connected.
+    pub(crate) fn close(&self, device_id: &str) -> bool {
+        self.update(device_id, |device| {
+            device.state.connected = false;
+
+            device.connection.take().is_some()
+        })
+        .unwrap_or(false)
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<()> {
+        self.changed.subscribe()
+    }
+
+    fn notify(&self) {
+        self.changed.send_replace(());
+    }
+}
+
+fn random_secret() -> String {
+    let mut secret = String::with_capacity(44);
+    secret.extend(std::iter::repeat_with(fastrand::alphanumeric).take(44));
+
+    secret
+}
@@ -0,0 +1,307 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use astarte_device_sdk::{
+    builder::DeviceBuilder,
+    client::{RecvError, StatusReason},
+    prelude::*,
+    store::SqliteStore,
+    transport::mqtt::MqttConfig,
+    AstarteData, DeviceClient, Value,
+};
+use astarte_device_sdk_fake_server::{FakeServer, IntrospectionEntry};
+use bson::Bson;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use tokio::task::JoinHandle;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+const DEVICE_DATASTREAM: &str = r#"{
+    "interface_name": "com.example.DeviceDatastream",
+    "version_major": 0,
+    "version_minor": 1,
+    "type": "datastream",
+    "ownership": "device",
+    "mappings": [
+        {
+            "endpoint": "/sensor/value",
+            "type": "double",
+            "explicit_timestamp": true
+        }
+    ]
+}"#;
+
+const DEVICE_PROPERTY: &str = r#"{
+    "interface_name": "com.example.DeviceProperty",
+    "version_major": 1,
+    "version_minor": 0,
+    "type": "properties",
+    "ownership": "device",
+    "mappings": [
+        {
+            "endpoint": "/name",
+            "type": "string"
+        }
+    ]
+}"#;
+
+const SERVER_PROPERTY: &str = r#"{
+    "interface_name": "com.example.ServerProperty",
+    "version_major": 0,
+    "version_minor": 2,
+    "type": "properties",
+    "ownership": "server",
+    "mappings": [
+        {
+            "endpoint": "/led/enabled",
+            "type": "boolean",
+            "allow_unset": true
+        }
+    ]
+}"#;
+
+type Device = DeviceClient<astarte_device_sdk::transport::mqtt::Mqtt<SqliteStore>>;
+
+async fn connect(
+    config: MqttConfig,
+    dir: &TempDir,
+) -> (Device, JoinHandle<Result<(), astarte_device_sdk::Error>>) {
+    let (client, connection) = DeviceBuilder::new()
+        .interface_str(DEVICE_DATASTREAM)
+        .unwrap()
+        .interface_str(DEVICE_PROPERTY)
+        .unwrap()
+        .interface_str(SERVER_PROPERTY)
+        .unwrap()
+        .store_dir(dir.path())
+        .await
+        .unwrap()
+        .connection(config)
+        .build()
+        .await
+        .unwrap();
+
+    (client, tokio::spawn(connection.handle_events()))
+}
+
+async fn recv(client: &Device) -> Result<astarte_device_sdk::DeviceEvent, RecvError> {
+    tokio::time::timeout(TIMEOUT, client.recv())
+        .await
+        .expect("timed out receiving the event")
+}
+
+#[tokio::test]
+async fn should_register_connect_and_publish() {
+    // The registration uses the default provider, it could be installed by another test
+    let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+    let server = FakeServer::start("test").await.unwrap();
+    let dir = TempDir::new().unwrap();
+
+    let (mut client, connection) = connect(server.mqtt_config("device_id"), &dir).await;
+
+    let state = server
+        .wait_for("device_id", TIMEOUT, |state| state.empty_cache > 0)
+        .await
+        .unwrap();
+
+    assert!(state.connected);
+    assert_eq!(state.certificates.len(), 1);
+    assert_eq!(state.device_properties, Some(Vec::new()));
+    assert!(state
+        .subscriptions
+        .contains(&"test/device_id/control/consumer/properties".to_string()));
+
+    let mut introspection = state.introspection;
+    introspection.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(
+        introspection,
+        [
+            IntrospectionEntry {
+                name: "com.example.DeviceDatastream".to_string(),
+                major: 0,
+                minor: 1,
+            },
+            IntrospectionEntry {
+                name: "com.example.DeviceProperty".to_string(),
+                major: 1,
+                minor: 0,
+            },
+            IntrospectionEntry {
+                name: "com.example.ServerProperty".to_string(),
+                major: 0,
+                minor: 2,
+            },
+        ]
+    );
+
+    let timestamp =
+        astarte_device_sdk::chrono::DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+
+    client
+        .send_individual_with_timestamp(
+            "com.example.DeviceDatastream",
+            "/sensor/value",
+            AstarteData::try_from(42.0).unwrap(),
+            timestamp,
+        )
+        .await
+        .unwrap();
+    client
+        .set_property(
+            "com.example.DeviceProperty",
+            "/name",
+            AstarteData::String("sensor".to_string()),
+        )
+        .await
+        .unwrap();
+
+    let state = server
+        .wait_for("device_id", TIMEOUT, |state| state.messages.len() == 2)
+        .await
+        .unwrap();
+
+    let value = state
+        .last_value("com.example.DeviceDatastream", "/sensor/value")
+        .unwrap();
+    assert_eq!(value.value().unwrap(), Some(Bson::Double(42.0)));
+    assert_eq!(value.timestamp().unwrap(), Some(timestamp));
+
+    let name = state
+        .last_value("com.example.DeviceProperty", "/name")
+        .unwrap();
+    assert_eq!(
+        name.value().unwrap(),
+        Some(Bson::String("sensor".to_string()))
+    );
+
+    // The device reconnects and sends the properties again
+    let mut status = client.watch_connection_status();
+    assert!(server.disconnect("device_id"));
+
+    tokio::time::timeout(
+        TIMEOUT,
+        status.wait_for(|event| event.reason == StatusReason::Reconnected),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+
+    let state = server.device("device_id").unwrap();
+    assert_eq!(state.connections, 2);
+    assert_eq!(state.empty_cache, 2);
+    assert_eq!(
+        state.device_properties,
+        Some(vec!["com.example.DeviceProperty/name".to_string()])
+    );
+    assert_eq!(state.messages_on("com.example.DeviceProperty").count(), 2);
+
+    client.disconnect().await.unwrap();
+    connection.await.unwrap().unwrap();
+
+    let state = server
+        .wait_for("device_id", TIMEOUT, |state| !state.connected)
+        .await
+        .unwrap();
+    assert_eq!(state.connections, 2);
+}
+
+#[tokio::test]
+async fn should_send_server_properties() {
+    let server = FakeServer::start("test").await.unwrap();
+    let dir = TempDir::new().unwrap();
+
+    let secret = server.register_device("device_id");
+
+    // Sent after the emptyCache
+    server
+        .set_property(
+            "device_id",
+            "com.example.ServerProperty",
+            "/led/enabled",
+            AstarteData::Boolean(true),
+        )
+        .unwrap();
+
+    let (mut client, connection) =
+        connect(server.mqtt_config_with_secret("device_id", &secret), &dir).await;
+
+    let event = recv(&client).await.unwrap();
+    assert_eq!(event.interface, "com.example.ServerProperty");
+    assert_eq!(event.path, "/led/enabled");
+    assert_eq!(
+        event.data,
+        Value::Property(Some(AstarteData::Boolean(true)))
+    );
+
+    let stored = client
+        .property("com.example.ServerProperty", "/led/enabled")
+        .await
+        .unwrap();
+    assert_eq!(stored, Some(AstarteData::Boolean(true)));
+
+    server
+        .unset_property("device_id", "com.example.ServerProperty", "/led/enabled")
+        .unwrap();
+
+    let event = recv(&client).await.unwrap();
+    assert_eq!(event.data, Value::Property(None));
+
+    client.disconnect().await.unwrap();
+    connection.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn should_reject_unknown_devices() {
+    let server = FakeServer::start("test").await.unwrap();
+
+    assert!(server.device("device_id").is_none());
+
+    let err = server
+        .send_individual(
+            "device_id",
+            "com.example.ServerDatastream",
+            "/value",
+            AstarteData::Integer(1),
+            None,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        astarte_device_sdk_fake_server::FakeServerError::NotRegistered(_)
+    ));
+
+    server.register_device("device_id");
+
+    let err = server
+        .send_individual(
+            "device_id",
+            "com.example.ServerDatastream",
+            "/value",
+            AstarteData::Integer(1),
+            None,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        astarte_device_sdk_fake_server::FakeServerError::NotConnected(_)
+    ));
+}
@@ -0,0 +1,431 @@
+/*
+ * This file is part of Astarte.
+ *
+ * Copyright 2025 SECO Mind Srl
+ *
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+#ifndef ASTARTE_DEVICE_SDK_H
+#define ASTARTE_DEVICE_SDK_H
+
+/* Generated by cbindgen from the astarte-device-sdk-ffi crate, do not edit. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Result code returned by the functions of the library.
+typedef enum {
+  // The operation completed successfully.
+  ASTARTE_RESULT_OK = 0,
+  // A NULL pointer, invalid UTF-8 string or invalid value was passed to the function.
+  ASTARTE_RESULT_INVALID_ARGUMENT,
+  // Couldn't parse or add an interface.
+  ASTARTE_RESULT_INTERFACE,
+  // The interface or mapping doesn't exist in the introspection.
+  ASTARTE_RESULT_NOT_FOUND,
+  // The data doesn't match the interface mapping.
+  ASTARTE_RESULT_VALIDATION,
+  // Couldn't access the store or the writable directory.
+  ASTARTE_RESULT_STORE,
+  // Error returned by the connection with Astarte.
+  ASTARTE_RESULT_CONNECTION,
+  // Astarte rejected the credentials of the device.
+  ASTARTE_RESULT_UNAUTHORIZED,
+  // The device is disconnected from Astarte.
+  ASTARTE_RESULT_DISCONNECTED,
+  // The operation didn't complete before the timeout.
+  ASTARTE_RESULT_TIMEOUT,
+  // An unexpected error or a panic occurred in the library.
+  ASTARTE_RESULT_INTERNAL,
+} AstarteResult;
+
+// Device connected to Astarte.
+//
+// The connection runs on a background runtime owned by the handle. The functions on the device can
+// be called from multiple threads.
+typedef struct AstarteDevice AstarteDevice;
+
+// Builder of the device.
+//
+// Create it with `astarte_device_builder_new`, configure it and pass it to
+// `astarte_device_connect`.
+typedef struct AstarteDeviceBuilder AstarteDeviceBuilder;
+
+// Configuration of the MQTT connection to Astarte.
+typedef struct {
+  // Realm of the device.
+  const char *realm;
+  // Id of the device.
+  const char *device_id;
+  // URL of the Astarte Pairing API.
+  const char *pairing_url;
+  // Credentials secret of the device, NULL to register it with the pairing token.
+  const char *credentials_secret;
+  // Pairing token used to register the device, NULL if the credentials secret is set.
+  const char *pairing_token;
+  // Ignore the TLS certificate errors, for testing only.
+  bool ignore_ssl_errors;
+} AstarteMqttConfig;
+
+// Buffer of bytes.
+typedef struct {
+  // Pointer to the bytes, can be NULL if the length is 0.
+  const uint8_t *data;
+  // Number of bytes.
+  size_t len;
+} AstarteBuffer;
+
+// Array of values.
+typedef struct {
+  // Pointer to the first element, can be NULL if the length is 0.
+  const double *data;
+  // Number of elements.
+  size_t len;
+} AstarteDoubleArray;
+
+// Array of values.
+typedef struct {
+  // Pointer to the first element, can be NULL if the length is 0.
+  const int32_t *data;
+  // Number of elements.
+  size_t len;
+} AstarteIntegerArray;
+
+// Array of values.
+typedef struct {
+  // Pointer to the first element, can be NULL if the length is 0.
+  const bool *data;
+  // Number of elements.
+  size_t len;
+} AstarteBooleanArray;
+
+// Array of values.
+typedef struct {
+  // Pointer to the first element, can be NULL if the length is 0.
+  const int64_t *data;
+  // Number of elements.
+  size_t len;
+} AstarteLongIntegerArray;
+
+// Array of NUL terminated UTF-8 strings.
+typedef struct {
+  // Pointer to the first string, can be NULL if the length is 0.
+  const char *const *data;
+  // Number of strings.
+  size_t len;
+} AstarteStringArray;
+
+// Array of values.
+typedef struct {
+  // Pointer to the first element, can be NULL if the length is 0.
+  const AstarteBuffer *data;
+  // Number of elements.
+  size_t len;
+} AstarteBinaryBlobArray;
+
+// Value of an Astarte mapping, tagged by its type.
+//
+// The date times are milliseconds since the Unix epoch in UTC.
+typedef enum {
+  // Double value, it must be a finite number.
+  ASTARTE_DATA_DOUBLE,
+  // Signed integer value.
+  ASTARTE_DATA_INTEGER,
+  // Boolean value.
+  ASTARTE_DATA_BOOLEAN,
+  // Long integer value.
+  ASTARTE_DATA_LONG_INTEGER,
+  // NUL terminated UTF-8 string.
+  ASTARTE_DATA_STRING,
+  // Binary value.
+  ASTARTE_DATA_BINARY_BLOB,
+  // Date time value.
+  ASTARTE_DATA_DATE_TIME,
+  // Double array value.
+  ASTARTE_DATA_DOUBLE_ARRAY,
+  // Integer array value.
+  ASTARTE_DATA_INTEGER_ARRAY,
+  // Boolean array value.
+  ASTARTE_DATA_BOOLEAN_ARRAY,
+  // Long integer array value.
+  ASTARTE_DATA_LONG_INTEGER_ARRAY,
+  // String array value.
+  ASTARTE_DATA_STRING_ARRAY,
+  // Binary array value.
+  ASTARTE_DATA_BINARY_BLOB_ARRAY,
+  // Date time array value.
+  ASTARTE_DATA_DATE_TIME_ARRAY,
+} AstarteData_Tag;
+
+typedef struct {
+  AstarteData_Tag tag;
+  union {
+    struct {
+      double double_;
+    };
+    struct {
+      int32_t integer;
+    };
+    struct {
+      bool boolean;
+    };
+    struct {
+      int64_t long_integer;
+    };
+    struct {
+      const char *string;
+    };
+    struct {
+      AstarteBuffer binary_blob;
+    };
+    struct {
+      int64_t date_time;
+    };
+    struct {
+      AstarteDoubleArray double_array;
+    };
+    struct {
+      AstarteIntegerArray integer_array;
+    };
+    struct {
+      AstarteBooleanArray boolean_array;
+    };
+    struct {
+      AstarteLongIntegerArray long_integer_array;
+    };
+    struct {
+      AstarteStringArray string_array;
+    };
+    struct {
+      AstarteBinaryBlobArray binary_blob_array;
+    };
+    struct {
+      AstarteLongIntegerArray date_time_array;
+    };
+  };
+} AstarteData;
+
+// Data received on an individual datastream.
+typedef struct {
+  // The received data.
+  AstarteData data;
+  // Timestamp of the data.
+  int64_t timestamp;
+} AstarteIndividualValue;
+
+// Named field of an object aggregate.
+typedef struct {
+  // NUL terminated name of the field.
+  const char *name;
+  // Value of the field.
+  AstarteData data;
+} AstarteObjectEntry;
+
+// Array of values.
+typedef struct {
+  // Pointer to the first element, can be NULL if the length is 0.
+  const AstarteObjectEntry *data;
+  // Number of elements.
+  size_t len;
+} AstarteObjectEntries;
+
+// Data received on an object datastream.
+typedef struct {
+  // Fields of the received object.
+  AstarteObjectEntries entries;
+  // Timestamp of the data.
+  int64_t timestamp;
+} AstarteObjectValue;
+
+// Value received from Astarte, tagged by the interface aggregation and type.
+//
+// The timestamps are milliseconds since the Unix epoch in UTC.
+typedef enum {
+  // Data received on an individual datastream.
+  ASTARTE_EVENT_VALUE_INDIVIDUAL,
+  // Data received on an object datastream.
+  ASTARTE_EVENT_VALUE_OBJECT,
+  // Property set by the server.
+  ASTARTE_EVENT_VALUE_PROPERTY,
+  // Property unset by the server.
+  ASTARTE_EVENT_VALUE_UNSET,
+} AstarteEventValue_Tag;
+
+typedef struct {
+  AstarteEventValue_Tag tag;
+  union {
+    struct {
+      AstarteIndividualValue individual;
+    };
+    struct {
+      AstarteObjectValue object;
+    };
+    struct {
+      AstarteData property;
+    };
+  };
+} AstarteEventValue;
+
+// Event received from Astarte.
+typedef struct {
+  // Name of the interface the event was received on.
+  const char *interface;
+  // Path of the mapping the event was received on.
+  const char *path;
+  // Received value.
+  AstarteEventValue value;
+} AstarteEvent;
+
+// Callback called for each event received from Astarte.
+//
+// It's called from a background thread and the event, with all the memory it points to, is valid
+// only for the duration of the call.
+typedef void (*AstarteEventCallback)(const AstarteEvent *event, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a new device builder.
+//
+// It must be freed with `astarte_device_builder_free` or consumed by
+// `astarte_device_connect`.
+AstarteDeviceBuilder *astarte_device_builder_new(void);
+
+// Frees a device builder that wasn't connected.
+//
+// # Safety
+//
+// The builder must be NULL or returned by `astarte_device_builder_new`, and not used after.
+void astarte_device_builder_free(AstarteDeviceBuilder *builder);
+
+// Adds an interface from its JSON definition to the device introspection.
+//
+// # Safety
+//
+// The builder must be valid and the interface a NUL terminated string.
+AstarteResult astarte_device_builder_add_interface(AstarteDeviceBuilder *builder,
+                                                   const char *interface);
+
+// Adds all the JSON interfaces in a directory to the device introspection.
+//
+// The directory is read when the device is connected.
+//
+// # Safety
+//
+// The builder must be valid and the path a NUL terminated string.
+AstarteResult astarte_device_builder_add_interface_directory(AstarteDeviceBuilder *builder,
+                                                             const char *path);
+
+// Sets the writable directory where the properties and credentials of the device are stored.
+//
+// # Safety
+//
+// The builder must be valid and the path a NUL terminated string.
+AstarteResult astarte_device_builder_store_dir(AstarteDeviceBuilder *builder, const char *path);
+
+// Configures the MQTT connection to Astarte.
+//
+// # Safety
+//
+// The builder and the configuration must be valid, with NUL terminated strings.
+AstarteResult astarte_device_builder_mqtt(AstarteDeviceBuilder *builder,
+                                          const AstarteMqttConfig *config);
+
+// Sets the callback called for each event received from Astarte.
+//
+// The callback is called from a background thread, with the given user data. The device must
+// not be disconnected from inside the callback.
+//
+// # Safety
+//
+// The builder must be valid, the callback and user data must be safe to use from another thread
+// until the device is disconnected.
+AstarteResult astarte_device_builder_event_callback(AstarteDeviceBuilder *builder,
+                                                    AstarteEventCallback callback,
+                                                    void *user_data);
+
+// Sets the timeout in milliseconds of the operations on the device.
+//
+// # Safety
+//
+// The builder must be valid.
+AstarteResult astarte_device_builder_timeout(AstarteDeviceBuilder *builder, uint64_t timeout_ms);
+
+// Builds the device and connects it to Astarte.
+//
+// The builder is always consumed, even on error. On success the device is written in `device` and
+// must be freed with `astarte_device_disconnect`.
+//
+// # Safety
+//
+// The builder must be returned by
+// `astarte_device_builder_new` and the device must
+// be a valid pointer.
+AstarteResult astarte_device_connect(AstarteDeviceBuilder *builder, AstarteDevice **device);
+
+// Disconnects the device from Astarte and frees it.
+//
+// Waits at most the timeout of the device for the connection to close, then stops it. It also
+// waits for the event callback to return, so it must not be called from inside the callback or it
+// will deadlock.
+//
+// # Safety
+//
+// The device must be NULL or returned by `astarte_device_connect`, and not used after.
+AstarteResult astarte_device_disconnect(AstarteDevice *device);
+
+// Sends an individual datastream on an interface.
+//
+// # Safety
+//
+// The device and the data must be valid, with NUL terminated strings.
+AstarteResult astarte_device_send_individual(const AstarteDevice *device,
+                                             const char *interface,
+                                             const char *path,
+                                             const AstarteData *data);
+
+// Sends an object datastream on an interface.
+//
+// # Safety
+//
+// The device must be valid and the entries an array of `len` elements, with NUL terminated
+// strings.
+AstarteResult astarte_device_send_object(const AstarteDevice *device,
+                                         const char *interface,
+                                         const char *path,
+                                         const AstarteObjectEntry *entries,
+                                         size_t len);
+
+// Sets a device property.
+//
+// # Safety
+//
+// The device and the data must be valid, with NUL terminated strings.
+AstarteResult astarte_device_set_property(const AstarteDevice *device,
+                                          const char *interface,
+                                          const char *path,
+                                          const AstarteData *data);
+
+// Unsets a device property.
+//
+// # Safety
+//
+// The device must be valid, with NUL terminated strings.
+AstarteResult astarte_device_unset_property(const AstarteDevice *device,
+                                            const char *interface,
+                                            const char *path);
+
+// Returns the message of the last error that occurred on the calling thread.
+//
+// The string is valid until the next call to a function of the library on the same thread. Returns
+// NULL if no error occurred.
+const char *astarte_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ASTARTE_DEVICE_SDK_H */
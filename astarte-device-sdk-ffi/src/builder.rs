@@ -0,0 +1,398 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Opaque handle to configure the device before connecting it.
+
+use std::{
+    ffi::{c_char, c_void},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+use astarte_device_sdk::{
+    astarte_interfaces::Interface,
+    builder::{DeviceBuilder, DEFAULT_REQUEST_TIMEOUT},
+    store::SqliteStore,
+    transport::mqtt::{Mqtt, MqttConfig},
+    DeviceClient, DeviceConnection, Error,
+};
+
+use crate::{
+    data::{mut_arg, str_arg},
+    error::{ffi_call, AstarteResult, FfiError},
+    event::{AstarteEventCallback, EventHandler},
+};
+
+/// Configuration of the MQTT connection to Astarte.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AstarteMqttConfig {
+    /// Realm of the device.
+    pub realm: *const c_char,
+    /// Id of the device.
+    pub device_id: *const c_char,
+    /// URL of the Astarte Pairing API.
+    pub pairing_url: *const c_char,
+    /// Credentials secret of the device, NULL to register it with the pairing token.
+    pub credentials_secret: *const c_char,
+    /// Pairing token used to register the device, NULL if the credentials secret is set.
+    pub pairing_token: *const c_char,
+    /// Ignore the TLS certificate errors, for testing only.
+    pub ignore_ssl_errors: bool,
+}
+
+impl AstarteMqttConfig {
+    /// Copies the C configuration into the [`MqttConfig`].
+    ///
+    /// # Safety
+    ///
+    /// All the strings must be NULL or valid, see [`str_arg`].
+    unsafe fn to_config(self) -> Result<MqttConfig, FfiError> {
+        let realm = str_arg(self.realm, "realm")?;
+        let device_id = str_arg(self.device_id, "device_id")?;
+        let pairing_url = str_arg(self.pairing_url, "pairing_url")?;
+
+        let mut config = match (
+            self.credentials_secret.is_null(),
+            self.pairing_token.is_null(),
+        ) {
+            (false, true) => {
+                let secret = str_arg(self.credentials_secret, "credentials_secret")?;
+
+                MqttConfig::with_credential_secret(realm, device_id, secret, pairing_url)
+            }
+            (true, false) => {
+                let token = str_arg(self.pairing_token, "pairing_token")?;
+
+                MqttConfig::with_pairing_token(realm, device_id, token, pairing_url)
+            }
+            _ => {
+                return Err(FfiError::invalid(
+                    "credentials_secret",
+                    "exactly one of the credentials secret or pairing token must be set",
+                ));
+            }
+        };
+
+        if self.ignore_ssl_errors {
+            config.ignore_ssl_errors();
+        }
+
+        Ok(config)
+    }
+}
+
+/// Builder of the device.
+///
+/// Create it with `astarte_device_builder_new`, configure it and pass it to
+/// `astarte_device_connect`.
+#[derive(Debug)]
+pub struct AstarteDeviceBuilder {
+    interfaces: Vec<Interface>,
+    interface_directories: Vec<PathBuf>,
+    store_dir: Option<PathBuf>,
+    mqtt: Option<MqttConfig>,
+    pub(crate) events: Option<EventHandler>,
+    pub(crate) timeout: Duration,
+}
+
+impl AstarteDeviceBuilder {
+    fn new() -> Self {
+        Self {
+            interfaces: Vec::new(),
+            interface_directories: Vec::new(),
+            store_dir: None,
+            mqtt: None,
+            events: None,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+
+    /// Builds the device with the SDK [`DeviceBuilder`].
+    pub(crate) async fn build(
+        self,
+    ) -> Result<
+        (
+            DeviceClient<Mqtt<SqliteStore>>,
+            DeviceConnection<Mqtt<SqliteStore>>,
+        ),
+        FfiError,
+    > {
+        let store_dir = self.store_dir.ok_or(FfiError::invalid(
+            "store_dir",
+            "the store directory is required",
+        ))?;
+        let mqtt = self.mqtt.ok_or(FfiError::invalid(
+            "mqtt",
+            "the MQTT configuration is required",
+        ))?;
+
+        let builder = self
+            .interfaces
+            .into_iter()
+            .try_fold(DeviceBuilder::new(), DeviceBuilder::interface)
+            .map_err(Error::from)?;
+
+        let builder = self
+            .interface_directories
+            .iter()
+            .try_fold(builder, DeviceBuilder::interface_directory)
+            .map_err(Error::from)?;
+
+        let res = builder
+            .store_dir(store_dir)
+            .await?
+            .connection(mqtt)
+            .build()
+            .await?;
+
+        Ok(res)
+    }
+}
+
+/// Creates a new device builder.
+///
+/// It must be freed with `astarte_device_builder_free` or consumed by
+/// `astarte_device_connect`.
+#[no_mangle]
+pub extern "C" fn astarte_device_builder_new() -> *mut AstarteDeviceBuilder {
+    Box::into_raw(Box::new(AstarteDeviceBuilder::new()))
+}
+
+/// Frees a device builder that wasn't connected.
+///
+/// # Safety
+///
+/// The builder must be NULL or returned by `astarte_device_builder_new`, and not used after.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_builder_free(builder: *mut AstarteDeviceBuilder) {
+    if !builder.is_null() {
+        drop(Box::from_raw(builder));
+    }
+}
+
+/// Adds an interface from its JSON definition to the device introspection.
+///
+/// # Safety
+///
+/// The builder must be valid and the interface a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_builder_add_interface(
+    builder: *mut AstarteDeviceBuilder,
+    interface: *const c_char,
+) -> AstarteResult {
+    ffi_call(|| {
+        let builder = mut_arg(builder, "builder")?;
+        let interface = str_arg(interface, "interface")?;
+
+        let interface = Interface::from_str(interface).map_err(Error::from)?;
+        builder.interfaces.push(interface);
+
+        Ok(())
+    })
+}
+
+/// Adds all the JSON interfaces in a directory to the device introspection.
+///
+/// The directory is read when the device is connected.
+///
+/// # Safety
+///
+/// The builder must be valid and the path a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_builder_add_interface_directory(
+    builder: *mut AstarteDeviceBuilder,
+    path: *const c_char,
+) -> AstarteResult {
+    ffi_call(|| {
+        let builder = mut_arg(builder, "builder")?;
+        let path = str_arg(path, "path")?;
+
+        builder.interface_directories.push(PathBuf::from(path));
+
+        Ok(())
+    })
+}
+
+/// Sets the writable directory where the properties and credentials of the device are stored.
+///
+/// # Safety
+///
+/// The builder must be valid and the path a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_builder_store_dir(
+    builder: *mut AstarteDeviceBuilder,
+    path: *const c_char,
+) -> AstarteResult {
+    ffi_call(|| {
+        let builder = mut_arg(builder, "builder")?;
+        let path = str_arg(path, "path")?;
+
+        builder.store_dir = Some(PathBuf::from(path));
+
+        Ok(())
+    })
+}
+
+/// Configures the MQTT connection to Astarte.
+///
+/// # Safety
+///
+/// The builder and the configuration must be valid, with NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_builder_mqtt(
+    builder: *mut AstarteDeviceBuilder,
+    config: *const AstarteMqttConfig,
+) -> AstarteResult {
+    ffi_call(|| {
+        let builder = mut_arg(builder, "builder")?;
+        if config.is_null() {
+            return Err(FfiError::null("config"));
+        }
+
+        builder.mqtt = Some((*config).to_config()?);
+
+        Ok(())
+    })
+}
+
+/// Sets the callback called for each event received from Astarte.
+///
+/// The callback is called from a background thread, with the given user data. The device must
+/// not be disconnected from inside the callback.
+///
+/// # Safety
+///
+/// The builder must be valid, the callback and user data must be safe to use from another thread
+/// until the device is disconnected.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_builder_event_callback(
+    builder: *mut AstarteDeviceBuilder,
+    callback: AstarteEventCallback,
+    user_data: *mut c_void,
+) -> AstarteResult {
+    ffi_call(|| {
+        let builder = mut_arg(builder, "builder")?;
+
+        builder.events = EventHandler::new(callback, user_data);
+
+        Ok(())
+    })
+}
+
+/// Sets the timeout in milliseconds of the operations on the device.
+///
+/// # Safety
+///
+/// The builder must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_builder_timeout(
+    builder: *mut AstarteDeviceBuilder,
+    timeout_ms: u64,
+) -> AstarteResult {
+    ffi_call(|| {
+        let builder = mut_arg(builder, "builder")?;
+
+        builder.timeout = Duration::from_millis(timeout_ms);
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const INTERFACE: &str = r#"{
+    "interface_name": "com.example.Sensor",
+    "version_major": 0,
+    "version_minor": 1,
+    "type": "datastream",
+    "ownership": "device",
+    "mappings": [
+        {
+            "endpoint": "/value",
+            "type": "double"
+        }
+    ]
+}"#;
+
+    #[test]
+    fn should_configure_the_builder() {
+        let builder = astarte_device_builder_new();
+
+        let interface = std::ffi::CString::new(INTERFACE).unwrap();
+        let invalid = c"{}";
+        let realm = c"realm";
+        let device_id = c"device_id";
+        let pairing_url = c"http://localhost:4003";
+        let secret = c"secret";
+
+        let mut config = AstarteMqttConfig {
+            realm: realm.as_ptr(),
+            device_id: device_id.as_ptr(),
+            pairing_url: pairing_url.as_ptr(),
+            credentials_secret: secret.as_ptr(),
+            pairing_token: ptr::null(),
+            ignore_ssl_errors: false,
+        };
+
+        // SAFETY: the pointers are valid
+        unsafe {
+            let res = astarte_device_builder_add_interface(builder, interface.as_ptr());
+            assert_eq!(res, AstarteResult::Ok);
+
+            let res = astarte_device_builder_add_interface(builder, invalid.as_ptr());
+            assert_eq!(res, AstarteResult::Interface);
+
+            let res = astarte_device_builder_mqtt(builder, &config);
+            assert_eq!(res, AstarteResult::Ok);
+
+            config.pairing_token = secret.as_ptr();
+            let res = astarte_device_builder_mqtt(builder, &config);
+            assert_eq!(res, AstarteResult::InvalidArgument);
+
+            let res = astarte_device_builder_store_dir(builder, ptr::null());
+            assert_eq!(res, AstarteResult::InvalidArgument);
+
+            let res = astarte_device_builder_timeout(builder, 100);
+            assert_eq!(res, AstarteResult::Ok);
+
+            let b = &*builder;
+            assert_eq!(b.interfaces.len(), 1);
+            assert!(b.mqtt.is_some());
+            assert_eq!(b.timeout, Duration::from_millis(100));
+
+            astarte_device_builder_free(builder);
+        }
+    }
+
+    #[test]
+    fn should_reject_null_builder() {
+        let path = c"/tmp";
+
+        // SAFETY: NULL pointers are checked
+        let res = unsafe { astarte_device_builder_store_dir(ptr::null_mut(), path.as_ptr()) };
+        assert_eq!(res, AstarteResult::InvalidArgument);
+    }
+}
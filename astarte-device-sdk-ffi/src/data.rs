@@ -0,0 +1,459 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tagged C representation of the Astarte data types.
+
+use std::{
+    any::Any,
+    ffi::{c_char, CStr, CString},
+    ptr, slice,
+};
+
+use astarte_device_sdk::{
+    aggregate::AstarteObject,
+    chrono::{DateTime, Utc},
+    types::AstarteData as SdkData,
+};
+use tracing::warn;
+
+use crate::error::FfiError;
+
+/// Buffer of bytes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AstarteBuffer {
+    /// Pointer to the bytes, can be NULL if the length is 0.
+    pub data: *const u8,
+    /// Number of bytes.
+    pub len: usize,
+}
+
+/// Array of values.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AstarteArray<T> {
+    /// Pointer to the first element, can be NULL if the length is 0.
+    pub data: *const T,
+    /// Number of elements.
+    pub len: usize,
+}
+
+/// Array of NUL terminated UTF-8 strings.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AstarteStringArray {
+    /// Pointer to the first string, can be NULL if the length is 0.
+    pub data: *const *const c_char,
+    /// Number of strings.
+    pub len: usize,
+}
+
+/// Value of an Astarte mapping, tagged by its type.
+///
+/// The date times are milliseconds since the Unix epoch in UTC.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub enum AstarteData {
+    /// Double value, it must be a finite number.
+    Double(f64),
+    /// Signed integer value.
+    Integer(i32),
+    /// Boolean value.
+    Boolean(bool),
+    /// Long integer value.
+    LongInteger(i64),
+    /// NUL terminated UTF-8 string.
+    String(*const c_char),
+    /// Binary value.
+    BinaryBlob(AstarteBuffer),
+    /// Date time value.
+    DateTime(i64),
+    /// Double array value.
+    DoubleArray(AstarteArray<f64>),
+    /// Integer array value.
+    IntegerArray(AstarteArray<i32>),
+    /// Boolean array value.
+    BooleanArray(AstarteArray<bool>),
+    /// Long integer array value.
+    LongIntegerArray(AstarteArray<i64>),
+    /// String array value.
+    StringArray(AstarteStringArray),
+    /// Binary array value.
+    BinaryBlobArray(AstarteArray<AstarteBuffer>),
+    /// Date time array value.
+    DateTimeArray(AstarteArray<i64>),
+}
+
+/// Named field of an object aggregate.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AstarteObjectEntry {
+    /// NUL terminated name of the field.
+    pub name: *const c_char,
+    /// Value of the field.
+    pub data: AstarteData,
+}
+
+/// Borrows a NUL terminated UTF-8 string.
+///
+/// # Safety
+///
+/// The pointer must be NULL or point to a NUL terminated string valid for the lifetime `'a`.
+pub(crate) unsafe fn str_arg<'a>(
+    ptr: *const c_char,
+    name: &'static str,
+) -> Result<&'a str, FfiError> {
+    if ptr.is_null() {
+        return Err(FfiError::null(name));
+    }
+
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| FfiError::invalid(name, "invalid UTF-8 string"))
+}
+
+/// Borrows an array passed by pointer and length.
+///
+/// # Safety
+///
+/// The pointer must be NULL with a length of 0, or point to `len` initialized elements valid for
+/// the lifetime `'a`.
+pub(crate) unsafe fn slice_arg<'a, T>(
+    data: *const T,
+    len: usize,
+    name: &'static str,
+) -> Result<&'a [T], FfiError> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+
+    if data.is_null() {
+        return Err(FfiError::null(name));
+    }
+
+    Ok(slice::from_raw_parts(data, len))
+}
+
+/// Borrows an opaque handle passed by the user.
+///
+/// # Safety
+///
+/// The pointer must be NULL or point to a valid handle not used by another thread.
+pub(crate) unsafe fn mut_arg<'a, T>(
+    ptr: *mut T,
+    name: &'static str,
+) -> Result<&'a mut T, FfiError> {
+    ptr.as_mut().ok_or(FfiError::null(name))
+}
+
+/// Borrows an opaque handle passed by the user.
+///
+/// # Safety
+///
+/// The pointer must be NULL or point to a valid handle.
+pub(crate) unsafe fn ref_arg<'a, T>(ptr: *const T, name: &'static str) -> Result<&'a T, FfiError> {
+    ptr.as_ref().ok_or(FfiError::null(name))
+}
+
+fn timestamp(millis: i64) -> Result<DateTime<Utc>, FfiError> {
+    DateTime::from_timestamp_millis(millis)
+        .ok_or(FfiError::invalid("data", "date time out of range"))
+}
+
+impl AstarteData {
+    /// Copies the C value into the SDK type.
+    ///
+    /// # Safety
+    ///
+    /// All the pointers in the value must be valid, see [`str_arg`] and [`slice_arg`].
+    pub(crate) unsafe fn to_sdk(self) -> Result<SdkData, FfiError> {
+        let value = match self {
+            AstarteData::Double(value) => SdkData::try_from(value)?,
+            AstarteData::Integer(value) => SdkData::Integer(value),
+            AstarteData::Boolean(value) => SdkData::Boolean(value),
+            AstarteData::LongInteger(value) => SdkData::LongInteger(value),
+            AstarteData::String(value) => SdkData::String(str_arg(value, "data")?.to_string()),
+            AstarteData::BinaryBlob(value) => SdkData::BinaryBlob(value.to_vec()?),
+            AstarteData::DateTime(value) => SdkData::DateTime(timestamp(value)?),
+            AstarteData::DoubleArray(value) => SdkData::try_from(value.as_slice()?.to_vec())?,
+            AstarteData::IntegerArray(value) => SdkData::IntegerArray(value.as_slice()?.to_vec()),
+            AstarteData::BooleanArray(value) => SdkData::BooleanArray(value.as_slice()?.to_vec()),
+            AstarteData::LongIntegerArray(value) => {
+                SdkData::LongIntegerArray(value.as_slice()?.to_vec())
+            }
+            AstarteData::StringArray(value) => {
+                let strings = slice_arg(value.data, value.len, "data")?
+                    .iter()
+                    .map(|s| str_arg(*s, "data").map(str::to_string))
+                    .collect::<Result<_, _>>()?;
+
+                SdkData::StringArray(strings)
+            }
+            AstarteData::BinaryBlobArray(value) => {
+                let blobs = value
+                    .as_slice()?
+                    .iter()
+                    .map(|b| b.to_vec())
+                    .collect::<Result<_, _>>()?;
+
+                SdkData::BinaryBlobArray(blobs)
+            }
+            AstarteData::DateTimeArray(value) => {
+                let times = value
+                    .as_slice()?
+                    .iter()
+                    .map(|t| timestamp(*t))
+                    .collect::<Result<_, _>>()?;
+
+                SdkData::DateTimeArray(times)
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+impl AstarteBuffer {
+    unsafe fn to_vec(self) -> Result<Vec<u8>, FfiError> {
+        slice_arg(self.data, self.len, "data").map(<[u8]>::to_vec)
+    }
+}
+
+impl<T> AstarteArray<T> {
+    unsafe fn as_slice<'a>(&self) -> Result<&'a [T], FfiError> {
+        slice_arg(self.data, self.len, "data")
+    }
+}
+
+/// Copies the C object entries into an [`AstarteObject`].
+///
+/// # Safety
+///
+/// The entries must be valid for `len` elements, see [`slice_arg`] and [`AstarteData::to_sdk`].
+pub(crate) unsafe fn object_to_sdk(
+    entries: *const AstarteObjectEntry,
+    len: usize,
+) -> Result<AstarteObject, FfiError> {
+    slice_arg(entries, len, "entries")?
+        .iter()
+        .map(|entry| {
+            let name = str_arg(entry.name, "name")?.to_string();
+            let data = entry.data.to_sdk()?;
+
+            Ok((name, data))
+        })
+        .collect()
+}
+
+/// Owns the memory the C values returned to the user point to.
+///
+/// The values are valid as long as the arena is alive.
+#[derive(Default)]
+pub(crate) struct Arena {
+    allocations: Vec<Box<dyn Any>>,
+}
+
+impl Arena {
+    /// Keeps the slice alive and returns a pointer to it.
+    fn keep<T>(&mut self, values: Vec<T>) -> AstarteArray<T>
+    where
+        T: 'static,
+    {
+        let values = values.into_boxed_slice();
+        let array = AstarteArray {
+            data: if values.is_empty() {
+                ptr::null()
+            } else {
+                values.as_ptr()
+            },
+            len: values.len(),
+        };
+
+        // Moving the box doesn't move the allocation the pointer refers to
+        self.allocations.push(Box::new(values));
+
+        array
+    }
+
+    pub(crate) fn string(&mut self, value: String) -> *const c_char {
+        let value = CString::new(value).unwrap_or_else(|err| {
+            warn!("string with an interior NUL byte, truncating it");
+
+            let pos = err.nul_position();
+            let mut bytes = err.into_vec();
+            bytes.truncate(pos);
+
+            // The bytes don't contain a NUL anymore
+            CString::new(bytes).unwrap_or_default()
+        });
+
+        let ptr = value.as_ptr();
+        self.allocations.push(Box::new(value));
+
+        ptr
+    }
+
+    fn buffer(&mut self, value: Vec<u8>) -> AstarteBuffer {
+        let AstarteArray { data, len } = self.keep(value);
+
+        AstarteBuffer { data, len }
+    }
+
+    /// Converts the value to its C representation, keeping alive the memory it points to.
+    pub(crate) fn data(&mut self, value: SdkData) -> AstarteData {
+        match value {
+            SdkData::Double(value) => AstarteData::Double(value.into()),
+            SdkData::Integer(value) => AstarteData::Integer(value),
+            SdkData::Boolean(value) => AstarteData::Boolean(value),
+            SdkData::LongInteger(value) => AstarteData::LongInteger(value),
+            SdkData::String(value) => AstarteData::String(self.string(value)),
+            SdkData::BinaryBlob(value) => AstarteData::BinaryBlob(self.buffer(value)),
+            SdkData::DateTime(value) => AstarteData::DateTime(value.timestamp_millis()),
+            SdkData::DoubleArray(value) => {
+                AstarteData::DoubleArray(self.keep(value.into_iter().map(f64::from).collect()))
+            }
+            SdkData::IntegerArray(value) => AstarteData::IntegerArray(self.keep(value)),
+            SdkData::BooleanArray(value) => AstarteData::BooleanArray(self.keep(value)),
+            SdkData::LongIntegerArray(value) => AstarteData::LongIntegerArray(self.keep(value)),
+            SdkData::StringArray(value) => {
+                let strings = value.into_iter().map(|s| self.string(s)).collect();
+                let AstarteArray { data, len } = self.keep(strings);
+
+                AstarteData::StringArray(AstarteStringArray { data, len })
+            }
+            SdkData::BinaryBlobArray(value) => {
+                let blobs = value.into_iter().map(|b| self.buffer(b)).collect();
+
+                AstarteData::BinaryBlobArray(self.keep(blobs))
+            }
+            SdkData::DateTimeArray(value) => AstarteData::DateTimeArray(
+                self.keep(value.iter().map(|t| t.timestamp_millis()).collect()),
+            ),
+        }
+    }
+
+    /// Converts the object to the C entries, keeping alive the memory they point to.
+    pub(crate) fn object(&mut self, value: AstarteObject) -> AstarteArray<AstarteObjectEntry> {
+        let entries = value
+            .into_key_values()
+            .map(|(name, data)| AstarteObjectEntry {
+                name: self.string(name),
+                data: self.data(data),
+            })
+            .collect();
+
+        self.keep(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use astarte_device_sdk::chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn all_values() -> Vec<SdkData> {
+        let date = Utc.timestamp_millis_opt(1_700_000_000_123).unwrap();
+
+        vec![
+            SdkData::try_from(4.2).unwrap(),
+            SdkData::Integer(-42),
+            SdkData::Boolean(true),
+            SdkData::LongInteger(i64::MAX),
+            SdkData::String("hello".to_string()),
+            SdkData::BinaryBlob(vec![1, 2, 3]),
+            SdkData::BinaryBlob(Vec::new()),
+            SdkData::DateTime(date),
+            SdkData::try_from(vec![1.0, 2.5]).unwrap(),
+            SdkData::IntegerArray(vec![1, 2]),
+            SdkData::BooleanArray(vec![true, false]),
+            SdkData::LongIntegerArray(vec![1, -2]),
+            SdkData::StringArray(vec!["a".to_string(), "b".to_string()]),
+            SdkData::BinaryBlobArray(vec![vec![1], Vec::new()]),
+            SdkData::DateTimeArray(vec![date, date]),
+        ]
+    }
+
+    #[test]
+    fn should_convert_data_round_trip() {
+        let mut arena = Arena::default();
+
+        for exp in all_values() {
+            let data = arena.data(exp.clone());
+
+            // SAFETY: the arena keeps the memory alive
+            let res = unsafe { data.to_sdk() }.unwrap();
+
+            assert_eq!(res, exp);
+        }
+    }
+
+    #[test]
+    fn should_convert_object() {
+        let exp = AstarteObject::from_iter([
+            ("name".to_string(), SdkData::String("sensor".to_string())),
+            ("value".to_string(), SdkData::Integer(3)),
+        ]);
+
+        let mut arena = Arena::default();
+        let entries = arena.object(exp.clone());
+
+        // SAFETY: the arena keeps the memory alive
+        let res = unsafe { object_to_sdk(entries.data, entries.len) }.unwrap();
+
+        assert_eq!(res, exp);
+    }
+
+    #[test]
+    fn should_reject_invalid_data() {
+        let data = AstarteData::String(ptr::null());
+        // SAFETY: NULL pointers are checked
+        let err = unsafe { data.to_sdk() }.unwrap_err();
+        assert!(matches!(err, FfiError::InvalidArgument { .. }));
+
+        let data = AstarteData::IntegerArray(AstarteArray {
+            data: ptr::null(),
+            len: 2,
+        });
+        // SAFETY: NULL pointers are checked
+        let err = unsafe { data.to_sdk() }.unwrap_err();
+        assert!(matches!(err, FfiError::InvalidArgument { .. }));
+
+        let data = AstarteData::Double(f64::NAN);
+        // SAFETY: no pointers
+        let err = unsafe { data.to_sdk() }.unwrap_err();
+        assert!(matches!(err, FfiError::Type(_)));
+
+        let data = AstarteData::DateTime(i64::MAX);
+        // SAFETY: no pointers
+        let err = unsafe { data.to_sdk() }.unwrap_err();
+        assert!(matches!(err, FfiError::InvalidArgument { .. }));
+    }
+
+    #[test]
+    fn should_truncate_interior_nul() {
+        let mut arena = Arena::default();
+
+        let ptr = arena.string("abc\0def".to_string());
+
+        // SAFETY: the arena keeps the string alive
+        let res = unsafe { str_arg(ptr, "str") }.unwrap();
+        assert_eq!(res, "abc");
+    }
+}
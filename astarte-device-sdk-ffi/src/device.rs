@@ -0,0 +1,351 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Opaque handle to a connected device.
+
+use std::{ffi::c_char, future::Future, thread, time::Duration};
+
+use astarte_device_sdk::{
+    client::{ClientDisconnect, RecvError},
+    store::SqliteStore,
+    transport::mqtt::Mqtt,
+    Client, DeviceClient, Error, EventLoop,
+};
+use tokio::{runtime::Runtime, task::JoinHandle};
+use tracing::{debug, error, warn};
+
+use crate::{
+    builder::AstarteDeviceBuilder,
+    data::{object_to_sdk, ref_arg, str_arg, AstarteData, AstarteObjectEntry},
+    error::{ffi_call, AstarteResult, FfiError},
+    event::EventHandler,
+};
+
+/// Device connected to Astarte.
+///
+/// The connection runs on a background runtime owned by the handle. The functions on the device can
+/// be called from multiple threads.
+pub struct AstarteDevice {
+    runtime: Runtime,
+    client: DeviceClient<Mqtt<SqliteStore>>,
+    connection: Option<JoinHandle<Result<(), Error>>>,
+    events: Option<thread::JoinHandle<()>>,
+    timeout: Duration,
+}
+
+impl AstarteDevice {
+    fn connect(mut builder: AstarteDeviceBuilder) -> Result<Self, FfiError> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("astarte-device")
+            .build()
+            .map_err(FfiError::Runtime)?;
+
+        let timeout = builder.timeout;
+        let events = builder.events.take();
+
+        let (client, connection) = runtime.block_on(builder.build())?;
+
+        let connection = runtime.spawn(connection.handle_events());
+
+        let events = events
+            .map(|handler| {
+                let client = client.clone();
+                let handle = runtime.handle().clone();
+
+                thread::Builder::new()
+                    .name("astarte-events".to_string())
+                    .spawn(move || receive_events(&handle, &client, &handler))
+                    .map_err(FfiError::Runtime)
+            })
+            .transpose()?;
+
+        Ok(Self {
+            runtime,
+            client,
+            connection: Some(connection),
+            events,
+            timeout,
+        })
+    }
+
+    /// Runs the future on the runtime, waiting at most the timeout.
+    fn block_on<F, T>(&self, fut: F) -> Result<T, FfiError>
+    where
+        F: Future<Output = Result<T, Error>>,
+    {
+        self.runtime
+            .block_on(async { tokio::time::timeout(self.timeout, fut).await })
+            .map_err(|_| FfiError::Timeout(self.timeout))?
+            .map_err(FfiError::from)
+    }
+
+    fn disconnect(mut self) -> Result<(), FfiError> {
+        let mut client = self.client.clone();
+        let res = self.block_on(client.disconnect());
+
+        if let Some(mut connection) = self.connection.take() {
+            let res = self.runtime.block_on(async {
+                match tokio::time::timeout(self.timeout, &mut connection).await {
+                    Ok(res) => res,
+                    Err(_) => {
+                        warn!("the connection didn't exit before the timeout, aborting it");
+
+                        connection.abort();
+
+                        connection.await
+                    }
+                }
+            });
+
+            match res {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => error!(error = %err, "the connection exited with an error"),
+                Err(err) if err.is_cancelled() => debug!("the connection task was aborted"),
+                Err(err) => error!(error = %err, "the connection task panicked"),
+            }
+        }
+
+        // The events receiver exits once the connection is closed or aborted
+        if let Some(events) = self.events.take() {
+            if events.join().is_err() {
+                error!("the event callback panicked");
+            }
+        }
+
+        res
+    }
+}
+
+impl std::fmt::Debug for AstarteDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AstarteDevice")
+            .field("timeout", &self.timeout)
+            .field("events", &self.events.is_some())
+            .finish()
+    }
+}
+
+/// Receives the events until the device is disconnected.
+fn receive_events(
+    handle: &tokio::runtime::Handle,
+    client: &DeviceClient<Mqtt<SqliteStore>>,
+    handler: &EventHandler,
+) {
+    loop {
+        match handle.block_on(client.recv()) {
+            Ok(event) => handler.call(event),
+            Err(RecvError::Disconnected) => {
+                debug!("device disconnected, stop receiving events");
+
+                break;
+            }
+            Err(err) => error!(error = %err, "couldn't receive the event"),
+        }
+    }
+}
+
+/// Builds the device and connects it to Astarte.
+///
+/// The builder is always consumed, even on error. On success the device is written in `device` and
+/// must be freed with `astarte_device_disconnect`.
+///
+/// # Safety
+///
+/// The builder must be returned by
+/// `astarte_device_builder_new` and the device must
+/// be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_connect(
+    builder: *mut AstarteDeviceBuilder,
+    device: *mut *mut AstarteDevice,
+) -> AstarteResult {
+    ffi_call(|| {
+        if builder.is_null() {
+            return Err(FfiError::null("builder"));
+        }
+
+        let builder = *Box::from_raw(builder);
+
+        if device.is_null() {
+            return Err(FfiError::null("device"));
+        }
+
+        let connected = AstarteDevice::connect(builder)?;
+        *device = Box::into_raw(Box::new(connected));
+
+        Ok(())
+    })
+}
+
+/// Disconnects the device from Astarte and frees it.
+///
+/// Waits at most the timeout of the device for the connection to close, then stops it. It also
+/// waits for the event callback to return, so it must not be called from inside the callback or it
+/// will deadlock.
+///
+/// # Safety
+///
+/// The device must be NULL or returned by `astarte_device_connect`, and not used after.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_disconnect(device: *mut AstarteDevice) -> AstarteResult {
+    ffi_call(|| {
+        if device.is_null() {
+            return Ok(());
+        }
+
+        Box::from_raw(device).disconnect()
+    })
+}
+
+/// Sends an individual datastream on an interface.
+///
+/// # Safety
+///
+/// The device and the data must be valid, with NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_send_individual(
+    device: *const AstarteDevice,
+    interface: *const c_char,
+    path: *const c_char,
+    data: *const AstarteData,
+) -> AstarteResult {
+    ffi_call(|| {
+        let device = ref_arg(device, "device")?;
+        let interface = str_arg(interface, "interface")?;
+        let path = str_arg(path, "path")?;
+        let data = ref_arg(data, "data")?.to_sdk()?;
+
+        let mut client = device.client.clone();
+
+        device.block_on(client.send_individual(interface, path, data))
+    })
+}
+
+/// Sends an object datastream on an interface.
+///
+/// # Safety
+///
+/// The device must be valid and the entries an array of `len` elements, with NUL terminated
+/// strings.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_send_object(
+    device: *const AstarteDevice,
+    interface: *const c_char,
+    path: *const c_char,
+    entries: *const AstarteObjectEntry,
+    len: usize,
+) -> AstarteResult {
+    ffi_call(|| {
+        let device = ref_arg(device, "device")?;
+        let interface = str_arg(interface, "interface")?;
+        let path = str_arg(path, "path")?;
+        let data = object_to_sdk(entries, len)?;
+
+        let mut client = device.client.clone();
+
+        device.block_on(client.send_object(interface, path, data))
+    })
+}
+
+/// Sets a device property.
+///
+/// # Safety
+///
+/// The device and the data must be valid, with NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_set_property(
+    device: *const AstarteDevice,
+    interface: *const c_char,
+    path: *const c_char,
+    data: *const AstarteData,
+) -> AstarteResult {
+    ffi_call(|| {
+        let device = ref_arg(device, "device")?;
+        let interface = str_arg(interface, "interface")?;
+        let path = str_arg(path, "path")?;
+        let data = ref_arg(data, "data")?.to_sdk()?;
+
+        let mut client = device.client.clone();
+
+        device.block_on(client.set_property(interface, path, data))
+    })
+}
+
+/// Unsets a device property.
+///
+/// # Safety
+///
+/// The device must be valid, with NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn astarte_device_unset_property(
+    device: *const AstarteDevice,
+    interface: *const c_char,
+    path: *const c_char,
+) -> AstarteResult {
+    ffi_call(|| {
+        let device = ref_arg(device, "device")?;
+        let interface = str_arg(interface, "interface")?;
+        let path = str_arg(path, "path")?;
+
+        let mut client = device.client.clone();
+
+        device.block_on(client.unset_property(interface, path))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_consume_builder_on_error() {
+        let builder = crate::builder::astarte_device_builder_new();
+        let mut device = ptr::null_mut();
+
+        // SAFETY: the builder is valid and consumed
+        let res = unsafe { astarte_device_connect(builder, &mut device) };
+
+        // The store directory is missing
+        assert_eq!(res, AstarteResult::InvalidArgument);
+        assert!(device.is_null());
+    }
+
+    #[test]
+    fn should_reject_null_device() {
+        let data = AstarteData::Integer(1);
+
+        // SAFETY: NULL pointers are checked
+        unsafe {
+            let res = astarte_device_send_individual(
+                ptr::null(),
+                c"com.example.Sensor".as_ptr(),
+                c"/value".as_ptr(),
+                &data,
+            );
+            assert_eq!(res, AstarteResult::InvalidArgument);
+
+            let res = astarte_device_disconnect(ptr::null_mut());
+            assert_eq!(res, AstarteResult::Ok);
+        }
+    }
+}
@@ -0,0 +1,249 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Error codes returned by the C functions.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CString},
+    fmt::Write,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+    time::Duration,
+};
+
+use astarte_device_sdk::{builder::BuilderError, types::TypeError, Error};
+use tracing::error;
+
+thread_local! {
+    /// Message of the last error that occurred on the calling thread.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Result code returned by the functions of the library.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AstarteResult {
+    /// The operation completed successfully.
+    Ok = 0,
+    /// A NULL pointer, invalid UTF-8 string or invalid value was passed to the function.
+    InvalidArgument,
+    /// Couldn't parse or add an interface.
+    Interface,
+    /// The interface or mapping doesn't exist in the introspection.
+    NotFound,
+    /// The data doesn't match the interface mapping.
+    Validation,
+    /// Couldn't access the store or the writable directory.
+    Store,
+    /// Error returned by the connection with Astarte.
+    Connection,
+    /// Astarte rejected the credentials of the device.
+    Unauthorized,
+    /// The device is disconnected from Astarte.
+    Disconnected,
+    /// The operation didn't complete before the timeout.
+    Timeout,
+    /// An unexpected error or a panic occurred in the library.
+    Internal,
+}
+
+impl From<&Error> for AstarteResult {
+    fn from(value: &Error) -> Self {
+        match value {
+            Error::Interface(_) | Error::AddInterface(_) => AstarteResult::Interface,
+            Error::InterfaceNotFound { .. } | Error::MappingNotFound { .. } => {
+                AstarteResult::NotFound
+            }
+            Error::InvalidEndpoint(_)
+            | Error::Types(_)
+            | Error::Validation(_)
+            | Error::Aggregation(_)
+            | Error::InterfaceType(_) => AstarteResult::Validation,
+            Error::Store(_) | Error::Retention(_) | Error::Session(_) => AstarteResult::Store,
+            Error::ConnectionTimeout | Error::Mqtt(_) => AstarteResult::Connection,
+            Error::Unauthorized(_) => AstarteResult::Unauthorized,
+            Error::Disconnected => AstarteResult::Disconnected,
+            _ => AstarteResult::Internal,
+        }
+    }
+}
+
+/// Error of a function call, converted to an [`AstarteResult`] at the FFI boundary.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum FfiError {
+    /// Invalid argument passed to the function.
+    #[error("invalid argument {name}: {reason}")]
+    InvalidArgument {
+        name: &'static str,
+        reason: &'static str,
+    },
+    /// The operation timed out.
+    #[error("operation timed out after {0:?}")]
+    Timeout(Duration),
+    /// Couldn't start the runtime or a thread.
+    #[error("couldn't start the runtime")]
+    Runtime(#[source] std::io::Error),
+    /// Couldn't convert the data.
+    #[error("invalid data")]
+    Type(#[from] TypeError),
+    /// Error returned by the device.
+    #[error(transparent)]
+    Device(#[from] Error),
+    /// Error returned while building the device.
+    #[error("couldn't build the device")]
+    Builder(#[from] BuilderError),
+}
+
+impl FfiError {
+    pub(crate) fn invalid(name: &'static str, reason: &'static str) -> Self {
+        Self::InvalidArgument { name, reason }
+    }
+
+    pub(crate) fn null(name: &'static str) -> Self {
+        Self::invalid(name, "NULL pointer")
+    }
+
+    fn code(&self) -> AstarteResult {
+        match self {
+            FfiError::InvalidArgument { .. } => AstarteResult::InvalidArgument,
+            FfiError::Timeout(_) => AstarteResult::Timeout,
+            FfiError::Runtime(_) => AstarteResult::Internal,
+            FfiError::Type(_) => AstarteResult::Validation,
+            FfiError::Device(err) => AstarteResult::from(err),
+            FfiError::Builder(BuilderError::Interface(_)) => AstarteResult::Interface,
+            FfiError::Builder(
+                BuilderError::Config(_)
+                | BuilderError::Io { .. }
+                | BuilderError::DirectoryMetadata { .. },
+            ) => AstarteResult::InvalidArgument,
+            FfiError::Builder(_) => AstarteResult::Store,
+        }
+    }
+}
+
+/// Formats the error with all its sources.
+fn report(err: &dyn std::error::Error) -> String {
+    let mut msg = err.to_string();
+
+    let mut source = err.source();
+    while let Some(err) = source {
+        let _ = write!(msg, ": {err}");
+
+        source = err.source();
+    }
+
+    msg
+}
+
+fn set_last_error(msg: String) {
+    // Interior NUL bytes would truncate the message, remove them
+    let msg = CString::new(msg.replace('\0', "")).unwrap_or_default();
+
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(msg));
+}
+
+/// Runs the body of an exported function, converting the error to a code.
+///
+/// The message of the error is saved to be returned by `astarte_last_error`, panics are caught
+/// to not unwind across the FFI boundary.
+pub(crate) fn ffi_call<F>(f: F) -> AstarteResult
+where
+    F: FnOnce() -> Result<(), FfiError>,
+{
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => AstarteResult::Ok,
+        Ok(Err(err)) => {
+            let msg = report(&err);
+
+            error!(error = msg, "astarte function call failed");
+
+            let code = err.code();
+            set_last_error(msg);
+
+            code
+        }
+        Err(_) => {
+            set_last_error("a panic occurred in the library".to_string());
+
+            AstarteResult::Internal
+        }
+    }
+}
+
+/// Returns the message of the last error that occurred on the calling thread.
+///
+/// The string is valid until the next call to a function of the library on the same thread. Returns
+/// NULL if no error occurred.
+#[no_mangle]
+pub extern "C" fn astarte_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |msg| msg.as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn last_error() -> String {
+        let msg = astarte_last_error();
+        assert!(!msg.is_null());
+
+        // SAFETY: the pointer is a valid CString owned by the thread local
+        unsafe { CStr::from_ptr(msg) }.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn should_map_error_codes() {
+        let res = ffi_call(|| Ok(()));
+        assert_eq!(res, AstarteResult::Ok);
+
+        let res = ffi_call(|| Err(FfiError::null("interface")));
+        assert_eq!(res, AstarteResult::InvalidArgument);
+        assert_eq!(last_error(), "invalid argument interface: NULL pointer");
+
+        let res = ffi_call(|| Err(Error::Disconnected.into()));
+        assert_eq!(res, AstarteResult::Disconnected);
+        assert_eq!(last_error(), "disconnected from Astarte");
+
+        let res = ffi_call(|| {
+            Err(Error::InterfaceNotFound {
+                name: "com.example.Missing".to_string(),
+            }
+            .into())
+        });
+        assert_eq!(res, AstarteResult::NotFound);
+
+        let res = ffi_call(|| Err(FfiError::Timeout(Duration::from_secs(1))));
+        assert_eq!(res, AstarteResult::Timeout);
+    }
+
+    #[test]
+    fn should_catch_panics() {
+        let res = ffi_call(|| panic!("boom"));
+        assert_eq!(res, AstarteResult::Internal);
+        assert_eq!(last_error(), "a panic occurred in the library");
+    }
+}
@@ -0,0 +1,199 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Events received from Astarte, passed to a C callback.
+
+use std::ffi::{c_char, c_void};
+
+use astarte_device_sdk::{DeviceEvent, Value};
+
+use crate::data::{Arena, AstarteArray, AstarteData, AstarteObjectEntry};
+
+/// Data received on an individual datastream.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AstarteIndividualValue {
+    /// The received data.
+    pub data: AstarteData,
+    /// Timestamp of the data.
+    pub timestamp: i64,
+}
+
+/// Data received on an object datastream.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AstarteObjectValue {
+    /// Fields of the received object.
+    pub entries: AstarteArray<AstarteObjectEntry>,
+    /// Timestamp of the data.
+    pub timestamp: i64,
+}
+
+/// Value received from Astarte, tagged by the interface aggregation and type.
+///
+/// The timestamps are milliseconds since the Unix epoch in UTC.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub enum AstarteEventValue {
+    /// Data received on an individual datastream.
+    Individual(AstarteIndividualValue),
+    /// Data received on an object datastream.
+    Object(AstarteObjectValue),
+    /// Property set by the server.
+    Property(AstarteData),
+    /// Property unset by the server.
+    Unset,
+}
+
+/// Event received from Astarte.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct AstarteEvent {
+    /// Name of the interface the event was received on.
+    pub interface: *const c_char,
+    /// Path of the mapping the event was received on.
+    pub path: *const c_char,
+    /// Received value.
+    pub value: AstarteEventValue,
+}
+
+/// Callback called for each event received from Astarte.
+///
+/// It's called from a background thread and the event, with all the memory it points to, is valid
+/// only for the duration of the call.
+pub type AstarteEventCallback =
+    Option<unsafe extern "C" fn(event: *const AstarteEvent, user_data: *mut c_void)>;
+
+/// Callback with the user data passed to it.
+#[derive(Debug)]
+pub(crate) struct EventHandler {
+    callback: unsafe extern "C" fn(event: *const AstarteEvent, user_data: *mut c_void),
+    user_data: *mut c_void,
+}
+
+// SAFETY: the user is required to pass a callback and data that can be used from another thread.
+unsafe impl Send for EventHandler {}
+
+impl EventHandler {
+    pub(crate) fn new(callback: AstarteEventCallback, user_data: *mut c_void) -> Option<Self> {
+        callback.map(|callback| Self {
+            callback,
+            user_data,
+        })
+    }
+
+    /// Converts the event and calls the callback with it.
+    pub(crate) fn call(&self, event: DeviceEvent) {
+        let mut arena = Arena::default();
+
+        let value = match event.data {
+            Value::Individual { data, timestamp } => {
+                AstarteEventValue::Individual(AstarteIndividualValue {
+                    data: arena.data(data),
+                    timestamp: timestamp.timestamp_millis(),
+                })
+            }
+            Value::Object { data, timestamp } => AstarteEventValue::Object(AstarteObjectValue {
+                entries: arena.object(data),
+                timestamp: timestamp.timestamp_millis(),
+            }),
+            Value::Property(Some(data)) => AstarteEventValue::Property(arena.data(data)),
+            Value::Property(None) => AstarteEventValue::Unset,
+        };
+
+        let event = AstarteEvent {
+            interface: arena.string(event.interface),
+            path: arena.string(event.path),
+            value,
+        };
+
+        // SAFETY: the event and the arena are valid for the duration of the call
+        unsafe { (self.callback)(&event, self.user_data) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use astarte_device_sdk::{chrono::Utc, AstarteData as SdkData};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct Received {
+        events: Vec<(String, String, Option<SdkData>)>,
+    }
+
+    unsafe extern "C" fn on_event(event: *const AstarteEvent, user_data: *mut c_void) {
+        let received = &mut *user_data.cast::<Received>();
+        let event = &*event;
+
+        let interface = CStr::from_ptr(event.interface)
+            .to_str()
+            .unwrap()
+            .to_string();
+        let path = CStr::from_ptr(event.path).to_str().unwrap().to_string();
+        let data = match event.value {
+            AstarteEventValue::Individual(AstarteIndividualValue { data, .. })
+            | AstarteEventValue::Property(data) => Some(data.to_sdk().unwrap()),
+            AstarteEventValue::Object(_) | AstarteEventValue::Unset => None,
+        };
+
+        received.events.push((interface, path, data));
+    }
+
+    #[test]
+    fn should_call_the_callback() {
+        let mut received = Received::default();
+
+        let handler =
+            EventHandler::new(Some(on_event), std::ptr::from_mut(&mut received).cast()).unwrap();
+
+        handler.call(DeviceEvent {
+            interface: "com.example.Individual".to_string(),
+            path: "/sensor/value".to_string(),
+            data: Value::Individual {
+                data: SdkData::Integer(42),
+                timestamp: Utc::now(),
+            },
+        });
+        handler.call(DeviceEvent {
+            interface: "com.example.Property".to_string(),
+            path: "/sensor/name".to_string(),
+            data: Value::Property(None),
+        });
+
+        assert_eq!(
+            received.events,
+            [
+                (
+                    "com.example.Individual".to_string(),
+                    "/sensor/value".to_string(),
+                    Some(SdkData::Integer(42))
+                ),
+                (
+                    "com.example.Property".to_string(),
+                    "/sensor/name".to_string(),
+                    None
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,56 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! C bindings for the Astarte Device SDK.
+//!
+//! The library is built as a `cdylib` and `staticlib`, the functions are declared in the
+//! `include/astarte_device_sdk.h` header generated with cbindgen.
+//!
+//! The devices and builders are opaque handles, the values are passed as the tagged
+//! [`AstarteData`] and every function returns an [`AstarteResult`] code, with the message of the
+//! error returned by [`astarte_last_error`].
+
+#![warn(missing_docs)]
+
+pub mod builder;
+pub mod data;
+pub mod device;
+pub mod error;
+pub mod event;
+
+pub use self::builder::AstarteDeviceBuilder;
+pub use self::data::AstarteData;
+pub use self::device::AstarteDevice;
+pub use self::error::{astarte_last_error, AstarteResult};
+pub use self::event::AstarteEvent;
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_have_an_up_to_date_header() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/astarte_device_sdk.h"));
+        let committed = include_str!("../include/astarte_device_sdk.h");
+
+        assert_eq!(
+            committed, generated,
+            "the header is outdated, copy it from the OUT_DIR of the build"
+        );
+    }
+}
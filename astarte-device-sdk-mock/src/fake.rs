@@ -0,0 +1,1233 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Stateful in memory implementation of the device client.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use astarte_device_sdk::aggregate::AstarteObject;
+use astarte_device_sdk::astarte_interfaces::interface::Retention;
+use astarte_device_sdk::astarte_interfaces::schema::Ownership;
+use astarte_device_sdk::astarte_interfaces::Interface;
+use astarte_device_sdk::client::{ClientDisconnect, RecvError, Status};
+use astarte_device_sdk::introspection::AddInterfaceError;
+use astarte_device_sdk::properties::PropAccess;
+use astarte_device_sdk::retention::{DeliveryError, DeliveryReceipt};
+use astarte_device_sdk::store::StoredProp;
+use astarte_device_sdk::{AstarteData, Client, DeviceEvent, Error, InterfaceValidator, Value};
+use async_channel::{Receiver, Sender};
+use chrono::{DateTime, Utc};
+use tokio::sync::oneshot;
+
+/// Data published by the [`FakeDeviceClient`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Published {
+    /// Name of the interface.
+    pub interface: String,
+    /// Path the data was published on.
+    pub path: String,
+    /// Published value.
+    pub value: PublishedValue,
+}
+
+/// Value published on an interface.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PublishedValue {
+    /// Individual datastream.
+    Individual {
+        /// Sent data.
+        data: AstarteData,
+        /// Explicit timestamp of the data.
+        timestamp: Option<DateTime<Utc>>,
+    },
+    /// Object datastream.
+    Object {
+        /// Sent data.
+        data: AstarteObject,
+        /// Explicit timestamp of the data.
+        timestamp: Option<DateTime<Utc>>,
+    },
+    /// Device property set.
+    Property(AstarteData),
+    /// Device property unset.
+    Unset,
+}
+
+#[derive(Debug)]
+struct State {
+    interfaces: InterfaceValidator,
+    status: Status,
+    properties: BTreeMap<(String, String), StoredProp>,
+    published: Vec<Published>,
+    queued: Vec<Queued>,
+}
+
+/// Data queued while offline, with the sender to confirm its receipt.
+#[derive(Debug)]
+struct Queued {
+    published: Published,
+    receipt: Option<oneshot::Sender<Result<(), DeliveryError>>>,
+}
+
+impl Queued {
+    /// Publishes the data, confirming the receipt.
+    fn publish(self) -> Published {
+        if let Some(receipt) = self.receipt {
+            // The receipt could have been dropped
+            let _ = receipt.send(Ok(()));
+        }
+
+        self.published
+    }
+}
+
+impl State {
+    fn new(interfaces: InterfaceValidator) -> Self {
+        Self {
+            interfaces,
+            status: Status::Connected,
+            properties: BTreeMap::new(),
+            published: Vec::new(),
+            queued: Vec::new(),
+        }
+    }
+
+    /// Publishes the data or queues it while offline.
+    ///
+    /// The receipt of the queued data is confirmed when it's published on reconnection.
+    fn publish(
+        &mut self,
+        published: Published,
+        retention: Retention,
+    ) -> Result<DeliveryReceipt, Error> {
+        match (self.status, retention) {
+            // Acknowledged by the fake broker once published, whatever the retention
+            (Status::Connected, _) => {
+                self.published.push(published);
+
+                Ok(DeliveryReceipt::resolved(Ok(())))
+            }
+            (Status::Disconnected, Retention::Discard) => {
+                Ok(DeliveryReceipt::resolved(Err(DeliveryError::Dropped)))
+            }
+            (Status::Disconnected, Retention::Volatile { .. } | Retention::Stored { .. }) => {
+                let (tx, rx) = oneshot::channel();
+
+                self.queued.push(Queued {
+                    published,
+                    receipt: Some(tx),
+                });
+
+                Ok(DeliveryReceipt::from_receiver(rx))
+            }
+            (Status::Closed, _) => Err(Error::Disconnected),
+        }
+    }
+
+    /// Publishes a property, properties are always sent on reconnection.
+    fn publish_property(&mut self, published: Published) -> Result<(), Error> {
+        match self.status {
+            Status::Connected => self.published.push(published),
+            Status::Disconnected => self.queued.push(Queued {
+                published,
+                receipt: None,
+            }),
+            Status::Closed => return Err(Error::Disconnected),
+        }
+
+        Ok(())
+    }
+
+    fn remove_props(&mut self, interface_name: &str) {
+        self.properties
+            .retain(|(interface, _), _| interface != interface_name);
+    }
+
+    fn add_interface(&mut self, interface: Interface) -> Result<bool, Error> {
+        let name = interface.interface_name().to_string();
+        let prev_major = self.interfaces.get(&name).map(Interface::version_major);
+        let major = interface.version_major();
+
+        let added = self.interfaces.add(interface)?;
+
+        if added && prev_major.is_some_and(|prev| prev != major) {
+            self.remove_props(&name);
+        }
+
+        Ok(added)
+    }
+
+    fn extend_interfaces<I>(&mut self, interfaces: I) -> Result<Vec<String>, Error>
+    where
+        I: IntoIterator<Item = Interface>,
+    {
+        let interfaces: Vec<Interface> = interfaces.into_iter().collect();
+        let major_changes: Vec<String> = interfaces
+            .iter()
+            .filter(|interface| {
+                self.interfaces
+                    .get(interface.interface_name())
+                    .is_some_and(|prev| prev.version_major() != interface.version_major())
+            })
+            .map(|interface| interface.interface_name().to_string())
+            .collect();
+
+        let added = self.interfaces.extend(interfaces)?;
+
+        for name in major_changes {
+            self.remove_props(&name);
+        }
+
+        Ok(added)
+    }
+
+    fn remove_interface(&mut self, interface_name: &str) -> bool {
+        if self.interfaces.remove(interface_name).is_none() {
+            return false;
+        }
+
+        self.remove_props(interface_name);
+        // Dropping the senders resolves the receipts as dropped
+        self.queued
+            .retain(|queued| queued.published.interface != interface_name);
+
+        true
+    }
+}
+
+/// Stateful fake of the [`DeviceClient`](astarte_device_sdk::DeviceClient).
+///
+/// It validates the data sent against the interfaces in the introspection, like the real client,
+/// and records it in memory to be checked by the tests. The events received are injected with
+/// [`inject`](Self::inject) and the connection can be toggled to simulate an offline period:
+///
+/// - publishes with retention discard are dropped, and their receipts resolve as dropped;
+/// - publishes with retention volatile or stored are queued and published on reconnection;
+/// - properties are stored and published on reconnection.
+///
+/// The publishes sent while connected are acknowledged immediately, whatever their retention.
+/// The receipts of the publishes queued while offline are confirmed on reconnection, or resolve as
+/// dropped if the interface is removed or the client is disconnected before it.
+///
+/// ```
+/// use astarte_device_sdk::astarte_interfaces::Interface;
+/// use astarte_device_sdk::{AstarteData, Client};
+/// use astarte_device_sdk_mock::fake::{FakeDeviceClient, PublishedValue};
+///
+/// # const INTERFACE: &str = r#"{
+/// #     "interface_name": "com.example.Sensor",
+/// #     "version_major": 0,
+/// #     "version_minor": 1,
+/// #     "type": "datastream",
+/// #     "ownership": "device",
+/// #     "mappings": [{ "endpoint": "/value", "type": "double" }]
+/// # }"#;
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let interface: Interface = INTERFACE.parse().unwrap();
+/// let mut client = FakeDeviceClient::with_interfaces([interface]).unwrap();
+///
+/// client
+///     .send_individual("com.example.Sensor", "/value", AstarteData::try_from(4.2).unwrap())
+///     .await
+///     .unwrap();
+///
+/// let published = client.published();
+/// assert_eq!(published[0].path, "/value");
+/// assert!(matches!(
+///     published[0].value,
+///     PublishedValue::Individual { data: AstarteData::Double(_), .. }
+/// ));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FakeDeviceClient {
+    state: Arc<Mutex<State>>,
+    events: Receiver<DeviceEvent>,
+    injector: Sender<DeviceEvent>,
+}
+
+impl FakeDeviceClient {
+    /// Creates a connected client without interfaces.
+    pub fn new() -> Self {
+        Self::with_validator(InterfaceValidator::new())
+    }
+
+    /// Creates a connected client with the given interfaces.
+    pub fn with_interfaces<I>(interfaces: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = Interface>,
+    {
+        let mut validator = InterfaceValidator::new();
+        validator.extend(interfaces)?;
+
+        Ok(Self::with_validator(validator))
+    }
+
+    fn with_validator(interfaces: InterfaceValidator) -> Self {
+        let (injector, events) = async_channel::unbounded();
+
+        Self {
+            state: Arc::new(Mutex::new(State::new(interfaces))),
+            events,
+            injector,
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // The lock is never held across a panic in the client
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Returns the data published to Astarte.
+    pub fn published(&self) -> Vec<Published> {
+        self.state().published.clone()
+    }
+
+    /// Returns and clears the data published to Astarte.
+    pub fn take_published(&self) -> Vec<Published> {
+        std::mem::take(&mut self.state().published)
+    }
+
+    /// Returns the data queued while offline, that will be published on reconnection.
+    pub fn queued(&self) -> Vec<Published> {
+        self.state()
+            .queued
+            .iter()
+            .map(|queued| queued.published.clone())
+            .collect()
+    }
+
+    /// Returns the status of the simulated connection.
+    pub fn status(&self) -> Status {
+        self.state().status
+    }
+
+    /// Simulates a disconnection from Astarte.
+    ///
+    /// It has no effect if the client was closed with [`disconnect`](ClientDisconnect::disconnect).
+    pub fn go_offline(&self) {
+        let mut state = self.state();
+
+        if state.status == Status::Connected {
+            state.status = Status::Disconnected;
+        }
+    }
+
+    /// Simulates a reconnection to Astarte, publishing the queued data.
+    ///
+    /// It has no effect if the client was closed with [`disconnect`](ClientDisconnect::disconnect).
+    pub fn go_online(&self) {
+        let mut state = self.state();
+
+        if state.status != Status::Disconnected {
+            return;
+        }
+
+        state.status = Status::Connected;
+
+        let queued = std::mem::take(&mut state.queued);
+        state
+            .published
+            .extend(queued.into_iter().map(Queued::publish));
+    }
+
+    /// Injects an event received from Astarte, to be returned by [`recv`](Client::recv).
+    ///
+    /// The server properties are stored if the interface is in the introspection, the events are
+    /// not validated to allow testing the handling of invalid data.
+    pub fn inject(&self, event: DeviceEvent) {
+        {
+            let mut state = self.state();
+            let major = state
+                .interfaces
+                .get(&event.interface)
+                .filter(|interface| interface.as_properties().is_some())
+                .map(Interface::version_major);
+
+            if let Some(interface_major) = major {
+                let key = (event.interface.clone(), event.path.clone());
+
+                match &event.data {
+                    Value::Property(Some(value)) => {
+                        let prop = StoredProp {
+                            interface: event.interface.clone(),
+                            path: event.path.clone(),
+                            value: value.clone(),
+                            interface_major,
+                            ownership: Ownership::Server,
+                        };
+
+                        state.properties.insert(key, prop);
+                    }
+                    Value::Property(None) => {
+                        state.properties.remove(&key);
+                    }
+                    Value::Individual { .. } | Value::Object { .. } => {}
+                }
+            }
+        }
+
+        // The channel is closed only on disconnect, the event is dropped like on a closed client
+        let _ = self.injector.try_send(event);
+    }
+
+    fn send_datastream(
+        &self,
+        interface: &str,
+        path: &str,
+        value: PublishedValue,
+        retention: Retention,
+    ) -> Result<DeliveryReceipt, Error> {
+        let published = Published {
+            interface: interface.to_string(),
+            path: path.to_string(),
+            value,
+        };
+
+        self.state().publish(published, retention)
+    }
+
+    fn individual(
+        &self,
+        interface: &str,
+        path: &str,
+        data: AstarteData,
+        timestamp: Option<DateTime<Utc>>,
+    ) -> Result<DeliveryReceipt, Error> {
+        let retention =
+            self.state()
+                .interfaces
+                .individual(interface, path, data.clone(), timestamp)?;
+
+        self.send_datastream(
+            interface,
+            path,
+            PublishedValue::Individual { data, timestamp },
+            retention,
+        )
+    }
+
+    fn object(
+        &self,
+        interface: &str,
+        path: &str,
+        data: AstarteObject,
+        timestamp: Option<DateTime<Utc>>,
+    ) -> Result<DeliveryReceipt, Error> {
+        let retention = self
+            .state()
+            .interfaces
+            .object(interface, path, data.clone(), timestamp)?;
+
+        self.send_datastream(
+            interface,
+            path,
+            PublishedValue::Object { data, timestamp },
+            retention,
+        )
+    }
+
+    fn filter_props<F>(&self, f: F) -> Vec<StoredProp>
+    where
+        F: Fn(&StoredProp) -> bool,
+    {
+        self.state()
+            .properties
+            .values()
+            .filter(|prop| f(prop))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Default for FakeDeviceClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client for FakeDeviceClient {
+    async fn send_object_with_timestamp(
+        &mut self,
+        interface_name: &str,
+        interface_path: &str,
+        data: AstarteObject,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), Error> {
+        self.object(interface_name, interface_path, data, Some(timestamp))
+            .map(drop)
+    }
+
+    async fn send_object(
+        &mut self,
+        interface_name: &str,
+        interface_path: &str,
+        data: AstarteObject,
+    ) -> Result<(), Error> {
+        self.object(interface_name, interface_path, data, None)
+            .map(drop)
+    }
+
+    async fn send_individual_with_timestamp(
+        &mut self,
+        interface_name: &str,
+        interface_path: &str,
+        data: AstarteData,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), Error> {
+        self.individual(interface_name, interface_path, data, Some(timestamp))
+            .map(drop)
+    }
+
+    async fn send_individual(
+        &mut self,
+        interface_name: &str,
+        interface_path: &str,
+        data: AstarteData,
+    ) -> Result<(), Error> {
+        self.individual(interface_name, interface_path, data, None)
+            .map(drop)
+    }
+
+    async fn send_individual_confirmed(
+        &mut self,
+        interface_name: &str,
+        interface_path: &str,
+        data: AstarteData,
+        timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<DeliveryReceipt, Error> {
+        self.individual(interface_name, interface_path, data, timestamp)
+    }
+
+    async fn send_object_confirmed(
+        &mut self,
+        interface_name: &str,
+        interface_path: &str,
+        data: AstarteObject,
+        timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<DeliveryReceipt, Error> {
+        self.object(interface_name, interface_path, data, timestamp)
+    }
+
+    async fn set_property(
+        &mut self,
+        interface_name: &str,
+        mapping_path: &str,
+        data: AstarteData,
+    ) -> Result<(), Error> {
+        let mut state = self.state();
+
+        let interface_major =
+            state
+                .interfaces
+                .property(interface_name, mapping_path, data.clone())?;
+
+        let key = (interface_name.to_string(), mapping_path.to_string());
+        if state
+            .properties
+            .get(&key)
+            .is_some_and(|prop| prop.value == data)
+        {
+            return Ok(());
+        }
+
+        state.properties.insert(
+            key,
+            StoredProp {
+                interface: interface_name.to_string(),
+                path: mapping_path.to_string(),
+                value: data.clone(),
+                interface_major,
+                ownership: Ownership::Device,
+            },
+        );
+
+        state.publish_property(Published {
+            interface: interface_name.to_string(),
+            path: mapping_path.to_string(),
+            value: PublishedValue::Property(data),
+        })
+    }
+
+    async fn unset_property(
+        &mut self,
+        interface_name: &str,
+        mapping_path: &str,
+    ) -> Result<(), Error> {
+        let mut state = self.state();
+
+        state.interfaces.unset(interface_name, mapping_path)?;
+
+        state
+            .properties
+            .remove(&(interface_name.to_string(), mapping_path.to_string()));
+
+        state.publish_property(Published {
+            interface: interface_name.to_string(),
+            path: mapping_path.to_string(),
+            value: PublishedValue::Unset,
+        })
+    }
+
+    async fn recv(&self) -> Result<DeviceEvent, RecvError> {
+        self.events
+            .recv()
+            .await
+            .map_err(|_| RecvError::Disconnected)
+    }
+}
+
+impl PropAccess for FakeDeviceClient {
+    async fn property(&self, interface: &str, path: &str) -> Result<Option<AstarteData>, Error> {
+        let state = self.state();
+
+        state.interfaces.property_mapping(interface, path)?;
+
+        let value = state
+            .properties
+            .get(&(interface.to_string(), path.to_string()))
+            .map(|prop| prop.value.clone());
+
+        Ok(value)
+    }
+
+    async fn interface_props(&self, interface: &str) -> Result<Vec<StoredProp>, Error> {
+        self.state().interfaces.properties(interface)?;
+
+        Ok(self.filter_props(|prop| prop.interface == interface))
+    }
+
+    async fn all_props(&self) -> Result<Vec<StoredProp>, Error> {
+        Ok(self.filter_props(|_| true))
+    }
+
+    async fn device_props(&self) -> Result<Vec<StoredProp>, Error> {
+        Ok(self.filter_props(|prop| prop.ownership == Ownership::Device))
+    }
+
+    async fn server_props(&self) -> Result<Vec<StoredProp>, Error> {
+        Ok(self.filter_props(|prop| prop.ownership == Ownership::Server))
+    }
+}
+
+impl ClientDisconnect for FakeDeviceClient {
+    async fn disconnect(&mut self) -> Result<(), Error> {
+        let mut state = self.state();
+
+        state.status = Status::Closed;
+        // The queued data will never be published
+        state.queued.clear();
+        drop(state);
+
+        self.injector.close();
+
+        Ok(())
+    }
+}
+
+impl astarte_device_sdk::introspection::DeviceIntrospection for FakeDeviceClient {
+    async fn get_interface<F, O>(&self, interface_name: &str, mut f: F) -> O
+    where
+        F: FnMut(Option<&Interface>) -> O + Send,
+    {
+        f(self.state().interfaces.get(interface_name))
+    }
+}
+
+impl astarte_device_sdk::introspection::DynamicIntrospection for FakeDeviceClient {
+    async fn add_interface(&mut self, interface: Interface) -> Result<bool, Error> {
+        self.state().add_interface(interface)
+    }
+
+    async fn extend_interfaces<I>(&mut self, interfaces: I) -> Result<Vec<String>, Error>
+    where
+        I: IntoIterator<Item = Interface> + Send,
+    {
+        self.state().extend_interfaces(interfaces)
+    }
+
+    async fn add_interface_from_file<P>(&mut self, file_path: P) -> Result<bool, Error>
+    where
+        P: AsRef<Path> + Send + Sync,
+    {
+        let path = file_path.as_ref();
+
+        let interface = std::fs::read_to_string(path).map_err(|err| AddInterfaceError::Io {
+            path: path.to_owned(),
+            backtrace: err,
+        })?;
+
+        let interface =
+            Interface::from_str(&interface).map_err(|err| AddInterfaceError::InterfaceFile {
+                path: path.to_owned(),
+                backtrace: err,
+            })?;
+
+        self.state().add_interface(interface)
+    }
+
+    async fn add_interface_from_str(&mut self, json_str: &str) -> Result<bool, Error> {
+        let interface = Interface::from_str(json_str).map_err(AddInterfaceError::Interface)?;
+
+        self.state().add_interface(interface)
+    }
+
+    async fn remove_interface(&mut self, interface_name: &str) -> Result<bool, Error> {
+        Ok(self.state().remove_interface(interface_name))
+    }
+
+    async fn remove_interfaces<I>(&mut self, interfaces_name: I) -> Result<Vec<String>, Error>
+    where
+        I: IntoIterator<Item = String> + Send,
+        I::IntoIter: Send,
+    {
+        let mut state = self.state();
+
+        let removed = interfaces_name
+            .into_iter()
+            .filter(|name| state.remove_interface(name))
+            .collect();
+
+        Ok(removed)
+    }
+}
+
+impl crate::DeviceIntrospection for FakeDeviceClient {
+    async fn get_interface<F, O>(&self, interface_name: &str, f: F) -> O
+    where
+        F: FnMut(Option<&Interface>) -> O + Send + 'static,
+        O: 'static,
+    {
+        astarte_device_sdk::introspection::DeviceIntrospection::get_interface(
+            self,
+            interface_name,
+            f,
+        )
+        .await
+    }
+}
+
+impl crate::DynamicIntrospection for FakeDeviceClient {
+    async fn add_interface(&mut self, interface: Interface) -> Result<bool, Error> {
+        astarte_device_sdk::introspection::DynamicIntrospection::add_interface(self, interface)
+            .await
+    }
+
+    async fn extend_interfaces<I>(&mut self, interfaces: I) -> Result<Vec<String>, Error>
+    where
+        I: IntoIterator<Item = Interface> + Send + 'static,
+    {
+        astarte_device_sdk::introspection::DynamicIntrospection::extend_interfaces(self, interfaces)
+            .await
+    }
+
+    async fn add_interface_from_file<P>(&mut self, file_path: P) -> Result<bool, Error>
+    where
+        P: AsRef<Path> + Send + Sync + 'static,
+    {
+        astarte_device_sdk::introspection::DynamicIntrospection::add_interface_from_file(
+            self, file_path,
+        )
+        .await
+    }
+
+    async fn add_interface_from_str(&mut self, json_str: &str) -> Result<bool, Error> {
+        astarte_device_sdk::introspection::DynamicIntrospection::add_interface_from_str(
+            self, json_str,
+        )
+        .await
+    }
+
+    async fn remove_interface(&mut self, interface_name: &str) -> Result<bool, Error> {
+        astarte_device_sdk::introspection::DynamicIntrospection::remove_interface(
+            self,
+            interface_name,
+        )
+        .await
+    }
+
+    async fn remove_interfaces<I>(&mut self, interfaces_name: I) -> Result<Vec<String>, Error>
+    where
+        I: IntoIterator<Item = String> + Send + 'static,
+        I::IntoIter: Send,
+    {
+        astarte_device_sdk::introspection::DynamicIntrospection::remove_interfaces(
+            self,
+            interfaces_name,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use astarte_device_sdk::introspection::DynamicIntrospection;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const DEVICE_DATASTREAM: &str = r#"{
+    "interface_name": "com.example.DeviceDatastream",
+    "version_major": 0,
+    "version_minor": 1,
+    "type": "datastream",
+    "ownership": "device",
+    "mappings": [
+        {
+            "endpoint": "/discard",
+            "type": "double"
+        },
+        {
+            "endpoint": "/volatile",
+            "type": "integer",
+            "retention": "volatile"
+        },
+        {
+            "endpoint": "/stored",
+            "type": "boolean",
+            "retention": "stored",
+            "explicit_timestamp": true
+        }
+    ]
+}"#;
+
+    const DEVICE_OBJECT: &str = r#"{
+    "interface_name": "com.example.DeviceObject",
+    "version_major": 0,
+    "version_minor": 1,
+    "type": "datastream",
+    "aggregation": "object",
+    "ownership": "device",
+    "mappings": [
+        {
+            "endpoint": "/sensor/value",
+            "type": "double",
+            "retention": "volatile"
+        },
+        {
+            "endpoint": "/sensor/name",
+            "type": "string",
+            "retention": "volatile"
+        }
+    ]
+}"#;
+
+    const DEVICE_PROPERTY: &str = r#"{
+    "interface_name": "com.example.DeviceProperty",
+    "version_major": 0,
+    "version_minor": 1,
+    "type": "properties",
+    "ownership": "device",
+    "mappings": [
+        {
+            "endpoint": "/%{sensor}/name",
+            "type": "string",
+            "allow_unset": true
+        }
+    ]
+}"#;
+
+    const SERVER_PROPERTY: &str = r#"{
+    "interface_name": "com.example.ServerProperty",
+    "version_major": 0,
+    "version_minor": 1,
+    "type": "properties",
+    "ownership": "server",
+    "mappings": [
+        {
+            "endpoint": "/enabled",
+            "type": "boolean"
+        }
+    ]
+}"#;
+
+    fn client() -> FakeDeviceClient {
+        let interfaces = [
+            DEVICE_DATASTREAM,
+            DEVICE_OBJECT,
+            DEVICE_PROPERTY,
+            SERVER_PROPERTY,
+        ]
+        .map(|i| Interface::from_str(i).unwrap());
+
+        FakeDeviceClient::with_interfaces(interfaces).unwrap()
+    }
+
+    fn published(interface: &str, path: &str, value: PublishedValue) -> Published {
+        Published {
+            interface: interface.to_string(),
+            path: path.to_string(),
+            value,
+        }
+    }
+
+    fn sensor_object() -> AstarteObject {
+        let mut object = AstarteObject::new();
+        object.insert("value".to_string(), AstarteData::try_from(4.2).unwrap());
+        object.insert("name".to_string(), AstarteData::String("temp".to_string()));
+
+        object
+    }
+
+    #[tokio::test]
+    async fn should_validate_and_record_sends() {
+        let mut client = client();
+        let timestamp = Utc::now();
+
+        client
+            .send_individual(
+                "com.example.DeviceDatastream",
+                "/discard",
+                AstarteData::try_from(1.0).unwrap(),
+            )
+            .await
+            .unwrap();
+        client
+            .send_individual_with_timestamp(
+                "com.example.DeviceDatastream",
+                "/stored",
+                AstarteData::Boolean(true),
+                timestamp,
+            )
+            .await
+            .unwrap();
+        client
+            .send_object("com.example.DeviceObject", "/sensor", sensor_object())
+            .await
+            .unwrap();
+
+        let err = client
+            .send_individual(
+                "com.example.DeviceDatastream",
+                "/discard",
+                AstarteData::String("1".to_string()),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Validation(_)), "{err:?}");
+
+        let err = client
+            .send_individual(
+                "com.example.DeviceDatastream",
+                "/stored",
+                AstarteData::Boolean(true),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Validation(_)), "{err:?}");
+
+        let err = client
+            .send_individual("com.example.Missing", "/value", AstarteData::Integer(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InterfaceNotFound { .. }), "{err:?}");
+
+        let err = client
+            .send_object("com.example.DeviceDatastream", "/discard", sensor_object())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Aggregation(_)), "{err:?}");
+
+        assert_eq!(
+            client.take_published(),
+            [
+                published(
+                    "com.example.DeviceDatastream",
+                    "/discard",
+                    PublishedValue::Individual {
+                        data: AstarteData::try_from(1.0).unwrap(),
+                        timestamp: None
+                    }
+                ),
+                published(
+                    "com.example.DeviceDatastream",
+                    "/stored",
+                    PublishedValue::Individual {
+                        data: AstarteData::Boolean(true),
+                        timestamp: Some(timestamp)
+                    }
+                ),
+                published(
+                    "com.example.DeviceObject",
+                    "/sensor",
+                    PublishedValue::Object {
+                        data: sensor_object(),
+                        timestamp: None
+                    }
+                ),
+            ]
+        );
+        assert!(client.published().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_queue_while_offline() {
+        let mut client = client();
+
+        client.go_offline();
+        assert_eq!(client.status(), Status::Disconnected);
+
+        let receipt = client
+            .send_individual_confirmed(
+                "com.example.DeviceDatastream",
+                "/discard",
+                AstarteData::try_from(1.0).unwrap(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(receipt.await, Err(DeliveryError::Dropped)));
+
+        let mut receipt = client
+            .send_individual_confirmed(
+                "com.example.DeviceDatastream",
+                "/volatile",
+                AstarteData::Integer(2),
+                None,
+            )
+            .await
+            .unwrap();
+        client
+            .set_property(
+                "com.example.DeviceProperty",
+                "/temp/name",
+                AstarteData::String("kitchen".to_string()),
+            )
+            .await
+            .unwrap();
+
+        assert!(client.published().is_empty());
+        assert_eq!(client.queued().len(), 2);
+        // Confirmed only once published
+        tokio::time::timeout(Duration::ZERO, &mut receipt)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            client
+                .property("com.example.DeviceProperty", "/temp/name")
+                .await
+                .unwrap(),
+            Some(AstarteData::String("kitchen".to_string()))
+        );
+
+        client.go_online();
+        assert_eq!(client.status(), Status::Connected);
+        assert!(client.queued().is_empty());
+        assert_eq!(receipt.await, Ok(()));
+        assert_eq!(
+            client.published(),
+            [
+                published(
+                    "com.example.DeviceDatastream",
+                    "/volatile",
+                    PublishedValue::Individual {
+                        data: AstarteData::Integer(2),
+                        timestamp: None
+                    }
+                ),
+                published(
+                    "com.example.DeviceProperty",
+                    "/temp/name",
+                    PublishedValue::Property(AstarteData::String("kitchen".to_string()))
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_drop_queued_receipts() {
+        let mut client = client();
+
+        client.go_offline();
+
+        let removed = client
+            .send_individual_confirmed(
+                "com.example.DeviceDatastream",
+                "/volatile",
+                AstarteData::Integer(1),
+                None,
+            )
+            .await
+            .unwrap();
+        let object = client
+            .send_object_confirmed("com.example.DeviceObject", "/sensor", sensor_object(), None)
+            .await
+            .unwrap();
+
+        client
+            .remove_interface("com.example.DeviceDatastream")
+            .await
+            .unwrap();
+        assert_eq!(removed.await, Err(DeliveryError::Dropped));
+        assert_eq!(client.queued().len(), 1);
+
+        client.disconnect().await.unwrap();
+        assert_eq!(object.await, Err(DeliveryError::Dropped));
+        assert!(client.queued().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_store_properties() {
+        let mut client = client();
+        let name = AstarteData::String("kitchen".to_string());
+
+        client
+            .set_property("com.example.DeviceProperty", "/temp/name", name.clone())
+            .await
+            .unwrap();
+        // Unchanged values are not sent again
+        client
+            .set_property("com.example.DeviceProperty", "/temp/name", name.clone())
+            .await
+            .unwrap();
+
+        let err = client
+            .set_property("com.example.ServerProperty", "/enabled", true.into())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Validation(_)), "{err:?}");
+
+        let props = client
+            .interface_props("com.example.DeviceProperty")
+            .await
+            .unwrap();
+        assert_eq!(props.len(), 1);
+        assert_eq!(props[0].value, name);
+        assert_eq!(props[0].ownership, Ownership::Device);
+
+        let err = client
+            .interface_props("com.example.DeviceDatastream")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InterfaceType(_)), "{err:?}");
+
+        client
+            .unset_property("com.example.DeviceProperty", "/temp/name")
+            .await
+            .unwrap();
+
+        assert!(client.all_props().await.unwrap().is_empty());
+        assert_eq!(
+            client.take_published(),
+            [
+                published(
+                    "com.example.DeviceProperty",
+                    "/temp/name",
+                    PublishedValue::Property(name)
+                ),
+                published(
+                    "com.example.DeviceProperty",
+                    "/temp/name",
+                    PublishedValue::Unset
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_receive_injected_events() {
+        let mut client = client();
+
+        client.inject(DeviceEvent {
+            interface: "com.example.ServerProperty".to_string(),
+            path: "/enabled".to_string(),
+            data: Value::Property(Some(AstarteData::Boolean(true))),
+        });
+
+        let event = client.recv().await.unwrap();
+        assert_eq!(event.interface, "com.example.ServerProperty");
+        assert_eq!(event.path, "/enabled");
+
+        let props = client.server_props().await.unwrap();
+        assert_eq!(props.len(), 1);
+        assert_eq!(props[0].value, AstarteData::Boolean(true));
+        assert!(client.device_props().await.unwrap().is_empty());
+
+        client.inject(DeviceEvent {
+            interface: "com.example.ServerProperty".to_string(),
+            path: "/enabled".to_string(),
+            data: Value::Property(None),
+        });
+        client.disconnect().await.unwrap();
+
+        // Events received before the disconnection are still returned
+        let event = client.recv().await.unwrap();
+        assert_eq!(event.data, Value::Property(None));
+        assert!(client.server_props().await.unwrap().is_empty());
+
+        assert!(matches!(client.recv().await, Err(RecvError::Disconnected)));
+
+        let err = client
+            .send_individual(
+                "com.example.DeviceDatastream",
+                "/volatile",
+                AstarteData::Integer(1),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Disconnected), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn should_update_the_introspection() {
+        let mut client = FakeDeviceClient::new();
+
+        assert!(client
+            .add_interface_from_str(DEVICE_PROPERTY)
+            .await
+            .unwrap());
+        assert!(!client
+            .add_interface_from_str(DEVICE_PROPERTY)
+            .await
+            .unwrap());
+
+        client
+            .set_property("com.example.DeviceProperty", "/temp/name", "a".into())
+            .await
+            .unwrap();
+
+        let added = client
+            .extend_interfaces([Interface::from_str(SERVER_PROPERTY).unwrap()])
+            .await
+            .unwrap();
+        assert_eq!(added, ["com.example.ServerProperty"]);
+
+        assert!(client
+            .remove_interface("com.example.DeviceProperty")
+            .await
+            .unwrap());
+        assert!(!client
+            .remove_interface("com.example.DeviceProperty")
+            .await
+            .unwrap());
+        assert!(client.all_props().await.unwrap().is_empty());
+
+        let err = client
+            .set_property("com.example.DeviceProperty", "/temp/name", "a".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InterfaceNotFound { .. }), "{err:?}");
+    }
+}
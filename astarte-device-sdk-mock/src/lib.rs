@@ -22,11 +22,16 @@ use astarte_device_sdk::aggregate::AstarteObject;
 use astarte_device_sdk::astarte_interfaces::Interface;
 use astarte_device_sdk::client::{ClientDisconnect, RecvError};
 use astarte_device_sdk::properties::PropAccess;
+use astarte_device_sdk::retention::DeliveryReceipt;
 use astarte_device_sdk::store::StoredProp;
 use astarte_device_sdk::transport::Connection;
 use astarte_device_sdk::{AstarteData, DeviceEvent, Error};
 use mockall::mock;
 
+pub mod fake;
+
+pub use self::fake::FakeDeviceClient;
+
 // Export public facing dependencies
 pub use mockall;
 
@@ -117,6 +122,22 @@ mock! {
             data: AstarteData,
         ) -> Result<(), Error>;
 
+        async fn send_individual_confirmed(
+            &mut self,
+            interface_name: &str,
+            interface_path: &str,
+            data: AstarteData,
+            timestamp: Option<chrono::DateTime<chrono::Utc>>,
+        ) -> Result<DeliveryReceipt, Error>;
+
+        async fn send_object_confirmed(
+            &mut self,
+            interface_name: &str,
+            interface_path: &str,
+            data: AstarteObject,
+            timestamp: Option<chrono::DateTime<chrono::Utc>>,
+        ) -> Result<DeliveryReceipt, Error>;
+
         async fn set_property(
             &mut self,
             interface_name: &str,
@@ -225,6 +246,26 @@ mod tests {
             Ok(())
         }
 
+        async fn send_individual_confirmed(
+            &mut self,
+            _interface_name: &str,
+            _interface_path: &str,
+            _data: AstarteData,
+            _timestamp: Option<chrono::DateTime<chrono::Utc>>,
+        ) -> Result<DeliveryReceipt, Error> {
+            Ok(DeliveryReceipt::resolved(Ok(())))
+        }
+
+        async fn send_object_confirmed(
+            &mut self,
+            _interface_name: &str,
+            _interface_path: &str,
+            _data: AstarteObject,
+            _timestamp: Option<chrono::DateTime<chrono::Utc>>,
+        ) -> Result<DeliveryReceipt, Error> {
+            Ok(DeliveryReceipt::resolved(Ok(())))
+        }
+
         async fn set_property(
             &mut self,
             _interface_name: &str,
@@ -0,0 +1,4 @@
+SELECT t_millis, counter
+FROM retention_publish
+WHERE
+    interface = ?;
@@ -0,0 +1,4 @@
+SELECT t_millis, counter
+FROM retention_publish
+ORDER BY t_millis ASC, counter ASC
+LIMIT ?;
@@ -236,4 +236,127 @@ mod tests {
 
         assert_eq!(value, other);
     }
+
+    #[test]
+    #[cfg(feature = "derive")]
+    fn should_derive_into_astarte_object_field_attributes() {
+        use crate::error::Error;
+        use crate::IntoAstarteObject;
+
+        // Alias the crate to the resulting macro
+        use crate::{self as astarte_device_sdk};
+
+        #[derive(Debug, Clone, Copy)]
+        struct Level(u8);
+
+        fn level_to_data(level: Level) -> Result<AstarteData, Error> {
+            let level = if level.0 > 5 { "high" } else { "low" };
+
+            Ok(AstarteData::from(level))
+        }
+
+        #[derive(IntoAstarteObject)]
+        struct Position {
+            latitude: f64,
+            longitude: f64,
+        }
+
+        #[derive(IntoAstarteObject)]
+        #[astarte_object(rename_all = "camelCase")]
+        struct Sensor {
+            #[astarte_object(rename = "sensor")]
+            sensor_name: String,
+            #[astarte_object(skip)]
+            #[allow(dead_code)]
+            cache: Vec<u8>,
+            #[astarte_object(with = level_to_data)]
+            alarm_level: Level,
+            #[astarte_object(flatten)]
+            position: Position,
+            description: Option<String>,
+            unit_label: Option<String>,
+        }
+
+        let sensor = Sensor {
+            sensor_name: "foo".to_string(),
+            cache: vec![1, 2, 3],
+            alarm_level: Level(7),
+            position: Position {
+                latitude: 45.0,
+                longitude: 7.5,
+            },
+            description: None,
+            unit_label: Some("lux".to_string()),
+        };
+
+        let object = AstarteObject::try_from(sensor).unwrap();
+
+        let expected = AstarteObject::from_iter([
+            ("sensor".to_string(), AstarteData::from("foo")),
+            ("alarmLevel".to_string(), AstarteData::from("high")),
+            ("latitude".to_string(), AstarteData::try_from(45.0).unwrap()),
+            ("longitude".to_string(), AstarteData::try_from(7.5).unwrap()),
+            ("unitLabel".to_string(), AstarteData::from("lux")),
+        ]);
+
+        assert_eq!(object, expected);
+        assert_eq!(object.get("cache"), None);
+        assert_eq!(object.get("description"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "derive")]
+    fn should_reject_duplicate_flattened_keys() {
+        use crate::error::Error;
+        use crate::IntoAstarteObject;
+
+        // Alias the crate to the resulting macro
+        use crate::{self as astarte_device_sdk};
+
+        #[derive(IntoAstarteObject)]
+        struct Position {
+            latitude: f64,
+            longitude: f64,
+        }
+
+        #[derive(IntoAstarteObject)]
+        struct Before {
+            latitude: f64,
+            #[astarte_object(flatten)]
+            position: Position,
+        }
+
+        #[derive(IntoAstarteObject)]
+        struct After {
+            #[astarte_object(flatten)]
+            position: Position,
+            #[astarte_object(rename = "longitude")]
+            other: f64,
+        }
+
+        let position = || Position {
+            latitude: 45.0,
+            longitude: 7.5,
+        };
+
+        let err = AstarteObject::try_from(Before {
+            latitude: 1.0,
+            position: position(),
+        })
+        .unwrap_err();
+        assert!(
+            matches!(&err, Error::DuplicateObjectKey(key) if key == "latitude"),
+            "{err:?}"
+        );
+
+        let err = AstarteObject::try_from(After {
+            position: position(),
+            other: 1.0,
+        })
+        .unwrap_err();
+        assert!(
+            matches!(&err, Error::DuplicateObjectKey(key) if key == "longitude"),
+            "{err:?}"
+        );
+    }
 }
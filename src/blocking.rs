@@ -0,0 +1,668 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Blocking client to use the device from synchronous code.
+//!
+//! The [`BlockingDeviceClient`] owns a Tokio runtime and runs the [`DeviceConnection`] event loop
+//! on a dedicated thread, exposing the same operations of the [`DeviceClient`] as blocking calls
+//! with a timeout.
+//!
+//! The methods must not be called from within an asynchronous runtime, or they will panic.
+//!
+//! ```no_run
+//! use astarte_device_sdk::{
+//!     blocking::BlockingDeviceClient, builder::DeviceBuilder, store::memory::MemoryStore,
+//!     transport::mqtt::MqttConfig,
+//! };
+//!
+//! let mqtt_config = MqttConfig::with_credential_secret("realm_id", "device_id", "credential_secret", "pairing_url");
+//!
+//! let builder = DeviceBuilder::new()
+//!     .store(MemoryStore::new())
+//!     .connection(mqtt_config);
+//!
+//! let client = BlockingDeviceClient::build(builder).unwrap();
+//!
+//! client.send_individual("my.interface.name", "/endpoint/path", 42.into()).unwrap();
+//!
+//! let event = client.recv().unwrap();
+//! ```
+
+use std::{
+    future::Future,
+    io,
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use astarte_interfaces::Interface;
+use tokio::{runtime::Runtime, sync::oneshot};
+use tracing::{debug, error};
+
+use crate::{
+    aggregate::AstarteObject,
+    builder::{ConnectionConfig, DeviceBuilder, DEFAULT_REQUEST_TIMEOUT},
+    client::{ClientDisconnect, RecvError, Status},
+    error::Report,
+    prelude::{DynamicIntrospection, PropAccess},
+    store::{StoreCapabilities, StoredProp},
+    transport::Connection,
+    types::AstarteData,
+    Client, DeviceClient, DeviceConnection, DeviceEvent, Error, EventLoop,
+};
+
+/// Error returned by the [`BlockingDeviceClient`].
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum BlockingError {
+    /// Couldn't create the runtime or the connection thread.
+    #[error("couldn't start the runtime")]
+    Runtime(#[source] io::Error),
+    /// The operation didn't complete before the timeout.
+    #[error("operation timed out after {0:?}")]
+    Timeout(Duration),
+    /// The operation on the client failed.
+    #[error(transparent)]
+    Client(#[from] Error),
+    /// Couldn't receive an event.
+    #[error(transparent)]
+    Recv(#[from] RecvError),
+    /// The connection thread panicked.
+    #[error("the connection thread panicked")]
+    Panicked,
+}
+
+/// Device client with blocking methods.
+///
+/// The client can be cloned to be used from multiple threads, the connection is stopped when the
+/// last clone is dropped or on [`disconnect`](Self::disconnect).
+///
+/// See the [module documentation](crate::blocking) for more information.
+pub struct BlockingDeviceClient<C>
+where
+    C: Connection,
+{
+    client: DeviceClient<C>,
+    background: Arc<Background>,
+    timeout: Duration,
+}
+
+/// Runtime and thread running the connection, shared between the clones of the client.
+struct Background {
+    /// Shared with the connection thread, so it's still available after the event loop exits.
+    runtime: Arc<Runtime>,
+    worker: Mutex<Option<Worker>>,
+}
+
+struct Worker {
+    shutdown: oneshot::Sender<()>,
+    thread: JoinHandle<Result<(), Error>>,
+}
+
+impl Background {
+    /// Stops the event loop and waits for the thread to exit.
+    fn join(&self) -> Result<(), BlockingError> {
+        let Some(Worker { shutdown, thread }) = self.lock().take() else {
+            return Ok(());
+        };
+
+        // The event loop could have already exited
+        let _ = shutdown.send(());
+
+        thread
+            .join()
+            .map_err(|_| BlockingError::Panicked)?
+            .map_err(BlockingError::Client)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Worker>> {
+        self.worker.lock().unwrap_or_else(|err| {
+            error!("connection worker mutex was poisoned");
+
+            err.into_inner()
+        })
+    }
+}
+
+impl<C> BlockingDeviceClient<C>
+where
+    C: Connection + 'static,
+    DeviceClient<C>: Client,
+{
+    /// Builds the device and starts its connection in the background.
+    ///
+    /// Each operation has a timeout of [`DEFAULT_REQUEST_TIMEOUT`], which can be changed with
+    /// [`set_timeout`](Self::set_timeout).
+    pub fn build<B, S>(builder: DeviceBuilder<B, S>) -> Result<Self, BlockingError>
+    where
+        B: ConnectionConfig<S, Conn = C>,
+        S: StoreCapabilities,
+        Error: From<B::Err>,
+        DeviceConnection<C>: EventLoop + Send + 'static,
+    {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(BlockingError::Runtime)?;
+
+        let (client, connection) = runtime.block_on(builder.build())?;
+
+        Self::spawn(runtime, client, connection.handle_events())
+    }
+
+    /// Runs the event loop on a dedicated thread driving the runtime.
+    fn spawn<F>(
+        runtime: Runtime,
+        client: DeviceClient<C>,
+        event_loop: F,
+    ) -> Result<Self, BlockingError>
+    where
+        F: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        let runtime = Arc::new(runtime);
+        let (shutdown, shutdown_rx) = oneshot::channel();
+
+        let thread = std::thread::Builder::new()
+            .name("astarte-connection".to_string())
+            .spawn({
+                let runtime = Arc::clone(&runtime);
+
+                move || {
+                    runtime.block_on(async move {
+                        tokio::select! {
+                            res = event_loop => res,
+                            _ = shutdown_rx => {
+                                debug!("shutting down the connection");
+
+                                Ok(())
+                            }
+                        }
+                    })
+                }
+            })
+            .map_err(BlockingError::Runtime)?;
+
+        Ok(Self {
+            client,
+            background: Arc::new(Background {
+                runtime,
+                worker: Mutex::new(Some(Worker { shutdown, thread })),
+            }),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+        })
+    }
+
+    /// Sets the timeout of each operation.
+    ///
+    /// It only applies to this instance, not to its clones.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Returns the timeout of each operation.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Returns a reference to the asynchronous client.
+    pub fn client(&self) -> &DeviceClient<C> {
+        &self.client
+    }
+
+    /// Runs the future on the runtime, waiting at most the timeout.
+    fn block_on<F, T, E>(&self, timeout: Duration, fut: F) -> Result<T, BlockingError>
+    where
+        F: Future<Output = Result<T, E>>,
+        BlockingError: From<E>,
+    {
+        // The runtime drives the timer itself once the connection thread has exited
+        self.background
+            .runtime
+            .block_on(async { tokio::time::timeout(timeout, fut).await })
+            .map_err(|_| BlockingError::Timeout(timeout))?
+            .map_err(BlockingError::from)
+    }
+
+    /// Returns the current connection status.
+    pub fn connection_status(&self) -> Status {
+        self.client.connection_status()
+    }
+
+    /// Send an individual datastream on an interface.
+    ///
+    /// See [`Client::send_individual`].
+    pub fn send_individual(
+        &self,
+        interface_name: &str,
+        mapping_path: &str,
+        data: AstarteData,
+    ) -> Result<(), BlockingError> {
+        let mut client = self.client.clone();
+
+        self.block_on(
+            self.timeout,
+            client.send_individual(interface_name, mapping_path, data),
+        )
+    }
+
+    /// Send an individual datastream on an interface, with an explicit timestamp.
+    ///
+    /// See [`Client::send_individual_with_timestamp`].
+    pub fn send_individual_with_timestamp(
+        &self,
+        interface_name: &str,
+        mapping_path: &str,
+        data: AstarteData,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), BlockingError> {
+        let mut client = self.client.clone();
+
+        self.block_on(
+            self.timeout,
+            client.send_individual_with_timestamp(interface_name, mapping_path, data, timestamp),
+        )
+    }
+
+    /// Send an object datastream on an interface.
+    ///
+    /// See [`Client::send_object`].
+    pub fn send_object(
+        &self,
+        interface_name: &str,
+        base_path: &str,
+        data: AstarteObject,
+    ) -> Result<(), BlockingError> {
+        let mut client = self.client.clone();
+
+        self.block_on(
+            self.timeout,
+            client.send_object(interface_name, base_path, data),
+        )
+    }
+
+    /// Send an object datastream on an interface, with an explicit timestamp.
+    ///
+    /// See [`Client::send_object_with_timestamp`].
+    pub fn send_object_with_timestamp(
+        &self,
+        interface_name: &str,
+        base_path: &str,
+        data: AstarteObject,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), BlockingError> {
+        let mut client = self.client.clone();
+
+        self.block_on(
+            self.timeout,
+            client.send_object_with_timestamp(interface_name, base_path, data, timestamp),
+        )
+    }
+
+    /// Set a device property.
+    ///
+    /// See [`Client::set_property`].
+    pub fn set_property(
+        &self,
+        interface_name: &str,
+        mapping_path: &str,
+        data: AstarteData,
+    ) -> Result<(), BlockingError> {
+        let mut client = self.client.clone();
+
+        self.block_on(
+            self.timeout,
+            client.set_property(interface_name, mapping_path, data),
+        )
+    }
+
+    /// Unset a device property.
+    ///
+    /// See [`Client::unset_property`].
+    pub fn unset_property(
+        &self,
+        interface_name: &str,
+        mapping_path: &str,
+    ) -> Result<(), BlockingError> {
+        let mut client = self.client.clone();
+
+        self.block_on(
+            self.timeout,
+            client.unset_property(interface_name, mapping_path),
+        )
+    }
+
+    /// Receives an event from Astarte, blocking until one is available.
+    ///
+    /// See [`Client::recv`].
+    pub fn recv(&self) -> Result<DeviceEvent, BlockingError> {
+        self.background
+            .runtime
+            .block_on(self.client.recv())
+            .map_err(BlockingError::from)
+    }
+
+    /// Receives an event from Astarte, waiting at most the given timeout.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<DeviceEvent, BlockingError> {
+        self.block_on(timeout, self.client.recv())
+    }
+
+    /// Get the value of a property given the interface and path.
+    ///
+    /// See [`PropAccess::property`].
+    pub fn property(
+        &self,
+        interface: &str,
+        path: &str,
+    ) -> Result<Option<AstarteData>, BlockingError> {
+        self.block_on(self.timeout, self.client.property(interface, path))
+    }
+
+    /// Get all the properties of the given interface.
+    pub fn interface_props(&self, interface: &str) -> Result<Vec<StoredProp>, BlockingError> {
+        self.block_on(self.timeout, self.client.interface_props(interface))
+    }
+
+    /// Get all the stored properties, device or server owners.
+    pub fn all_props(&self) -> Result<Vec<StoredProp>, BlockingError> {
+        self.block_on(self.timeout, self.client.all_props())
+    }
+
+    /// Get all the stored device properties.
+    pub fn device_props(&self) -> Result<Vec<StoredProp>, BlockingError> {
+        self.block_on(self.timeout, self.client.device_props())
+    }
+
+    /// Get all the stored server properties.
+    pub fn server_props(&self) -> Result<Vec<StoredProp>, BlockingError> {
+        self.block_on(self.timeout, self.client.server_props())
+    }
+
+    /// Add a new [`Interface`] to the device introspection.
+    ///
+    /// See [`DynamicIntrospection::add_interface`].
+    pub fn add_interface(&self, interface: Interface) -> Result<bool, BlockingError>
+    where
+        DeviceClient<C>: DynamicIntrospection,
+    {
+        let mut client = self.client.clone();
+
+        self.block_on(self.timeout, client.add_interface(interface))
+    }
+
+    /// Add a new interface from the provided file.
+    ///
+    /// See [`DynamicIntrospection::add_interface_from_file`].
+    pub fn add_interface_from_file<P>(&self, file_path: P) -> Result<bool, BlockingError>
+    where
+        P: AsRef<Path> + Send + Sync,
+        DeviceClient<C>: DynamicIntrospection,
+    {
+        let mut client = self.client.clone();
+
+        self.block_on(self.timeout, client.add_interface_from_file(file_path))
+    }
+
+    /// Add a new interface from a string.
+    ///
+    /// See [`DynamicIntrospection::add_interface_from_str`].
+    pub fn add_interface_from_str(&self, json_str: &str) -> Result<bool, BlockingError>
+    where
+        DeviceClient<C>: DynamicIntrospection,
+    {
+        let mut client = self.client.clone();
+
+        self.block_on(self.timeout, client.add_interface_from_str(json_str))
+    }
+
+    /// Remove the interface with the name specified as argument.
+    ///
+    /// See [`DynamicIntrospection::remove_interface`].
+    pub fn remove_interface(&self, interface_name: &str) -> Result<bool, BlockingError>
+    where
+        DeviceClient<C>: DynamicIntrospection,
+    {
+        let mut client = self.client.clone();
+
+        self.block_on(self.timeout, client.remove_interface(interface_name))
+    }
+
+    /// Cleanly disconnects the device, waiting for the connection to stop.
+    ///
+    /// The clones of the client can no longer send or receive data. Returns the error that
+    /// stopped the connection, if any.
+    pub fn disconnect(self) -> Result<(), BlockingError>
+    where
+        DeviceClient<C>: ClientDisconnect,
+    {
+        let mut client = self.client.clone();
+
+        self.block_on(self.timeout, client.disconnect())?;
+
+        self.background.join()
+    }
+}
+
+// Cannot be derived it has specific generic bounds.
+impl<C> Clone for BlockingDeviceClient<C>
+where
+    C: Connection,
+{
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            background: Arc::clone(&self.background),
+            timeout: self.timeout,
+        }
+    }
+}
+
+impl<C> std::fmt::Debug for BlockingDeviceClient<C>
+where
+    C: Connection,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockingDeviceClient")
+            .field("timeout", &self.timeout)
+            .field("running", &self.background.lock().is_some())
+            .finish()
+    }
+}
+
+impl Drop for Background {
+    fn drop(&mut self) {
+        match self.join() {
+            Ok(()) => {}
+            Err(BlockingError::Client(err)) => {
+                error!(error = %Report::new(err), "the connection exited with an error");
+            }
+            Err(err) => error!(error = %Report::new(err), "couldn't stop the connection"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use astarte_interfaces::MappingPath;
+    use chrono::Utc;
+    use pretty_assertions::assert_eq;
+
+    use crate::client::tests::mock_client;
+    use crate::event::Value;
+    use crate::store::memory::MemoryStore;
+    use crate::store::PropertyStore;
+    use crate::test::DEVICE_PROPERTIES;
+    use crate::transport::mock::{MockCon, MockSender};
+
+    use super::*;
+
+    fn blocking_client(
+        interfaces: &[&str],
+    ) -> (
+        BlockingDeviceClient<MockCon<MemoryStore>>,
+        flume::Sender<Result<DeviceEvent, RecvError>>,
+    ) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let (client, tx) = mock_client(interfaces);
+
+        let client = BlockingDeviceClient::spawn(runtime, client, std::future::pending()).unwrap();
+
+        (client, tx)
+    }
+
+    #[test]
+    fn should_recv_blocking() {
+        let (client, tx) = blocking_client(&[]);
+
+        let exp = DeviceEvent {
+            interface: "interface".to_string(),
+            path: "path".to_string(),
+            data: Value::Individual {
+                data: AstarteData::LongInteger(42),
+                timestamp: Utc::now(),
+            },
+        };
+
+        tx.send(Ok(exp.clone())).unwrap();
+
+        let event = client.recv().unwrap();
+        assert_eq!(event, exp);
+
+        let err = client.recv_timeout(Duration::from_millis(10)).unwrap_err();
+        assert!(matches!(err, BlockingError::Timeout(_)));
+    }
+
+    #[test]
+    fn should_get_property_blocking() {
+        let (client, _tx) = blocking_client(&[DEVICE_PROPERTIES]);
+
+        let interface = Interface::from_str(DEVICE_PROPERTIES).unwrap();
+        let path = MappingPath::try_from("/sensor1/name").unwrap();
+        let prop = crate::store::StoredProp {
+            interface: interface.interface_name(),
+            path: path.as_str(),
+            value: &AstarteData::String("name".to_string()),
+            interface_major: interface.version_major(),
+            ownership: interface.ownership(),
+        };
+
+        client
+            .background
+            .runtime
+            .block_on(client.client.store.store_prop(prop))
+            .unwrap();
+
+        let value = client
+            .property(interface.interface_name(), "/sensor1/name")
+            .unwrap();
+        assert_eq!(value, Some(AstarteData::String("name".to_string())));
+
+        assert_eq!(client.device_props().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn should_stop_on_drop() {
+        let (client, _tx) = blocking_client(&[]);
+
+        let thread = client
+            .background
+            .lock()
+            .as_ref()
+            .unwrap()
+            .thread
+            .thread()
+            .clone();
+        assert_eq!(thread.name(), Some("astarte-connection"));
+
+        drop(client);
+    }
+
+    #[test]
+    fn should_share_the_connection_between_clones() {
+        let (mut client, tx) = blocking_client(&[]);
+
+        client
+            .client
+            .sender
+            .expect_clone()
+            .once()
+            .returning(MockSender::new);
+
+        let exp = DeviceEvent {
+            interface: "interface".to_string(),
+            path: "path".to_string(),
+            data: Value::Individual {
+                data: AstarteData::LongInteger(42),
+                timestamp: Utc::now(),
+            },
+        };
+
+        let receiver = client.clone();
+        let handle = std::thread::spawn(move || receiver.recv().unwrap());
+
+        tx.send(Ok(exp.clone())).unwrap();
+        assert_eq!(handle.join().unwrap(), exp);
+
+        // The connection is still running until the last clone is dropped
+        assert!(client.background.lock().is_some());
+        assert_eq!(Arc::strong_count(&client.background), 1);
+    }
+
+    #[test]
+    fn should_return_after_event_loop_error() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let (client, _tx) = mock_client(&[]);
+
+        let client =
+            BlockingDeviceClient::spawn(runtime, client, async { Err(Error::ConnectionTimeout) })
+                .unwrap();
+
+        while !client
+            .background
+            .lock()
+            .as_ref()
+            .unwrap()
+            .thread
+            .is_finished()
+        {
+            std::thread::yield_now();
+        }
+
+        let err = client.recv_timeout(Duration::from_millis(10)).unwrap_err();
+        assert!(matches!(err, BlockingError::Timeout(_)));
+
+        assert!(client.all_props().unwrap().is_empty());
+
+        let err = client.background.join().unwrap_err();
+        assert!(matches!(
+            err,
+            BlockingError::Client(Error::ConnectionTimeout)
+        ));
+    }
+}
@@ -0,0 +1,595 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Declarative configuration of the whole device, read from a TOML or JSON file.
+
+use std::{
+    io,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use serde_with::{serde_as, DisplayFromStr, PickFirst, Same};
+use tracing::{debug, warn};
+
+use crate::{
+    store::{sqlite::options::SqliteOptions, SqliteStore},
+    transport::mqtt::MqttConfig,
+};
+
+use super::{BuilderError, DeviceBuilder};
+
+/// Prefix of the environment variables overriding the configuration file.
+pub const CONFIG_ENV_PREFIX: &str = "ASTARTE_DEVICE_";
+
+/// Separator of the nested keys in the environment variables.
+const ENV_SEPARATOR: &str = "__";
+
+/// Deserializes a number or a boolean also from a string, like the one of an environment
+/// variable overriding a key missing from the file.
+pub(crate) type FromEnv = PickFirst<(Same, DisplayFromStr)>;
+
+/// Error returned while reading the [`DeviceConfig`].
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// Couldn't read the configuration file.
+    #[error("couldn't read the configuration file {}", .path.display())]
+    Read {
+        /// Path to the configuration file.
+        path: PathBuf,
+        /// Reason why the file couldn't be read.
+        #[source]
+        backtrace: io::Error,
+    },
+    /// The extension of the file is not `.toml` or `.json`.
+    #[error("unsupported configuration format for {}, expected a .toml or .json file", .0.display())]
+    Format(PathBuf),
+    /// Couldn't parse the TOML file.
+    #[error("couldn't parse the TOML configuration")]
+    Toml(#[from] toml::de::Error),
+    /// Couldn't parse the JSON file or deserialize the configuration.
+    #[error("couldn't deserialize the configuration")]
+    Json(#[from] serde_json::Error),
+    /// Invalid gRPC endpoint of the Message Hub.
+    #[cfg(feature = "message-hub")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "message-hub")))]
+    #[error("invalid Message Hub endpoint")]
+    Grpc(#[from] crate::transport::grpc::GrpcError),
+}
+
+/// Format of the configuration file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+
+        if ext.eq_ignore_ascii_case("toml") {
+            Some(Format::Toml)
+        } else if ext.eq_ignore_ascii_case("json") {
+            Some(Format::Json)
+        } else {
+            None
+        }
+    }
+}
+
+/// Configuration of the whole device.
+///
+/// It's read from a TOML or JSON file with [`DeviceConfig::read`] or
+/// [`DeviceBuilder::from_config`]. Relative paths are resolved from the directory of the file.
+///
+/// ```toml
+/// interface_directories = ["interfaces"]
+/// channel_size = 50
+/// connection_timeout_secs = 5
+/// volatile_retention = 1000
+/// stored_retention = 1000000
+///
+/// [store]
+/// path = "/var/lib/astarte"
+/// db_max_size = { unit = "mib", value = 512 }
+///
+/// [transport.mqtt]
+/// realm = "realm"
+/// device_id = "device_id"
+/// credentials_secret = "secret"
+/// pairing_url = "https://api.astarte.localhost/pairing"
+/// ```
+///
+/// Each value can be overridden with an environment variable prefixed by [`CONFIG_ENV_PREFIX`],
+/// with the nested keys separated by a double underscore. For example
+/// `ASTARTE_DEVICE_TRANSPORT__MQTT__CREDENTIALS_SECRET` overrides the `credentials_secret` of
+/// the MQTT transport. The numbers and booleans are parsed from the value, while the arrays are
+/// parsed as JSON only if they replace an array of the file.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceConfig {
+    #[serde(default)]
+    pub(crate) interface_directories: Vec<PathBuf>,
+    #[serde_as(as = "Option<FromEnv>")]
+    pub(crate) channel_size: Option<usize>,
+    #[serde_as(as = "Option<FromEnv>")]
+    pub(crate) connection_timeout_secs: Option<u64>,
+    #[serde_as(as = "Option<FromEnv>")]
+    pub(crate) volatile_retention: Option<NonZeroUsize>,
+    #[serde_as(as = "Option<FromEnv>")]
+    pub(crate) stored_retention: Option<NonZeroUsize>,
+    pub(crate) store: StoreConfig,
+    pub(crate) transport: TransportConfig,
+}
+
+/// Configuration of the SQLite store of the device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreConfig {
+    /// Writable directory where the database is created.
+    pub(crate) path: PathBuf,
+    #[serde(flatten)]
+    pub(crate) options: SqliteOptions,
+}
+
+/// Transport used to connect the device.
+// The configuration is read only once, so we allow large_enum_variant
+#[allow(clippy::large_enum_variant)]
+#[non_exhaustive]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportConfig {
+    /// Connect directly to Astarte through MQTT.
+    Mqtt(MqttConfig),
+    /// Connect through the Astarte Message Hub.
+    #[cfg(feature = "message-hub")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "message-hub")))]
+    Grpc(GrpcTransportConfig),
+}
+
+/// Configuration of the connection to the Astarte Message Hub.
+#[cfg(feature = "message-hub")]
+#[cfg_attr(docsrs, doc(cfg(feature = "message-hub")))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcTransportConfig {
+    /// Identifier of the node connecting to the Message Hub.
+    pub(crate) node_id: uuid::Uuid,
+    /// URL of the Message Hub gRPC server.
+    pub(crate) endpoint: String,
+}
+
+/// Builder configured from a [`DeviceConfig`], with the transport chosen in the file.
+#[allow(clippy::large_enum_variant)]
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ConfiguredBuilder {
+    /// Builder for a device connected through MQTT.
+    Mqtt(DeviceBuilder<MqttConfig, SqliteStore>),
+    /// Builder for a device connected through the Astarte Message Hub.
+    #[cfg(feature = "message-hub")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "message-hub")))]
+    Grpc(DeviceBuilder<crate::transport::grpc::GrpcConfig, SqliteStore>),
+}
+
+impl DeviceConfig {
+    /// Reads the configuration file, overridden by the environment variables.
+    ///
+    /// The format is chosen by the `.toml` or `.json` extension of the file.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+
+        let format = Format::from_path(path).ok_or_else(|| ConfigError::Format(path.to_owned()))?;
+
+        let content = std::fs::read_to_string(path).map_err(|err| ConfigError::Read {
+            path: path.to_owned(),
+            backtrace: err,
+        })?;
+
+        let mut config = Self::parse(&content, format, env_vars())?;
+
+        if let Some(base) = path.parent() {
+            config.resolve_paths(base);
+        }
+
+        Ok(config)
+    }
+
+    /// Parses the configuration, applying the overrides from the variables.
+    fn parse<I>(content: &str, format: Format, vars: I) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut value: Value = match format {
+            Format::Toml => toml::from_str(content)?,
+            Format::Json => serde_json::from_str(content)?,
+        };
+
+        apply_env_overrides(&mut value, vars);
+
+        serde_json::from_value(value).map_err(ConfigError::Json)
+    }
+
+    /// Makes the relative paths relative to the directory of the configuration file.
+    fn resolve_paths(&mut self, base: &Path) {
+        for dir in &mut self.interface_directories {
+            if dir.is_relative() {
+                *dir = base.join(&*dir);
+            }
+        }
+
+        if self.store.path.is_relative() {
+            self.store.path = base.join(&self.store.path);
+        }
+    }
+
+    /// Returns a mutable reference to the transport, to configure the options not available in
+    /// the file.
+    pub fn transport_mut(&mut self) -> &mut TransportConfig {
+        &mut self.transport
+    }
+
+    /// Creates the builder with the interfaces, store and transport of the configuration.
+    pub async fn into_builder(self) -> Result<ConfiguredBuilder, BuilderError> {
+        let mut builder = DeviceBuilder::new();
+
+        for dir in &self.interface_directories {
+            builder = builder.interface_directory(dir)?;
+        }
+
+        if let Some(size) = self.channel_size {
+            builder = builder.channel_size(size);
+        }
+
+        if let Some(secs) = self.connection_timeout_secs {
+            builder = builder.connection_timeout(Duration::from_secs(secs));
+        }
+
+        if let Some(items) = self.volatile_retention {
+            builder = builder.max_volatile_retention(items);
+        }
+
+        let builder = builder.writable_dir(&self.store.path)?;
+
+        debug!(path = %self.store.path.display(), "connecting to the store");
+
+        let store = SqliteStore::connect_with_options(&self.store.path, self.store.options).await?;
+
+        let mut builder = builder.store(store);
+
+        if let Some(items) = self.stored_retention {
+            builder = builder.max_stored_retention(items);
+        }
+
+        match self.transport {
+            TransportConfig::Mqtt(mqtt) => Ok(ConfiguredBuilder::Mqtt(builder.connection(mqtt))),
+            #[cfg(feature = "message-hub")]
+            TransportConfig::Grpc(grpc) => {
+                let grpc =
+                    crate::transport::grpc::GrpcConfig::from_url(grpc.node_id, grpc.endpoint)
+                        .map_err(ConfigError::Grpc)?;
+
+                Ok(ConfiguredBuilder::Grpc(builder.connection(grpc)))
+            }
+        }
+    }
+}
+
+/// Returns the environment variables, skipping the ones that are not valid UTF-8.
+fn env_vars() -> impl Iterator<Item = (String, String)> {
+    std::env::vars_os().filter_map(
+        |(key, value)| match (key.into_string(), value.into_string()) {
+            (Ok(key), Ok(value)) => Some((key, value)),
+            (Ok(key), Err(_)) if key.starts_with(CONFIG_ENV_PREFIX) => {
+                warn!(key, "configuration override is not valid UTF-8, skipping");
+
+                None
+            }
+            _ => None,
+        },
+    )
+}
+
+/// Sets the values of the environment variables with the [`CONFIG_ENV_PREFIX`] in the
+/// configuration.
+///
+/// A value replacing a non-string value of the file is parsed as JSON to support numbers,
+/// booleans and arrays, falling back to a string. The other values, including the ones for keys
+/// missing from the file, are kept as strings and the numbers and booleans are parsed from them
+/// while deserializing with [`FromEnv`].
+fn apply_env_overrides<I>(config: &mut Value, vars: I)
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    for (key, raw) in vars {
+        let Some(key) = key.strip_prefix(CONFIG_ENV_PREFIX) else {
+            continue;
+        };
+
+        let segments: Vec<String> = key
+            .split(ENV_SEPARATOR)
+            .map(str::to_ascii_lowercase)
+            .collect();
+
+        let Some((last, parents)) = segments.split_last() else {
+            continue;
+        };
+
+        if segments.iter().any(String::is_empty) {
+            warn!(key, "invalid configuration override, skipping");
+
+            continue;
+        }
+
+        let mut current = &mut *config;
+        for segment in parents {
+            current = as_object(current)
+                .entry(segment.as_str())
+                .or_insert_with(|| Value::Object(Map::new()));
+        }
+
+        let object = as_object(current);
+
+        let value = match object.get(last) {
+            Some(Value::String(_)) | None => Value::String(raw),
+            Some(_) => serde_json::from_str(&raw).unwrap_or(Value::String(raw)),
+        };
+
+        debug!(key, "configuration overridden by the environment");
+
+        object.insert(last.clone(), value);
+    }
+}
+
+/// Returns the value as an object, replacing it if it's not.
+fn as_object(value: &mut Value) -> &mut Map<String, Value> {
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+
+    match value {
+        Value::Object(map) => map,
+        _ => unreachable!("value was replaced with an object"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use crate::store::sqlite::options::SizeLimit;
+    use crate::transport::mqtt::Credential;
+
+    use super::*;
+
+    const CONFIG: &str = r#"
+interface_directories = ["interfaces"]
+channel_size = 10
+connection_timeout_secs = 2
+stored_retention = 100
+
+[store]
+path = "store"
+max_page_count = 1000
+
+[transport.mqtt]
+realm = "realm"
+device_id = "device_id"
+pairing_token = "token"
+pairing_url = "http://api.astarte.localhost/pairing"
+ignore_ssl_errors = false
+"#;
+
+    // The pattern is refutable only with the message-hub feature
+    #[allow(irrefutable_let_patterns)]
+    #[test]
+    fn should_parse_toml_and_json() {
+        let config = DeviceConfig::parse(CONFIG, Format::Toml, []).unwrap();
+
+        assert_eq!(config.interface_directories, [PathBuf::from("interfaces")]);
+        assert_eq!(config.channel_size, Some(10));
+        assert_eq!(config.connection_timeout_secs, Some(2));
+        assert_eq!(config.stored_retention, NonZeroUsize::new(100));
+        assert_eq!(config.store.path, PathBuf::from("store"));
+
+        let TransportConfig::Mqtt(mqtt) = &config.transport else {
+            panic!("expected an MQTT transport");
+        };
+        assert_eq!(mqtt.realm, "realm");
+        assert_eq!(
+            mqtt.credential,
+            Credential::ParingToken {
+                pairing_token: "token".to_string()
+            }
+        );
+        assert_eq!(mqtt.keepalive, Duration::from_secs(30));
+        assert_eq!(serde_json::to_value(mqtt).unwrap()["keepalive"], 30);
+
+        let json = serde_json::to_string(&config).unwrap();
+        let config = DeviceConfig::parse(&json, Format::Json, []).unwrap();
+        assert_eq!(config.store.path, PathBuf::from("store"));
+    }
+
+    // The pattern is refutable only with the message-hub feature
+    #[allow(irrefutable_let_patterns)]
+    #[test]
+    fn should_override_with_env() {
+        let vars = [
+            ("ASTARTE_DEVICE_CHANNEL_SIZE", "20"),
+            ("ASTARTE_DEVICE_STORE__PATH", "/var/lib/astarte"),
+            ("ASTARTE_DEVICE_TRANSPORT__MQTT__DEVICE_ID", "1234"),
+            ("ASTARTE_DEVICE_TRANSPORT__MQTT__IGNORE_SSL_ERRORS", "true"),
+            ("ASTARTE_DEVICE___INVALID", "skipped"),
+            ("OTHER_VARIABLE", "skipped"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        let config = DeviceConfig::parse(CONFIG, Format::Toml, vars).unwrap();
+
+        assert_eq!(config.channel_size, Some(20));
+        assert_eq!(config.store.path, PathBuf::from("/var/lib/astarte"));
+
+        let TransportConfig::Mqtt(mqtt) = &config.transport else {
+            panic!("expected an MQTT transport");
+        };
+        assert_eq!(mqtt.device_id, "1234");
+        assert!(mqtt.ignore_ssl_errors);
+    }
+
+    // The pattern is refutable only with the message-hub feature
+    #[allow(irrefutable_let_patterns)]
+    #[test]
+    fn should_override_keys_missing_from_the_file() {
+        const CONFIG: &str = r#"
+[store]
+path = "store"
+
+[transport.mqtt]
+realm = "realm"
+device_id = "device_id"
+pairing_token = "token"
+pairing_url = "http://api.astarte.localhost/pairing"
+"#;
+
+        let vars = [
+            ("ASTARTE_DEVICE_CHANNEL_SIZE", "20"),
+            ("ASTARTE_DEVICE_VOLATILE_RETENTION", "30"),
+            ("ASTARTE_DEVICE_STORE__MAX_READERS", "2"),
+            ("ASTARTE_DEVICE_STORE__MAX_PAGE_COUNT", "1000"),
+            ("ASTARTE_DEVICE_TRANSPORT__MQTT__IGNORE_SSL_ERRORS", "true"),
+            ("ASTARTE_DEVICE_TRANSPORT__MQTT__KEEPALIVE", "60"),
+            ("ASTARTE_DEVICE_TRANSPORT__MQTT__BOUNDED_CHANNEL_SIZE", "5"),
+            ("ASTARTE_DEVICE_TRANSPORT__MQTT__CERTIFICATE_RENEWAL", "0.5"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        let config = DeviceConfig::parse(CONFIG, Format::Toml, vars).unwrap();
+
+        assert_eq!(config.channel_size, Some(20));
+        assert_eq!(config.volatile_retention, NonZeroUsize::new(30));
+        assert_eq!(
+            config.store.options.max_readers(),
+            NonZeroUsize::new(2).unwrap()
+        );
+        assert!(matches!(
+            config.store.options.db_size_limit(),
+            SizeLimit::MaxPageCount(count) if count.get() == 1000
+        ));
+
+        let TransportConfig::Mqtt(mqtt) = &config.transport else {
+            panic!("expected an MQTT transport");
+        };
+        assert!(mqtt.ignore_ssl_errors);
+        assert_eq!(mqtt.keepalive, Duration::from_secs(60));
+        assert_eq!(mqtt.bounded_channel_size, 5);
+        assert_eq!(mqtt.certificate_renewal, Some(0.5));
+
+        let err = DeviceConfig::parse(
+            CONFIG,
+            Format::Toml,
+            [(
+                "ASTARTE_DEVICE_CHANNEL_SIZE".to_string(),
+                "many".to_string(),
+            )],
+        )
+        .unwrap_err();
+        assert!(matches!(err, ConfigError::Json(_)));
+    }
+
+    #[test]
+    fn should_keep_missing_and_string_overrides_as_strings() {
+        let mut config = serde_json::json!({
+            "name": "name",
+            "count": 1,
+            "nested": { "enabled": false },
+        });
+
+        let vars = [
+            ("ASTARTE_DEVICE_NAME", "42"),
+            ("ASTARTE_DEVICE_COUNT", "2"),
+            ("ASTARTE_DEVICE_NESTED__ENABLED", "true"),
+            ("ASTARTE_DEVICE_NESTED__SECRET", "1234"),
+            ("ASTARTE_DEVICE_MISSING", "true"),
+            ("ASTARTE_DEVICE_COUNT_INVALID", "[1,"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        apply_env_overrides(&mut config, vars);
+
+        assert_eq!(
+            config,
+            serde_json::json!({
+                "name": "42",
+                "count": 2,
+                "nested": { "enabled": true, "secret": "1234" },
+                "missing": "true",
+                "count_invalid": "[1,",
+            })
+        );
+    }
+
+    // The pattern is refutable only with the message-hub feature
+    #[allow(irrefutable_let_patterns)]
+    #[tokio::test]
+    async fn should_build_from_config() {
+        let dir = TempDir::new().unwrap();
+
+        let interfaces = dir.path().join("interfaces");
+        tokio::fs::create_dir(&interfaces).await.unwrap();
+        tokio::fs::write(
+            interfaces.join("interface.json"),
+            crate::test::DEVICE_PROPERTIES,
+        )
+        .await
+        .unwrap();
+        tokio::fs::create_dir(dir.path().join("store"))
+            .await
+            .unwrap();
+
+        let path = dir.path().join("device.toml");
+        tokio::fs::write(&path, CONFIG).await.unwrap();
+
+        let config = DeviceConfig::read(&path).unwrap();
+        assert_eq!(config.store.path, dir.path().join("store"));
+
+        let builder = DeviceBuilder::from_config(&path).await.unwrap();
+
+        let ConfiguredBuilder::Mqtt(builder) = builder else {
+            panic!("expected an MQTT builder");
+        };
+        assert_eq!(builder.channel_size, 10);
+        assert_eq!(builder.connection_timeout, Duration::from_secs(2));
+        assert_eq!(builder.stored_retention.get(), 100);
+        assert_eq!(builder.writable_dir, Some(dir.path().join("store")));
+        assert!(builder
+            .interfaces
+            .get("org.astarte-platform.rust.examples.individual-properties.DeviceProperties")
+            .is_some());
+    }
+
+    #[test]
+    fn should_reject_unknown_format() {
+        let err = DeviceConfig::read("device.yaml").unwrap_err();
+
+        assert!(matches!(err, ConfigError::Format(_)));
+    }
+}
@@ -35,13 +35,14 @@ use astarte_interfaces::Interface;
 use tracing::debug;
 use tracing::info;
 
-use crate::client::DeviceClient;
+use crate::client::{DeviceClient, Subscriptions};
 use crate::connection::DeviceConnection;
 use crate::interfaces::Interfaces;
 use crate::introspection::AddInterfaceError;
 use crate::retention::memory::VolatileStore;
 use crate::retention::RetentionError;
 use crate::retention::StoredRetention;
+use crate::retry::Backoff;
 use crate::state::SharedState;
 use crate::store::sqlite::SqliteError;
 use crate::store::wrapper::StoreWrapper;
@@ -52,6 +53,16 @@ use crate::transport::Connection;
 use crate::utils::const_conv::const_non_zero_usize;
 use crate::Error;
 
+#[cfg(feature = "message-hub")]
+pub use self::config::GrpcTransportConfig;
+pub use self::config::{
+    ConfigError, ConfiguredBuilder, DeviceConfig, StoreConfig, TransportConfig, CONFIG_ENV_PREFIX,
+};
+
+pub(crate) use self::config::FromEnv;
+
+mod config;
+
 /// Default capacity of the channels
 ///
 /// This constant is the default bounded channel size for *both* the rumqttc AsyncClient and
@@ -106,6 +117,12 @@ pub enum BuilderError {
     /// Couldn't set the maximum number of items in the store
     #[error("couldn't set the maximum number of items in the store")]
     Retention(#[from] RetentionError),
+    /// Couldn't read the device configuration
+    #[error("couldn't read the device configuration")]
+    Config(#[from] ConfigError),
+    /// Couldn't add the interfaces of the device configuration
+    #[error("couldn't add the interfaces")]
+    Interface(#[from] AddInterfaceError),
 }
 
 /// Marker struct to identify a builder with no store configured
@@ -167,6 +184,31 @@ impl DeviceBuilder<NoConnect, NoStore> {
             connection_timeout: DEFAULT_REQUEST_TIMEOUT,
         }
     }
+
+    /// Create the builder from a TOML or JSON configuration file describing the whole device.
+    ///
+    /// The values in the file can be overridden with environment variables, see [`DeviceConfig`]
+    /// for the format.
+    ///
+    /// ```no_run
+    /// use astarte_device_sdk::builder::{ConfiguredBuilder, DeviceBuilder};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     match DeviceBuilder::from_config("device.toml").await.unwrap() {
+    ///         ConfiguredBuilder::Mqtt(builder) => {
+    ///             let (client, connection) = builder.build().await.unwrap();
+    ///         }
+    ///         _ => unimplemented!("other transports"),
+    ///     }
+    /// }
+    /// ```
+    pub async fn from_config<P>(path: P) -> Result<ConfiguredBuilder, BuilderError>
+    where
+        P: AsRef<Path>,
+    {
+        DeviceConfig::read(path)?.into_builder().await
+    }
 }
 
 impl<S, C> DeviceBuilder<S, C> {
@@ -383,7 +425,11 @@ where
 
         let volatile_store = VolatileStore::with_capacity(self.volatile_retention);
 
-        let state = Arc::new(SharedState::new(self.interfaces, volatile_store));
+        let state = Arc::new(SharedState::new(
+            self.interfaces,
+            volatile_store,
+            Subscriptions::with_capacity(self.channel_size),
+        ));
 
         let config = BuildConfig {
             store: self.store,
@@ -398,6 +444,7 @@ where
             sender,
             store,
             connected,
+            backoff,
         } = self.connection_config.connect(config).await?;
 
         // NOTE store the connection status
@@ -418,7 +465,8 @@ where
         let client =
             DeviceClient::new(sender.clone(), rx_client, store.clone(), Arc::clone(&state));
 
-        let connection = DeviceConnection::new(tx_connection, store, state, connection, sender);
+        let connection =
+            DeviceConnection::new(tx_connection, store, state, connection, sender, backoff);
 
         Ok((client, connection))
     }
@@ -439,6 +487,7 @@ where
     pub(crate) sender: C::Sender,
     pub(crate) store: StoreWrapper<C::Store>,
     pub(crate) connected: bool,
+    pub(crate) backoff: Backoff,
 }
 
 /// Crate private connection implementation.
@@ -611,6 +660,7 @@ mod test {
                     sender,
                     store: StoreWrapper::new(config.store),
                     connected: true,
+                    backoff: Backoff::default(),
                 })
             });
 
@@ -22,6 +22,7 @@ use astarte_interfaces::MappingPath;
 use tracing::debug;
 
 use crate::client::ValidatedIndividual;
+use crate::retention::DeliveryReceipt;
 use crate::transport::Connection;
 use crate::{AstarteData, Error};
 
@@ -37,7 +38,8 @@ where
         path: &MappingPath<'_>,
         data: AstarteData,
         timestamp: Option<Timestamp>,
-    ) -> Result<(), Error>
+        confirm: bool,
+    ) -> Result<Option<DeliveryReceipt>, Error>
     where
         C::Sender: Publish,
     {
@@ -49,7 +51,14 @@ where
         debug!("sending individual {}{}", interface_name, path);
         debug!("sending individual type {}", validated.data.display_type());
 
-        Self::send(&self.state, &self.store, &mut self.sender, validated).await
+        Self::send(
+            &self.state,
+            &self.store,
+            &mut self.sender,
+            validated,
+            confirm,
+        )
+        .await
     }
 }
 
@@ -68,7 +77,7 @@ mod tests {
 
     use crate::client::tests::{mock_client, mock_client_with_store};
     use crate::retention::memory::ItemValue;
-    use crate::retention::{PublishInfo, RetentionId, StoredRetention};
+    use crate::retention::{DeliveryError, PublishInfo, RetentionId, StoredRetention};
     use crate::store::SqliteStore;
     use crate::test::{
         E2E_DEVICE_DATASTREAM, E2E_DEVICE_DATASTREAM_NAME, STORED_DEVICE_DATASTREAM,
@@ -156,6 +165,102 @@ mod tests {
         assert_eq!(item, ItemValue::Individual(expected));
     }
 
+    #[tokio::test]
+    async fn send_datastream_individual_confirmed_volatile() {
+        let (mut client, _tx) = mock_client(&[VOLATILE_DEVICE_DATASTREAM]);
+
+        client.state.status.set_connected(true);
+
+        let path = "/endpoint1";
+        let value = 42i64;
+
+        let (id_tx, id_rx) = std::sync::mpsc::channel();
+
+        let mut seq = Sequence::new();
+        client
+            .sender
+            .expect_send_individual_stored()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(move |id, _| {
+                id_tx.send(id).unwrap();
+
+                Ok(())
+            });
+
+        let receipt = client
+            .send_individual_confirmed(VOLATILE_DEVICE_DATASTREAM_NAME, path, value.into(), None)
+            .await
+            .unwrap();
+
+        let id = id_rx.recv().unwrap();
+        assert!(matches!(id, RetentionId::Volatile(_)));
+
+        client.state.receipts.received(&id);
+
+        tokio::time::timeout(Duration::from_secs(2), receipt)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn send_datastream_individual_confirmed_connected_discard() {
+        let (mut client, _tx) = mock_client(&[E2E_DEVICE_DATASTREAM]);
+
+        client.state.status.set_connected(true);
+
+        let (id_tx, id_rx) = std::sync::mpsc::channel();
+
+        client
+            .sender
+            .expect_send_individual_stored()
+            .once()
+            .returning(move |id, _| {
+                id_tx.send(id).unwrap();
+
+                Ok(())
+            });
+
+        let receipt = client
+            .send_individual_confirmed(
+                E2E_DEVICE_DATASTREAM_NAME,
+                "/integer_endpoint",
+                42.into(),
+                Some(Utc::now()),
+            )
+            .await
+            .unwrap();
+
+        let id = id_rx.recv().unwrap();
+        assert!(matches!(id, RetentionId::Volatile(_)));
+        // Not retained, but still waiting for the ack
+        assert!(client.state.volatile_store.pop_next().await.is_none());
+        assert!(!client.state.receipts.is_empty());
+
+        client.state.receipts.received(&id);
+
+        assert_eq!(receipt.await, Ok(()));
+        assert!(client.state.receipts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn send_datastream_individual_confirmed_offline_discard() {
+        let (mut client, _tx) = mock_client(&[E2E_DEVICE_DATASTREAM]);
+
+        let receipt = client
+            .send_individual_confirmed(
+                E2E_DEVICE_DATASTREAM_NAME,
+                "/integer_endpoint",
+                42.into(),
+                Some(Utc::now()),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(receipt.await, Err(DeliveryError::Dropped));
+    }
+
     #[tokio::test]
     async fn send_datastream_individual_connected_stored_no_retention_cap() {
         let (mut client, _tx) = mock_client(&[STORED_DEVICE_DATASTREAM]);
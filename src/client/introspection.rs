@@ -28,8 +28,8 @@ use tracing::{debug, error};
 use crate::error::Report;
 use crate::introspection::{AddInterfaceError, DeviceIntrospection};
 use crate::prelude::DynamicIntrospection;
-use crate::retention::memory::VolatileStore;
-use crate::retention::StoredRetention;
+use crate::retention::{StoredRetention, StoredRetentionExt};
+use crate::state::SharedState;
 use crate::store::wrapper::StoreWrapper;
 use crate::store::{PropertyStore, StoreCapabilities};
 use crate::transport::{Connection, Register};
@@ -47,16 +47,16 @@ where
     // delete them from the stores (volatile and non). Instead we remove all the values with the
     // given interface from each store.
     async fn cleanup_interface(
-        volatile_store: &VolatileStore,
+        state: &SharedState,
         store: &StoreWrapper<C::Store>,
         interface: &Interface,
     ) {
         match interface.inner() {
             InterfaceTypeAggregation::DatastreamIndividual(interface) => {
-                Self::cleanup_retention(volatile_store, store, interface.name()).await
+                Self::cleanup_retention(state, store, interface.name()).await
             }
             InterfaceTypeAggregation::DatastreamObject(interface) => {
-                Self::cleanup_retention(volatile_store, store, interface.name()).await
+                Self::cleanup_retention(state, store, interface.name()).await
             }
             InterfaceTypeAggregation::Properties(properties) => {
                 let res = store.delete_interface(properties).await;
@@ -68,13 +68,14 @@ where
         }
     }
 
-    // Cleans up the volatile and store retention.
+    // Cleans up the volatile and store retention, dropping the receipts of the removed publishes.
     async fn cleanup_retention(
-        volatile_store: &VolatileStore,
+        state: &SharedState,
         store: &StoreWrapper<C::Store>,
         interface_name: &str,
     ) {
-        volatile_store.delete_interface(interface_name).await;
+        state.volatile_store.delete_interface(interface_name).await;
+        state.volatile_store.notify_removed(&state.receipts).await;
 
         if let Some(retention) = store.get_retention() {
             let res = retention.delete_interface(interface_name).await;
@@ -82,6 +83,8 @@ where
             if let Err(err) = res {
                 error!(error = %Report::new(err),"failed to remove interfaces from retention");
             }
+
+            retention.notify_removed(&state.receipts).await;
         }
     }
 }
@@ -129,7 +132,7 @@ where
         self.sender.add_interface(&interfaces, &to_add).await?;
 
         if to_add.is_major_change() {
-            Self::cleanup_interface(&self.state.volatile_store, &self.store, &to_add).await;
+            Self::cleanup_interface(&self.state, &self.store, &to_add).await;
         }
 
         drop(interfaces);
@@ -172,7 +175,7 @@ where
             .filter(|interface| interface.is_major_change());
 
         for interface in major_changes {
-            Self::cleanup_interface(&self.state.volatile_store, &self.store, interface).await;
+            Self::cleanup_interface(&self.state, &self.store, interface).await;
         }
 
         let names = to_add.keys().cloned().collect();
@@ -232,7 +235,7 @@ where
 
         self.sender.remove_interface(&interfaces, to_remove).await?;
 
-        Self::cleanup_interface(&self.state.volatile_store, &self.store, to_remove).await;
+        Self::cleanup_interface(&self.state, &self.store, to_remove).await;
 
         drop(interfaces);
         debug!("removing interface from introspection");
@@ -279,7 +282,7 @@ where
             .await?;
 
         for interface in to_remove.values() {
-            Self::cleanup_interface(&self.state.volatile_store, &self.store, interface).await;
+            Self::cleanup_interface(&self.state, &self.store, interface).await;
         }
 
         let removed_names: Vec<String> = to_remove.keys().map(|k| k.to_string()).collect();
@@ -28,9 +28,9 @@ use crate::{
     error::{AggregationError, InterfaceTypeError},
     retention::{
         memory::{ItemValue, VolatileItemError},
-        Id, RetentionId, StoredRetention, StoredRetentionExt,
+        DeliveryError, DeliveryReceipt, Id, RetentionId, StoredRetention, StoredRetentionExt,
     },
-    state::{SharedState, Status},
+    state::SharedState,
     store::StoreCapabilities,
     transport::{mqtt::error::MqttError, Connection, Publish},
     Timestamp,
@@ -48,6 +48,11 @@ mod individual;
 mod introspection;
 mod object;
 mod property;
+mod subscribe;
+
+pub use self::subscribe::EventStream;
+pub(crate) use self::subscribe::Subscriptions;
+pub use crate::state::{Status, StatusEvent, StatusReason};
 
 /// Error generated by or received from the connection.
 #[non_exhaustive]
@@ -114,6 +119,28 @@ pub enum RecvError {
 
 // Safe conversion for connection error
 impl RecvError {
+    /// Returns the interface and path the error was received on, if known.
+    pub(crate) fn interface_path(&self) -> Option<(&str, &str)> {
+        match self {
+            RecvError::MappingNotFound { interface, mapping } => Some((interface, mapping)),
+            RecvError::MissingTimestamp {
+                interface_name,
+                path,
+            }
+            | RecvError::Unset {
+                interface_name,
+                path,
+            } => Some((interface_name, path)),
+            RecvError::Ownership { interface, path } => Some((interface, path)),
+            RecvError::Connection(_)
+            | RecvError::InvalidEndpoint(_)
+            | RecvError::InterfaceNotFound { .. }
+            | RecvError::Aggregation(_)
+            | RecvError::InterfaceType(_)
+            | RecvError::Disconnected => None,
+        }
+    }
+
     pub(crate) fn mqtt_connection_error(value: MqttError) -> Self {
         RecvError::Connection(value.into())
     }
@@ -245,6 +272,53 @@ pub trait Client: Clone {
         timestamp: chrono::DateTime<chrono::Utc>,
     ) -> impl Future<Output = Result<(), Error>> + Send;
 
+    /// Send an individual datastream on an interface, returning a receipt for the delivery.
+    ///
+    /// The returned [`DeliveryReceipt`] resolves when Astarte acknowledged the publish, or with an
+    /// error if the publish was dropped or expired. The timestamp is optional and has the same
+    /// meaning of [`send_individual_with_timestamp`](crate::Client::send_individual_with_timestamp).
+    ///
+    /// ```no_run
+    /// use astarte_device_sdk::{
+    ///     store::memory::MemoryStore, builder::DeviceBuilder,
+    ///     transport::mqtt::MqttConfig, types::AstarteData, prelude::*,
+    /// };
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mqtt_config = MqttConfig::with_credential_secret("realm_id", "device_id", "credential_secret", "pairing_url");
+    ///
+    ///     let (mut client, connection) = DeviceBuilder::new().store(MemoryStore::new())
+    ///         .connection(mqtt_config).build().await.unwrap();
+    ///
+    ///     let value: i32 = 42;
+    ///     let receipt = client.send_individual_confirmed("my.interface.name", "/endpoint/path", value.into(), None)
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     receipt.await.expect("publish not delivered");
+    /// }
+    /// ```
+    fn send_individual_confirmed(
+        &mut self,
+        interface_name: &str,
+        mapping_path: &str,
+        data: AstarteData,
+        timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> impl Future<Output = Result<DeliveryReceipt, Error>> + Send;
+
+    /// Send an object datastream on an interface, returning a receipt for the delivery.
+    ///
+    /// The usage is the same of
+    /// [`send_individual_confirmed`](crate::Client::send_individual_confirmed).
+    fn send_object_confirmed(
+        &mut self,
+        interface_name: &str,
+        base_path: &str,
+        data: AstarteObject,
+        timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> impl Future<Output = Result<DeliveryReceipt, Error>> + Send;
+
     /// Send an individual datastream on an interface.
     ///
     /// ```no_run
@@ -328,7 +402,7 @@ where
     C: Connection,
 {
     // Sender of the connection.
-    sender: C::Sender,
+    pub(crate) sender: C::Sender,
     // We use flume instead of the mpsc channel for the DeviceEvents for the connection to che
     // client since we need the Receiver end to be cloneable. Flume provides an async mpmc
     // channel/queue that fits our needs and doesn't suffer from the "slow receiver" problem.
@@ -357,12 +431,55 @@ where
         }
     }
 
+    /// Returns the current status of the connection with Astarte.
+    pub fn connection_status(&self) -> Status {
+        self.state.status.connection()
+    }
+
+    /// Subscribe to the changes of the connection [`Status`].
+    ///
+    /// The receiver will yield a [`StatusEvent`] with the reason and the time of every
+    /// transition after the call. The current status is already seen by the receiver, so
+    /// [`changed`](tokio::sync::watch::Receiver::changed) doesn't return it: read it with
+    /// [`borrow`](tokio::sync::watch::Receiver::borrow) first, or call
+    /// [`mark_changed`](tokio::sync::watch::Receiver::mark_changed) on the receiver.
+    ///
+    /// ```no_run
+    /// use astarte_device_sdk::{
+    ///     store::memory::MemoryStore, builder::DeviceBuilder,
+    ///     transport::mqtt::MqttConfig, client::Status, prelude::*,
+    /// };
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mqtt_config = MqttConfig::with_credential_secret("realm_id", "device_id", "credential_secret", "pairing_url");
+    ///
+    ///     let (client, connection) = DeviceBuilder::new().store(MemoryStore::new())
+    ///         .connection(mqtt_config).build().await.unwrap();
+    ///
+    ///     let mut status = client.watch_connection_status();
+    ///     println!("current status {:?}", status.borrow().status);
+    ///
+    ///     while status.changed().await.is_ok() {
+    ///         let event = *status.borrow_and_update();
+    ///
+    ///         if event.status == Status::Disconnected {
+    ///             println!("offline since {} because {:?}", event.timestamp, event.reason);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn watch_connection_status(&self) -> tokio::sync::watch::Receiver<StatusEvent> {
+        self.state.status.subscribe()
+    }
+
     async fn send<T>(
         state: &SharedState,
         store: &StoreWrapper<C::Store>,
         sender: &mut C::Sender,
         data: T,
-    ) -> Result<(), Error>
+        confirm: bool,
+    ) -> Result<Option<DeliveryReceipt>, Error>
     where
         T: ClientPacket + TryInto<ItemValue, Error = VolatileItemError> + Clone,
         C::Store: StoreCapabilities,
@@ -374,7 +491,7 @@ where
             }
             Status::Disconnected => {
                 trace!("publish while connection is offline");
-                return Self::offline_send(state, store, sender, data).await;
+                return Self::offline_send(state, store, sender, data, confirm).await;
             }
             Status::Closed => {
                 return Err(Error::Disconnected);
@@ -382,9 +499,28 @@ where
         }
 
         match data.get_retention() {
-            Retention::Volatile { .. } => Self::send_volatile(state, sender, data).await,
-            Retention::Stored { .. } => Self::send_stored(state, store, sender, data).await,
-            Retention::Discard => data.send(sender).await,
+            Retention::Volatile { .. } => Self::send_volatile(state, sender, data, confirm).await,
+            Retention::Stored { .. } => {
+                Self::send_stored(state, store, sender, data, confirm).await
+            }
+            Retention::Discard if confirm => {
+                // Not stored in the retention, the id is only used to wait for the broker ack
+                let id = RetentionId::Volatile(state.retention_ctx.next());
+                let receipt = Self::register_receipt(state, id, &data, confirm);
+
+                if let Err(err) = data.send_stored(id, sender).await {
+                    state.receipts.dropped(&id);
+
+                    return Err(err);
+                }
+
+                Ok(receipt)
+            }
+            Retention::Discard => {
+                data.send(sender).await?;
+
+                Ok(None)
+            }
         }
     }
 
@@ -393,7 +529,8 @@ where
         store: &StoreWrapper<C::Store>,
         sender: &mut C::Sender,
         data: T,
-    ) -> Result<(), Error>
+        confirm: bool,
+    ) -> Result<Option<DeliveryReceipt>, Error>
     where
         T: ClientPacket + TryInto<ItemValue, Error = VolatileItemError>,
         C::Store: StoreCapabilities,
@@ -402,25 +539,40 @@ where
         match data.get_retention() {
             Retention::Discard => {
                 debug!("drop publish with retention discard since disconnected");
+
+                Ok(confirm.then(|| DeliveryReceipt::resolved(Err(DeliveryError::Dropped))))
             }
             Retention::Volatile { .. } => {
                 let id = state.retention_ctx.next();
+                let receipt =
+                    Self::register_receipt(state, RetentionId::Volatile(id), &data, confirm);
+
+                Self::push_volatile(state, id, data, false).await;
 
-                state.volatile_store.push_unsent(id, data).await;
+                Ok(receipt)
             }
             Retention::Stored { .. } => {
                 let id = state.retention_ctx.next();
 
                 if let Some(retention) = store.get_retention() {
+                    let receipt =
+                        Self::register_receipt(state, RetentionId::Stored(id), &data, confirm);
+
                     data.store_publish(&id, sender, retention, false).await?;
+                    retention.notify_removed(&state.receipts).await;
+
+                    Ok(receipt)
                 } else {
                     warn!(?store, "storing interface with retention 'Stored' in volatile store since the store doesn't support retention");
-                    state.volatile_store.push_unsent(id, data).await;
+                    let receipt =
+                        Self::register_receipt(state, RetentionId::Volatile(id), &data, confirm);
+
+                    Self::push_volatile(state, id, data, false).await;
+
+                    Ok(receipt)
                 }
             }
         }
-
-        Ok(())
     }
 
     async fn send_stored<T>(
@@ -428,7 +580,8 @@ where
         store: &StoreWrapper<C::Store>,
         sender: &mut C::Sender,
         data: T,
-    ) -> Result<(), Error>
+        confirm: bool,
+    ) -> Result<Option<DeliveryReceipt>, Error>
     where
         T: ClientPacket + TryInto<ItemValue, Error = VolatileItemError> + Clone,
         C::Store: StoreCapabilities,
@@ -436,30 +589,75 @@ where
     {
         let Some(retention) = store.get_retention() else {
             warn!(?store, "storing interface with retention 'Stored' in volatile store since the store doesn't support retention");
-            return Self::send_volatile(state, sender, data).await;
+            return Self::send_volatile(state, sender, data, confirm).await;
         };
 
         // generate id after the check to avoid wasting an id generation in case it gets regenerated in send_volatile
         let id = state.retention_ctx.next();
+        let receipt = Self::register_receipt(state, RetentionId::Stored(id), &data, confirm);
 
         data.store_publish(&id, sender, retention, true).await?;
-        data.send_stored(RetentionId::Stored(id), sender).await
+        retention.notify_removed(&state.receipts).await;
+        data.send_stored(RetentionId::Stored(id), sender).await?;
+
+        Ok(receipt)
     }
 
     async fn send_volatile<T>(
         state: &SharedState,
         sender: &mut C::Sender,
         data: T,
-    ) -> Result<(), Error>
+        confirm: bool,
+    ) -> Result<Option<DeliveryReceipt>, Error>
     where
         T: ClientPacket + TryInto<ItemValue, Error = VolatileItemError> + Clone,
         C::Store: StoreCapabilities,
         C::Sender: Publish,
     {
         let id = state.retention_ctx.next();
+        let receipt = Self::register_receipt(state, RetentionId::Volatile(id), &data, confirm);
+
+        Self::push_volatile(state, id, data.clone(), true).await;
+        data.send_stored(RetentionId::Volatile(id), sender).await?;
+
+        Ok(receipt)
+    }
+
+    /// Pushes the data in the volatile store, dropping the receipts of the removed items.
+    async fn push_volatile<T>(state: &SharedState, id: Id, data: T, sent: bool)
+    where
+        T: TryInto<ItemValue, Error = VolatileItemError>,
+    {
+        let removed = if sent {
+            state.volatile_store.push_sent(id, data).await
+        } else {
+            state.volatile_store.push_unsent(id, data).await
+        };
+
+        if let Some(removed) = removed {
+            debug!(%removed, "volatile store full, removed publish");
+
+            state.receipts.dropped(&RetentionId::Volatile(removed));
+        }
 
-        state.volatile_store.push_sent(id, data.clone()).await;
-        data.send_stored(RetentionId::Volatile(id), sender).await
+        state.volatile_store.notify_removed(&state.receipts).await;
+    }
+
+    /// Registers the receipt before sending, so that it can't be resolved before registering it.
+    fn register_receipt<T>(
+        state: &SharedState,
+        id: RetentionId,
+        data: &T,
+        confirm: bool,
+    ) -> Option<DeliveryReceipt>
+    where
+        T: ClientPacket,
+    {
+        confirm.then(|| {
+            let expiry = data.get_retention().as_expiry().copied();
+
+            state.receipts.register(id, expiry)
+        })
     }
 }
 
@@ -492,8 +690,9 @@ where
     ) -> Result<(), Error> {
         let path = MappingPath::try_from(base_path)?;
 
-        self.send_datastream_object(interface_name, &path, data, Some(timestamp))
+        self.send_datastream_object(interface_name, &path, data, Some(timestamp), false)
             .await
+            .map(drop)
     }
 
     async fn send_object(
@@ -504,8 +703,9 @@ where
     ) -> Result<(), Error> {
         let path = MappingPath::try_from(base_path)?;
 
-        self.send_datastream_object(interface_name, &path, data, None)
+        self.send_datastream_object(interface_name, &path, data, None, false)
             .await
+            .map(drop)
     }
 
     async fn send_individual(
@@ -516,8 +716,9 @@ where
     ) -> Result<(), Error> {
         let path = MappingPath::try_from(mapping_path)?;
 
-        self.send_datastream_individual(interface_name, &path, data, None)
+        self.send_datastream_individual(interface_name, &path, data, None, false)
             .await
+            .map(drop)
     }
 
     async fn send_individual_with_timestamp(
@@ -529,8 +730,45 @@ where
     ) -> Result<(), Error> {
         let mapping = MappingPath::try_from(mapping_path)?;
 
-        self.send_datastream_individual(interface_name, &mapping, data, Some(timestamp))
+        self.send_datastream_individual(interface_name, &mapping, data, Some(timestamp), false)
             .await
+            .map(drop)
+    }
+
+    async fn send_individual_confirmed(
+        &mut self,
+        interface_name: &str,
+        mapping_path: &str,
+        data: AstarteData,
+        timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<DeliveryReceipt, Error> {
+        let mapping = MappingPath::try_from(mapping_path)?;
+
+        let receipt = self
+            .send_datastream_individual(interface_name, &mapping, data, timestamp, true)
+            .await?;
+
+        debug_assert!(receipt.is_some(), "BUG: receipt was requested");
+
+        Ok(receipt.unwrap_or(DeliveryReceipt::resolved(Err(DeliveryError::Dropped))))
+    }
+
+    async fn send_object_confirmed(
+        &mut self,
+        interface_name: &str,
+        base_path: &str,
+        data: AstarteObject,
+        timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<DeliveryReceipt, Error> {
+        let path = MappingPath::try_from(base_path)?;
+
+        let receipt = self
+            .send_datastream_object(interface_name, &path, data, timestamp, true)
+            .await?;
+
+        debug_assert!(receipt.is_some(), "BUG: receipt was requested");
+
+        Ok(receipt.unwrap_or(DeliveryReceipt::resolved(Err(DeliveryError::Dropped))))
     }
 
     async fn set_property(
@@ -574,7 +812,7 @@ where
     async fn disconnect(&mut self) -> Result<(), Error> {
         self.sender.disconnect().await?;
 
-        self.state.status.close();
+        self.state.status.close(StatusReason::Disconnect);
 
         Ok(())
     }
@@ -750,6 +988,7 @@ pub(crate) mod tests {
         let state = SharedState::new(
             interfaces,
             VolatileStore::with_capacity(DEFAULT_VOLATILE_CAPACITY),
+            Subscriptions::with_capacity(DEFAULT_CHANNEL_SIZE),
         );
 
         let client = DeviceClient::new(sender, rx, StoreWrapper::new(store), Arc::new(state));
@@ -804,8 +1043,14 @@ pub(crate) mod tests {
             .in_sequence(&mut seq)
             .returning(|| Ok(()));
 
+        let status = client.watch_connection_status();
+
         client.disconnect().await.unwrap();
 
-        assert_eq!(client.state.status.connection(), Status::Closed);
+        assert_eq!(client.connection_status(), Status::Closed);
+
+        let event = *status.borrow();
+        assert_eq!(event.status, Status::Closed);
+        assert_eq!(event.reason, StatusReason::Disconnect);
     }
 }
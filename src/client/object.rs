@@ -22,6 +22,7 @@ use astarte_interfaces::MappingPath;
 use tracing::debug;
 
 use crate::client::ValidatedObject;
+use crate::retention::DeliveryReceipt;
 use crate::Error;
 use crate::{aggregate::AstarteObject, transport::Connection};
 
@@ -37,7 +38,8 @@ where
         path: &MappingPath<'_>,
         data: AstarteObject,
         timestamp: Option<chrono::DateTime<chrono::Utc>>,
-    ) -> Result<(), Error>
+        confirm: bool,
+    ) -> Result<Option<DeliveryReceipt>, Error>
     where
         C::Sender: Publish,
     {
@@ -48,7 +50,14 @@ where
 
         debug!("sending object {}{}", interface_name, path);
 
-        Self::send(&self.state, &self.store, &mut self.sender, validated).await
+        Self::send(
+            &self.state,
+            &self.store,
+            &mut self.sender,
+            validated,
+            confirm,
+        )
+        .await
     }
 }
 
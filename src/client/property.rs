@@ -431,4 +431,177 @@ mod tests {
 
         assert_eq!(prop, None);
     }
+
+    #[tokio::test]
+    #[cfg(feature = "derive")]
+    async fn should_derive_astarte_properties() {
+        use crate::properties::PropertiesError;
+        use crate::transport::mock::MockSender;
+        use crate::AstarteProperties;
+
+        // Alias the crate to the resulting macro
+        use crate::{self as astarte_device_sdk};
+
+        #[derive(Debug, Clone, PartialEq, AstarteProperties)]
+        #[astarte_properties(
+            interface = "org.astarte-platform.rust.e2etest.DeviceProperty",
+            path = "/sensor_1"
+        )]
+        struct Sensor {
+            longinteger_endpoint: i64,
+            string_endpoint: Option<String>,
+            double_endpoint: f64,
+        }
+
+        let (mut client, _tx) = mock_client(&[E2E_DEVICE_PROPERTY]);
+
+        client.state.status.set_connected(true);
+
+        for (path, value) in [
+            (
+                "/sensor_1/longinteger_endpoint",
+                AstarteData::LongInteger(42),
+            ),
+            (
+                "/sensor_1/string_endpoint",
+                AstarteData::String("old".to_string()),
+            ),
+        ] {
+            client
+                .store
+                .store_prop(StoredProp {
+                    interface: E2E_DEVICE_PROPERTY_NAME,
+                    path,
+                    value: &value,
+                    interface_major: 0,
+                    ownership: Ownership::Device,
+                })
+                .await
+                .unwrap();
+        }
+
+        // Only the changed fields are sent by the cloned client
+        client.sender.expect_clone().once().returning(|| {
+            let mut sender = MockSender::new();
+
+            sender
+                .expect_unset()
+                .once()
+                .with(predicate::eq(ValidatedUnset {
+                    interface: E2E_DEVICE_PROPERTY_NAME.to_string(),
+                    path: "/sensor_1/string_endpoint".to_string(),
+                }))
+                .returning(|_| Ok(()));
+            sender
+                .expect_send_property()
+                .once()
+                .with(predicate::eq(ValidatedProperty {
+                    interface: E2E_DEVICE_PROPERTY_NAME.to_string(),
+                    path: "/sensor_1/double_endpoint".to_string(),
+                    version_major: 0,
+                    data: AstarteData::try_from(1.5).unwrap(),
+                }))
+                .returning(|_| Ok(()));
+
+            sender
+        });
+
+        let sensor = Sensor {
+            longinteger_endpoint: 42,
+            string_endpoint: None,
+            double_endpoint: 1.5,
+        };
+
+        sensor.set_all(&client).await.unwrap();
+
+        let loaded = Sensor::load(&client).await.unwrap();
+        assert_eq!(loaded, sensor);
+
+        let double = StoredProp {
+            interface: E2E_DEVICE_PROPERTY_NAME,
+            path: "/sensor_1/double_endpoint",
+            value: AstarteData::try_from(1.5).unwrap(),
+            interface_major: 0,
+            ownership: Ownership::Device,
+        };
+        client
+            .store
+            .delete_prop(&PropertyMapping::from(&double))
+            .await
+            .unwrap();
+
+        let err = Sensor::load(&client).await.unwrap_err();
+        assert!(
+            matches!(err, Error::Properties(PropertiesError::Missing { .. })),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "derive")]
+    async fn should_not_send_derived_properties_on_error() {
+        use crate::test::DEVICE_PROPERTIES_NO_UNSET;
+        use crate::transport::mock::MockSender;
+        use crate::AstarteProperties;
+
+        // Alias the crate to the resulting macro
+        use crate::{self as astarte_device_sdk};
+
+        const NO_UNSET_NAME: &str =
+            "org.astarte-platform.rust.examples.individual-properties.DevicePropertyNoUnset";
+
+        #[derive(Debug, Clone, PartialEq, AstarteProperties)]
+        #[astarte_properties(
+            interface = "org.astarte-platform.rust.e2etest.DeviceProperty",
+            path = "/sensor_1"
+        )]
+        struct Sensor {
+            longinteger_endpoint: i64,
+            double_endpoint: f64,
+        }
+
+        #[derive(Debug, Clone, PartialEq, AstarteProperties)]
+        #[astarte_properties(
+            interface = "org.astarte-platform.rust.examples.individual-properties.DevicePropertyNoUnset",
+            path = "/sensor_1"
+        )]
+        struct NoUnset {
+            enable: Option<bool>,
+        }
+
+        let (mut client, _tx) = mock_client(&[E2E_DEVICE_PROPERTY, DEVICE_PROPERTIES_NO_UNSET]);
+
+        client.state.status.set_connected(true);
+
+        client
+            .store
+            .store_prop(StoredProp {
+                interface: NO_UNSET_NAME,
+                path: "/sensor_1/enable",
+                value: &AstarteData::Boolean(true),
+                interface_major: 0,
+                ownership: Ownership::Device,
+            })
+            .await
+            .unwrap();
+
+        // The cloned clients must not send anything
+        client
+            .sender
+            .expect_clone()
+            .times(2)
+            .returning(MockSender::new);
+
+        // The invalid double is converted before sending the changed long integer
+        let sensor = Sensor {
+            longinteger_endpoint: 42,
+            double_endpoint: f64::NAN,
+        };
+
+        let err = sensor.set_all(&client).await.unwrap_err();
+        assert!(matches!(err, Error::Types(_)), "{err:?}");
+
+        let err = NoUnset { enable: None }.set_all(&client).await.unwrap_err();
+        assert!(matches!(err, Error::Validation(_)), "{err:?}");
+    }
 }
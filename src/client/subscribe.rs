@@ -0,0 +1,283 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Subscriptions to the events received on a specific interface.
+
+use std::pin::Pin;
+use std::sync::{Mutex, MutexGuard};
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use tracing::{debug, error};
+
+use crate::state::Status;
+use crate::transport::Connection;
+use crate::DeviceEvent;
+
+use super::{DeviceClient, RecvError};
+
+type EventSender = flume::Sender<Result<DeviceEvent, RecvError>>;
+
+impl<C> DeviceClient<C>
+where
+    C: Connection,
+{
+    /// Subscribe to the events received on an interface.
+    ///
+    /// The events and errors for the interface will be yielded by the returned stream instead of
+    /// being received through [`recv`](crate::Client::recv). If more subscriptions match the
+    /// same event, each of them will receive a copy of it. The events not matched by any
+    /// subscription will still be returned by [`recv`](crate::Client::recv).
+    ///
+    /// The stream buffers up to [`channel_size`](crate::builder::DeviceBuilder::channel_size)
+    /// events. If it's not polled and the buffer is full, the connection waits for it to be read,
+    /// like it does for [`recv`](crate::Client::recv).
+    ///
+    /// The stream ends when the connection is closed.
+    ///
+    /// ```no_run
+    /// use astarte_device_sdk::{
+    ///     store::memory::MemoryStore, builder::DeviceBuilder,
+    ///     transport::mqtt::MqttConfig, prelude::*,
+    /// };
+    /// use futures::StreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mqtt_config = MqttConfig::with_credential_secret("realm_id", "device_id", "credential_secret", "pairing_url");
+    ///
+    ///     let (client, connection) = DeviceBuilder::new().store(MemoryStore::new())
+    ///         .connection(mqtt_config).build().await.unwrap();
+    ///
+    ///     let mut commands = client.subscribe("com.example.Commands");
+    ///
+    ///     while let Some(event) = commands.next().await {
+    ///         println!("received {:?}", event);
+    ///     }
+    /// }
+    /// ```
+    pub fn subscribe(&self, interface_name: &str) -> EventStream {
+        self.subscribe_inner(interface_name, None)
+    }
+
+    /// Subscribe to the events received on an interface, with a path starting with the prefix.
+    ///
+    /// The prefix is matched on whole path levels, so `/sensor` will match `/sensor/value` but
+    /// not `/sensor2/value`. See [`subscribe`](DeviceClient::subscribe) for more information.
+    pub fn subscribe_path(&self, interface_name: &str, path_prefix: &str) -> EventStream {
+        self.subscribe_inner(interface_name, Some(path_prefix))
+    }
+
+    fn subscribe_inner(&self, interface_name: &str, path_prefix: Option<&str>) -> EventStream {
+        let (tx, rx) = flume::bounded(self.state.subscriptions.capacity);
+
+        // Drop the sender to end the stream immediately
+        if self.state.status.connection() != Status::Closed {
+            self.state
+                .subscriptions
+                .push(interface_name, path_prefix, tx);
+        }
+
+        EventStream {
+            rx: rx.into_stream(),
+        }
+    }
+}
+
+/// Stream of the events received on a subscribed interface.
+///
+/// Returned by [`DeviceClient::subscribe`].
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct EventStream {
+    rx: flume::r#async::RecvStream<'static, Result<DeviceEvent, RecvError>>,
+}
+
+impl Stream for EventStream {
+    type Item = Result<DeviceEvent, RecvError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().rx).poll_next(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rx.size_hint()
+    }
+}
+
+#[derive(Debug)]
+struct Subscriber {
+    interface: String,
+    path_prefix: Option<String>,
+    tx: EventSender,
+}
+
+impl Subscriber {
+    fn matches(&self, interface: &str, path: &str) -> bool {
+        if self.interface != interface {
+            return false;
+        }
+
+        let Some(prefix) = self.path_prefix.as_deref() else {
+            return true;
+        };
+
+        let prefix = prefix.trim_end_matches('/');
+
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+}
+
+/// Registered subscriptions, shared between the client and the connection.
+#[derive(Debug)]
+pub(crate) struct Subscriptions {
+    /// Number of events buffered by each subscription.
+    capacity: usize,
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl Subscriptions {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn push(&self, interface: &str, path_prefix: Option<&str>, tx: EventSender) {
+        debug!(interface, path_prefix, "new subscription");
+
+        self.lock().push(Subscriber {
+            interface: interface.to_string(),
+            path_prefix: path_prefix.map(str::to_string),
+            tx,
+        });
+    }
+
+    /// Returns the senders of the subscriptions matching the interface and path.
+    ///
+    /// The subscriptions with the stream dropped are removed.
+    pub(crate) fn matching(&self, interface: &str, path: &str) -> Vec<EventSender> {
+        let mut subscribers = self.lock();
+
+        subscribers.retain(|sub| !sub.tx.is_disconnected());
+
+        subscribers
+            .iter()
+            .filter(|sub| sub.matches(interface, path))
+            .map(|sub| sub.tx.clone())
+            .collect()
+    }
+
+    /// Drop all the subscriptions, ending the streams.
+    pub(crate) fn close(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Subscriber>> {
+        self.subscribers.lock().unwrap_or_else(|err| {
+            error!("subscriptions mutex was poisoned");
+
+            err.into_inner()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use futures::StreamExt;
+    use pretty_assertions::assert_eq;
+
+    use crate::client::tests::mock_client;
+    use crate::state::StatusReason;
+    use crate::{AstarteData, Value};
+
+    use super::*;
+
+    #[test]
+    fn should_match_path_prefix() {
+        let (tx, _rx) = flume::unbounded();
+
+        let sub = Subscriber {
+            interface: "com.example.Sensor".to_string(),
+            path_prefix: Some("/sensor/".to_string()),
+            tx,
+        };
+
+        assert!(sub.matches("com.example.Sensor", "/sensor"));
+        assert!(sub.matches("com.example.Sensor", "/sensor/value"));
+        assert!(!sub.matches("com.example.Sensor", "/sensor2/value"));
+        assert!(!sub.matches("com.example.Other", "/sensor/value"));
+    }
+
+    #[tokio::test]
+    async fn should_receive_subscribed() {
+        let (client, _tx) = mock_client(&[]);
+
+        let mut stream = client.subscribe_path("interface", "/sensor");
+
+        let exp = DeviceEvent {
+            interface: "interface".to_string(),
+            path: "/sensor/value".to_string(),
+            data: Value::Individual {
+                data: AstarteData::LongInteger(42),
+                timestamp: Utc::now(),
+            },
+        };
+
+        let senders = client
+            .state
+            .subscriptions
+            .matching("interface", "/sensor/value");
+        assert_eq!(senders.len(), 1);
+        assert!(client
+            .state
+            .subscriptions
+            .matching("interface", "/other/value")
+            .is_empty());
+
+        senders[0].send_async(Ok(exp.clone())).await.unwrap();
+        drop(senders);
+
+        let event = stream.next().await.unwrap().unwrap();
+        assert_eq!(event, exp);
+
+        client.state.status.close(StatusReason::ConnectionDropped);
+        client.state.subscriptions.close();
+
+        assert!(stream.next().await.is_none());
+        assert!(client.subscribe("interface").next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_remove_dropped() {
+        let (client, _tx) = mock_client(&[]);
+
+        let stream = client.subscribe("interface");
+        drop(stream);
+
+        assert!(client
+            .state
+            .subscriptions
+            .matching("interface", "/path")
+            .is_empty());
+        assert!(client.state.subscriptions.lock().is_empty());
+    }
+}
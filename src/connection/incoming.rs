@@ -21,6 +21,8 @@ use astarte_interfaces::{DatastreamIndividual, DatastreamObject, MappingPath, Pr
 use tracing::{debug, error, warn};
 
 use crate::client::RecvError;
+use crate::error::Report;
+use crate::event::DeviceEvent;
 use crate::interfaces::MappingRef;
 use crate::store::{PropertyMapping, PropertyStore, StoredProp};
 use crate::transport::{Connection, Receive, TransportError};
@@ -83,6 +85,64 @@ where
         Ok(data)
     }
 
+    /// Sends the event to the matching subscriptions, or to the client if none matches.
+    ///
+    /// The events are sent to all the matching subscriptions, while errors are sent only to the
+    /// first one, since they cannot be cloned. A subscription that is full is waited for, like
+    /// the channel of the client.
+    pub(crate) async fn dispatch(
+        &self,
+        event: Result<DeviceEvent, RecvError>,
+    ) -> Result<(), Error> {
+        let event = match event {
+            Ok(event) => {
+                let subscribers = self
+                    .state
+                    .subscriptions
+                    .matching(&event.interface, &event.path);
+
+                let mut delivered = false;
+                for tx in subscribers {
+                    // The stream was dropped in the meantime
+                    if tx.send_async(Ok(event.clone())).await.is_ok() {
+                        delivered = true;
+                    }
+                }
+
+                if delivered {
+                    return Ok(());
+                }
+
+                Ok(event)
+            }
+            Err(err) => {
+                let subscribers = err
+                    .interface_path()
+                    .map(|(interface, path)| self.state.subscriptions.matching(interface, path))
+                    .unwrap_or_default();
+
+                let mut event = Err(err);
+                for tx in subscribers {
+                    match tx.send_async(event).await {
+                        Ok(()) => return Ok(()),
+                        Err(flume::SendError(value)) => event = value,
+                    }
+                }
+
+                event
+            }
+        };
+
+        self.tx.send_async(event).await.map_err(|send_err| {
+            match send_err.into_inner() {
+                Ok(_) => debug!("disconnected"),
+                Err(err) => error!(error = %Report::new(err), "failed to send receive error"),
+            }
+
+            Error::Disconnected
+        })
+    }
+
     /// Handles the payload of an interface with [`InterfaceAggregation::Individual`]
     async fn handle_property(
         &self,
@@ -548,4 +608,46 @@ mod tests {
             TransportError::Recv(RecvError::Ownership { .. })
         ));
     }
+
+    #[tokio::test]
+    async fn dispatch_waits_for_full_subscription() {
+        let (connection, rx) = mock_connection(&[]);
+
+        let event = DeviceEvent {
+            interface: "interface".to_string(),
+            path: "/sensor/value".to_string(),
+            data: Value::Individual {
+                data: AstarteData::Integer(42),
+                timestamp: Utc::now(),
+            },
+        };
+
+        let (full_tx, full_rx) = flume::bounded(1);
+        full_tx.send(Ok(event.clone())).unwrap();
+        connection
+            .state
+            .subscriptions
+            .push("interface", None, full_tx);
+
+        let (tx, sub_rx) = flume::bounded(1);
+        connection.state.subscriptions.push("interface", None, tx);
+
+        let dispatch = connection.dispatch(Ok(event.clone()));
+        tokio::pin!(dispatch);
+
+        tokio::time::timeout(std::time::Duration::from_millis(100), &mut dispatch)
+            .await
+            .expect_err("dispatch didn't wait for the full subscription");
+
+        assert_eq!(full_rx.recv_async().await.unwrap().unwrap(), event);
+
+        tokio::time::timeout(std::time::Duration::from_secs(1), dispatch)
+            .await
+            .expect("dispatch blocked after the subscription was read")
+            .unwrap();
+
+        assert_eq!(full_rx.try_recv().unwrap().unwrap(), event);
+        assert_eq!(sub_rx.try_recv().unwrap().unwrap(), event);
+        assert!(rx.is_empty());
+    }
 }
@@ -23,11 +23,10 @@ use std::sync::Arc;
 
 use chrono::Utc;
 use tokio::task::JoinHandle;
-use tracing::{debug, error, info, instrument, trace, warn};
+use tracing::{error, info, instrument, trace, warn};
 
-use crate::error::Report;
-use crate::retry::ExponentialIter;
-use crate::state::{SharedState, Status};
+use crate::retry::Backoff;
+use crate::state::{SharedState, Status, StatusReason};
 use crate::transport::TransportError;
 use crate::Timestamp;
 use crate::{
@@ -35,7 +34,6 @@ use crate::{
     event::DeviceEvent,
     store::wrapper::StoreWrapper,
     transport::{Connection, Publish, Receive, Reconnect},
-    Error,
 };
 
 mod incoming;
@@ -88,7 +86,7 @@ where
     sender: C::Sender,
     state: Arc<SharedState>,
     resend: Option<JoinHandle<()>>,
-    backoff: ExponentialIter,
+    backoff: Backoff,
 }
 
 impl<C> DeviceConnection<C>
@@ -101,6 +99,7 @@ where
         state: Arc<SharedState>,
         connection: C,
         sender: C::Sender,
+        backoff: Backoff,
     ) -> Self {
         Self {
             tx,
@@ -109,7 +108,7 @@ where
             connection,
             sender,
             resend: None,
-            backoff: ExponentialIter::default(),
+            backoff,
         }
     }
 
@@ -174,11 +173,9 @@ where
                 data,
             })?;
 
-        self.tx.send(Ok(event)).map_err(|err| {
-            debug!(error = %Report::new(err), "disconnected");
-
-            TransportError::Transport(Error::Disconnected)
-        })?;
+        self.dispatch(Ok(event))
+            .await
+            .map_err(TransportError::Transport)?;
 
         Ok(self.state.status.connection())
     }
@@ -190,7 +187,8 @@ where
 {
     fn drop(&mut self) {
         self.state.introspection.close();
-        self.state.status.close();
+        self.state.status.close(StatusReason::ConnectionDropped);
+        self.state.subscriptions.close();
     }
 }
 
@@ -216,16 +214,7 @@ where
                 }
                 // send the error to the client
                 Err(TransportError::Recv(recv_err)) => {
-                    self.tx
-                        .send_async(Err(recv_err))
-                        .await
-                        .map_err(|send_err| {
-                            if let Err(err) = send_err.into_inner() {
-                                error!(error = %Report::new(err), "failed to send receive error");
-                            }
-
-                            Error::Disconnected
-                        })?;
+                    self.dispatch(Err(recv_err)).await?;
                 }
             }
         }
@@ -245,6 +234,7 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use crate::builder::{DEFAULT_CHANNEL_SIZE, DEFAULT_VOLATILE_CAPACITY};
+    use crate::client::Subscriptions;
     use crate::interfaces::Interfaces;
     use crate::retention::memory::VolatileStore;
     use crate::store::memory::MemoryStore;
@@ -284,6 +274,7 @@ mod tests {
         let state = SharedState::new(
             interfaces,
             VolatileStore::with_capacity(DEFAULT_VOLATILE_CAPACITY),
+            Subscriptions::with_capacity(DEFAULT_CHANNEL_SIZE),
         );
 
         let connection = DeviceConnection::new(
@@ -292,6 +283,7 @@ mod tests {
             Arc::new(state),
             connection,
             sender,
+            Backoff::default(),
         );
 
         (connection, rx)
@@ -379,4 +371,54 @@ mod tests {
 
         assert_eq!(Status::Connected, status);
     }
+
+    #[tokio::test]
+    async fn dispatch_subscriptions() {
+        let (connection, rx) = mock_connection(&[E2E_SERVER_DATASTREAM]);
+
+        let (sub_tx, sub_rx) = flume::unbounded();
+        connection.state.subscriptions.push(
+            E2E_SERVER_DATASTREAM_NAME,
+            Some("/boolean_endpoint"),
+            sub_tx,
+        );
+
+        let event = |path: &str| DeviceEvent {
+            interface: E2E_SERVER_DATASTREAM_NAME.to_string(),
+            path: path.to_string(),
+            data: crate::Value::Individual {
+                data: AstarteData::Boolean(true),
+                timestamp: Utc::now(),
+            },
+        };
+
+        let exp = event("/boolean_endpoint");
+        connection.dispatch(Ok(exp.clone())).await.unwrap();
+        assert_eq!(sub_rx.try_recv().unwrap().unwrap(), exp);
+        assert!(rx.is_empty());
+
+        let exp = event("/integer_endpoint");
+        connection.dispatch(Ok(exp.clone())).await.unwrap();
+        assert_eq!(rx.try_recv().unwrap().unwrap(), exp);
+        assert!(sub_rx.is_empty());
+
+        connection
+            .dispatch(Err(RecvError::MissingTimestamp {
+                interface_name: E2E_SERVER_DATASTREAM_NAME.to_string(),
+                path: "/boolean_endpoint".to_string(),
+            }))
+            .await
+            .unwrap();
+        assert!(matches!(
+            sub_rx.try_recv().unwrap(),
+            Err(RecvError::MissingTimestamp { .. })
+        ));
+        assert!(rx.is_empty());
+
+        // Falls back to the client once the subscription is dropped
+        drop(sub_rx);
+        let exp = event("/boolean_endpoint");
+        connection.dispatch(Ok(exp.clone())).await.unwrap();
+        assert_eq!(rx.try_recv().unwrap().unwrap(), exp);
+    }
 }
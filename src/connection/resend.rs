@@ -17,7 +17,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::sync::Arc;
-use std::time::Duration;
 
 use tracing::{debug, error, trace};
 
@@ -25,6 +24,7 @@ use crate::builder::DEFAULT_CHANNEL_SIZE;
 use crate::error::Report;
 use crate::retention::memory::ItemValue;
 use crate::retention::{RetentionId, StoredRetention, StoredRetentionExt};
+use crate::retry::Backoff;
 use crate::state::SharedState;
 use crate::store::wrapper::StoreWrapper;
 use crate::store::StoreCapabilities;
@@ -52,6 +52,8 @@ where
             retention.cleanup_introspection(&interfaces).await?;
         }
 
+        retention.notify_removed(&self.state.receipts).await;
+
         self.resend_retention(false).await;
 
         Ok(())
@@ -97,7 +99,7 @@ where
                 }
             }
 
-            if let Err(err) = Self::resend_stored_publishes(&mut store, &mut sender).await {
+            if let Err(err) = Self::resend_stored_publishes(&mut store, &mut sender, &state).await {
                 error!(error = %Report::new(&err), "error sending stored retention");
             }
         }));
@@ -139,6 +141,8 @@ where
                 .get_unsent(&mut buf, DEFAULT_CHANNEL_SIZE)
                 .await;
 
+            state.volatile_store.notify_removed(&state.receipts).await;
+
             trace!("loaded {count} volatile publishes");
 
             for (id, value) in buf.drain(..) {
@@ -172,6 +176,7 @@ where
     async fn resend_stored_publishes(
         store: &mut StoreWrapper<C::Store>,
         sender: &mut C::Sender,
+        state: &SharedState,
     ) -> Result<(), Error>
     where
         C::Sender: Publish,
@@ -188,6 +193,8 @@ where
                 .unsent_publishes(DEFAULT_CHANNEL_SIZE, &mut buf)
                 .await?;
 
+            retention.notify_removed(&state.receipts).await;
+
             trace!("loaded {count} stored publishes");
 
             for (id, info) in buf.drain(..) {
@@ -207,6 +214,21 @@ where
         Ok(())
     }
 
+    /// Waits the delay of the reconnect policy, or errors if the attempts are exhausted.
+    async fn wait_backoff(backoff: &mut Backoff) -> Result<(), Error> {
+        let Some(timeout) = backoff.next() else {
+            error!("reconnection attempts exhausted");
+
+            return Err(Error::ConnectionTimeout);
+        };
+
+        debug!("waiting {timeout:?} before retrying");
+
+        tokio::time::sleep(timeout).await;
+
+        Ok(())
+    }
+
     async fn reconnect(&mut self) -> Result<(), Error>
     where
         C: Reconnect,
@@ -218,7 +240,7 @@ where
         // connection will loop that will keep throwing errors.
         //
         // The back-off will start with a wait time of 0s and increase exponentially until it
-        // reaches a max. We also keep track of when the last disconnection happened, to reset the
+        // reaches a max. We also keep track of when the connection was established, to reset the
         // wait time only if the connection has been stable for a certain duration of time.
         //
         // An example of an error loop is when the MQTT connection Interfaces (the device
@@ -231,21 +253,14 @@ where
         //
         // If we didn't keep track of the last disconnection, the error loop above would continue to
         // happen without timeouts, wasting device bandwidth and resources.
-        let timeout = self.backoff.next();
-
-        debug!("waiting {timeout} seconds before retrying");
-
-        tokio::time::sleep(Duration::from_secs(timeout)).await;
+        Self::wait_backoff(&mut self.backoff).await?;
 
         while !self.connection.reconnect(&interfaces).await? {
-            let timeout = self.backoff.next();
-
-            debug!("waiting {timeout} seconds before retrying");
-
-            tokio::time::sleep(Duration::from_secs(timeout)).await;
+            Self::wait_backoff(&mut self.backoff).await?;
         }
 
         // Now we are reconnected
+        self.backoff.connected();
         self.state.status.set_connected(true);
 
         Ok(())
@@ -262,12 +277,16 @@ mod tests {
 
     use crate::connection::tests::{mock_connection, mock_connection_with_store};
     use crate::retention::{PublishInfo, RetentionId, StoredRetention, StoredRetentionExt};
+    use crate::retry::tests::ResetImmediately;
+    use crate::retry::{FixedDelay, MaxAttempts};
     use crate::store::{SqliteStore, StoreCapabilities};
     use crate::test::{STORED_DEVICE_DATASTREAM, STORED_DEVICE_DATASTREAM_NAME};
     use crate::transport::mock::MockSender;
     use crate::validate::ValidatedIndividual;
     use crate::AstarteData;
 
+    use super::*;
+
     #[tokio::test]
     async fn reconnect_success_no_data() {
         let (mut connection, _rx) = mock_connection(&[]);
@@ -292,6 +311,60 @@ mod tests {
         connection.reconnect_and_resend().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn reconnect_attempts_exhausted() {
+        let (mut connection, _rx) = mock_connection(&[]);
+
+        connection.backoff = Backoff::new(Arc::new(MaxAttempts::new(
+            FixedDelay::new(Duration::ZERO),
+            2,
+        )));
+
+        connection
+            .connection
+            .expect_reconnect()
+            .with(predicate::always())
+            .times(2)
+            .returning(|_| Ok(false));
+
+        let err = connection.reconnect().await.unwrap_err();
+
+        assert!(matches!(err, Error::ConnectionTimeout));
+    }
+
+    #[tokio::test]
+    async fn reconnect_attempts_reset_between_outages() {
+        let (mut connection, _rx) = mock_connection(&[]);
+
+        connection.backoff = Backoff::new(Arc::new(ResetImmediately(MaxAttempts::new(
+            FixedDelay::new(Duration::ZERO),
+            2,
+        ))));
+
+        let mut seq = Sequence::new();
+
+        // each outage uses all the attempts
+        for _ in 0..2 {
+            connection
+                .connection
+                .expect_reconnect()
+                .with(predicate::always())
+                .once()
+                .in_sequence(&mut seq)
+                .returning(|_| Ok(false));
+            connection
+                .connection
+                .expect_reconnect()
+                .with(predicate::always())
+                .once()
+                .in_sequence(&mut seq)
+                .returning(|_| Ok(true));
+        }
+
+        connection.reconnect().await.unwrap();
+        connection.reconnect().await.unwrap();
+    }
+
     #[tokio::test]
     async fn sqlite_init_stored_retention_simple() {
         let tmp = TempDir::new().unwrap();
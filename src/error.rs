@@ -31,6 +31,8 @@ use crate::retention::RetentionError;
 use crate::session::SessionError;
 use crate::store::error::StoreError;
 use crate::transport::mqtt::error::MqttError;
+use crate::transport::mqtt::PollError;
+use crate::types::serde::SerdeError;
 use crate::types::TypeError;
 use crate::validate::UserValidationError;
 
@@ -72,6 +74,9 @@ pub enum Error {
     /// Errors when converting between Astarte types.
     #[error("couldn't convert to Astarte Type")]
     Types(#[from] TypeError),
+    /// Errors when converting with serde from or into the Astarte types.
+    #[error("couldn't convert with serde")]
+    Serde(#[from] SerdeError),
     /// Error while parsing the /control/consumer/properties payload.
     #[error("couldn't handle properties")]
     Properties(#[from] PropertiesError),
@@ -84,6 +89,9 @@ pub enum Error {
     /// Invalid aggregation between the interface and the data.
     #[error(transparent)]
     Aggregation(#[from] AggregationError),
+    /// A flattened field of an object has the same key of another field.
+    #[error("duplicate key {0} in the object")]
+    DuplicateObjectKey(String),
     /// Invalid interface type between the interface and the data.
     #[error(transparent)]
     InterfaceType(#[from] InterfaceTypeError),
@@ -92,7 +100,13 @@ pub enum Error {
     Infallible(#[from] Infallible),
     /// Error returned by the MQTT connection.
     #[error(transparent)]
-    Mqtt(#[from] MqttError),
+    Mqtt(MqttError),
+    /// Astarte rejected the credentials of the device and it couldn't be registered again.
+    ///
+    /// This is an unrecoverable error for the SDK, the device needs a new credential secret or
+    /// pairing token.
+    #[error("the device credentials were rejected by Astarte")]
+    Unauthorized(#[source] crate::transport::mqtt::PairingError),
     /// Error when the Device is disconnected from Astarte or client.
     ///
     /// This is an unrecoverable error for the SDK.
@@ -111,6 +125,16 @@ pub enum Error {
     Grpc(#[from] crate::transport::grpc::GrpcError),
 }
 
+impl From<MqttError> for Error {
+    fn from(value: MqttError) -> Self {
+        match value {
+            MqttError::Pairing(err) if err.is_unauthorized() => Error::Unauthorized(err),
+            MqttError::Poll(PollError::Unauthorized(err)) => Error::Unauthorized(err),
+            err => Error::Mqtt(err),
+        }
+    }
+}
+
 /// Aggregation error.
 ///
 /// This provides additional context in case of an aggregation error
@@ -18,7 +18,9 @@
 
 //! Event returned form the loop.
 
-use astarte_interfaces::mapping::endpoint::EndpointError;
+use std::fmt::Display;
+
+use astarte_interfaces::mapping::endpoint::{Endpoint, EndpointError};
 use astarte_interfaces::mapping::path::MappingPathError;
 
 use crate::aggregate::AstarteObject;
@@ -26,6 +28,11 @@ use crate::error::{AggregationError, InterfaceTypeError};
 use crate::types::TypeError;
 use crate::{AstarteData, Timestamp};
 
+mod params;
+pub mod router;
+
+pub use self::params::{PathParams, PathParamsError};
+
 /// Astarte device event data structure.
 ///
 /// Data structure received from the [`Client`](crate::DeviceClient) when the
@@ -40,6 +47,25 @@ pub struct DeviceEvent {
     pub data: Value,
 }
 
+impl DeviceEvent {
+    /// Returns the named parameters of the event path, captured by the endpoint of the mapping.
+    ///
+    /// For an object the endpoint can be the one of a mapping or the object path, see
+    /// [`PathParams::new`] and [`PathParams::new_object`] for more information.
+    pub fn path_params<'a, T>(
+        &'a self,
+        endpoint: &'a Endpoint<T>,
+    ) -> Result<PathParams<'a>, PathParamsError>
+    where
+        T: AsRef<str> + Display,
+    {
+        match self.data {
+            Value::Object { .. } => PathParams::new_object(endpoint, &self.path),
+            Value::Individual { .. } | Value::Property(_) => PathParams::new(endpoint, &self.path),
+        }
+    }
+}
+
 /// Conversion error from an [`DeviceEvent].
 #[derive(thiserror::Error, Debug)]
 #[non_exhaustive]
@@ -88,6 +114,9 @@ pub enum FromEventError {
     /// couldn't parse the [`crate::astarte_interfaces::MappingPath`]
     #[error("couldn't parse the mapping path")]
     MappingPath(#[from] MappingPathError),
+    /// couldn't extract the parameters from the path
+    #[error("couldn't extract the path parameters")]
+    PathParams(#[from] PathParamsError),
 }
 
 /// Converts a struct form an [`DeviceEvent`].
@@ -165,6 +194,14 @@ pub trait FromEvent: Sized {
 
     /// Perform the conversion from the event.
     fn from_event(event: DeviceEvent) -> Result<Self, Self::Err>;
+
+    /// Name of the interface the type is converted from.
+    ///
+    /// It's used by the [`EventRouter`](router::EventRouter) to select the handlers for an event.
+    /// Returns [`None`] by default, to try the conversion on the events of every interface.
+    fn interface_name() -> Option<&'static str> {
+        None
+    }
 }
 
 /// Data for an [`Astarte data event`](DeviceEvent).
@@ -372,6 +409,52 @@ mod tests {
         };
 
         assert_eq!(sensor, expected);
+        assert_eq!(Sensor::interface_name(), Some("com.example.Sensor"));
+    }
+
+    #[test]
+    #[cfg(feature = "derive")]
+    fn should_derive_form_event_obj_path_params() {
+        use crate::aggregate::AstarteObject;
+        use crate::{DeviceEvent, FromEvent, Value};
+
+        // Alias the crate to the resulting macro
+        use crate::{self as astarte_device_sdk};
+
+        #[derive(Debug, FromEvent, PartialEq, Eq)]
+        #[from_event(
+            interface = "com.example.Sensor",
+            path = "/%{sensor_id}",
+            aggregation = "object"
+        )]
+        struct Sensor {
+            #[from_event(path_param = "sensor_id")]
+            id: u32,
+            value: i32,
+        }
+
+        let mut data = AstarteObject::new();
+        data.insert("value".to_string(), 42i32.into());
+
+        let event = |path: &str| DeviceEvent {
+            interface: "com.example.Sensor".to_string(),
+            path: path.to_string(),
+            data: Value::Object {
+                data: data.clone(),
+                timestamp: Utc::now(),
+            },
+        };
+
+        let sensor = Sensor::from_event(event("/7")).expect("couldn't parse the event");
+
+        assert_eq!(sensor, Sensor { id: 7, value: 42 });
+
+        let err = Sensor::from_event(event("/foo")).unwrap_err();
+
+        assert!(matches!(
+            err,
+            FromEventError::PathParams(PathParamsError::Parse { name, .. }) if name == "sensor_id"
+        ));
     }
 
     #[test]
@@ -489,4 +572,137 @@ mod tests {
 
         assert_eq!(temperature, expected);
     }
+
+    #[test]
+    #[cfg(feature = "derive")]
+    fn should_derive_form_event_dispatch() {
+        use crate::aggregate::AstarteObject;
+        use crate::{DeviceEvent, FromEvent, Value};
+
+        // Alias the crate to the resulting macro
+        use crate::{self as astarte_device_sdk};
+
+        #[derive(Debug, FromEvent, PartialEq)]
+        #[from_event(rename_all = "camelCase")]
+        enum Commands {
+            #[from_event(interface = "com.example.Led", path = "/%{led_id}/enable")]
+            LedEnable(bool),
+            #[from_event(
+                interface = "com.example.Config",
+                path = "/threshold",
+                interface_type = "properties",
+                allow_unset = true
+            )]
+            Threshold(Option<i32>),
+            #[from_event(
+                interface = "com.example.Move",
+                path = "/%{motor_id}",
+                aggregation = "object"
+            )]
+            Move {
+                #[from_event(path_param = "motor_id")]
+                motor: u32,
+                target_speed: i32,
+            },
+            #[from_event(fallback = true)]
+            Unknown(DeviceEvent),
+        }
+
+        let event = DeviceEvent {
+            interface: "com.example.Led".to_string(),
+            path: "/1/enable".to_string(),
+            data: Value::Individual {
+                data: true.into(),
+                timestamp: Utc::now(),
+            },
+        };
+        assert_eq!(
+            Commands::from_event(event).expect("couldn't parse the event"),
+            Commands::LedEnable(true)
+        );
+
+        let event = DeviceEvent {
+            interface: "com.example.Config".to_string(),
+            path: "/threshold".to_string(),
+            data: Value::Property(None),
+        };
+        assert_eq!(
+            Commands::from_event(event).expect("couldn't parse the event"),
+            Commands::Threshold(None)
+        );
+
+        let mut data = AstarteObject::new();
+        data.insert("targetSpeed".to_string(), 42i32.into());
+        let event = DeviceEvent {
+            interface: "com.example.Move".to_string(),
+            path: "/3".to_string(),
+            data: Value::Object {
+                data,
+                timestamp: Utc::now(),
+            },
+        };
+        assert_eq!(
+            Commands::from_event(event).expect("couldn't parse the event"),
+            Commands::Move {
+                motor: 3,
+                target_speed: 42
+            }
+        );
+
+        let event = DeviceEvent {
+            interface: "com.example.Other".to_string(),
+            path: "/foo".to_string(),
+            data: Value::Property(None),
+        };
+        assert_eq!(
+            Commands::from_event(event.clone()).expect("couldn't parse the event"),
+            Commands::Unknown(event)
+        );
+
+        // A matching mapping with the wrong data is still an error
+        let event = DeviceEvent {
+            interface: "com.example.Led".to_string(),
+            path: "/1/enable".to_string(),
+            data: Value::Property(None),
+        };
+        assert!(matches!(
+            Commands::from_event(event),
+            Err(FromEventError::InterfaceType(_))
+        ));
+
+        assert_eq!(Commands::interface_name(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "derive")]
+    fn should_derive_form_event_dispatch_without_fallback() {
+        use crate::{DeviceEvent, FromEvent, Value};
+
+        // Alias the crate to the resulting macro
+        use crate::{self as astarte_device_sdk};
+
+        #[derive(Debug, FromEvent, PartialEq)]
+        enum Commands {
+            #[from_event(interface = "com.example.Led", path = "/enable")]
+            LedEnable(bool),
+        }
+
+        let event = |interface: &str, path: &str| DeviceEvent {
+            interface: interface.to_string(),
+            path: path.to_string(),
+            data: Value::Individual {
+                data: true.into(),
+                timestamp: Utc::now(),
+            },
+        };
+
+        assert!(matches!(
+            Commands::from_event(event("com.example.Led", "/disable")),
+            Err(FromEventError::Path { interface: "com.example.Led", base_path }) if base_path == "/disable"
+        ));
+        assert!(matches!(
+            Commands::from_event(event("com.example.Other", "/enable")),
+            Err(FromEventError::Interface(interface)) if interface == "com.example.Other"
+        ));
+    }
 }
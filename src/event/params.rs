@@ -0,0 +1,259 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parameters captured from the path of an event by a parametric endpoint.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use astarte_interfaces::mapping::endpoint::{Endpoint, Level};
+use astarte_interfaces::mapping::path::MappingPathError;
+use astarte_interfaces::MappingPath;
+
+use crate::error::DynError;
+
+/// Error while extracting the parameters from a path.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PathParamsError {
+    /// Couldn't parse the path.
+    #[error("couldn't parse the mapping path")]
+    MappingPath(#[from] MappingPathError),
+    /// The path doesn't match the endpoint.
+    #[error("the path {path} doesn't match the endpoint {endpoint}")]
+    Mismatch {
+        /// Endpoint the path was matched with.
+        endpoint: String,
+        /// Path of the event.
+        path: String,
+    },
+    /// The endpoint doesn't have a parameter with the name.
+    #[error("missing path parameter {0}")]
+    Missing(String),
+    /// Couldn't parse the value of the parameter.
+    #[error("couldn't parse the path parameter {name}")]
+    Parse {
+        /// Name of the parameter.
+        name: String,
+        /// Error returned by [`FromStr`].
+        #[source]
+        source: DynError,
+    },
+}
+
+/// Named parameters of a path, captured by an [`Endpoint`] like `/%{sensor_id}/value`.
+///
+/// ```
+/// use astarte_device_sdk::astarte_interfaces::mapping::endpoint::Endpoint;
+/// use astarte_device_sdk::event::PathParams;
+///
+/// let endpoint = Endpoint::<String>::try_from("/%{sensor_id}/value").unwrap();
+/// let params = PathParams::new(&endpoint, "/42/value").unwrap();
+///
+/// assert_eq!(params.get("sensor_id"), Some("42"));
+/// assert_eq!(params.parse::<u32>("sensor_id").unwrap(), 42);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathParams<'a> {
+    params: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> PathParams<'a> {
+    /// Captures the parameters of the path matching the endpoint.
+    ///
+    /// The path must have the same levels of the endpoint, use [`PathParams::new_object`] for the
+    /// path of an object datastream.
+    pub fn new<T>(endpoint: &'a Endpoint<T>, path: &'a str) -> Result<Self, PathParamsError>
+    where
+        T: AsRef<str> + Display,
+    {
+        Self::capture(endpoint, path, false)
+    }
+
+    /// Captures the parameters of the path of an object datastream matching the endpoint.
+    ///
+    /// The path of an object datastream is the one of the endpoint without the last level, so
+    /// both the mapping endpoint and the object path can be used.
+    pub fn new_object<T>(endpoint: &'a Endpoint<T>, path: &'a str) -> Result<Self, PathParamsError>
+    where
+        T: AsRef<str> + Display,
+    {
+        Self::capture(endpoint, path, true)
+    }
+
+    fn capture<T>(
+        endpoint: &'a Endpoint<T>,
+        path: &'a str,
+        object: bool,
+    ) -> Result<Self, PathParamsError>
+    where
+        T: AsRef<str> + Display,
+    {
+        let mapping = MappingPath::try_from(path)?;
+
+        let levels = endpoint.iter().count();
+        let path_levels = mapping.len();
+        let is_object_path = object && levels == path_levels + 1;
+
+        if levels != path_levels && !is_object_path {
+            return Err(Self::mismatch(endpoint, path));
+        }
+
+        let mut params = Vec::new();
+
+        // The path was validated, so it starts with a `/`
+        for (level, value) in endpoint.iter().zip(path.split('/').skip(1)) {
+            match level {
+                Level::Simple(level) if level.as_ref() == value => {}
+                Level::Simple(_) => return Err(Self::mismatch(endpoint, path)),
+                Level::Parameter(name) => params.push((name.as_ref(), value)),
+            }
+        }
+
+        Ok(Self { params })
+    }
+
+    fn mismatch<T>(endpoint: &Endpoint<T>, path: &str) -> PathParamsError
+    where
+        T: Display,
+    {
+        PathParamsError::Mismatch {
+            endpoint: endpoint.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    /// Returns the value of the parameter.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.params
+            .iter()
+            .find_map(|(param, value)| (*param == name).then_some(*value))
+    }
+
+    /// Parses the value of the parameter.
+    pub fn parse<T>(&self, name: &str) -> Result<T, PathParamsError>
+    where
+        T: FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        let value = self
+            .get(name)
+            .ok_or_else(|| PathParamsError::Missing(name.to_string()))?;
+
+        value.parse().map_err(|err: T::Err| PathParamsError::Parse {
+            name: name.to_string(),
+            source: err.into(),
+        })
+    }
+
+    /// Iterates over the names and values of the parameters.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.params.iter().copied()
+    }
+
+    /// Returns the number of parameters.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Returns `true` if the endpoint has no parameters.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_capture_params() {
+        let endpoint = Endpoint::<String>::try_from("/%{sensor_id}/values/%{idx}").unwrap();
+
+        let params = PathParams::new(&endpoint, "/foo/values/3").unwrap();
+
+        assert_eq!(params.len(), 2);
+        assert_eq!(params.get("sensor_id"), Some("foo"));
+        assert_eq!(params.parse::<u8>("idx").unwrap(), 3);
+        assert_eq!(
+            params.iter().collect::<Vec<_>>(),
+            [("sensor_id", "foo"), ("idx", "3")]
+        );
+
+        assert!(matches!(
+            params.parse::<u8>("sensor_id"),
+            Err(PathParamsError::Parse { name, .. }) if name == "sensor_id"
+        ));
+        assert!(matches!(
+            params.parse::<u8>("missing"),
+            Err(PathParamsError::Missing(name)) if name == "missing"
+        ));
+    }
+
+    #[test]
+    fn should_capture_object_path() {
+        let endpoint = Endpoint::<String>::try_from("/%{sensor_id}/value").unwrap();
+
+        let params = PathParams::new_object(&endpoint, "/foo").unwrap();
+        assert_eq!(params.get("sensor_id"), Some("foo"));
+
+        let params = PathParams::new_object(&endpoint, "/foo/value").unwrap();
+        assert_eq!(params.get("sensor_id"), Some("foo"));
+
+        let endpoint = Endpoint::<String>::try_from("/%{sensor_id}").unwrap();
+        let params = PathParams::new_object(&endpoint, "/foo").unwrap();
+        assert_eq!(params.get("sensor_id"), Some("foo"));
+    }
+
+    #[test]
+    fn should_not_match_shorter_individual_path() {
+        let endpoint = Endpoint::<String>::try_from("/%{sensor_id}/value").unwrap();
+
+        let err = PathParams::new(&endpoint, "/foo").unwrap_err();
+
+        assert!(matches!(
+            err,
+            PathParamsError::Mismatch { path, .. } if path == "/foo"
+        ));
+    }
+
+    #[test]
+    fn should_not_match() {
+        let endpoint = Endpoint::<String>::try_from("/%{sensor_id}/value").unwrap();
+
+        for path in ["/foo/other", "/foo/value/more/levels"] {
+            let err = PathParams::new(&endpoint, path).unwrap_err();
+
+            assert!(matches!(
+                err,
+                PathParamsError::Mismatch { endpoint, path: p }
+                    if endpoint == "/%{sensor_id}/value" && p == path
+            ));
+        }
+
+        assert!(matches!(
+            PathParams::new(&endpoint, "foo"),
+            Err(PathParamsError::MappingPath(_))
+        ));
+    }
+}
@@ -0,0 +1,442 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Dispatch the received events to typed handlers.
+//!
+//! The [`EventRouter`] receives the events from a [`Client`] and converts them with
+//! [`FromEvent`] into the type expected by the first matching handler.
+
+use std::future::Future;
+
+use astarte_interfaces::mapping::endpoint::{Endpoint, EndpointError};
+use astarte_interfaces::MappingPath;
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt};
+use tracing::{debug, error, trace};
+
+use crate::client::RecvError;
+use crate::error::{DynError, Report};
+use crate::{Client, DeviceEvent, FromEvent};
+
+type Handler = Box<dyn Fn(DeviceEvent) -> Result<BoxFuture<'static, ()>, DynError> + Send + Sync>;
+
+type ErrorHandler = Box<dyn Fn(RouteError) + Send + Sync>;
+
+/// Error while routing an event.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum RouteError {
+    /// Couldn't receive the event from the client.
+    #[error("couldn't receive the event")]
+    Recv(#[from] RecvError),
+    /// Couldn't convert the event to the type of the handler.
+    #[error("couldn't convert the event received on {interface}{path}")]
+    Conversion {
+        /// Interface of the event.
+        interface: String,
+        /// Path of the event.
+        path: String,
+        /// Error returned by [`FromEvent::from_event`].
+        #[source]
+        source: DynError,
+    },
+}
+
+struct Route {
+    interface: Option<&'static str>,
+    endpoint: Option<Endpoint<String>>,
+    handler: Handler,
+}
+
+impl Route {
+    fn matches(&self, event: &DeviceEvent) -> bool {
+        if self.interface.is_some_and(|name| name != event.interface) {
+            return false;
+        }
+
+        let Some(endpoint) = &self.endpoint else {
+            return true;
+        };
+
+        MappingPath::try_from(event.path.as_str()).is_ok_and(|path| endpoint.eq_mapping(&path))
+    }
+}
+
+/// Routes the events received by a [`Client`] to async handlers.
+///
+/// The handlers are registered with the type they expect, and are selected by the interface
+/// returned by [`FromEvent::interface_name`] and an optional endpoint. The event is passed to the
+/// first matching handler, in the order they were registered.
+///
+/// ```no_run
+/// use astarte_device_sdk::{
+///     store::memory::MemoryStore, builder::DeviceBuilder, transport::mqtt::MqttConfig,
+///     event::{router::EventRouter, FromEventError}, prelude::*, DeviceEvent,
+/// };
+///
+/// #[derive(Debug)]
+/// struct LedCommand(bool);
+///
+/// impl FromEvent for LedCommand {
+///     type Err = FromEventError;
+///
+///     fn from_event(event: DeviceEvent) -> Result<Self, Self::Err> {
+///         let (data, _timestamp) = event
+///             .data
+///             .try_into_individual()
+///             .map_err(|_| FromEventError::Interface(event.interface))?;
+///
+///         Ok(LedCommand(data.try_into()?))
+///     }
+///
+///     fn interface_name() -> Option<&'static str> {
+///         Some("com.example.Led")
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mqtt_config = MqttConfig::with_credential_secret("realm_id", "device_id", "credential_secret", "pairing_url");
+///
+///     let (client, connection) = DeviceBuilder::new().store(MemoryStore::new())
+///         .connection(mqtt_config).build().await.unwrap();
+///
+///     let router = EventRouter::new()
+///         .on(|cmd: LedCommand| async move {
+///             println!("received {cmd:?}");
+///         })
+///         .fallback(|event| async move {
+///             println!("unhandled event {event:?}");
+///         })
+///         .concurrency(4);
+///
+///     router.run(&client).await;
+/// }
+/// ```
+pub struct EventRouter {
+    routes: Vec<Route>,
+    fallback: Option<Handler>,
+    on_error: Option<ErrorHandler>,
+    concurrency: usize,
+}
+
+impl EventRouter {
+    /// Create a router without handlers, processing one event at the time.
+    pub fn new() -> Self {
+        Self {
+            routes: Vec::new(),
+            fallback: None,
+            on_error: None,
+            concurrency: 1,
+        }
+    }
+
+    /// Register an handler for the events converted into `T`.
+    ///
+    /// The handler is called for the events received on the interface returned by
+    /// [`FromEvent::interface_name`], or on every event if it returns [`None`].
+    pub fn on<T, F, Fut>(mut self, handler: F) -> Self
+    where
+        T: FromEvent,
+        T::Err: Into<DynError>,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.routes.push(Route {
+            interface: T::interface_name(),
+            endpoint: None,
+            handler: Self::handler(handler),
+        });
+
+        self
+    }
+
+    /// Register an handler for the events converted into `T`, received on a matching endpoint.
+    ///
+    /// The endpoint can contain parameters, like `/%{sensor_id}/value`.
+    pub fn on_endpoint<T, F, Fut>(
+        mut self,
+        endpoint: &str,
+        handler: F,
+    ) -> Result<Self, EndpointError>
+    where
+        T: FromEvent,
+        T::Err: Into<DynError>,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let endpoint = Endpoint::try_from(endpoint)?;
+
+        self.routes.push(Route {
+            interface: T::interface_name(),
+            endpoint: Some(endpoint),
+            handler: Self::handler(handler),
+        });
+
+        Ok(self)
+    }
+
+    /// Register an handler for the events not matched by any other handler.
+    ///
+    /// By default the events are ignored.
+    pub fn fallback<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(DeviceEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.fallback = Some(Box::new(move |event| Ok(handler(event).boxed())));
+
+        self
+    }
+
+    /// Register a callback for the errors while receiving or converting the events.
+    ///
+    /// By default the errors are logged.
+    pub fn on_error<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(RouteError) + Send + Sync + 'static,
+    {
+        self.on_error = Some(Box::new(handler));
+
+        self
+    }
+
+    /// Maximum number of handlers running at the same time.
+    ///
+    /// With the default of 1 the events are handled in the order they are received, while a
+    /// limit of 0 means no limit.
+    pub fn concurrency(mut self, limit: usize) -> Self {
+        self.concurrency = limit;
+
+        self
+    }
+
+    fn handler<T, F, Fut>(handler: F) -> Handler
+    where
+        T: FromEvent,
+        T::Err: Into<DynError>,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Box::new(move |event| {
+            T::from_event(event)
+                .map(|value| handler(value).boxed())
+                .map_err(Into::into)
+        })
+    }
+
+    /// Receive the events from the client and dispatch them, until the client is disconnected.
+    pub async fn run<C>(&self, client: &C)
+    where
+        C: Client + Sync,
+    {
+        let events = futures::stream::unfold(client, |client| async move {
+            match client.recv().await {
+                Err(RecvError::Disconnected) => None,
+                res => Some((res, client)),
+            }
+        });
+
+        events
+            .for_each_concurrent(self.concurrency, |res| async move {
+                match res {
+                    Ok(event) => self.dispatch(event).await,
+                    Err(err) => self.error(RouteError::Recv(err)),
+                }
+            })
+            .await;
+
+        debug!("client disconnected, router stopped");
+    }
+
+    /// Dispatch a single event to the first matching handler.
+    pub async fn dispatch(&self, event: DeviceEvent) {
+        let Some(route) = self.routes.iter().find(|route| route.matches(&event)) else {
+            trace!(
+                interface = event.interface,
+                path = event.path,
+                "no route matched"
+            );
+
+            if let Some(fallback) = &self.fallback {
+                if let Ok(fut) = fallback(event) {
+                    fut.await;
+                }
+            }
+
+            return;
+        };
+
+        let interface = event.interface.clone();
+        let path = event.path.clone();
+
+        match (route.handler)(event) {
+            Ok(fut) => fut.await,
+            Err(source) => self.error(RouteError::Conversion {
+                interface,
+                path,
+                source,
+            }),
+        }
+    }
+
+    fn error(&self, err: RouteError) {
+        match &self.on_error {
+            Some(on_error) => on_error(err),
+            None => error!(error = %Report::new(err), "couldn't route the event"),
+        }
+    }
+}
+
+impl Default for EventRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for EventRouter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventRouter")
+            .field("routes", &self.routes.len())
+            .field("fallback", &self.fallback.is_some())
+            .field("on_error", &self.on_error.is_some())
+            .field("concurrency", &self.concurrency)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::Utc;
+    use pretty_assertions::assert_eq;
+
+    use crate::client::tests::mock_client;
+    use crate::event::FromEventError;
+    use crate::{AstarteData, Value};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Led(bool);
+
+    impl FromEvent for Led {
+        type Err = FromEventError;
+
+        fn from_event(event: DeviceEvent) -> Result<Self, Self::Err> {
+            let (data, _) = event
+                .data
+                .try_into_individual()
+                .map_err(|_| FromEventError::Interface(event.interface))?;
+
+            Ok(Led(data.try_into()?))
+        }
+
+        fn interface_name() -> Option<&'static str> {
+            Some("com.example.Led")
+        }
+    }
+
+    fn event(interface: &str, path: &str, data: impl Into<AstarteData>) -> DeviceEvent {
+        DeviceEvent {
+            interface: interface.to_string(),
+            path: path.to_string(),
+            data: Value::Individual {
+                data: data.into(),
+                timestamp: Utc::now(),
+            },
+        }
+    }
+
+    fn recorder<T: Send + 'static>() -> (Arc<Mutex<Vec<T>>>, impl Fn(T) -> BoxFuture<'static, ()>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let cl = Arc::clone(&calls);
+
+        (calls, move |value| {
+            cl.lock().unwrap().push(value);
+
+            futures::future::ready(()).boxed()
+        })
+    }
+
+    #[tokio::test]
+    async fn should_dispatch_to_handlers() {
+        let (leds, led_handler) = recorder::<Led>();
+        let (first, first_handler) = recorder::<Led>();
+        let (unmatched, fallback) = recorder::<DeviceEvent>();
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let cl = Arc::clone(&errors);
+
+        let router = EventRouter::new()
+            .on_endpoint("/first/enable", first_handler)
+            .unwrap()
+            .on(led_handler)
+            .fallback(fallback)
+            .on_error(move |err| cl.lock().unwrap().push(err));
+
+        router
+            .dispatch(event("com.example.Led", "/first/enable", true))
+            .await;
+        router
+            .dispatch(event("com.example.Led", "/second/enable", false))
+            .await;
+        router
+            .dispatch(event("com.example.Led", "/second/enable", 42i32))
+            .await;
+
+        let other = event("com.example.Other", "/first/enable", true);
+        router.dispatch(other.clone()).await;
+
+        assert_eq!(*first.lock().unwrap(), [Led(true)]);
+        assert_eq!(*leds.lock().unwrap(), [Led(false)]);
+        assert_eq!(*unmatched.lock().unwrap(), [other]);
+
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            RouteError::Conversion { interface, path, .. }
+                if interface == "com.example.Led" && path == "/second/enable"
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_run_until_disconnected() {
+        let (client, tx) = mock_client(&[]);
+        let (leds, led_handler) = recorder::<Led>();
+
+        let router = EventRouter::new().on(led_handler).concurrency(2);
+
+        tx.send(Ok(event("com.example.Led", "/1/enable", true)))
+            .unwrap();
+        tx.send(Err(RecvError::InterfaceNotFound {
+            name: "com.example.Missing".to_string(),
+        }))
+        .unwrap();
+        tx.send(Ok(event("com.example.Led", "/2/enable", false)))
+            .unwrap();
+        drop(tx);
+
+        router.run(&client).await;
+
+        let mut leds = leds.lock().unwrap();
+        leds.sort_by_key(|led| led.0);
+        assert_eq!(*leds, [Led(false), Led(true)]);
+    }
+}
@@ -21,7 +21,7 @@ use std::collections::HashMap;
 use std::fmt::Display;
 use std::ops::Deref;
 
-use astarte_interfaces::interface::InterfaceTypeAggregation;
+use astarte_interfaces::interface::{InterfaceTypeAggregation, Retention};
 use astarte_interfaces::schema::{Aggregation, InterfaceType};
 use astarte_interfaces::{error::Error as InterfaceError, Interface};
 use astarte_interfaces::{
@@ -30,10 +30,14 @@ use astarte_interfaces::{
 use itertools::Itertools;
 use tracing::{debug, trace, warn};
 
+use crate::aggregate::AstarteObject;
 use crate::error::AggregationError;
+use crate::introspection::AddInterfaceError;
 use crate::session::IntrospectionInterface;
-use crate::validate::UserValidationError;
-use crate::{error::InterfaceTypeError, Error};
+use crate::validate::{
+    UserValidationError, ValidatedIndividual, ValidatedObject, ValidatedProperty, ValidatedUnset,
+};
+use crate::{error::InterfaceTypeError, AstarteData, Error, Timestamp};
 
 #[derive(Clone, Debug, Default)]
 pub(crate) struct Interfaces {
@@ -346,6 +350,155 @@ impl FromIterator<Interface> for Interfaces {
     }
 }
 
+/// Interfaces of a device, used to validate the data sent on them.
+///
+/// It performs the same checks as the [`DeviceClient`](crate::DeviceClient) without a connection.
+// Public to be used in the mock crate.
+#[doc(hidden)]
+#[derive(Clone, Debug, Default)]
+pub struct InterfaceValidator {
+    interfaces: Interfaces,
+}
+
+impl InterfaceValidator {
+    /// Creates an empty set of interfaces.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the interface with the given name.
+    pub fn get(&self, interface_name: &str) -> Option<&Interface> {
+        self.interfaces.get(interface_name)
+    }
+
+    /// Iterates over the interfaces.
+    pub fn iter(&self) -> impl Iterator<Item = &Interface> {
+        self.interfaces.iter()
+    }
+
+    /// Adds an interface, returns `false` if it's already present.
+    pub fn add(&mut self, interface: Interface) -> Result<bool, Error> {
+        let Some(to_add) = self
+            .interfaces
+            .validate(interface)
+            .map_err(AddInterfaceError::Interface)?
+        else {
+            return Ok(false);
+        };
+
+        self.interfaces.add(to_add);
+
+        Ok(true)
+    }
+
+    /// Adds many interfaces, returns the names of the ones added.
+    pub fn extend<I>(&mut self, interfaces: I) -> Result<Vec<String>, Error>
+    where
+        I: IntoIterator<Item = Interface>,
+    {
+        let to_add = self
+            .interfaces
+            .validate_many(interfaces)
+            .map_err(AddInterfaceError::Interface)?;
+
+        let names = to_add.keys().cloned().collect();
+
+        self.interfaces.extend(to_add);
+
+        Ok(names)
+    }
+
+    /// Removes an interface, returning it if present.
+    pub fn remove(&mut self, interface_name: &str) -> Option<Interface> {
+        self.interfaces.remove(interface_name)
+    }
+
+    /// Validates an individual datastream and returns the retention of its mapping.
+    pub fn individual(
+        &self,
+        interface_name: &str,
+        path: &str,
+        data: AstarteData,
+        timestamp: Option<Timestamp>,
+    ) -> Result<Retention, Error> {
+        let path = MappingPath::try_from(path)?;
+        let mapping = self.interfaces.get_individual(interface_name, &path)?;
+
+        let validated = ValidatedIndividual::validate(mapping, data, timestamp)?;
+
+        Ok(validated.retention)
+    }
+
+    /// Validates an object datastream and returns the retention of the interface.
+    pub fn object(
+        &self,
+        interface_name: &str,
+        path: &str,
+        data: AstarteObject,
+        timestamp: Option<Timestamp>,
+    ) -> Result<Retention, Error> {
+        let path = MappingPath::try_from(path)?;
+        let interface = self.interfaces.get_object(interface_name, &path)?;
+
+        let validated = ValidatedObject::validate(interface, &path, data, timestamp)?;
+
+        Ok(validated.retention)
+    }
+
+    /// Validates a device property and returns the major version of the interface.
+    pub fn property(
+        &self,
+        interface_name: &str,
+        path: &str,
+        data: AstarteData,
+    ) -> Result<i32, Error> {
+        let path = MappingPath::try_from(path)?;
+        let mapping = self.interfaces.get_property(interface_name, &path)?;
+
+        let validated = ValidatedProperty::validate(mapping, data)?;
+
+        Ok(validated.version_major)
+    }
+
+    /// Validates the unset of a device property.
+    pub fn unset(&self, interface_name: &str, path: &str) -> Result<(), Error> {
+        let path = MappingPath::try_from(path)?;
+        let mapping = self.interfaces.get_property(interface_name, &path)?;
+
+        ValidatedUnset::validate(mapping)?;
+
+        Ok(())
+    }
+
+    /// Returns the interface if it's a property.
+    pub fn properties(&self, interface_name: &str) -> Result<&Properties, Error> {
+        let interface = self
+            .get(interface_name)
+            .ok_or_else(|| Error::InterfaceNotFound {
+                name: interface_name.to_string(),
+            })?;
+
+        let properties = interface.as_properties().ok_or_else(|| {
+            InterfaceTypeError::new(
+                interface_name,
+                InterfaceType::Properties,
+                interface.interface_type(),
+            )
+        })?;
+
+        Ok(properties)
+    }
+
+    /// Checks that the path is a mapping of a property interface.
+    pub fn property_mapping(&self, interface_name: &str, path: &str) -> Result<(), Error> {
+        let path = MappingPath::try_from(path)?;
+
+        self.interfaces.get_property(interface_name, &path)?;
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct Validated {
     interface: Interface,
@@ -777,4 +930,63 @@ pub(crate) mod tests {
             .unwrap_err();
         assert!(matches!(err, Error::InterfaceType { .. }), "{err:?}");
     }
+
+    #[test]
+    fn validator_should_check_the_data() {
+        let mut validator = InterfaceValidator::new();
+
+        let added = validator
+            .extend([
+                Interface::from_str(E2E_DEVICE_DATASTREAM).unwrap(),
+                Interface::from_str(E2E_DEVICE_PROPERTY).unwrap(),
+            ])
+            .unwrap();
+        assert_eq!(added.len(), 2);
+        assert!(!validator
+            .add(Interface::from_str(E2E_DEVICE_PROPERTY).unwrap())
+            .unwrap());
+
+        let retention = validator
+            .individual(
+                E2E_DEVICE_DATASTREAM_NAME,
+                "/boolean_endpoint",
+                AstarteData::Boolean(true),
+                Some(chrono::Utc::now()),
+            )
+            .unwrap();
+        assert_eq!(retention, Retention::Discard);
+
+        let err = validator
+            .individual(
+                E2E_DEVICE_DATASTREAM_NAME,
+                "/boolean_endpoint",
+                AstarteData::Integer(1),
+                Some(chrono::Utc::now()),
+            )
+            .unwrap_err();
+        assert!(matches!(err, Error::Validation(_)), "{err:?}");
+
+        let major = validator
+            .property(
+                E2E_DEVICE_PROPERTY_NAME,
+                "/sensor_1/integer_endpoint",
+                AstarteData::Integer(1),
+            )
+            .unwrap();
+        assert_eq!(major, 0);
+        validator
+            .unset(E2E_DEVICE_PROPERTY_NAME, "/sensor_1/integer_endpoint")
+            .unwrap();
+
+        let err = validator
+            .properties(E2E_DEVICE_DATASTREAM_NAME)
+            .unwrap_err();
+        assert!(matches!(err, Error::InterfaceType(_)), "{err:?}");
+
+        assert!(validator.remove(E2E_DEVICE_PROPERTY_NAME).is_some());
+        let err = validator
+            .property_mapping(E2E_DEVICE_PROPERTY_NAME, "/sensor_1/integer_endpoint")
+            .unwrap_err();
+        assert!(matches!(err, Error::InterfaceNotFound { .. }), "{err:?}");
+    }
 }
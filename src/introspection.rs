@@ -21,6 +21,7 @@
 use std::{
     future::Future,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 
 use astarte_interfaces::{error::Error as InterfaceError, Interface};
@@ -82,6 +83,22 @@ impl AddInterfaceError {
     }
 }
 
+/// Interface defined by a Rust type.
+///
+/// It's implemented by the `AstarteInterface` derive macro, to use the type as the source of the
+/// interface definition and export the JSON.
+pub trait AstarteInterface {
+    /// Name of the interface.
+    const INTERFACE_NAME: &'static str;
+    /// JSON definition of the interface.
+    const INTERFACE_JSON: &'static str;
+
+    /// Returns the [`Interface`] to add to the device introspection.
+    fn interface() -> Result<Interface, InterfaceError> {
+        Interface::from_str(Self::INTERFACE_JSON)
+    }
+}
+
 /// Trait that permits a client to query the interfaces in the device introspection.
 pub trait DeviceIntrospection {
     /// Returns a reference to the [`Interface`] with the given name.
@@ -148,3 +165,48 @@ pub trait DynamicIntrospection {
         I: IntoIterator<Item = String> + Send,
         I::IntoIter: Send;
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "derive")]
+    fn should_derive_astarte_interface() {
+        use astarte_interfaces::schema::{Ownership, Reliability};
+
+        use crate::builder::DeviceBuilder;
+        use crate::chrono::{DateTime, Utc};
+        use crate::AstarteInterface;
+
+        // Alias the crate to the resulting macro
+        use crate::{self as astarte_device_sdk};
+
+        #[derive(AstarteInterface)]
+        #[astarte_interface(
+            name = "com.example.Sensor",
+            major = 1,
+            aggregation = "object",
+            path = "/%{sensor_id}",
+            reliability = "guaranteed",
+            explicit_timestamp = true,
+            rename_all = "camelCase"
+        )]
+        #[allow(dead_code)]
+        struct Sensor {
+            temperature_value: f64,
+            samples: Vec<i32>,
+            updated: DateTime<Utc>,
+        }
+
+        assert_eq!(Sensor::INTERFACE_NAME, "com.example.Sensor");
+
+        let interface = Sensor::interface().unwrap();
+        assert_eq!(interface.ownership(), Ownership::Device);
+
+        let object = interface.as_datastream_object().unwrap();
+        assert_eq!(object.reliability(), Reliability::Guaranteed);
+        assert!(object.explicit_timestamp());
+        assert!(Sensor::INTERFACE_JSON.contains(r#""endpoint": "/%{sensor_id}/temperatureValue""#));
+
+        DeviceBuilder::new().interface(interface).unwrap();
+    }
+}
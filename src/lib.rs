@@ -35,6 +35,7 @@
 pub mod _docs;
 
 pub mod aggregate;
+pub mod blocking;
 pub mod builder;
 pub mod client;
 pub mod connection;
@@ -45,7 +46,7 @@ pub mod introspection;
 pub mod prelude;
 pub mod properties;
 pub mod retention;
-mod retry;
+pub mod retry;
 pub mod session;
 pub(crate) mod state;
 pub mod store;
@@ -60,8 +61,14 @@ pub use crate::connection::{DeviceConnection, EventLoop};
 pub use crate::error::Error;
 pub use crate::event::Value;
 pub use crate::event::{DeviceEvent, FromEvent};
+pub use crate::introspection::AstarteInterface;
+pub use crate::properties::AstarteProperties;
 pub use crate::types::AstarteData;
 
+// Public to be used in the mock crate.
+#[doc(hidden)]
+pub use crate::interfaces::InterfaceValidator;
+
 // Re-export rumqttc since we return its types in some methods
 pub use astarte_interfaces;
 pub use chrono;
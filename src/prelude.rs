@@ -24,13 +24,13 @@ pub mod device {
     pub use crate::client::Client;
     pub use crate::client::ClientDisconnect;
     pub use crate::connection::EventLoop;
-    pub use crate::introspection::{DeviceIntrospection, DynamicIntrospection};
+    pub use crate::introspection::{AstarteInterface, DeviceIntrospection, DynamicIntrospection};
     pub use crate::FromEvent;
 }
 
 /// Exports common trait used when accessing stored properties.
 pub mod properties {
-    pub use crate::properties::PropAccess;
+    pub use crate::properties::{AstarteProperties, PropAccess};
     pub use crate::store::PropertyStore;
 }
 
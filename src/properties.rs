@@ -28,7 +28,7 @@ use futures::{future, StreamExt, TryStreamExt};
 use tracing::{debug, error, warn};
 
 use crate::{
-    client::DeviceClient,
+    client::{Client, DeviceClient},
     error::{Error, InterfaceTypeError},
     store::{PropertyMapping, PropertyStore, StoredProp},
     transport::Connection,
@@ -54,6 +54,14 @@ pub enum PropertiesError {
     /// Error encoding the zlib compressed payload.
     #[error("error encoding the zlib compressed payload")]
     Encode(#[source] std::io::Error),
+    /// The property is not stored.
+    #[error("missing the property {interface}{path}")]
+    Missing {
+        /// Name of the interface.
+        interface: String,
+        /// Path of the property.
+        path: String,
+    },
 }
 
 /// Trait to access the stored properties.
@@ -101,6 +109,33 @@ pub trait PropAccess {
     fn server_props(&self) -> impl Future<Output = Result<Vec<StoredProp>, Error>> + Send;
 }
 
+/// Struct mapped to the properties of an interface.
+///
+/// It's implemented by the `AstarteProperties` derive macro, mapping each field to a property path.
+pub trait AstarteProperties: Sized {
+    /// Name of the interface.
+    const INTERFACE_NAME: &'static str;
+
+    /// Sets the properties that are different from the stored ones.
+    ///
+    /// The optional fields that are [`None`] are unset if stored.
+    ///
+    /// All the fields are converted and the stored properties are read before sending anything,
+    /// then the unsets are sent before the sets. A conversion error, or an unset on a mapping
+    /// without `allow_unset`, is returned before any property is set, but an error while
+    /// sending can leave the properties partially applied.
+    fn set_all<C>(&self, client: &C) -> impl Future<Output = Result<(), Error>> + Send
+    where
+        C: Client + PropAccess + Send + Sync;
+
+    /// Builds the struct from the stored properties.
+    ///
+    /// Returns [`PropertiesError::Missing`] if a property of a non optional field is not stored.
+    fn load<C>(client: &C) -> impl Future<Output = Result<Self, Error>> + Send
+    where
+        C: PropAccess + Sync;
+}
+
 impl<C> PropAccess for DeviceClient<C>
 where
     C: Connection,
@@ -34,7 +34,7 @@ use crate::{
     validate::{ValidatedIndividual, ValidatedObject},
 };
 
-use super::Id;
+use super::{Id, Receipts, RetentionId};
 
 /// Struct for the volatile retention.
 ///
@@ -52,18 +52,20 @@ impl VolatileStore {
         }
     }
 
-    pub(crate) async fn push_sent<T>(&self, id: Id, value: T)
+    /// Push a sent item, returning the id of the oldest item if it was removed to make space.
+    pub(crate) async fn push_sent<T>(&self, id: Id, value: T) -> Option<Id>
     where
         T: TryInto<ItemValue, Error = VolatileItemError>,
     {
-        self.store.lock().await.push(id, value, true);
+        self.store.lock().await.push(id, value, true)
     }
 
-    pub(crate) async fn push_unsent<T>(&self, id: Id, value: T)
+    /// Push an unsent item, returning the id of the oldest item if it was removed to make space.
+    pub(crate) async fn push_unsent<T>(&self, id: Id, value: T) -> Option<Id>
     where
         T: TryInto<ItemValue, Error = VolatileItemError>,
     {
-        self.store.lock().await.push(id, value, false);
+        self.store.lock().await.push(id, value, false)
     }
 
     pub(crate) async fn mark_sent(&self, id: &Id, sent: bool) -> Option<bool> {
@@ -92,6 +94,21 @@ impl VolatileStore {
         self.store.lock().await.set_capacity(capacity);
     }
 
+    /// Returns the ids of the items removed without being received since the last call.
+    ///
+    /// These are the items that expired, or that were removed with their interface or when
+    /// reducing the capacity.
+    pub(crate) async fn take_removed(&self) -> Vec<Id> {
+        std::mem::take(&mut self.store.lock().await.removed)
+    }
+
+    /// Resolves the receipts of the items removed without being received.
+    pub(crate) async fn notify_removed(&self, receipts: &Receipts) {
+        let removed = self.take_removed().await;
+
+        receipts.dropped_all(removed.into_iter().map(RetentionId::Volatile));
+    }
+
     #[cfg(test)]
     pub(crate) async fn pop_next(&self) -> Option<ItemValue> {
         self.store.lock().await.pop_next()
@@ -101,23 +118,27 @@ impl VolatileStore {
 #[derive(Debug)]
 struct State {
     store: VecDeque<VolatileItem>,
+    removed: Vec<Id>,
 }
 
 impl State {
     fn with_capacity(capacity: usize) -> Self {
         Self {
             store: VecDeque::with_capacity(capacity),
+            removed: Vec::new(),
         }
     }
 
-    fn push<T>(&mut self, id: Id, value: T, sent: bool)
+    fn push<T>(&mut self, id: Id, value: T, sent: bool) -> Option<Id>
     where
         T: TryInto<ItemValue, Error = VolatileItemError>,
     {
+        let mut removed = None;
+
         if self.is_full() {
             if self.store.capacity() == 0 {
                 // we shouldn't store anything
-                return;
+                return Some(id);
             }
 
             // remote the expired only if its full, it will be done while iterating
@@ -125,7 +146,7 @@ impl State {
 
             // If still full, remove the oldest one
             if self.is_full() {
-                self.store.pop_front();
+                removed = self.store.pop_front().map(|item| item.id);
             }
         }
 
@@ -137,11 +158,13 @@ impl State {
             Err(err) => {
                 error!("{err}");
 
-                return;
+                return removed;
             }
         };
 
         self.store.push_back(VolatileItem::new(id, item, sent));
+
+        removed
     }
 
     fn reset_sent(&mut self) {
@@ -183,7 +206,15 @@ impl State {
     fn remove_expired(&mut self) {
         let now = SystemTime::now();
 
-        self.store.retain(|item| !item.is_expired(now));
+        self.store.retain(|item| {
+            let expired = item.is_expired(now);
+
+            if expired {
+                self.removed.push(item.id);
+            }
+
+            !expired
+        });
     }
 
     fn is_full(&mut self) -> bool {
@@ -197,7 +228,8 @@ impl State {
 
         if capacity < current {
             let diff = self.store.len().saturating_sub(capacity);
-            self.store.drain(..diff);
+            self.removed
+                .extend(self.store.drain(..diff).map(|item| item.id));
             self.store.shrink_to_fit();
         }
 
@@ -217,6 +249,7 @@ impl State {
 
             if expired_or_interface {
                 count += 1;
+                self.removed.push(v.id);
             }
 
             !expired_or_interface
@@ -459,9 +492,12 @@ mod tests {
 
         assert!(store.is_full());
 
-        store.push(ctx.next(), info3.clone(), false);
+        // the expired item is removed, without removing the oldest one
+        let expired: Vec<Id> = store.store.iter().map(|item| item.id).collect();
+        assert_eq!(store.push(ctx.next(), info3.clone(), false), None);
 
         assert_eq!(store.store[0].value, ItemValue::Individual(info3));
+        assert_eq!(store.removed, expired);
     }
 
     #[test]
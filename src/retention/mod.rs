@@ -41,8 +41,12 @@ use crate::{
 };
 
 pub(crate) mod memory;
+mod receipt;
 pub(crate) mod sqlite;
 
+pub(crate) use self::receipt::Receipts;
+pub use self::receipt::{DeliveryError, DeliveryReceipt};
+
 /// Error returned by the retention.
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
@@ -330,6 +334,15 @@ pub trait StoredRetention: Clone + Send + Sync {
         &self,
         size: NonZeroUsize,
     ) -> impl Future<Output = Result<(), RetentionError>> + Send;
+
+    /// Returns the ids of the publishes removed without being received since the last call.
+    ///
+    /// These are the publishes that expired, that were removed to free space for newer ones, or
+    /// that were deleted with their interface. The SDK uses them to resolve the pending delivery
+    /// receipts, the default implementation doesn't track them.
+    fn take_removed(&self) -> impl Future<Output = Result<Vec<Id>, RetentionError>> + Send {
+        async { Ok(Vec::new()) }
+    }
 }
 
 /// Interface and major version of a [`PublishInfo`] stored in the retention.
@@ -375,6 +388,16 @@ pub(crate) trait StoredRetentionExt: StoredRetention {
         self.store_publish(id, publish).await
     }
 
+    /// Resolves the receipts of the publishes removed from the retention without being received.
+    async fn notify_removed(&self, receipts: &Receipts) {
+        match self.take_removed().await {
+            Ok(ids) => receipts.dropped_all(ids.into_iter().map(RetentionId::Stored)),
+            Err(err) => {
+                warn!(error = %Report::new(err), "couldn't get the publishes removed from the retention");
+            }
+        }
+    }
+
     /// Removes the outdated interfaces from the introspection
     async fn cleanup_introspection(&self, interfaces: &Interfaces) -> Result<(), RetentionError> {
         let iter = self
@@ -0,0 +1,328 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Delivery receipts for the publishes tracked by the retention.
+//!
+//! A receipt is registered for a [`RetentionId`] before the publish is sent or stored, and it's
+//! resolved when the retention marks the same id as received.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+use tokio::time::Sleep;
+use tracing::{error, trace};
+
+use super::RetentionId;
+
+type PendingMap = HashMap<RetentionId, oneshot::Sender<Result<(), DeliveryError>>>;
+
+/// Error returned by a [`DeliveryReceipt`] when the delivery couldn't be confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum DeliveryError {
+    /// The publish was removed from the retention before being delivered.
+    ///
+    /// This happens when the retention is full, when the publish expired in the retention, or
+    /// when the device was disconnected.
+    #[error("the publish was dropped before being delivered")]
+    Dropped,
+    /// The retention expiry of the publish elapsed before the delivery.
+    #[error("the publish expired before being delivered")]
+    Expired,
+}
+
+/// Future that resolves when the publish has been acknowledged.
+///
+/// It's returned by the `send_*_confirmed` methods of the [`Client`](crate::Client). For MQTT it
+/// waits for the PUBACK or PUBCOMP of the broker, while for the Message Hub it resolves when the
+/// message was accepted by the hub.
+///
+/// Mappings with reliability unreliable are confirmed once published, since the broker doesn't
+/// acknowledge them. Publishes with retention discard wait for the acknowledgment too, but they
+/// are not sent again if the connection is lost before it.
+///
+/// Dropping the receipt stops tracking the delivery, but the publish is still sent.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct DeliveryReceipt {
+    inner: ReceiptInner,
+}
+
+#[derive(Debug)]
+enum ReceiptInner {
+    Ready(Option<Result<(), DeliveryError>>),
+    Channel(oneshot::Receiver<Result<(), DeliveryError>>),
+    Pending {
+        id: RetentionId,
+        rx: oneshot::Receiver<Result<(), DeliveryError>>,
+        expiry: Option<Pin<Box<Sleep>>>,
+        pending: Weak<Mutex<PendingMap>>,
+    },
+}
+
+impl DeliveryReceipt {
+    /// Creates a receipt that is already resolved with the given result.
+    ///
+    /// This is useful to mock the [`Client`](crate::Client) in tests.
+    pub fn resolved(result: Result<(), DeliveryError>) -> Self {
+        Self {
+            inner: ReceiptInner::Ready(Some(result)),
+        }
+    }
+
+    /// Creates a receipt that resolves with the result sent on the channel.
+    ///
+    /// The receipt resolves as [`DeliveryError::Dropped`] if the sender is dropped. This is useful
+    /// to mock the [`Client`](crate::Client) in tests.
+    pub fn from_receiver(rx: oneshot::Receiver<Result<(), DeliveryError>>) -> Self {
+        Self {
+            inner: ReceiptInner::Channel(rx),
+        }
+    }
+
+    fn pending(
+        id: RetentionId,
+        rx: oneshot::Receiver<Result<(), DeliveryError>>,
+        expiry: Option<Duration>,
+        pending: Weak<Mutex<PendingMap>>,
+    ) -> Self {
+        Self {
+            inner: ReceiptInner::Pending {
+                id,
+                rx,
+                expiry: expiry.map(|expiry| Box::pin(tokio::time::sleep(expiry))),
+                pending,
+            },
+        }
+    }
+
+    /// Removes the sender of a pending receipt, so it's no longer tracked.
+    fn untrack(&mut self) {
+        if let ReceiptInner::Pending { id, pending, .. } = &self.inner {
+            if let Some(pending) = pending.upgrade() {
+                lock(&pending).remove(id);
+            }
+        }
+    }
+}
+
+impl Future for DeliveryReceipt {
+    type Output = Result<(), DeliveryError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let res = match &mut this.inner {
+            ReceiptInner::Ready(res) => {
+                return Poll::Ready(res.take().expect("receipt polled after completion"));
+            }
+            ReceiptInner::Channel(rx) => {
+                std::task::ready!(Pin::new(rx).poll(cx)).unwrap_or(Err(DeliveryError::Dropped))
+            }
+            ReceiptInner::Pending { rx, expiry, .. } => {
+                if let Poll::Ready(res) = Pin::new(rx).poll(cx) {
+                    // The sender is dropped only when the device is dropped
+                    res.unwrap_or(Err(DeliveryError::Dropped))
+                } else {
+                    match expiry {
+                        Some(expiry) => {
+                            std::task::ready!(expiry.as_mut().poll(cx));
+
+                            Err(DeliveryError::Expired)
+                        }
+                        None => return Poll::Pending,
+                    }
+                }
+            }
+        };
+
+        this.untrack();
+        this.inner = ReceiptInner::Ready(None);
+
+        Poll::Ready(res)
+    }
+}
+
+impl Drop for DeliveryReceipt {
+    fn drop(&mut self) {
+        self.untrack();
+    }
+}
+
+/// Pending receipts waiting for the publish to be received.
+#[derive(Debug, Default)]
+pub(crate) struct Receipts {
+    pending: Arc<Mutex<PendingMap>>,
+}
+
+impl Receipts {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a receipt for the publish with the given id.
+    ///
+    /// The expiry is the one of the interface retention.
+    pub(crate) fn register(&self, id: RetentionId, expiry: Option<Duration>) -> DeliveryReceipt {
+        let (tx, rx) = oneshot::channel();
+
+        let prev = self.lock().insert(id, tx);
+
+        debug_assert!(prev.is_none(), "The IDs should be unique");
+
+        DeliveryReceipt::pending(id, rx, expiry, Arc::downgrade(&self.pending))
+    }
+
+    /// Resolve the receipt of a received publish.
+    pub(crate) fn received(&self, id: &RetentionId) {
+        self.resolve(id, Ok(()));
+    }
+
+    /// Resolve the receipt of a publish that was dropped.
+    pub(crate) fn dropped(&self, id: &RetentionId) {
+        self.resolve(id, Err(DeliveryError::Dropped));
+    }
+
+    /// Resolve the receipts of the publishes removed from the retention without being received.
+    pub(crate) fn dropped_all(&self, ids: impl IntoIterator<Item = RetentionId>) {
+        for id in ids {
+            self.dropped(&id);
+        }
+    }
+
+    fn resolve(&self, id: &RetentionId, res: Result<(), DeliveryError>) {
+        let Some(tx) = self.lock().remove(id) else {
+            return;
+        };
+
+        trace!(%id, ?res, "resolving receipt");
+
+        // The receiver could have been dropped, we don't care
+        let _ = tx.send(res);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PendingMap> {
+        lock(&self.pending)
+    }
+}
+
+fn lock(pending: &Mutex<PendingMap>) -> MutexGuard<'_, PendingMap> {
+    pending.lock().unwrap_or_else(|err| {
+        error!("receipts mutex was poisoned");
+
+        err.into_inner()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::retention::Context;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn should_resolve_received() {
+        let ctx = Context::new();
+        let receipts = Receipts::new();
+
+        let id = RetentionId::Stored(ctx.next());
+        let receipt = receipts.register(id, None);
+
+        receipts.received(&id);
+
+        assert_eq!(receipt.await, Ok(()));
+        assert!(receipts.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_resolve_dropped() {
+        let ctx = Context::new();
+        let receipts = Receipts::new();
+
+        let id = RetentionId::Volatile(ctx.next());
+        let receipt = receipts.register(id, None);
+
+        receipts.dropped(&id);
+
+        assert_eq!(receipt.await, Err(DeliveryError::Dropped));
+
+        let id = RetentionId::Volatile(ctx.next());
+        let receipt = receipts.register(id, None);
+
+        drop(receipts);
+
+        assert_eq!(receipt.await, Err(DeliveryError::Dropped));
+    }
+
+    #[tokio::test]
+    async fn should_resolve_from_receiver() {
+        let (tx, rx) = oneshot::channel();
+        let receipt = DeliveryReceipt::from_receiver(rx);
+
+        tx.send(Ok(())).unwrap();
+        assert_eq!(receipt.await, Ok(()));
+
+        let (tx, rx) = oneshot::channel();
+        let receipt = DeliveryReceipt::from_receiver(rx);
+
+        drop(tx);
+        assert_eq!(receipt.await, Err(DeliveryError::Dropped));
+    }
+
+    #[tokio::test]
+    async fn should_expire() {
+        let ctx = Context::new();
+        let receipts = Receipts::new();
+
+        let id = RetentionId::Volatile(ctx.next());
+        let receipt = receipts.register(id, Some(Duration::from_millis(10)));
+
+        assert_eq!(receipt.await, Err(DeliveryError::Expired));
+        assert!(receipts.lock().is_empty());
+    }
+
+    #[test]
+    fn should_untrack_dropped_receipt() {
+        let ctx = Context::new();
+        let receipts = Receipts::new();
+
+        let id = RetentionId::Stored(ctx.next());
+        let receipt = receipts.register(id, None);
+
+        assert_eq!(receipts.lock().len(), 1);
+
+        drop(receipt);
+
+        assert!(receipts.lock().is_empty());
+
+        // resolving an untracked id is a no-op
+        receipts.received(&id);
+    }
+}
@@ -330,6 +330,13 @@ impl StoredRetention for SqliteStore {
             .await
             .map_err(|err| RetentionError::set_capacity(size, err))
     }
+
+    async fn take_removed(&self) -> Result<Vec<Id>, RetentionError> {
+        self.pool
+            .acquire_writer(|writer| Ok::<_, SqliteError>(writer.take_removed_publishes()))
+            .await
+            .map_err(RetentionError::from)
+    }
 }
 
 impl WriteConnection {
@@ -548,6 +555,7 @@ mod tests {
 
         let res = fetch_publish(&store, &id2).await;
         assert!(res.is_none());
+        assert_eq!(store.take_removed().await.unwrap(), [id2]);
 
         let res = fetch_publish(&store, &id3).await.unwrap();
 
@@ -608,6 +616,8 @@ mod tests {
 
         let res = fetch_publish(&store, &id1).await;
         assert!(res.is_none());
+        assert_eq!(store.take_removed().await.unwrap(), [id1]);
+        assert!(store.take_removed().await.unwrap().is_empty());
 
         let res = fetch_publish(&store, &id2).await.unwrap();
 
@@ -723,4 +733,30 @@ mod tests {
         let res = fetch_publish(&store, &id).await.unwrap();
         assert!(!res.sent);
     }
+
+    #[tokio::test]
+    async fn should_take_removed_with_interface() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let store = SqliteStore::connect(dir.path()).await.unwrap();
+
+        let ctx = Context::new();
+
+        let id1 = ctx.next();
+        store
+            .store_publish(&id1, publish_with_expiry("/path1", None))
+            .await
+            .unwrap();
+        let id2 = ctx.next();
+        store
+            .store_publish(&id2, publish_with_expiry("/path2", None))
+            .await
+            .unwrap();
+
+        // received publishes are not reported as removed
+        store.mark_received(&id1).await.unwrap();
+        store.delete_interface("com.Foo").await.unwrap();
+
+        assert_eq!(store.take_removed().await.unwrap(), [id2]);
+    }
 }
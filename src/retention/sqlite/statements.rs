@@ -18,7 +18,7 @@
 
 use std::{borrow::Cow, collections::HashSet, time::Duration};
 
-use rusqlite::{Connection, OptionalExtension, Transaction};
+use rusqlite::{CachedStatement, Connection, OptionalExtension, Params, Transaction};
 use tracing::{debug, instrument, trace, warn};
 
 use crate::retention::{Id, PublishInfo, StoredInterface};
@@ -128,14 +128,30 @@ impl WriteConnection {
     }
 
     /// Remove the N oldest elements from the store
-    pub(crate) fn remove_oldest(&self, to_remove: usize) -> Result<usize, SqliteError> {
+    pub(crate) fn remove_oldest(&mut self, to_remove: usize) -> Result<usize, SqliteError> {
+        let ids = self.oldest_ids(to_remove)?;
+
+        let removed = {
+            let mut statement = self
+                .prepare_cached(include_query!(
+                    "queries/retention/write/delete_n_oldest.sql"
+                ))
+                .map_err(SqliteError::Prepare)?;
+
+            statement.execute([to_remove]).map_err(SqliteError::Query)?
+        };
+
+        self.removed_publishes.extend(ids);
+
+        Ok(removed)
+    }
+
+    fn oldest_ids(&self, limit: usize) -> Result<Vec<Id>, SqliteError> {
         let mut statement = self
-            .prepare_cached(include_query!(
-                "queries/retention/write/delete_n_oldest.sql"
-            ))
+            .prepare_cached(include_query!("queries/retention/read/n_oldest_ids.sql"))
             .map_err(SqliteError::Prepare)?;
 
-        statement.execute([to_remove]).map_err(SqliteError::Query)
+        query_ids(&mut statement, [limit])
     }
 
     pub(super) fn update_publish_sent_flag(&self, id: &Id, sent: bool) -> Result<(), SqliteError> {
@@ -177,17 +193,28 @@ impl WriteConnection {
     pub(super) fn delete_interface(&mut self, interface: &str) -> Result<(), SqliteError> {
         let transaction = self.transaction().map_err(SqliteError::Transaction)?;
 
-        Self::delete_interface_transaction(&transaction, interface)?;
+        let ids = Self::delete_interface_transaction(&transaction, interface)?;
 
         transaction.commit().map_err(SqliteError::Transaction)?;
 
+        self.removed_publishes.extend(ids);
+
         Ok(())
     }
 
+    /// Deletes the publishes and mappings of the interface, returning the ids of the publishes.
     fn delete_interface_transaction(
         transaction: &Transaction,
         interface: &str,
-    ) -> Result<(), SqliteError> {
+    ) -> Result<Vec<Id>, SqliteError> {
+        let mut statement = transaction
+            .prepare_cached(include_query!(
+                "queries/retention/read/ids_by_interface.sql"
+            ))
+            .map_err(SqliteError::Prepare)?;
+
+        let ids = query_ids(&mut statement, [interface])?;
+
         // Delete publishes
         let mut statement = transaction
             .prepare_cached(include_query!(
@@ -206,24 +233,43 @@ impl WriteConnection {
 
         statement.execute([interface]).map_err(SqliteError::Query)?;
 
-        Ok(())
+        Ok(ids)
     }
 
-    pub(super) fn delete_expired(&self, now: &TimestampSecs) -> Result<usize, SqliteError> {
-        let mut statement = self
-            .prepare_cached(include_query!("queries/retention/write/delete_expired.sql"))
-            .map_err(SqliteError::Prepare)?;
-
+    pub(super) fn delete_expired(&mut self, now: &TimestampSecs) -> Result<usize, SqliteError> {
         let timestamp = now.to_bytes();
         let timestamp = timestamp.as_slice();
 
-        let deleted = statement.execute([timestamp]).map_err(SqliteError::Query)?;
+        let ids = self.expired_ids(timestamp)?;
+
+        let deleted = {
+            let mut statement = self
+                .prepare_cached(include_query!("queries/retention/write/delete_expired.sql"))
+                .map_err(SqliteError::Prepare)?;
+
+            statement.execute([timestamp]).map_err(SqliteError::Query)?
+        };
 
         debug!(deleted, "deleted expired records");
 
+        self.removed_publishes.extend(ids);
+
         Ok(deleted)
     }
 
+    fn expired_ids(&self, timestamp: &[u8]) -> Result<Vec<Id>, SqliteError> {
+        let mut statement = self
+            .prepare_cached(include_query!("queries/retention/read/expired_ids.sql"))
+            .map_err(SqliteError::Prepare)?;
+
+        query_ids(&mut statement, [timestamp])
+    }
+
+    /// Returns the ids of the publishes removed without being received.
+    pub(super) fn take_removed_publishes(&mut self) -> Vec<Id> {
+        std::mem::take(&mut self.removed_publishes)
+    }
+
     pub(super) fn reset_all_sent(&self) -> Result<(), SqliteError> {
         let mut statement = self
             .prepare_cached(include_query!("queries/retention/write/reset_all_sent.sql"))
@@ -307,6 +353,23 @@ impl ReadConnection {
     }
 }
 
+/// Collects the ids of the publishes selected by the statement.
+fn query_ids<P: Params>(
+    statement: &mut CachedStatement<'_>,
+    params: P,
+) -> Result<Vec<Id>, SqliteError> {
+    statement
+        .query_map(params, |row| {
+            Ok(Id {
+                timestamp: row.get(0)?,
+                counter: row.get(1)?,
+            })
+        })
+        .map_err(SqliteError::Query)?
+        .collect::<Result<Vec<Id>, rusqlite::Error>>()
+        .map_err(SqliteError::Query)
+}
+
 fn read_mapping(
     connection: &Connection,
     interface: &str,
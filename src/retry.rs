@@ -16,73 +16,289 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-//! Module to handle the retry of the MQTT connection
+//! Policies for the delay between the reconnection attempts.
+//!
+//! The policy can be configured on the connection, for example with
+//! [`MqttConfig::reconnect_policy`](crate::transport::mqtt::MqttConfig::reconnect_policy).
 
+use std::fmt::Debug;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use tracing::trace;
 
-/// Iterator that yields a delay that will increase exponentially till the max,
-#[derive(Debug, Clone, Copy)]
-pub(crate) struct ExponentialIter {
-    n: u32,
-    max: u32,
-    reset_after: Duration,
-    last: Option<Instant>,
+/// Default duration the connection needs to be stable to reset the attempts.
+const DEFAULT_RESET_AFTER: Duration = Duration::from_secs(256 * 4);
+
+/// Computes the delay before each reconnection attempt.
+///
+/// The attempts are counted from the last disconnection, and reset once the connection has been
+/// stable for [`reset_after`](ReconnectPolicy::reset_after).
+pub trait ReconnectPolicy: Debug + Send + Sync {
+    /// Returns the delay before the attempt, or [`None`] to stop reconnecting.
+    ///
+    /// The first attempt after a disconnection is `0`.
+    fn delay(&self, attempt: u32) -> Option<Duration>;
+
+    /// Duration the connection needs to be stable to reset the attempts.
+    fn reset_after(&self) -> Duration {
+        DEFAULT_RESET_AFTER
+    }
 }
 
-impl ExponentialIter {
-    pub(crate) fn next(&mut self) -> u64 {
-        if self
-            .last
-            .is_some_and(|instant| instant.elapsed() > self.reset_after)
-        {
-            trace!("resetting timeout");
+/// Delay that increases exponentially till the max, optionally with a random jitter.
+///
+/// The first attempt is immediate, the following ones wait `base * 2^(attempt - 1)`. With the
+/// jitter, each delay is randomized between half and the whole of the computed delay, so that a
+/// fleet of devices disconnected at the same time will not reconnect all together.
+///
+/// This is the default policy, with a base of 1 second, a max of 256 seconds and no jitter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExponentialBackoff {
+    base: Duration,
+    max: Duration,
+    jitter: bool,
+}
 
-            // Start from the beginning
-            self.n = 0;
+impl ExponentialBackoff {
+    /// Create a policy with the given base and maximum delay.
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max,
+            jitter: false,
         }
+    }
+
+    /// Randomize the delays.
+    pub fn with_jitter(mut self) -> Self {
+        self.jitter = true;
 
-        self.last = Some(Instant::now());
+        self
+    }
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(256))
+    }
+}
 
-        let v = ((self.n > 0) as u64).wrapping_shl(self.n.saturating_sub(1));
+impl ReconnectPolicy for ExponentialBackoff {
+    fn delay(&self, attempt: u32) -> Option<Duration> {
+        let Some(exp) = attempt.checked_sub(1) else {
+            return Some(Duration::ZERO);
+        };
 
-        self.n = self.n.saturating_add(1).min(self.max);
+        let delay = 2u32
+            .checked_pow(exp)
+            .and_then(|factor| self.base.checked_mul(factor))
+            .map_or(self.max, |delay| delay.min(self.max));
 
-        v
+        if !self.jitter {
+            return Some(delay);
+        }
+
+        let half = delay / 2;
+        let jitter = fastrand::u128(..=(delay - half).as_nanos());
+
+        Some(half + Duration::from_nanos(u64::try_from(jitter).unwrap_or(u64::MAX)))
     }
 }
 
-impl Default for ExponentialIter {
-    fn default() -> Self {
-        Self {
-            n: 0,
-            max: 9,
-            reset_after: Duration::from_secs(256 * 4),
-            last: None,
+/// Wait the same delay before each attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedDelay {
+    delay: Duration,
+}
+
+impl FixedDelay {
+    /// Create a policy with the given delay.
+    pub fn new(delay: Duration) -> Self {
+        Self { delay }
+    }
+}
+
+impl ReconnectPolicy for FixedDelay {
+    fn delay(&self, _attempt: u32) -> Option<Duration> {
+        Some(self.delay)
+    }
+}
+
+/// Stop reconnecting after a maximum number of attempts.
+///
+/// Once the attempts are exhausted the connection will return
+/// [`Error::ConnectionTimeout`](crate::Error::ConnectionTimeout).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxAttempts<P> {
+    policy: P,
+    max: u32,
+}
+
+impl<P> MaxAttempts<P> {
+    /// Limit the attempts of the given policy.
+    pub fn new(policy: P, max: u32) -> Self {
+        Self { policy, max }
+    }
+}
+
+impl<P> ReconnectPolicy for MaxAttempts<P>
+where
+    P: ReconnectPolicy,
+{
+    fn delay(&self, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max {
+            return None;
         }
+
+        self.policy.delay(attempt)
+    }
+
+    fn reset_after(&self) -> Duration {
+        self.policy.reset_after()
     }
 }
 
-impl Iterator for ExponentialIter {
-    type Item = u64;
+/// Keeps track of the attempts for a [`ReconnectPolicy`].
+#[derive(Debug, Clone)]
+pub(crate) struct Backoff {
+    policy: Arc<dyn ReconnectPolicy>,
+    attempt: u32,
+    /// When the connection was last established.
+    connected_at: Option<Instant>,
+}
+
+impl Backoff {
+    pub(crate) fn new(policy: Arc<dyn ReconnectPolicy>) -> Self {
+        Self {
+            policy,
+            attempt: 0,
+            connected_at: None,
+        }
+    }
+
+    /// Uses the configured policy or the default one.
+    pub(crate) fn with_policy(policy: Option<Arc<dyn ReconnectPolicy>>) -> Self {
+        policy.map(Self::new).unwrap_or_default()
+    }
+
+    /// Records that the connection was established.
+    ///
+    /// The attempts are reset on the next disconnection, if the connection stayed up for the
+    /// [`reset_after`](ReconnectPolicy::reset_after) of the policy.
+    pub(crate) fn connected(&mut self) {
+        self.connected_at = Some(Instant::now());
+    }
+
+    /// Returns the delay before the next attempt, or [`None`] if the attempts are exhausted.
+    pub(crate) fn next(&mut self) -> Option<Duration> {
+        if self
+            .connected_at
+            .take()
+            .is_some_and(|instant| instant.elapsed() >= self.policy.reset_after())
+        {
+            trace!("connection was stable, resetting the attempts");
+
+            // Start from the beginning
+            self.attempt = 0;
+        }
+
+        let delay = self.policy.delay(self.attempt);
+
+        self.attempt = self.attempt.saturating_add(1);
+
+        delay
+    }
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        Some(self.next())
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Arc::new(ExponentialBackoff::default()))
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use pretty_assertions::assert_eq;
 
-    use super::ExponentialIter;
+    use super::*;
 
     #[test]
     fn iter_delays() {
         let expected = [0, 1, 2, 4, 8, 16, 32, 64, 128, 256, 256, 256, 256];
-        let delay: Vec<u64> = ExponentialIter::default().take(13).collect();
+        let mut backoff = Backoff::default();
+        let delay: Vec<u64> = std::iter::from_fn(|| backoff.next().map(|d| d.as_secs()))
+            .take(13)
+            .collect();
 
         assert_eq!(delay, expected);
     }
+
+    #[test]
+    fn exponential_with_jitter() {
+        let policy =
+            ExponentialBackoff::new(Duration::from_secs(2), Duration::from_secs(60)).with_jitter();
+
+        assert_eq!(policy.delay(0), Some(Duration::ZERO));
+
+        for attempt in 1..40 {
+            let exp = Duration::from_secs(2u64.saturating_pow(attempt).min(60));
+            let delay = policy.delay(attempt).unwrap();
+
+            assert!(delay >= exp / 2 && delay <= exp, "{delay:?} for {exp:?}");
+        }
+    }
+
+    #[test]
+    fn max_attempts() {
+        let policy = MaxAttempts::new(FixedDelay::new(Duration::from_secs(3)), 2);
+        let mut backoff = Backoff::new(Arc::new(policy));
+
+        assert_eq!(backoff.next(), Some(Duration::from_secs(3)));
+        assert_eq!(backoff.next(), Some(Duration::from_secs(3)));
+        assert_eq!(backoff.next(), None);
+    }
+
+    /// Policy that resets the attempts as soon as the device connects.
+    #[derive(Debug)]
+    pub(crate) struct ResetImmediately(pub(crate) MaxAttempts<FixedDelay>);
+
+    impl ReconnectPolicy for ResetImmediately {
+        fn delay(&self, attempt: u32) -> Option<Duration> {
+            self.0.delay(attempt)
+        }
+
+        fn reset_after(&self) -> Duration {
+            Duration::ZERO
+        }
+    }
+
+    #[test]
+    fn reset_attempts_between_outages() {
+        let policy = MaxAttempts::new(FixedDelay::new(Duration::from_secs(3)), 2);
+        let mut backoff = Backoff::new(Arc::new(ResetImmediately(policy)));
+
+        // first outage
+        assert_eq!(backoff.next(), Some(Duration::from_secs(3)));
+        assert_eq!(backoff.next(), Some(Duration::from_secs(3)));
+        backoff.connected();
+
+        // second outage after a stable connection
+        assert_eq!(backoff.next(), Some(Duration::from_secs(3)));
+        assert_eq!(backoff.next(), Some(Duration::from_secs(3)));
+        assert_eq!(backoff.next(), None);
+    }
+
+    #[test]
+    fn keep_attempts_on_unstable_connection() {
+        let policy = MaxAttempts::new(FixedDelay::new(Duration::from_secs(3)), 2);
+        let mut backoff = Backoff::new(Arc::new(policy));
+
+        assert_eq!(backoff.next(), Some(Duration::from_secs(3)));
+        // disconnected again before the default reset_after
+        backoff.connected();
+        assert_eq!(backoff.next(), Some(Duration::from_secs(3)));
+        backoff.connected();
+        assert_eq!(backoff.next(), None);
+    }
 }
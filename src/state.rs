@@ -19,12 +19,18 @@
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 
+use chrono::Utc;
+use tokio::sync::watch;
 use tokio::sync::RwLock;
 use tokio::sync::Semaphore;
+use tracing::debug;
 
+use crate::client::Subscriptions;
 use crate::interfaces::Interfaces;
 use crate::retention;
 use crate::retention::memory::VolatileStore;
+use crate::retention::Receipts;
+use crate::Timestamp;
 
 /// Shared status between the connection and client.
 ///
@@ -37,28 +43,79 @@ pub(crate) struct SharedState {
     pub(crate) interfaces: RwLock<Interfaces>,
     pub(crate) volatile_store: VolatileStore,
     pub(crate) retention_ctx: retention::Context,
+    pub(crate) receipts: Receipts,
+    pub(crate) subscriptions: Subscriptions,
     pub(crate) status: ConnectionStatus,
 }
 
 impl SharedState {
-    pub(crate) fn new(interfaces: Interfaces, volatile_store: VolatileStore) -> Self {
+    pub(crate) fn new(
+        interfaces: Interfaces,
+        volatile_store: VolatileStore,
+        subscriptions: Subscriptions,
+    ) -> Self {
         Self {
             introspection: Semaphore::new(1),
             interfaces: RwLock::new(interfaces),
             volatile_store,
             retention_ctx: retention::Context::new(),
+            receipts: Receipts::new(),
+            subscriptions,
             status: ConnectionStatus::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum Status {
+/// Status of the connection with Astarte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Status {
+    /// The device is connected to Astarte.
     Connected,
+    /// The device is disconnected and it will try to reconnect.
     Disconnected,
+    /// The connection was closed and the device will not reconnect.
     Closed,
 }
 
+/// Reason of a change in the connection [`Status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StatusReason {
+    /// The device was created, but never connected.
+    Created,
+    /// The device connected to Astarte for the first time.
+    Connected,
+    /// The device connected to Astarte again after a disconnection.
+    Reconnected,
+    /// The connection to Astarte was lost.
+    ConnectionLost,
+    /// The client requested a disconnection.
+    Disconnect,
+    /// The connection was dropped.
+    ConnectionDropped,
+}
+
+/// Change of the connection [`Status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusEvent {
+    /// New status of the connection.
+    pub status: Status,
+    /// Reason of the change.
+    pub reason: StatusReason,
+    /// When the change happened.
+    pub timestamp: Timestamp,
+}
+
+impl StatusEvent {
+    fn new(status: Status, reason: StatusReason) -> Self {
+        Self {
+            status,
+            reason,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
 /// Shared state of the connection
 #[derive(Debug)]
 pub(crate) struct ConnectionStatus {
@@ -66,6 +123,12 @@ pub(crate) struct ConnectionStatus {
     closed: AtomicBool,
     /// Flag if we are connected
     connected: AtomicBool,
+    /// Flag if we ever connected, to distinguish reconnections
+    was_connected: AtomicBool,
+    /// Transitions of the status to the subscribed clients.
+    ///
+    /// Every change is done while holding the channel lock, so the events are ordered.
+    events: watch::Sender<StatusEvent>,
 }
 
 impl ConnectionStatus {
@@ -77,16 +140,53 @@ impl ConnectionStatus {
             // this should happen after the [`ConnectionConfig`](crate::builder::ConnectionConfig) `connect` method
             // to ensure the device is already marked as connected after the `build` function is called
             connected: AtomicBool::new(false),
+            was_connected: AtomicBool::new(false),
+            events: watch::Sender::new(StatusEvent::new(
+                Status::Disconnected,
+                StatusReason::Created,
+            )),
         }
     }
 
     /// Set the state of the connection.
     pub(crate) fn set_connected(&self, connected: bool) {
-        self.connected.store(connected, Ordering::Release);
+        self.events.send_if_modified(|event| {
+            self.connected.store(connected, Ordering::Release);
+
+            let reason = if !connected {
+                StatusReason::ConnectionLost
+            } else if self.was_connected.swap(true, Ordering::AcqRel) {
+                StatusReason::Reconnected
+            } else {
+                StatusReason::Connected
+            };
+
+            self.transition(event, reason)
+        });
     }
 
-    pub(crate) fn close(&self) {
-        self.closed.store(true, Ordering::Release);
+    /// Close the connection for the given reason.
+    pub(crate) fn close(&self, reason: StatusReason) {
+        self.events.send_if_modified(|event| {
+            self.closed.store(true, Ordering::Release);
+
+            self.transition(event, reason)
+        });
+    }
+
+    /// Updates the event if the status changed.
+    fn transition(&self, event: &mut StatusEvent, reason: StatusReason) -> bool {
+        let status = self.connection();
+
+        if event.status == status {
+            return false;
+        }
+
+        debug!(?status, ?reason, "connection status changed");
+
+        *event = StatusEvent::new(status, reason);
+
+        true
     }
 
     pub(crate) fn connection(&self) -> Status {
@@ -98,6 +198,11 @@ impl ConnectionStatus {
             Status::Disconnected
         }
     }
+
+    /// Subscribe to the changes of the connection status.
+    pub(crate) fn subscribe(&self) -> watch::Receiver<StatusEvent> {
+        self.events.subscribe()
+    }
 }
 
 impl Default for ConnectionStatus {
@@ -105,3 +210,47 @@ impl Default for ConnectionStatus {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_track_transitions() {
+        let status = ConnectionStatus::new();
+        let mut rx = status.subscribe();
+
+        assert_eq!(rx.borrow_and_update().reason, StatusReason::Created);
+
+        status.set_connected(true);
+        let event = *rx.borrow_and_update();
+        assert_eq!(event.status, Status::Connected);
+        assert_eq!(event.reason, StatusReason::Connected);
+
+        // no transition
+        status.set_connected(true);
+        assert!(!rx.has_changed().unwrap());
+
+        status.set_connected(false);
+        let event = *rx.borrow_and_update();
+        assert_eq!(event.status, Status::Disconnected);
+        assert_eq!(event.reason, StatusReason::ConnectionLost);
+
+        status.set_connected(true);
+        let event = *rx.borrow_and_update();
+        assert_eq!(event.status, Status::Connected);
+        assert_eq!(event.reason, StatusReason::Reconnected);
+
+        status.close(StatusReason::Disconnect);
+        let event = *rx.borrow_and_update();
+        assert_eq!(event.status, Status::Closed);
+        assert_eq!(event.reason, StatusReason::Disconnect);
+
+        // closed is final
+        status.set_connected(false);
+        status.close(StatusReason::ConnectionDropped);
+        assert!(!rx.has_changed().unwrap());
+    }
+}
@@ -27,6 +27,7 @@ use tracing::{debug, error, instrument, trace, warn};
 
 use crate::builder::DEFAULT_STORE_CAPACITY;
 use crate::error::Report;
+use crate::retention::Id;
 
 use super::options::{SqliteOptions, SqlitePragmas};
 use super::{SqliteError, SQLITE_BUSY_TIMEOUT, SQLITE_CACHE_SIZE};
@@ -145,6 +146,8 @@ pub(crate) struct WriteConnection {
     connection: Connection,
     /// Maximum number of retention item to store
     pub(crate) retention_capacity: NonZeroUsize,
+    /// Publishes removed from the retention without being received.
+    pub(crate) removed_publishes: Vec<Id>,
 }
 
 impl Deref for WriteConnection {
@@ -181,6 +184,7 @@ impl SqliteConnection for WriteConnection {
         let connection = Self {
             connection,
             retention_capacity: DEFAULT_STORE_CAPACITY,
+            removed_publishes: Vec::new(),
         };
 
         connection.apply_pragmas(options)?;
@@ -35,6 +35,7 @@ use rusqlite::{
     ToSql,
 };
 use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 use statements::include_query;
 use tracing::{debug, error, info, instrument, trace};
 
@@ -42,6 +43,7 @@ use self::pool::Connections;
 use self::{connection::SqliteConnection, options::SqliteOptions};
 use super::{OptStoredProp, PropertyMapping, PropertyStore, StoreCapabilities, StoredProp};
 use crate::{
+    builder::FromEnv,
     transport::mqtt::payload::{Payload, PayloadError},
     types::{de::BsonConverter, AstarteData, TypeError},
     utils::const_conv::{const_non_zero_u32, const_non_zero_u64, const_non_zero_usize},
@@ -204,27 +206,28 @@ pub enum ValueError {
 }
 
 /// Dimension of the database
+#[serde_as]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
 #[serde(tag = "unit", content = "value")]
 pub enum Size {
     /// Dimension expressed in KiloBytes
     #[serde(rename = "kb")]
-    Kb(NonZeroU64),
+    Kb(#[serde_as(as = "FromEnv")] NonZeroU64),
     /// Dimension expressed in MegaBytes
     #[serde(rename = "mb")]
-    Mb(NonZeroU64),
+    Mb(#[serde_as(as = "FromEnv")] NonZeroU64),
     /// Dimension expressed in GigaBytes
     #[serde(rename = "gb")]
-    Gb(NonZeroU64),
+    Gb(#[serde_as(as = "FromEnv")] NonZeroU64),
     /// Dimension expressed in KibiBytes
     #[serde(rename = "kib")]
-    KiB(NonZeroU64),
+    KiB(#[serde_as(as = "FromEnv")] NonZeroU64),
     /// Dimension expressed in MebiBytes
     #[serde(rename = "mib")]
-    MiB(NonZeroU64),
+    MiB(#[serde_as(as = "FromEnv")] NonZeroU64),
     /// Dimension expressed in GibiBytes
     #[serde(rename = "gib")]
-    GiB(NonZeroU64),
+    GiB(#[serde_as(as = "FromEnv")] NonZeroU64),
 }
 
 impl Size {
@@ -471,10 +474,17 @@ impl SqliteStore {
     // this is to avoid setting the default pragmas before the user overrides them by using
     // the setters
     pub async fn connect(writable_path: impl AsRef<Path>) -> Result<Self, SqliteError> {
+        Self::connect_with_options(writable_path, SqliteOptions::default()).await
+    }
+
+    /// Connect to the SQLite database in the writable path, configured with the options.
+    pub(crate) async fn connect_with_options(
+        writable_path: impl AsRef<Path>,
+        options: SqliteOptions,
+    ) -> Result<Self, SqliteError> {
         // TODO: rename the database to store.db since it doesn't contain only  properties
         let db = writable_path.as_ref().join("prop-cache.db");
 
-        let options = SqliteOptions::default();
         Self::new(db, options).await
     }
 
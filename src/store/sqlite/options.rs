@@ -24,8 +24,10 @@
 use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
 
 use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 use tracing::{error, instrument, trace};
 
+use crate::builder::FromEnv;
 use crate::error::Report;
 
 use super::connection::SqliteConnection;
@@ -34,11 +36,12 @@ use super::{
 };
 
 /// Choices of limit of the size of the sqlite database
+#[serde_as]
 #[derive(Debug, Clone, Copy, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SizeLimit {
     /// Set a size limit based on the number of pages
-    MaxPageCount(NonZeroU32),
+    MaxPageCount(#[serde_as(as = "FromEnv")] NonZeroU32),
     /// Set a size limit based on the actual size of the db file
     ///
     /// This value will be approximated if it's not a multiple of the database page size.
@@ -46,9 +49,11 @@ pub enum SizeLimit {
 }
 
 /// SQLite options that can be set externally to tweak the behaviour of the connections.
+#[serde_as]
 #[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
 pub(crate) struct SqliteOptions {
     // Maximum number of read connection to open
+    #[serde_as(as = "Option<FromEnv>")]
     max_readers: Option<NonZeroUsize>,
     // Maximum size of the database file.
     //
@@ -58,6 +58,7 @@ use crate::client::RecvError;
 use crate::error::{AggregationError, InterfaceTypeError, Report};
 use crate::interfaces::MappingRef;
 use crate::retention::{PublishInfo, RetentionId};
+use crate::retry::{Backoff, ReconnectPolicy};
 use crate::state::SharedState;
 use crate::{
     builder::{ConnectionConfig, DeviceTransport},
@@ -185,6 +186,8 @@ impl<S> GrpcClient<S> {
             }
         }
 
+        self.state.receipts.received(id);
+
         Ok(())
     }
 
@@ -579,12 +582,17 @@ impl GrpcPayload {
 pub struct GrpcConfig {
     uuid: Uuid,
     endpoint: Endpoint,
+    reconnect_policy: Option<Arc<dyn ReconnectPolicy>>,
 }
 
 impl GrpcConfig {
     /// Create a new config.
     pub const fn new(uuid: Uuid, endpoint: Endpoint) -> Self {
-        Self { uuid, endpoint }
+        Self {
+            uuid,
+            endpoint,
+            reconnect_policy: None,
+        }
     }
 
     /// Create a new config from node id and Message Hub endpoint.
@@ -598,6 +606,15 @@ impl GrpcConfig {
     pub fn endpoint_mut(&mut self) -> &mut Endpoint {
         &mut self.endpoint
     }
+
+    /// Sets the policy for the delay between the reconnection attempts.
+    ///
+    /// Defaults to an [`ExponentialBackoff`](crate::retry::ExponentialBackoff).
+    pub fn reconnect_policy(&mut self, policy: impl ReconnectPolicy + 'static) -> &mut Self {
+        self.reconnect_policy = Some(Arc::new(policy));
+
+        self
+    }
 }
 
 impl<S> ConnectionConfig<S> for GrpcConfig
@@ -627,6 +644,11 @@ where
 
         // HACK: disable the volatile retention
         config.state.volatile_store.set_capacity(0).await;
+        config
+            .state
+            .volatile_store
+            .notify_removed(&config.state.receipts)
+            .await;
 
         let state = Arc::clone(&config.state);
 
@@ -639,6 +661,7 @@ where
             store,
             // NOTE if the attach is successful we have correctly established a connection with the grpc server
             connected: true,
+            backoff: Backoff::with_policy(self.reconnect_policy),
         })
     }
 }
@@ -688,7 +711,11 @@ mod test {
         DEVICE_OBJECT, DEVICE_PROPERTIES, DEVICE_PROPERTIES_NAME, E2E_DEVICE_PROPERTY,
         E2E_DEVICE_PROPERTY_NAME, E2E_SERVER_DATASTREAM,
     };
-    use crate::{aggregate::AstarteObject, builder::DEFAULT_VOLATILE_CAPACITY};
+    use crate::{
+        aggregate::AstarteObject,
+        builder::{DEFAULT_CHANNEL_SIZE, DEFAULT_VOLATILE_CAPACITY},
+        client::Subscriptions,
+    };
 
     use super::*;
 
@@ -805,6 +832,7 @@ mod test {
         let state = SharedState::new(
             interfaces,
             VolatileStore::with_capacity(DEFAULT_VOLATILE_CAPACITY),
+            Subscriptions::with_capacity(DEFAULT_CHANNEL_SIZE),
         );
 
         let client = GrpcClient::new(message_hub_client_tx, store, Arc::new(state));
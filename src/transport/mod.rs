@@ -102,7 +102,8 @@ pub(crate) trait Publish {
 
     /// Sends validated individual values with stored retention over this connection.
     ///
-    /// The id is to identify the packet to confirm it was received by the server.
+    /// The id is to identify the packet to confirm it was received by the server. It's also used
+    /// for the confirmed publishes with retention discard, which aren't retained.
     fn send_individual_stored(
         &mut self,
         id: RetentionId,
@@ -111,7 +112,8 @@ pub(crate) trait Publish {
 
     /// Sends validated objects values with stored retention over this connection
     ///
-    /// The id is to identify the packet to confirm it was received by the server.
+    /// The id is to identify the packet to confirm it was received by the server. It's also used
+    /// for the confirmed publishes with retention discard, which aren't retained.
     fn send_object_stored(
         &mut self,
         id: RetentionId,
@@ -20,32 +20,36 @@
 
 use rumqttc::{MqttOptions, NetworkOptions, Transport};
 use serde::{Deserialize, Serialize};
-use std::{
-    fmt::{Debug, Display},
-    io,
-    path::{Path, PathBuf},
-    sync::Arc,
-    time::Duration,
-};
-use tokio::fs;
+use serde_with::{serde_as, DurationSeconds, PickFirst};
+use std::{fmt::Debug, future::Future, io, path::PathBuf, sync::Arc, time::Duration};
 use tracing::{debug, warn};
 use url::Url;
 
 use crate::{
-    builder::{BuildConfig, ConnectionConfig, DeviceTransport, DEFAULT_CHANNEL_SIZE},
+    builder::{BuildConfig, ConnectionConfig, DeviceTransport, FromEnv, DEFAULT_CHANNEL_SIZE},
     error::Report,
+    retry::{Backoff, ReconnectPolicy},
     store::{wrapper::StoreWrapper, StoreCapabilities},
     transport::mqtt::{
-        config::transport::TransportProvider, connection::MqttConnection, error::MqttError,
-        retention::MqttRetention, ClientId,
+        config::transport::TransportProvider,
+        connection::MqttConnection,
+        credentials::{
+            CredentialKind, CredentialStore, FileCredentialStore, SharedCredentialStore,
+        },
+        crypto::KeyAlgorithm,
+        error::MqttError,
+        retention::MqttRetention,
+        ClientId,
     },
 };
 
 use self::tls::is_env_ignore_ssl;
 
 use super::{
-    client::AsyncClient, pairing::ApiClient, registration::register_device_with_timeout, Mqtt,
-    MqttClient, PairingError, SharedState, DEFAULT_KEEP_ALIVE,
+    client::AsyncClient,
+    pairing::ApiClient,
+    registration::{Registration, RegistrationCallback},
+    Mqtt, MqttClient, PairingError, Proxy, SharedState, SpkiPin, DEFAULT_KEEP_ALIVE,
 };
 
 mod tls;
@@ -73,7 +77,8 @@ pub enum Credential {
     ///
     /// You need to set a writable directory on the builder to store the registered credential
     /// secret used for authentication. You can either use the [`crate::builder::DeviceBuilder::writable_dir`] or
-    /// [`crate::builder::DeviceBuilder::store_dir`] methods.
+    /// [`crate::builder::DeviceBuilder::store_dir`] methods, or a custom
+    /// [`MqttConfig::credential_store`].
     ParingToken {
         /// The JWT secret to pair the device to astarte.
         pairing_token: String,
@@ -118,6 +123,9 @@ impl Debug for Credential {
 /// - does not ignore SSL errors.
 /// - has a keepalive of 30 seconds
 /// - has a default bounded channel size of [`crate::builder::DEFAULT_CHANNEL_SIZE`]
+///
+/// The keepalive is serialized in seconds.
+#[serde_as]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MqttConfig {
     pub(crate) realm: String,
@@ -125,9 +133,52 @@ pub struct MqttConfig {
     #[serde(flatten)]
     pub(crate) credential: Credential,
     pub(crate) pairing_url: String,
+    #[serde(default)]
+    #[serde_as(as = "FromEnv")]
     pub(crate) ignore_ssl_errors: bool,
+    #[serde(default = "default_keepalive")]
+    #[serde_as(as = "PickFirst<(DurationSeconds<u64>, DurationSeconds<String>)>")]
     pub(crate) keepalive: Duration,
+    #[serde(default = "default_channel_size")]
+    #[serde_as(as = "FromEnv")]
     pub(crate) bounded_channel_size: usize,
+    #[serde(skip)]
+    pub(crate) reconnect_policy: Option<Arc<dyn ReconnectPolicy>>,
+    pub(crate) websocket_url: Option<Url>,
+    pub(crate) proxy: Option<Proxy>,
+    #[serde_as(as = "Option<FromEnv>")]
+    pub(crate) certificate_renewal: Option<f64>,
+    #[serde(default)]
+    pub(crate) key_algorithm: KeyAlgorithm,
+    #[serde(default)]
+    pub(crate) ca_certificates: Vec<String>,
+    #[serde(default)]
+    pub(crate) spki_pins: Vec<SpkiPin>,
+    #[serde(skip)]
+    pub(crate) credential_store: Option<SharedCredentialStore>,
+    #[serde(skip)]
+    pub(crate) registration_callback: Option<RegistrationCallback>,
+}
+
+/// Loads the credentials secret from the store, returns [`None`] if it's not stored.
+pub(crate) async fn load_secret(
+    store: &SharedCredentialStore,
+) -> Result<Option<String>, PairingError> {
+    let Some(secret) = store.load_dyn(CredentialKind::Secret).await? else {
+        return Ok(None);
+    };
+
+    String::from_utf8(secret).map(Some).map_err(|err| {
+        PairingError::InvalidCredentials(io::Error::new(io::ErrorKind::InvalidData, err))
+    })
+}
+
+const fn default_keepalive() -> Duration {
+    Duration::from_secs(DEFAULT_KEEP_ALIVE)
+}
+
+const fn default_channel_size() -> usize {
+    DEFAULT_CHANNEL_SIZE
 }
 
 #[derive(Clone, Debug)]
@@ -178,6 +229,15 @@ impl MqttConfig {
             ignore_ssl_errors: false,
             keepalive: Duration::from_secs(DEFAULT_KEEP_ALIVE),
             bounded_channel_size: DEFAULT_CHANNEL_SIZE,
+            reconnect_policy: None,
+            websocket_url: None,
+            proxy: None,
+            certificate_renewal: None,
+            key_algorithm: KeyAlgorithm::default(),
+            ca_certificates: Vec::new(),
+            spki_pins: Vec::new(),
+            credential_store: None,
+            registration_callback: None,
         }
     }
 
@@ -278,73 +338,188 @@ impl MqttConfig {
         self
     }
 
+    /// Sets the policy for the delay between the reconnection attempts.
+    ///
+    /// Defaults to an [`ExponentialBackoff`](crate::retry::ExponentialBackoff).
+    pub fn reconnect_policy(&mut self, policy: impl ReconnectPolicy + 'static) -> &mut Self {
+        self.reconnect_policy = Some(Arc::new(policy));
+
+        self
+    }
+
+    /// Connect to the broker over a WebSocket at the given `wss://` URL.
+    ///
+    /// By default the connection uses the broker URL returned by the pairing API, which can
+    /// already be a `wss://` URL. This forces the WebSocket transport, for example when only the
+    /// HTTPS port is reachable. The device still authenticates with its client certificate.
+    ///
+    /// Requires the `websocket` feature.
+    pub fn websocket_url(&mut self, url: Url) -> &mut Self {
+        self.websocket_url = Some(url);
+
+        self
+    }
+
+    /// Connect to Astarte through an HTTP proxy.
+    ///
+    /// The proxy is used for the pairing API and to tunnel the MQTT connection. Without it, the
+    /// proxy in the `HTTPS_PROXY` environment variable is used, honouring `NO_PROXY`.
+    pub fn proxy(&mut self, proxy: Proxy) -> &mut Self {
+        self.proxy = Some(proxy);
+
+        self
+    }
+
+    /// Renew the client certificate after the fraction of its validity period.
+    ///
+    /// The certificate is renewed in the background, then the connection is re-established with
+    /// it without losing the queued messages. The fraction is clamped between `0.1` and `1.0`, so
+    /// the certificate is not renewed right after it's created, and defaults to `0.8`.
+    pub fn certificate_renewal(&mut self, fraction: f64) -> &mut Self {
+        self.certificate_renewal = Some(fraction);
+
+        self
+    }
+
+    /// Sets the algorithm of the private key generated for the device certificate.
+    ///
+    /// Defaults to [`KeyAlgorithm::EcdsaP256`]. A certificate already stored is reused until it's
+    /// renewed, even if it was generated with another algorithm.
+    pub fn key_algorithm(&mut self, algorithm: KeyAlgorithm) -> &mut Self {
+        self.key_algorithm = algorithm;
+
+        self
+    }
+
+    /// Trust the CA certificates in the PEM bundle, in addition to the system ones.
+    ///
+    /// The CAs are used to verify both the pairing API and the broker, for example for an
+    /// installation of Astarte with a private CA. Can be called multiple times to add more bundles.
+    pub fn ca_certificates(&mut self, pem: impl Into<String>) -> &mut Self {
+        self.ca_certificates.push(pem.into());
+
+        self
+    }
+
+    /// Pin the public key of a certificate of the pairing API and the broker.
+    ///
+    /// When at least a pin is set, the server certificate chain must be valid and contain a
+    /// certificate with one of the pinned public keys. Pinning the key of the CA allows the server
+    /// certificates to be renewed.
+    pub fn spki_pin(&mut self, pin: SpkiPin) -> &mut Self {
+        self.spki_pins.push(pin);
+
+        self
+    }
+
+    /// Store the credentials of the device in the given store.
+    ///
+    /// By default the credential secret, the certificate and the private key are stored as files
+    /// in the writable directory with a [`FileCredentialStore`].
+    pub fn credential_store(&mut self, store: impl CredentialStore) -> &mut Self {
+        self.credential_store = Some(Arc::new(store));
+
+        self
+    }
+
+    /// Register the device again with the callback when Astarte rejects its credentials.
+    ///
+    /// When the certificate is revoked or the credential secret is reset, the device is
+    /// registered again with the pairing token, if it was configured, or with this callback. The
+    /// callback returns the new credential secret, which is persisted in the credential store.
+    /// Without either of them, the connection fails with [`Error::Unauthorized`](crate::Error::Unauthorized).
+    pub fn registration_callback<F, Fut, E>(&mut self, callback: F) -> &mut Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, E>> + Send + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        self.registration_callback = Some(RegistrationCallback::new(callback));
+
+        self
+    }
+
+    /// Returns how to register the device again when the credentials are rejected.
+    fn registration(&self) -> Option<Registration> {
+        if let Some(callback) = &self.registration_callback {
+            return Some(Registration::Callback(callback.clone()));
+        }
+
+        match &self.credential {
+            Credential::Secret { .. } => None,
+            Credential::ParingToken { pairing_token } => {
+                Some(Registration::PairingToken(pairing_token.clone()))
+            }
+        }
+    }
+
+    /// Returns the configured credential store or the default one in the writable directory.
+    fn resolve_credential_store(
+        &self,
+        writable_dir: Option<&PathBuf>,
+    ) -> Option<SharedCredentialStore> {
+        self.credential_store.clone().or_else(|| {
+            writable_dir.map(|dir| Arc::new(FileCredentialStore::new(dir)) as SharedCredentialStore)
+        })
+    }
+
     /// Retrieves the credentials for the connection
+    ///
+    /// The device is registered with the same TLS configuration of the pairing API.
     async fn credentials(
-        &mut self,
-        writable_dir: &Option<PathBuf>,
+        &self,
+        provider: &TransportProvider,
+        store: Option<&SharedCredentialStore>,
         timeout: Duration,
     ) -> Result<String, MqttError> {
-        // We need to clone to not return something owning a mutable reference to self
+        // The pairing token is kept to register the device again if the secret gets rejected
         match &self.credential {
             Credential::Secret { credentials_secret } => Ok(credentials_secret.clone()),
             Credential::ParingToken { pairing_token } => {
                 debug!("pairing token provided, retrieving credentials secret");
 
-                let Some(dir) = &writable_dir else {
+                let Some(store) = store else {
                     return Err(MqttError::NoStorePairingToken);
                 };
 
                 let secret = self
-                    .read_secret_or_register(dir, pairing_token, timeout)
+                    .read_secret_or_register(provider, store, pairing_token, timeout)
                     .await?;
 
-                self.credential = Credential::secret(secret.clone());
-
                 Ok(secret)
             }
         }
     }
 
-    /// Register the device and stores the credentials secret in the given directory
+    /// Register the device and stores the credentials secret in the given store
     async fn read_secret_or_register(
         &self,
-        store_dir: &Path,
+        provider: &TransportProvider,
+        store: &SharedCredentialStore,
         pairing_token: &str,
         timeout: Duration,
     ) -> Result<String, PairingError> {
-        let credential_file = store_dir.join(CREDENTIAL_FILE);
-
-        match fs::read_to_string(&credential_file).await {
-            Ok(secret) => return Ok(secret),
-            Err(err) if err.kind() == io::ErrorKind::NotFound => {
-                debug!("no credential file {}", credential_file.display())
-            }
-            Err(err) => {
-                return Err(PairingError::ReadCredential {
-                    path: credential_file,
-                    backtrace: err,
-                });
-            }
+        if let Some(secret) = load_secret(store).await? {
+            return Ok(secret);
         }
 
-        let secret = register_device_with_timeout(
-            pairing_token,
-            &self.pairing_url,
-            &self.realm,
-            &self.device_id,
-            timeout,
-        )
-        .await?;
+        debug!(?store, "no credential secret stored");
+
+        let client_id = ClientId {
+            realm: self.realm.as_str(),
+            device_id: self.device_id.as_str(),
+        };
+
+        let secret = Registration::PairingToken(pairing_token.to_string())
+            .register(provider, client_id, timeout)
+            .await?;
 
         // We can register the device multiple times with the same pairing token if the device
-        // hasn't connected. If the call to write the file fails, we will just re-register the
+        // hasn't connected. If the call to store the secret fails, we will just re-register the
         // device.
-        fs::write(&credential_file, &secret).await.map_err(|err| {
-            PairingError::WriteCredential {
-                path: credential_file,
-                backtrace: err,
-            }
-        })?;
+        store
+            .store_dyn(CredentialKind::Secret, secret.as_bytes())
+            .await?;
 
         Ok(secret)
     }
@@ -361,11 +536,26 @@ impl MqttConfig {
         let host = broker_url
             .host_str()
             .ok_or_else(|| PairingError::Config("missing host in url".to_string()))?;
-        let port = broker_url
-            .port()
-            .ok_or_else(|| PairingError::Config("missing port in url".to_string()))?;
 
-        let mut mqtt_opts = MqttOptions::new(client_id, host, port);
+        #[cfg(feature = "websocket")]
+        let websocket = matches!(transport, Transport::Wss(_));
+        #[cfg(not(feature = "websocket"))]
+        let websocket = false;
+
+        let mut mqtt_opts = if websocket {
+            let port = broker_url
+                .port_or_known_default()
+                .ok_or_else(|| PairingError::Config("missing port in url".to_string()))?;
+
+            // For WebSockets rumqttc reads the host and port from the full URL
+            MqttOptions::new(client_id, broker_url.as_str(), port)
+        } else {
+            let port = broker_url
+                .port()
+                .ok_or_else(|| PairingError::Config("missing port in url".to_string()))?;
+
+            MqttOptions::new(client_id, host, port)
+        };
 
         let keep_alive = self.keepalive.as_secs();
         let conn_timeout = timeout.as_secs();
@@ -382,44 +572,106 @@ impl MqttConfig {
 
         mqtt_opts.set_transport(transport);
 
+        if let Some(proxy) = Proxy::resolve(self.proxy.as_ref(), broker_url) {
+            debug!(?proxy, "connecting to the broker through a proxy");
+
+            mqtt_opts.set_proxy(proxy.mqtt_proxy()?);
+        }
+
         // Set the clean_session since this is the first connection.
         mqtt_opts.set_clean_session(true);
 
         Ok((mqtt_opts, net_opts))
     }
 
+    /// Configures the provider for the pairing API and the MQTT transport.
+    pub(crate) async fn transport_provider(
+        &self,
+        secret: String,
+        store: Option<SharedCredentialStore>,
+    ) -> Result<TransportProvider, PairingError> {
+        let pairing_url = self.pairing_url.parse()?;
+
+        let insecure_ssl = self.ignore_ssl_errors || is_env_ignore_ssl();
+
+        let mut provider =
+            TransportProvider::configure(pairing_url, secret, store, insecure_ssl).await?;
+
+        for pem in &self.ca_certificates {
+            provider.add_ca_certificates(pem)?;
+        }
+        provider.set_spki_pins(&self.spki_pins);
+        provider.set_registration(self.registration());
+        provider.set_proxy(self.proxy.clone());
+        provider.set_key_algorithm(self.key_algorithm);
+
+        if let Some(fraction) = self.certificate_renewal {
+            provider.set_renewal(fraction);
+        }
+
+        Ok(provider)
+    }
+
+    /// Retrieves the broker URL and creates the transport to connect to it.
+    async fn broker_transport(
+        &self,
+        provider: &mut TransportProvider,
+        timeout: Duration,
+    ) -> Result<(Url, Transport), PairingError> {
+        let client = ApiClient::from_transport(provider, &self.realm, &self.device_id, timeout)?;
+
+        let borker_url = match &self.websocket_url {
+            Some(url) => url.clone(),
+            None => client.get_broker_url().await?,
+        };
+
+        provider.set_websocket(borker_url.scheme() == "wss");
+
+        let transport = provider.transport(&client).await?;
+
+        Ok((borker_url, transport))
+    }
+
     pub(crate) async fn try_create_transport(
         &mut self,
         config: &PartialConfig,
         timeout: Duration,
     ) -> Result<MqttTransportOptions, MqttError> {
-        let secret = self.credentials(&config.writable_dir, timeout).await?;
+        let store = self.resolve_credential_store(config.writable_dir.as_ref());
 
-        let pairing_url = self
-            .pairing_url
-            .parse()
-            .map_err(|err| MqttError::Pairing(PairingError::InvalidUrl(err)))?;
-
-        let insecure_ssl = self.ignore_ssl_errors || is_env_ignore_ssl();
+        // The secret is set once retrieved, since the registration needs the TLS configuration
+        let mut provider = self
+            .transport_provider(String::new(), store.clone())
+            .await
+            .map_err(MqttError::Pairing)?;
 
-        let provider = TransportProvider::configure(
-            pairing_url,
-            secret,
-            config.writable_dir.clone(),
-            insecure_ssl,
-        )
-        .await
-        .map_err(MqttError::Pairing)?;
+        let secret = self.credentials(&provider, store.as_ref(), timeout).await?;
+        provider.set_credential_secret(secret);
 
-        let client = ApiClient::from_transport(&provider, &self.realm, &self.device_id, timeout)
-            .map_err(MqttError::Pairing)?;
+        let (borker_url, transport) = match self.broker_transport(&mut provider, timeout).await {
+            Ok(res) => res,
+            Err(err) if err.is_unauthorized() => {
+                warn!(error = %Report::new(&err), "credentials rejected by Astarte");
 
-        let borker_url = client.get_broker_url().await.map_err(MqttError::Pairing)?;
+                let client_id = ClientId {
+                    realm: self.realm.as_str(),
+                    device_id: self.device_id.as_str(),
+                };
 
-        let transport = provider
-            .transport(&client)
-            .await
-            .map_err(MqttError::Pairing)?;
+                if !provider
+                    .register_again(client_id, timeout)
+                    .await
+                    .map_err(MqttError::Pairing)?
+                {
+                    return Err(MqttError::Pairing(err));
+                }
+
+                self.broker_transport(&mut provider, timeout)
+                    .await
+                    .map_err(MqttError::Pairing)?
+            }
+            Err(err) => return Err(MqttError::Pairing(err)),
+        };
 
         let (mqtt_opts, net_opts) = self
             .build_mqtt_opts(transport, &borker_url, timeout)
@@ -474,6 +726,7 @@ impl MqttConfig {
                     &interfaces,
                     store_wrapper,
                     timeout,
+                    Backoff::with_policy(self.reconnect_policy.clone()),
                 )
                 .await?;
 
@@ -563,69 +816,144 @@ where
             connection,
             store: store_wrapper,
             connected,
+            backoff: Backoff::with_policy(self.reconnect_policy),
         })
     }
 }
 
-/// Private keys file, to be a type safe when passed to functions.
-#[derive(Debug, Clone)]
-pub(crate) struct PrivateKeyFile(PathBuf);
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
 
-impl PrivateKeyFile {
-    /// Create a path to the key file.
-    pub(crate) fn new(dir: impl AsRef<Path>) -> Self {
-        Self(dir.as_ref().join(PRIVATE_KEY_FILE))
-    }
+    use super::*;
 
-    /// Gets the path to the file.
-    pub(crate) fn path(&self) -> &Path {
-        &self.0
-    }
-}
+    /// TLS configuration of a test server for localhost, with the PEM of its private CA.
+    ///
+    /// When the client CA is passed, the server requires a client certificate signed by it.
+    fn localhost_tls_server(client_ca: Option<&str>) -> (String, Arc<rustls::ServerConfig>) {
+        let ca_key = rcgen::KeyPair::generate().unwrap();
+        let mut ca_params = rcgen::CertificateParams::new([]).unwrap();
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let server_key = rcgen::KeyPair::generate().unwrap();
+        let server = rcgen::CertificateParams::new(["localhost".to_string()])
+            .unwrap()
+            .signed_by(&server_key, &ca, &ca_key)
+            .unwrap();
+
+        let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+        let builder = rustls::ServerConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()
+            .unwrap();
+        let builder = match client_ca {
+            Some(client_ca) => {
+                let mut roots = rustls::RootCertStore::empty();
+                for cert in rustls_pemfile::certs(&mut client_ca.as_bytes()) {
+                    roots.add(cert.unwrap()).unwrap();
+                }
+
+                let verifier = rustls::server::WebPkiClientVerifier::builder_with_provider(
+                    Arc::new(roots),
+                    provider,
+                )
+                .build()
+                .unwrap();
 
-impl Display for PrivateKeyFile {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0.display())
-    }
-}
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+        let config = builder
+            .with_single_cert(
+                vec![server.der().clone()],
+                rustls::pki_types::PrivateKeyDer::Pkcs8(server_key.serialize_der().into()),
+            )
+            .unwrap();
 
-impl AsRef<Path> for PrivateKeyFile {
-    fn as_ref(&self) -> &Path {
-        self.path()
+        (ca.pem(), Arc::new(config))
     }
-}
 
-/// Certificate file, to be a type safe check.
-#[derive(Debug, Clone)]
-pub(crate) struct CertificateFile(PathBuf);
+    /// Reads an HTTP request head, till the empty line.
+    fn read_http_head(stream: &mut impl Read) -> String {
+        let mut buf = Vec::new();
+        let mut byte = [0u8; 1];
+        while !buf.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte).unwrap();
+            buf.push(byte[0]);
+        }
 
-impl CertificateFile {
-    /// Create a path to the certificate file.
-    pub(crate) fn new(dir: impl AsRef<Path>) -> Self {
-        Self(dir.as_ref().join(CERTIFICATE_FILE))
+        String::from_utf8(buf).unwrap()
     }
 
-    /// Gets the path to the file.
-    pub(crate) fn path(&self) -> &Path {
-        &self.0
-    }
-}
+    #[tokio::test]
+    async fn should_register_with_ca_certificates() {
+        let (ca_pem, server_config) = localhost_tls_server(None);
+
+        // Stand-in for the pairing API, only trusted through the CA certificate
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let conn = rustls::ServerConnection::new(server_config).unwrap();
+            let mut stream = rustls::StreamOwned::new(conn, stream);
+
+            let head = read_http_head(&mut stream);
+            let len: usize = head
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse().unwrap())
+                })
+                .unwrap();
+            let mut body = vec![0u8; len];
+            stream.read_exact(&mut body).unwrap();
+
+            let response = r#"{"data":{"credentials_secret":"secret"}}"#;
+            write!(
+                stream,
+                "HTTP/1.1 201 Created\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{response}",
+                response.len()
+            )
+            .unwrap();
+            stream.flush().unwrap();
 
-impl Display for CertificateFile {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0.display())
-    }
-}
+            head
+        });
 
-impl AsRef<Path> for CertificateFile {
-    fn as_ref(&self) -> &Path {
-        self.path()
-    }
-}
+        let mut config = MqttConfig::with_pairing_token(
+            "realm",
+            "device",
+            "token",
+            format!("https://localhost:{port}"),
+        );
+        config.ca_certificates(ca_pem);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let dir = tempfile::tempdir().unwrap();
+        let store: SharedCredentialStore = Arc::new(FileCredentialStore::new(dir.path()));
+
+        let provider = config
+            .transport_provider(String::new(), Some(Arc::clone(&store)))
+            .await
+            .unwrap();
+
+        let secret = config
+            .read_secret_or_register(&provider, &store, "token", Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(secret, "secret");
+
+        let head = tokio::task::spawn_blocking(move || server.join().unwrap())
+            .await
+            .unwrap();
+        assert!(
+            head.starts_with("POST /v1/realm/agent/devices HTTP/1.1\r\n"),
+            "{head}"
+        );
+    }
 
     #[test]
     fn test_default_mqtt_config() {
@@ -698,10 +1026,198 @@ mod tests {
 
     #[test]
     fn check_key_and_cert_file() {
-        let key = PrivateKeyFile::new("/foo");
-        assert_eq!(key.path(), Path::new("/foo/priv-key.der"));
+        let store = FileCredentialStore::new("/foo");
+
+        let key = store.path(CredentialKind::PrivateKey);
+        assert_eq!(key, std::path::Path::new("/foo/priv-key.der"));
+
+        let cert = store.path(CredentialKind::Certificate);
+        assert_eq!(cert, std::path::Path::new("/foo/certificate.pem"));
+    }
+
+    #[test]
+    fn should_build_tls_mqtt_opts() {
+        let mqtt_config = MqttConfig::with_credential_secret("realm", "device", "test", "test");
+
+        let url = Url::parse("mqtts://broker.example.com:8883/").unwrap();
+        let (opts, _) = mqtt_config
+            .build_mqtt_opts(Transport::Tcp, &url, Duration::from_secs(10))
+            .unwrap();
+
+        assert_eq!(
+            opts.broker_address(),
+            ("broker.example.com".to_string(), 8883)
+        );
+        assert_eq!(opts.client_id(), "realm/device");
+    }
+
+    #[tokio::test]
+    async fn should_tunnel_through_proxy() {
+        use tokio::io::AsyncReadExt;
+        use tokio::net::TcpListener;
+
+        // Stand-in for the proxy, it only reads the CONNECT request
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let mut mqtt_config = MqttConfig::with_credential_secret("realm", "device", "test", "test");
+        mqtt_config.proxy(
+            Proxy::new(format!("http://127.0.0.1:{}", addr.port()).parse().unwrap())
+                .basic_auth("user", "password"),
+        );
+
+        let url = Url::parse("mqtts://broker.example.com:8883/").unwrap();
+        let (opts, net_opts) = mqtt_config
+            .build_mqtt_opts(Transport::Tcp, &url, Duration::from_secs(10))
+            .unwrap();
+
+        let (_client, mut eventloop) = rumqttc::AsyncClient::new(opts, 10);
+        eventloop.set_network_options(net_opts);
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 256];
+            while !buf.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut chunk).await.unwrap();
+                assert_ne!(n, 0, "connection closed");
+                buf.extend_from_slice(&chunk[..n]);
+            }
+
+            String::from_utf8(buf).unwrap()
+        });
+
+        // The connection fails since the stand-in doesn't reply
+        let _ = tokio::time::timeout(Duration::from_secs(2), eventloop.poll()).await;
+
+        let request = server.await.unwrap();
+
+        assert!(
+            request.starts_with("CONNECT broker.example.com:8883 HTTP/1.1\r\n"),
+            "{request}"
+        );
+        // base64 of user:password
+        assert!(request.contains("Basic dXNlcjpwYXNzd29yZA=="), "{request}");
+    }
+
+    #[cfg(feature = "websocket")]
+    #[test]
+    fn should_build_websocket_mqtt_opts() {
+        let mqtt_config = MqttConfig::with_credential_secret("realm", "device", "test", "test");
+
+        let url = Url::parse("wss://broker.example.com/mqtt").unwrap();
+        let (opts, _) = mqtt_config
+            .build_mqtt_opts(
+                Transport::wss_with_default_config(),
+                &url,
+                Duration::from_secs(10),
+            )
+            .unwrap();
+
+        assert_eq!(
+            opts.broker_address(),
+            ("wss://broker.example.com/mqtt".to_string(), 443)
+        );
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn should_connect_websocket_with_tls() {
+        use async_tungstenite::tungstenite::handshake::server::{Request, Response};
+        use async_tungstenite::tungstenite::Message;
+
+        use crate::transport::mqtt::pairing::tests::{mock_create_certificate, ASTARTE_CA_PEM};
+
+        // The pairing mock signs the device certificate with the Astarte test CA
+        let (ca_pem, server_config) = localhost_tls_server(Some(ASTARTE_CA_PEM));
+
+        // Stand-in for the broker, only trusted through the CA certificate
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let broker = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let conn = rustls::ServerConnection::new(server_config).unwrap();
+            let stream = rustls::StreamOwned::new(conn, stream);
+
+            let mut ws = async_tungstenite::tungstenite::accept_hdr(
+                stream,
+                |req: &Request, mut res: Response| {
+                    assert_eq!(req.uri().path(), "/mqtt");
+
+                    res.headers_mut()
+                        .insert("sec-websocket-protocol", "mqtt".parse().unwrap());
+
+                    Ok(res)
+                },
+            )
+            .unwrap();
+
+            let peer_certificates = ws.get_ref().conn.peer_certificates();
+            assert!(
+                peer_certificates.is_some_and(|certs| !certs.is_empty()),
+                "missing client certificate"
+            );
+
+            let connect = match ws.read().unwrap() {
+                Message::Binary(connect) => connect,
+                msg => panic!("expected a binary message, got {msg:?}"),
+            };
+
+            // CONNACK with the session not present and the connection accepted
+            ws.send(Message::Binary(vec![0x20, 0x02, 0x00, 0x00]))
+                .unwrap();
+
+            connect
+        });
+
+        let mut pairing = mockito::Server::new_async().await;
+        let mock = mock_create_certificate(&mut pairing).create_async().await;
+
+        let mut mqtt_config =
+            MqttConfig::with_credential_secret("realm", "device_id", "secret", pairing.url());
+        mqtt_config
+            .ca_certificates(ca_pem)
+            .websocket_url(Url::parse(&format!("wss://localhost:{port}/mqtt")).unwrap());
+
+        let timeout = Duration::from_secs(5);
+        let mut provider = mqtt_config
+            .transport_provider("secret".to_string(), None)
+            .await
+            .unwrap();
+        let (url, transport) = mqtt_config
+            .broker_transport(&mut provider, timeout)
+            .await
+            .unwrap();
+        let (opts, net_opts) = mqtt_config
+            .build_mqtt_opts(transport, &url, timeout)
+            .unwrap();
+
+        let (_client, mut eventloop) = rumqttc::AsyncClient::new(opts, 10);
+        eventloop.set_network_options(net_opts);
+
+        let event = tokio::time::timeout(timeout, eventloop.poll())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            matches!(
+                event,
+                rumqttc::Event::Incoming(rumqttc::Packet::ConnAck(rumqttc::ConnAck {
+                    code: rumqttc::ConnectReturnCode::Success,
+                    ..
+                }))
+            ),
+            "{event:?}"
+        );
+
+        let connect = tokio::task::spawn_blocking(move || broker.join().unwrap())
+            .await
+            .unwrap();
+        // MQTT CONNECT packet type
+        assert_eq!(connect.first(), Some(&0x10));
 
-        let cert = CertificateFile::new("/foo");
-        assert_eq!(cert.path(), Path::new("/foo/certificate.pem"));
+        mock.assert_async().await;
     }
 }
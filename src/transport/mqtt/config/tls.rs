@@ -18,20 +18,34 @@
 
 //! MQTT TLS configuration
 
-use std::{io, sync::Arc};
+use std::{
+    io,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use rustls::{
-    client::WantsClientCert,
+    client::{
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        WantsClientCert, WebPkiServerVerifier,
+    },
     crypto::CryptoProvider,
-    pki_types::{CertificateDer, PrivatePkcs8KeyDer},
-    ClientConfig, ConfigBuilder, RootCertStore,
+    pki_types::{CertificateDer, PrivatePkcs8KeyDer, ServerName, UnixTime},
+    CertificateError, ClientConfig, ConfigBuilder, DigitallySignedStruct, RootCertStore,
+    SignatureScheme,
 };
 use tracing::{debug, error, info, instrument, warn};
 use x509_parser::prelude::X509Certificate;
 
-use crate::{error::Report, transport::mqtt::PairingError};
+use crate::{
+    error::Report,
+    transport::mqtt::{
+        credentials::{CredentialKind, DynCredentialStore},
+        PairingError, SpkiPin,
+    },
+};
 
-use super::{CertificateFile, ClientId, PrivateKeyFile};
+use super::ClientId;
 
 pub(crate) fn is_env_ignore_ssl() -> bool {
     matches!(
@@ -47,37 +61,40 @@ pub(crate) struct ClientAuth {
 }
 
 impl ClientAuth {
-    pub(crate) async fn try_read(
-        certificates: CertificateFile,
-        key: PrivateKeyFile,
+    pub(crate) async fn try_load(
+        store: &dyn DynCredentialStore,
         client_id: ClientId<&str>,
     ) -> Option<Self> {
-        let res = Self::read_cert_and_key(certificates, key, client_id).await;
+        let res = Self::load_cert_and_key(store, client_id).await;
 
         match res {
             Ok(auth) => auth,
-            Err(err) if err.kind() == io::ErrorKind::NotFound => {
-                debug!("credential files are missing");
-
-                None
-            }
             Err(err) => {
-                error!("couldn't read certificates {err}");
+                error!(error = %Report::new(err), "couldn't read certificates");
 
                 None
             }
         }
     }
 
-    /// Function to read the certificate and the key
-    async fn read_cert_and_key(
-        certificates: CertificateFile,
-        key: PrivateKeyFile,
+    /// Function to load the certificate and the key
+    async fn load_cert_and_key(
+        store: &dyn DynCredentialStore,
         client_id: ClientId<&str>,
-    ) -> Result<Option<Self>, io::Error> {
-        let pem = tokio::fs::read_to_string(certificates.path()).await?;
+    ) -> Result<Option<Self>, PairingError> {
+        let Some(pem) = store.load_dyn(CredentialKind::Certificate).await? else {
+            debug!("certificate is missing");
 
-        let k_r = tokio::fs::read(key).await?;
+            return Ok(None);
+        };
+        let pem = String::from_utf8(pem).map_err(|err| {
+            PairingError::InvalidCredentials(io::Error::new(io::ErrorKind::InvalidData, err))
+        })?;
+
+        let k_r = store
+            .load_dyn(CredentialKind::PrivateKey)
+            .await?
+            .unwrap_or_default();
         if k_r.is_empty() {
             debug!("no private key found");
             return Ok(None);
@@ -85,6 +102,7 @@ impl ClientAuth {
         let private_key = PrivatePkcs8KeyDer::from(k_r);
 
         Self::try_from_pem_cert(pem, private_key, client_id)
+            .map_err(PairingError::InvalidCredentials)
     }
 
     pub(crate) fn try_from_pem_cert(
@@ -148,8 +166,9 @@ impl ClientAuth {
     pub(crate) fn tls_config(
         self,
         roots: Arc<RootCertStore>,
+        pins: &Arc<[SpkiPin]>,
     ) -> Result<rustls::ClientConfig, PairingError> {
-        tls_config_builder(roots)?
+        tls_config_builder(roots, pins)?
             .with_client_auth_cert(vec![self.der], self.private_key.into())
             .map_err(PairingError::Tls)
     }
@@ -165,6 +184,31 @@ impl ClientAuth {
     pub(crate) fn pem(&self) -> &str {
         &self.pem
     }
+
+    /// Returns the time to renew the certificate, after the fraction of its validity period.
+    pub(crate) fn renew_at(&self, fraction: f64) -> Option<SystemTime> {
+        let cert = match x509_parser::parse_x509_certificate(&self.der) {
+            Ok((_remaining, cert)) => cert,
+            Err(err) => {
+                warn!(error=%Report::new(err), "couldn't parse the certificate validity");
+
+                return None;
+            }
+        };
+
+        let validity = cert.validity();
+        let not_before = u64::try_from(validity.not_before.timestamp()).ok()?;
+        let not_after = u64::try_from(validity.not_after.timestamp()).ok()?;
+
+        let lifetime = not_after.checked_sub(not_before)?;
+        // Precision loss is fine, since it's only a delay
+        #[allow(clippy::cast_precision_loss)]
+        let elapsed = Duration::try_from_secs_f64(lifetime as f64 * fraction).ok()?;
+
+        SystemTime::UNIX_EPOCH
+            .checked_add(Duration::from_secs(not_before))?
+            .checked_add(elapsed)
+    }
 }
 
 #[cfg(feature = "webpki")]
@@ -202,19 +246,54 @@ pub(crate) async fn read_root_cert_store() -> Result<RootCertStore, PairingError
     .map_err(PairingError::ReadNativeCerts)
 }
 
+/// Adds the certificates in the PEM bundle to the trusted roots.
+///
+/// Returns the number of certificates added.
+pub(crate) fn add_ca_certificates(
+    roots: &mut RootCertStore,
+    pem: &str,
+) -> Result<usize, PairingError> {
+    let certs = rustls_pemfile::certs(&mut pem.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(PairingError::CaCertificate)?;
+
+    if certs.is_empty() {
+        return Err(PairingError::Config(
+            "no certificate in the CA bundle".to_string(),
+        ));
+    }
+
+    let count = certs.len();
+    for cert in certs {
+        roots.add(cert).map_err(PairingError::Tls)?;
+    }
+
+    debug!(count, "added CA certificates");
+
+    Ok(count)
+}
+
 pub(crate) fn tls_config_builder(
     roots: Arc<RootCertStore>,
+    pins: &Arc<[SpkiPin]>,
 ) -> Result<ConfigBuilder<ClientConfig, WantsClientCert>, PairingError> {
     let provider = CryptoProvider::get_default()
         .cloned()
         .unwrap_or_else(|| Arc::new(rustls::crypto::aws_lc_rs::default_provider()));
 
-    let builder = rustls::ClientConfig::builder_with_provider(provider)
+    let builder = rustls::ClientConfig::builder_with_provider(Arc::clone(&provider))
         .with_safe_default_protocol_versions()
-        .map_err(PairingError::Tls)?
-        .with_root_certificates(roots);
+        .map_err(PairingError::Tls)?;
 
-    Ok(builder)
+    if pins.is_empty() {
+        return Ok(builder.with_root_certificates(roots));
+    }
+
+    let verifier = PinnedVerifier::new(roots, provider, Arc::clone(pins))?;
+
+    Ok(builder
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier)))
 }
 
 pub(crate) fn insecure_tls_config_builder(
@@ -232,6 +311,85 @@ pub(crate) fn insecure_tls_config_builder(
     Ok(builder)
 }
 
+/// Verifies the server certificate with the roots, then checks that one of the certificates in the
+/// chain has a pinned public key.
+#[derive(Debug)]
+struct PinnedVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    roots: Arc<RootCertStore>,
+    pins: Arc<[SpkiPin]>,
+}
+
+impl PinnedVerifier {
+    fn new(
+        roots: Arc<RootCertStore>,
+        provider: Arc<CryptoProvider>,
+        pins: Arc<[SpkiPin]>,
+    ) -> Result<Self, PairingError> {
+        let inner = WebPkiServerVerifier::builder_with_provider(Arc::clone(&roots), provider)
+            .build()
+            .map_err(|err| {
+                PairingError::Config(format!("couldn't build the certificate verifier, {err}"))
+            })?;
+
+        Ok(Self { inner, roots, pins })
+    }
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+
+        if !SpkiPin::matches_chain(&self.pins, end_entity, intermediates, &self.roots.roots) {
+            error!(
+                ?server_name,
+                "no pinned public key in the server certificate chain"
+            );
+
+            return Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ));
+        }
+
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
 #[derive(Debug)]
 struct NoVerifier;
 
@@ -288,7 +446,11 @@ impl rustls::client::danger::ServerCertVerifier for NoVerifier {
 pub(crate) mod tests {
     use tempfile::TempDir;
 
-    use crate::transport::mqtt::{crypto::Bundle, pairing::tests::self_sign_csr_to_pem};
+    use crate::transport::mqtt::{
+        credentials::{CredentialStore, FileCredentialStore},
+        crypto::{Bundle, KeyAlgorithm},
+        pairing::tests::self_sign_csr_to_pem,
+    };
 
     use super::*;
 
@@ -303,16 +465,17 @@ pub(crate) mod tests {
     async fn should_read_keys() {
         let dir = TempDir::new().unwrap();
 
-        let cert = CertificateFile::new(dir.path());
-        tokio::fs::write(&cert, TEST_CERTIFICATE).await.unwrap();
-
-        let key = PrivateKeyFile::new(dir.path());
-        tokio::fs::write(&key, TEST_PRIVATE_KEY).await.unwrap();
+        let store = FileCredentialStore::new(dir.path());
 
-        let client = ClientAuth::try_read(cert.clone(), key.clone(), TEST_CLIENT_ID)
+        tokio::fs::write(store.path(CredentialKind::Certificate), TEST_CERTIFICATE)
+            .await
+            .unwrap();
+        tokio::fs::write(store.path(CredentialKind::PrivateKey), TEST_PRIVATE_KEY)
             .await
             .unwrap();
 
+        let client = ClientAuth::try_load(&store, TEST_CLIENT_ID).await.unwrap();
+
         let cert_der = &client.der;
 
         let rustls_pemfile::Item::X509Certificate(exp) =
@@ -329,72 +492,209 @@ pub(crate) mod tests {
         assert_eq!(client.private_key.secret_pkcs8_der(), TEST_PRIVATE_KEY);
 
         let root_cert_store = Arc::new(rustls::RootCertStore::empty());
-        client.tls_config(root_cert_store).unwrap();
+        client.tls_config(root_cert_store, &Arc::from([])).unwrap();
 
         // Reuse the file setup
-        let client = ClientAuth::try_read(cert, key, TEST_CLIENT_ID)
-            .await
-            .unwrap();
+        let client = ClientAuth::try_load(&store, TEST_CLIENT_ID).await.unwrap();
 
         client.insecure_tls_config().unwrap();
     }
 
+    #[test]
+    fn should_compute_renewal() {
+        let private_key = PrivatePkcs8KeyDer::from(TEST_PRIVATE_KEY.to_vec());
+        let auth = ClientAuth::try_from_pem_cert(
+            TEST_CERTIFICATE.to_string(),
+            private_key,
+            TEST_CLIENT_ID,
+        )
+        .unwrap()
+        .unwrap();
+
+        // Valid from 2024-04-04T11:33:59Z for 60 seconds
+        let not_before = SystemTime::UNIX_EPOCH + Duration::from_secs(1712230439);
+
+        assert_eq!(auth.renew_at(0.0), Some(not_before));
+        assert_eq!(
+            auth.renew_at(0.5),
+            Some(not_before + Duration::from_secs(30))
+        );
+        assert_eq!(
+            auth.renew_at(1.0),
+            Some(not_before + Duration::from_secs(60))
+        );
+    }
+
     #[tokio::test]
     async fn test_valid_certificate() {
         let client_id = ClientId {
             realm: "realm",
             device_id: "device_id",
         };
-        let bundle = Bundle::generate_key(client_id.realm, client_id.device_id).unwrap();
+        let bundle = Bundle::generate_key(
+            client_id.realm,
+            client_id.device_id,
+            KeyAlgorithm::default(),
+        )
+        .unwrap();
 
         let dir = tempfile::tempdir().unwrap();
-        let certificate_file = CertificateFile::new(&dir);
-        let private_key_file = PrivateKeyFile::new(&dir);
+        let store = FileCredentialStore::new(dir.path());
 
         let cert = self_sign_csr_to_pem(&bundle.csr);
 
-        tokio::fs::write(certificate_file.path(), cert)
+        store
+            .store(CredentialKind::Certificate, cert.as_bytes())
             .await
             .unwrap();
-        tokio::fs::write(
-            private_key_file.path(),
-            bundle.private_key.secret_pkcs8_der(),
-        )
-        .await
-        .unwrap();
-
-        let cert = ClientAuth::try_read(certificate_file, private_key_file, client_id)
+        store
+            .store(
+                CredentialKind::PrivateKey,
+                bundle.private_key.secret_pkcs8_der(),
+            )
             .await
             .unwrap();
 
+        let cert = ClientAuth::try_load(&store, client_id).await.unwrap();
+
         assert!(cert.verify_certificate_data(client_id))
     }
 
+    #[test]
+    fn should_configure_tls_with_key_algorithm() {
+        let algorithms = [
+            KeyAlgorithm::EcdsaP256,
+            KeyAlgorithm::EcdsaP384,
+            KeyAlgorithm::Ed25519,
+            KeyAlgorithm::Rsa2048,
+            KeyAlgorithm::Rsa4096,
+        ];
+
+        for algorithm in algorithms {
+            let bundle =
+                Bundle::generate_key(TEST_CLIENT_ID.realm, TEST_CLIENT_ID.device_id, algorithm)
+                    .unwrap();
+
+            let cert = self_sign_csr_to_pem(&bundle.csr);
+
+            let client = ClientAuth::try_from_pem_cert(cert, bundle.private_key, TEST_CLIENT_ID)
+                .unwrap()
+                .unwrap();
+
+            let root_cert_store = Arc::new(rustls::RootCertStore::empty());
+            client
+                .tls_config(root_cert_store, &Arc::from([]))
+                .unwrap_or_else(|err| panic!("{algorithm:?}: {err}"));
+        }
+    }
+
+    fn ca_and_server_certificate() -> (rcgen::Certificate, rcgen::Certificate) {
+        let ca_key = rcgen::KeyPair::generate().unwrap();
+        let mut ca_params = rcgen::CertificateParams::new([]).unwrap();
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let server_key = rcgen::KeyPair::generate().unwrap();
+        let server = rcgen::CertificateParams::new(["astarte.localhost".to_string()])
+            .unwrap()
+            .signed_by(&server_key, &ca, &ca_key)
+            .unwrap();
+
+        (ca, server)
+    }
+
+    #[test]
+    fn should_add_ca_certificates() {
+        let (ca, _) = ca_and_server_certificate();
+
+        let mut roots = RootCertStore::empty();
+        let added = add_ca_certificates(&mut roots, &ca.pem()).unwrap();
+        assert_eq!(added, 1);
+        assert_eq!(roots.len(), 1);
+
+        let err = add_ca_certificates(&mut roots, "").unwrap_err();
+        assert!(matches!(err, PairingError::Config(_)));
+
+        let invalid = "-----BEGIN CERTIFICATE-----\nnot base64!\n-----END CERTIFICATE-----\n";
+        let err = add_ca_certificates(&mut roots, invalid).unwrap_err();
+        assert!(matches!(err, PairingError::CaCertificate(_)));
+    }
+
+    #[test]
+    fn should_verify_pinned_certificate() {
+        let (ca, server) = ca_and_server_certificate();
+        let (other_ca, _) = ca_and_server_certificate();
+
+        let mut roots = RootCertStore::empty();
+        add_ca_certificates(&mut roots, &ca.pem()).unwrap();
+        let roots = Arc::new(roots);
+        let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+
+        let name = ServerName::try_from("astarte.localhost").unwrap();
+        let verify = |pins: Vec<SpkiPin>| {
+            PinnedVerifier::new(Arc::clone(&roots), Arc::clone(&provider), pins.into())
+                .unwrap()
+                .verify_server_cert(server.der(), &[], &name, &[], UnixTime::now())
+        };
+
+        let ca_pin = SpkiPin::from_certificate(ca.der()).unwrap();
+        let server_pin = SpkiPin::from_certificate(server.der()).unwrap();
+        let other_pin = SpkiPin::from_certificate(other_ca.der()).unwrap();
+
+        // The end entity or the CA can be pinned
+        verify(vec![server_pin]).unwrap();
+        verify(vec![other_pin, ca_pin]).unwrap();
+
+        let err = verify(vec![other_pin]).unwrap_err();
+        assert_eq!(
+            err,
+            rustls::Error::InvalidCertificate(CertificateError::ApplicationVerificationFailure)
+        );
+
+        // The chain must still be valid
+        let roots = Arc::new(RootCertStore::empty());
+        let res = PinnedVerifier::new(roots, provider, Arc::from([server_pin]));
+        assert!(res.is_err());
+
+        // Without pins the roots are used directly
+        let mut roots = RootCertStore::empty();
+        add_ca_certificates(&mut roots, &ca.pem()).unwrap();
+        tls_config_builder(Arc::new(roots), &Arc::from([]))
+            .unwrap()
+            .with_no_client_auth();
+    }
+
     #[tokio::test]
     async fn test_invalid_certificate_subject_cn() {
-        let bundle = Bundle::generate_key(TEST_CLIENT_ID.realm, TEST_CLIENT_ID.device_id).unwrap();
+        let bundle = Bundle::generate_key(
+            TEST_CLIENT_ID.realm,
+            TEST_CLIENT_ID.device_id,
+            KeyAlgorithm::default(),
+        )
+        .unwrap();
 
         let dir = tempfile::tempdir().unwrap();
-        let certificate_file = CertificateFile::new(&dir);
-        let private_key_file = PrivateKeyFile::new(&dir);
+        let store = FileCredentialStore::new(dir.path());
 
         let cert = self_sign_csr_to_pem(&bundle.csr);
 
-        tokio::fs::write(certificate_file.path(), cert)
+        store
+            .store(CredentialKind::Certificate, cert.as_bytes())
+            .await
+            .unwrap();
+        store
+            .store(
+                CredentialKind::PrivateKey,
+                bundle.private_key.secret_pkcs8_der(),
+            )
             .await
             .unwrap();
-        tokio::fs::write(
-            private_key_file.path(),
-            bundle.private_key.secret_pkcs8_der(),
-        )
-        .await
-        .unwrap();
 
         let diff_client_id = ClientId {
             realm: "realm",
             device_id: "different_device_id",
         };
-        let cert = ClientAuth::try_read(certificate_file, private_key_file, diff_client_id).await;
+        let cert = ClientAuth::try_load(&store, diff_client_id).await;
 
         assert!(cert.is_none())
     }
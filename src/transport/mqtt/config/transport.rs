@@ -17,36 +17,61 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use core::str;
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    fmt::Debug,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use rumqttc::Transport;
 use rustls::pki_types::PrivatePkcs8KeyDer;
 use rustls::RootCertStore;
-use tokio::fs;
 use tracing::{debug, error, info, instrument, warn};
 use url::Url;
 
+use super::tls::ClientAuth;
 use super::ClientId;
-use super::{tls::ClientAuth, CertificateFile, PrivateKeyFile};
-use crate::transport::mqtt::config::tls::read_root_cert_store;
+use crate::transport::mqtt::config::tls::{add_ca_certificates, read_root_cert_store};
 use crate::{
     error::Report,
     transport::mqtt::{
         config::tls::{insecure_tls_config_builder, tls_config_builder},
-        crypto::Bundle,
+        credentials::{CredentialKind, SharedCredentialStore},
+        crypto::{Bundle, KeyAlgorithm},
         pairing::ApiClient,
-        PairingError,
+        registration::Registration,
+        PairingError, Proxy, SpkiPin,
     },
 };
 
+/// Default fraction of the certificate validity after which it's renewed.
+pub(crate) const DEFAULT_CERTIFICATE_RENEWAL: f64 = 0.8;
+
+/// Minimum fraction of the certificate validity after which it's renewed.
+///
+/// A smaller fraction would renew the certificate right after it's created, reconnecting in a
+/// loop.
+pub(crate) const MIN_CERTIFICATE_RENEWAL: f64 = 0.1;
+
 /// Structure to create an authenticated [`Transport`]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct TransportProvider {
     pairing_url: Url,
     credential_secret: String,
-    store_dir: Option<PathBuf>,
+    store: Option<SharedCredentialStore>,
     insecure_ssl: bool,
     root_cert_store: Arc<RootCertStore>,
+    /// Public keys pinned for the server certificates.
+    spki_pins: Arc<[SpkiPin]>,
+    websocket: bool,
+    proxy: Option<Proxy>,
+    key_algorithm: KeyAlgorithm,
+    /// Registers the device again when the credentials are rejected.
+    registration: Option<Registration>,
+    /// Fraction of the certificate validity after which it's renewed.
+    renewal: f64,
+    /// When the certificate of the current transport should be renewed.
+    renew_at: Option<SystemTime>,
 }
 
 impl TransportProvider {
@@ -54,7 +79,7 @@ impl TransportProvider {
     pub(crate) async fn configure(
         pairing_url: Url,
         credential_secret: String,
-        store_dir: Option<PathBuf>,
+        store: Option<SharedCredentialStore>,
         insecure_ssl: bool,
     ) -> Result<Self, PairingError> {
         debug!("reading root cert store from native certs");
@@ -63,17 +88,85 @@ impl TransportProvider {
         Ok(Self {
             pairing_url,
             credential_secret,
-            store_dir,
+            store,
             insecure_ssl,
             root_cert_store: Arc::new(root_certs),
+            spki_pins: Arc::from([]),
+            websocket: false,
+            proxy: None,
+            key_algorithm: KeyAlgorithm::default(),
+            registration: None,
+            renewal: DEFAULT_CERTIFICATE_RENEWAL,
+            renew_at: None,
         })
     }
 
+    /// Trust the CA certificates in the PEM bundle, in addition to the system ones.
+    pub(crate) fn add_ca_certificates(&mut self, pem: &str) -> Result<(), PairingError> {
+        add_ca_certificates(Arc::make_mut(&mut self.root_cert_store), pem)?;
+
+        Ok(())
+    }
+
+    /// Only accept server certificates chains with one of the pinned public keys.
+    pub(crate) fn set_spki_pins(&mut self, pins: &[SpkiPin]) {
+        self.spki_pins = Arc::from(pins);
+    }
+
+    /// Connect to the broker over a WebSocket, with the same TLS configuration.
+    pub(crate) fn set_websocket(&mut self, websocket: bool) {
+        self.websocket = websocket;
+    }
+
+    /// Connect to the pairing API through the proxy.
+    pub(crate) fn set_proxy(&mut self, proxy: Option<Proxy>) {
+        self.proxy = proxy;
+    }
+
+    /// Generate the private key of new certificates with the algorithm.
+    pub(crate) fn set_key_algorithm(&mut self, algorithm: KeyAlgorithm) {
+        self.key_algorithm = algorithm;
+    }
+
+    /// Register the device again when Astarte rejects the credentials.
+    pub(crate) fn set_registration(&mut self, registration: Option<Registration>) {
+        self.registration = registration;
+    }
+
+    /// Sets the credential secret used to authenticate to the pairing API.
+    pub(crate) fn set_credential_secret(&mut self, secret: String) {
+        self.credential_secret = secret;
+    }
+
+    /// Renew the certificate after the fraction of its validity.
+    ///
+    /// The fraction is clamped between [`MIN_CERTIFICATE_RENEWAL`] and `1.0`.
+    pub(crate) fn set_renewal(&mut self, fraction: f64) {
+        if fraction.is_nan() {
+            warn!("invalid certificate renewal, using the default");
+
+            self.renewal = DEFAULT_CERTIFICATE_RENEWAL;
+
+            return;
+        }
+
+        let renewal = fraction.clamp(MIN_CERTIFICATE_RENEWAL, 1.0);
+
+        if renewal != fraction {
+            warn!(
+                fraction,
+                renewal, "certificate renewal out of range, clamped"
+            );
+        }
+
+        self.renewal = renewal;
+    }
+
     pub(crate) fn api_tls_config(&self) -> Result<rustls::ClientConfig, PairingError> {
         let client_cfg = if self.insecure_ssl {
             insecure_tls_config_builder()?.with_no_client_auth()
         } else {
-            tls_config_builder(self.root_cert_store())?.with_no_client_auth()
+            tls_config_builder(self.root_cert_store(), &self.spki_pins)?.with_no_client_auth()
         };
 
         debug!("TLS client config read");
@@ -86,7 +179,7 @@ impl TransportProvider {
         &self,
         client: &ApiClient<'_>,
     ) -> Result<(Bundle, String), PairingError> {
-        let bundle = Bundle::generate_key(client.realm, client.device_id)?;
+        let bundle = Bundle::generate_key(client.realm, client.device_id, self.key_algorithm)?;
 
         let certificate = client.create_certificate(&bundle.csr).await?;
 
@@ -95,37 +188,39 @@ impl TransportProvider {
         Ok((bundle, certificate))
     }
 
-    /// Store the credentials to files.
+    /// Store the credentials in the credential store.
     async fn store_credentials(
         &self,
-        private_key_file: &PrivateKeyFile,
+        store: &SharedCredentialStore,
         private_key: &PrivatePkcs8KeyDer<'_>,
-        certificate_file: &CertificateFile,
         certificate: &str,
     ) {
         // Don't fail here since the SDK can always regenerate the certificate,
-        if let Err(err) = fs::write(&certificate_file, &certificate).await {
-            error!(error = %Report::new(&err), file = %certificate_file, "couldn't write certificate file");
+        if let Err(err) = store
+            .store_dyn(CredentialKind::Certificate, certificate.as_bytes())
+            .await
+        {
+            error!(error = %Report::new(&err), "couldn't store the certificate");
         }
-        if let Err(err) = fs::write(&private_key_file, &private_key.secret_pkcs8_der()).await {
-            error!(error = %Report::new(err), file = %private_key_file, "couldn't write private key file");
+        if let Err(err) = store
+            .store_dyn(CredentialKind::PrivateKey, private_key.secret_pkcs8_der())
+            .await
+        {
+            error!(error = %Report::new(err), "couldn't store the private key");
         }
     }
 
-    /// Read credentials from the filesystem.
+    /// Read credentials from the credential store.
     async fn read_credentials(&self, client_id: ClientId<&str>) -> Option<ClientAuth> {
-        let Some(store_dir) = &self.store_dir else {
-            debug!("no store directory");
+        let Some(store) = &self.store else {
+            debug!("no credential store");
 
             return None;
         };
 
-        debug!("reading existing credentials from {}", store_dir.display());
+        debug!(?store, "reading existing credentials");
 
-        let certificate_file = CertificateFile::new(store_dir);
-        let private_key_file = PrivateKeyFile::new(store_dir);
-
-        ClientAuth::try_read(certificate_file, private_key_file, client_id).await
+        ClientAuth::try_load(store.as_ref(), client_id).await
     }
 
     fn root_cert_store(&self) -> Arc<RootCertStore> {
@@ -133,39 +228,55 @@ impl TransportProvider {
     }
 
     /// Config the TLS for the transport.
-    fn config_transport(&self, client_auth: ClientAuth) -> Result<Transport, PairingError> {
+    fn config_transport(&mut self, client_auth: ClientAuth) -> Result<Transport, PairingError> {
+        self.renew_at = client_auth.renew_at(self.renewal);
+
+        debug!(renew_at = ?self.renew_at, "certificate renewal scheduled");
+
         let config = if self.insecure_ssl {
             client_auth.insecure_tls_config()?
         } else {
             let roots = self.root_cert_store();
-            client_auth.tls_config(roots)?
+            client_auth.tls_config(roots, &self.spki_pins)?
         };
 
-        Ok(Transport::tls_with_config(
-            rumqttc::TlsConfiguration::Rustls(Arc::new(config)),
+        let tls_config = rumqttc::TlsConfiguration::Rustls(Arc::new(config));
+
+        if self.websocket {
+            return Self::websocket_transport(tls_config);
+        }
+
+        Ok(Transport::tls_with_config(tls_config))
+    }
+
+    #[cfg(feature = "websocket")]
+    fn websocket_transport(
+        tls_config: rumqttc::TlsConfiguration,
+    ) -> Result<Transport, PairingError> {
+        Ok(Transport::wss_with_config(tls_config))
+    }
+
+    #[cfg(not(feature = "websocket"))]
+    fn websocket_transport(
+        _tls_config: rumqttc::TlsConfiguration,
+    ) -> Result<Transport, PairingError> {
+        Err(PairingError::Config(
+            "the websocket feature is required to connect to a wss:// broker".to_string(),
         ))
     }
 
-    /// Creates a new credential and if a store directory is set, it stores it
+    /// Creates a new credential and if a credential store is set, it stores it
     async fn create_credentials(&self, client: &ApiClient<'_>) -> Result<ClientAuth, PairingError> {
         debug!("creating new transport credentials");
 
         let (bundle, certificate) = self.create_certificate(client).await?;
 
-        // If no store dir is set we just create a new certificate
-        if let Some(store_dir) = &self.store_dir {
+        // If no store is set we just create a new certificate
+        if let Some(store) = &self.store {
             debug!("storing credentials");
 
-            let certificate_file = CertificateFile::new(store_dir);
-            let private_key_file = PrivateKeyFile::new(store_dir);
-
-            self.store_credentials(
-                &private_key_file,
-                &bundle.private_key,
-                &certificate_file,
-                &certificate,
-            )
-            .await
+            self.store_credentials(store, &bundle.private_key, &certificate)
+                .await
         }
 
         match ClientAuth::try_from_pem_cert(
@@ -209,7 +320,7 @@ impl TransportProvider {
 
     /// Create a new transport with the given credentials
     pub(crate) async fn transport(
-        &self,
+        &mut self,
         client: &ApiClient<'_>,
     ) -> Result<Transport, PairingError> {
         let client_auth = self.retrieve_credentials(client).await?;
@@ -217,10 +328,56 @@ impl TransportProvider {
         self.config_transport(client_auth)
     }
 
+    /// Create a new transport with a new certificate, without using the stored one.
+    pub(crate) async fn recreate_transport(
+        &mut self,
+        client: &ApiClient<'_>,
+    ) -> Result<Transport, PairingError> {
+        let client_auth = self.create_credentials(client).await?;
+
+        self.config_transport(client_auth)
+    }
+
+    /// Registers the device again to get a new credential secret.
+    ///
+    /// The new secret is stored, while the certificate and private key issued with the old one are
+    /// removed. Returns `false` if no registration is configured.
+    pub(crate) async fn register_again(
+        &mut self,
+        client_id: ClientId<&str>,
+        timeout: Duration,
+    ) -> Result<bool, PairingError> {
+        let Some(registration) = &self.registration else {
+            debug!("no registration configured");
+
+            return Ok(false);
+        };
+
+        info!("registering the device again");
+
+        let secret = registration.register(self, client_id, timeout).await?;
+
+        if let Some(store) = &self.store {
+            store
+                .store_dyn(CredentialKind::Secret, secret.as_bytes())
+                .await?;
+
+            for kind in [CredentialKind::Certificate, CredentialKind::PrivateKey] {
+                if let Err(err) = store.remove_dyn(kind).await {
+                    warn!(error = %Report::new(err), "couldn't remove the stale {kind}");
+                }
+            }
+        }
+
+        self.credential_secret = secret;
+
+        Ok(true)
+    }
+
     // validate the existing certificate is valid, if valid use it for the transport
     // if it's invalid recreate the certificate
     pub(crate) async fn validate_transport(
-        &self,
+        &mut self,
         client: &ApiClient<'_>,
     ) -> Result<Transport, PairingError> {
         let client_auth = if let Some(client_auth) = self.verify_certificate(client).await? {
@@ -239,20 +396,13 @@ impl TransportProvider {
         &self,
         client: &ApiClient<'_>,
     ) -> Result<Option<ClientAuth>, PairingError> {
-        let Some(store_dir) = &self.store_dir else {
-            warn!(
-                store_dir = ?self.store_dir,
-                "no device store directory, assuming invalid"
-            );
+        let Some(store) = &self.store else {
+            warn!("no device credential store, assuming invalid");
             return Ok(None);
         };
 
-        let certificate_file = CertificateFile::new(store_dir);
-        let key_file = PrivateKeyFile::new(store_dir);
-
-        let Some(client_auth) = ClientAuth::try_read(
-            certificate_file,
-            key_file,
+        let Some(client_auth) = ClientAuth::try_load(
+            store.as_ref(),
             ClientId {
                 realm: client.realm,
                 device_id: client.device_id,
@@ -261,8 +411,8 @@ impl TransportProvider {
         .await
         else {
             warn!(
-                ?store_dir,
-                "no device certificate file in store dir, assuming invalid"
+                ?store,
+                "no device certificate in the store, assuming invalid"
             );
             return Ok(None);
         };
@@ -280,6 +430,56 @@ impl TransportProvider {
     pub(crate) fn credential_secret(&self) -> &str {
         &self.credential_secret
     }
+
+    pub(crate) fn proxy(&self) -> Option<&Proxy> {
+        self.proxy.as_ref()
+    }
+
+    /// Returns when the certificate of the current transport should be renewed.
+    pub(crate) fn renew_at(&self) -> Option<SystemTime> {
+        self.renew_at
+    }
+
+    #[cfg(test)]
+    pub(crate) fn set_renew_at(&mut self, renew_at: SystemTime) {
+        self.renew_at = Some(renew_at);
+    }
+
+    /// Creates a new certificate and a transport using it.
+    ///
+    /// The provider is taken by value, so the renewal can run in a separate task.
+    pub(crate) async fn renew(
+        mut self,
+        client_id: ClientId,
+        timeout: Duration,
+    ) -> Result<RenewedTransport, PairingError> {
+        info!("renewing the device certificate");
+
+        let api =
+            ApiClient::from_transport(&self, &client_id.realm, &client_id.device_id, timeout)?;
+
+        let client_auth = self.create_credentials(&api).await?;
+        let transport = self.config_transport(client_auth)?;
+
+        Ok(RenewedTransport {
+            provider: self,
+            transport,
+        })
+    }
+}
+
+/// Transport with a renewed certificate.
+pub(crate) struct RenewedTransport {
+    pub(crate) provider: TransportProvider,
+    pub(crate) transport: Transport,
+}
+
+impl Debug for RenewedTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenewedTransport")
+            .field("provider", &self.provider)
+            .finish_non_exhaustive()
+    }
 }
 
 #[cfg(test)]
@@ -291,10 +491,40 @@ mod tests {
     use rumqttc::TlsConfiguration;
     use tempfile::TempDir;
 
-    use crate::transport::mqtt::pairing::tests::mock_create_certificate;
+    use crate::transport::mqtt::{
+        credentials::FileCredentialStore, pairing::tests::mock_create_certificate,
+    };
 
     use super::*;
 
+    #[tokio::test]
+    async fn should_clamp_renewal() {
+        let mut provider = TransportProvider::configure(
+            "http://localhost".parse().unwrap(),
+            "secret".to_string(),
+            None,
+            false,
+        )
+        .await
+        .unwrap();
+
+        provider.set_renewal(0.5);
+        assert_eq!(provider.renewal, 0.5);
+
+        // Would renew the certificate as soon as it's created
+        provider.set_renewal(0.0);
+        assert_eq!(provider.renewal, MIN_CERTIFICATE_RENEWAL);
+
+        provider.set_renewal(-1.0);
+        assert_eq!(provider.renewal, MIN_CERTIFICATE_RENEWAL);
+
+        provider.set_renewal(2.0);
+        assert_eq!(provider.renewal, 1.0);
+
+        provider.set_renewal(f64::NAN);
+        assert_eq!(provider.renewal, DEFAULT_CERTIFICATE_RENEWAL);
+    }
+
     #[tokio::test]
     async fn should_create_transport_insecure() {
         let dir = TempDir::new().unwrap();
@@ -307,10 +537,10 @@ mod tests {
             .await;
 
         // With store
-        let provider = TransportProvider::configure(
+        let mut provider = TransportProvider::configure(
             server.url().parse().unwrap(),
             "secret".to_string(),
-            Some(dir.path().to_owned()),
+            Some(Arc::new(FileCredentialStore::new(dir.path()))),
             true,
         )
         .await
@@ -327,19 +557,22 @@ mod tests {
             Transport::Tls(TlsConfiguration::Rustls(..))
         ));
 
-        let certificate_file = CertificateFile::new(dir.path());
-        let private_key_file = PrivateKeyFile::new(dir.path());
+        let store = FileCredentialStore::new(dir.path());
 
-        let cert = tokio::fs::read_to_string(&certificate_file).await.unwrap();
+        let cert = tokio::fs::read_to_string(store.path(CredentialKind::Certificate))
+            .await
+            .unwrap();
         rustls_pemfile::certs(&mut cert.as_bytes())
             .next()
             .unwrap()
             .unwrap();
-        let key = tokio::fs::read(&private_key_file).await.unwrap();
+        let key = tokio::fs::read(store.path(CredentialKind::PrivateKey))
+            .await
+            .unwrap();
         assert!(!key.is_empty());
 
         // Without store
-        let provider = TransportProvider::configure(
+        let mut provider = TransportProvider::configure(
             server.url().parse().unwrap(),
             "secret".to_string(),
             None,
@@ -369,10 +602,10 @@ mod tests {
             .await;
 
         // With store
-        let provider = TransportProvider::configure(
+        let mut provider = TransportProvider::configure(
             server.url().parse().unwrap(),
             "secret".to_string(),
-            Some(dir.path().to_owned()),
+            Some(Arc::new(FileCredentialStore::new(dir.path()))),
             false,
         )
         .await
@@ -384,19 +617,22 @@ mod tests {
 
         let _ = provider.transport(&api).await.unwrap();
 
-        let certificate_file = CertificateFile::new(dir.path());
-        let private_key_file = PrivateKeyFile::new(dir.path());
+        let store = FileCredentialStore::new(dir.path());
 
-        let cert = tokio::fs::read_to_string(&certificate_file).await.unwrap();
+        let cert = tokio::fs::read_to_string(store.path(CredentialKind::Certificate))
+            .await
+            .unwrap();
         rustls_pemfile::certs(&mut cert.as_bytes())
             .next()
             .unwrap()
             .unwrap();
-        let key = tokio::fs::read(&private_key_file).await.unwrap();
+        let key = tokio::fs::read(store.path(CredentialKind::PrivateKey))
+            .await
+            .unwrap();
         assert!(!key.is_empty());
 
         // Without store
-        let provider = TransportProvider::configure(
+        let mut provider = TransportProvider::configure(
             server.url().parse().unwrap(),
             "secret".to_string(),
             None,
@@ -414,6 +650,75 @@ mod tests {
         mock.assert_async().await;
     }
 
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn should_create_websocket_transport() {
+        let dir = TempDir::new().unwrap();
+
+        let mut server = Server::new_async().await;
+
+        let mock = mock_create_certificate(&mut server).create_async().await;
+
+        let mut provider = TransportProvider::configure(
+            server.url().parse().unwrap(),
+            "secret".to_string(),
+            Some(Arc::new(FileCredentialStore::new(dir.path()))),
+            false,
+        )
+        .await
+        .expect("failed to configure transport provider");
+        provider.set_websocket(true);
+
+        let api =
+            ApiClient::from_transport(&provider, "realm", "device_id", Duration::from_secs(10))
+                .expect("failed to create api client");
+
+        let transport = provider.transport(&api).await.unwrap();
+
+        assert!(matches!(
+            transport,
+            Transport::Wss(TlsConfiguration::Rustls(..))
+        ));
+
+        // The stored certificate is reused when recreating the transport
+        let transport = provider.transport(&api).await.unwrap();
+
+        assert!(matches!(
+            transport,
+            Transport::Wss(TlsConfiguration::Rustls(..))
+        ));
+
+        mock.assert_async().await;
+    }
+
+    #[cfg(not(feature = "websocket"))]
+    #[tokio::test]
+    async fn should_require_websocket_feature() {
+        let mut server = Server::new_async().await;
+
+        let mock = mock_create_certificate(&mut server).create_async().await;
+
+        let mut provider = TransportProvider::configure(
+            server.url().parse().unwrap(),
+            "secret".to_string(),
+            None,
+            false,
+        )
+        .await
+        .expect("failed to configure transport provider");
+        provider.set_websocket(true);
+
+        let api =
+            ApiClient::from_transport(&provider, "realm", "device_id", Duration::from_secs(10))
+                .expect("failed to create api client");
+
+        let res = provider.transport(&api).await;
+
+        assert!(matches!(res, Err(PairingError::Config(_))));
+
+        mock.assert_async().await;
+    }
+
     #[tokio::test]
     async fn should_recreate_transport() {
         let dir = TempDir::new().unwrap();
@@ -426,10 +731,10 @@ mod tests {
             .await;
 
         // With store
-        let provider = TransportProvider::configure(
+        let mut provider = TransportProvider::configure(
             server.url().parse().unwrap(),
             "secret".to_string(),
-            Some(dir.path().to_owned()),
+            Some(Arc::new(FileCredentialStore::new(dir.path()))),
             false,
         )
         .await
@@ -441,19 +746,22 @@ mod tests {
 
         let _ = provider.validate_transport(&api).await.unwrap();
 
-        let certificate_file = CertificateFile::new(dir.path());
-        let private_key_file = PrivateKeyFile::new(dir.path());
+        let store = FileCredentialStore::new(dir.path());
 
-        let cert = tokio::fs::read_to_string(&certificate_file).await.unwrap();
+        let cert = tokio::fs::read_to_string(store.path(CredentialKind::Certificate))
+            .await
+            .unwrap();
         rustls_pemfile::certs(&mut cert.as_bytes())
             .next()
             .unwrap()
             .unwrap();
-        let key = tokio::fs::read(&private_key_file).await.unwrap();
+        let key = tokio::fs::read(store.path(CredentialKind::PrivateKey))
+            .await
+            .unwrap();
         assert!(!key.is_empty());
 
         // Without store
-        let provider = TransportProvider::configure(
+        let mut provider = TransportProvider::configure(
             server.url().parse().unwrap(),
             "secret".to_string(),
             None,
@@ -482,10 +790,12 @@ mod tests {
             .create_async()
             .await;
 
-        let provider = TransportProvider::configure(
+        let mut provider = TransportProvider::configure(
             server.url().parse().unwrap(),
             "secret".to_string(),
-            Some(dir.path().join("non existing")),
+            Some(Arc::new(FileCredentialStore::new(
+                dir.path().join("non existing"),
+            ))),
             false,
         )
         .await
@@ -51,8 +51,8 @@ use std::{
 };
 
 use rumqttc::{
-    mqttbytes, ClientError, ConnectionError, Event, Packet, Publish, QoS, StateError, TokenError,
-    Transport,
+    mqttbytes, ClientError, ConnectReturnCode, ConnectionError, Event, Packet, Publish, QoS,
+    StateError, TokenError, Transport,
 };
 use sync_wrapper::SyncWrapper;
 use tokio::task::{JoinError, JoinHandle};
@@ -62,7 +62,7 @@ use crate::{
     error::Report,
     interfaces::Interfaces,
     properties::{encode_set_properties, PropertiesError},
-    retry::ExponentialIter,
+    retry::Backoff,
     session::StoredSession,
     store::{wrapper::StoreWrapper, OptStoredProp, PropertyStore, StoreCapabilities},
     transport::mqtt::{
@@ -72,11 +72,17 @@ use crate::{
 
 use super::{
     client::{AsyncClient, EventLoop},
-    config::{transport::TransportProvider, PartialConfig},
+    config::{
+        transport::{RenewedTransport, TransportProvider},
+        PartialConfig,
+    },
     error::MqttError,
     ClientId, MqttConfig, PairingError, PayloadError, SessionData,
 };
 
+/// Delay before retrying a failed certificate renewal.
+const RENEWAL_RETRY: Duration = Duration::from_secs(60);
+
 /// Errors while initializing the MQTT connection.
 #[non_exhaustive]
 #[derive(thiserror::Error, Debug)]
@@ -110,6 +116,9 @@ pub enum PollError {
     /// Couldn't reconnect to Astarte
     #[error("couldn't reconnect to Astarte")]
     Pairing(#[from] PairingError),
+    /// Astarte rejected the credentials and the device couldn't be registered again
+    #[error("the device credentials were rejected by Astarte")]
+    Unauthorized(#[source] PairingError),
 }
 
 impl InitError {
@@ -155,11 +164,18 @@ impl NeedsTransport {
                 // NOTE we were never connected so we need to set the clean session to ensure no acks stored by the server will be returned
                 Connection::set_clean_session(&mut eventloop, true);
 
+                let client_id = ClientId {
+                    realm: self.mqtt_config.realm.clone(),
+                    device_id: self.mqtt_config.device_id.clone(),
+                };
+
                 let connection = Connection {
                     client: client.clone(),
                     eventloop: SyncWrapper::new(eventloop),
                     provider,
+                    renewal: CertRenewal::new(client_id, timeout),
                     session_synced: false,
+                    credentials_refused: false,
                 };
 
                 let _ = self.client_sender.set(client);
@@ -256,6 +272,7 @@ impl MqttConnection {
         client: AsyncClient,
         eventloop: EventLoop,
         provider: TransportProvider,
+        client_id: ClientId<&str>,
         state: impl Into<State>,
         timeout: Duration,
     ) -> Self {
@@ -263,7 +280,9 @@ impl MqttConnection {
             client,
             eventloop: SyncWrapper::new(eventloop),
             provider,
+            renewal: CertRenewal::new(client_id.into(), timeout),
             session_synced: false,
+            credentials_refused: false,
         };
 
         Self {
@@ -311,6 +330,7 @@ impl MqttConnection {
     ///
     /// This function assumes that the connection is valid, but will handle disconnection and
     /// reconnection automatically.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn wait_connack<S>(
         client: AsyncClient,
         mut eventloop: EventLoop,
@@ -319,6 +339,7 @@ impl MqttConnection {
         interfaces: &Interfaces,
         store: &StoreWrapper<S>,
         timeout: Duration,
+        mut backoff: Backoff,
     ) -> Result<Self, MqttError>
     where
         S: PropertyStore + StoreCapabilities,
@@ -330,13 +351,12 @@ impl MqttConnection {
         // persistently store inflight message
         Connection::set_clean_session(&mut eventloop, true);
 
-        let mut mqtt_connection = Self::new(client, eventloop, provider, Connecting, timeout);
+        let mut mqtt_connection =
+            Self::new(client, eventloop, provider, client_id, Connecting, timeout);
         if let Some(link) = mqtt_connection.link.get_mut() {
             link.connection.set_session_synced(session_sync);
         }
 
-        let mut exp_back = ExponentialIter::default();
-
         let start = SystemTime::now();
 
         while !mqtt_connection
@@ -352,11 +372,14 @@ impl MqttConnection {
                 break;
             }
 
-            let backoff = exp_back.next();
+            let Some(delay) = backoff.next() else {
+                info!("Reconnection attempts exhausted exiting connection loop without connection established");
+                break;
+            };
 
-            debug!("waiting {backoff} seconds before retrying");
+            debug!("waiting {delay:?} before retrying");
 
-            tokio::time::sleep(Duration::from_secs(backoff)).await;
+            tokio::time::sleep(delay).await;
         }
 
         Ok(mqtt_connection)
@@ -475,8 +498,11 @@ struct Connection {
     //       https://doc.rust-lang.org/std/sync/struct.Exclusive.html
     eventloop: SyncWrapper<EventLoop>,
     provider: TransportProvider,
+    renewal: CertRenewal,
     /// Whether the stored introspection matches the current one
     session_synced: bool,
+    /// The broker refused the credentials, so the certificate needs to be recreated
+    credentials_refused: bool,
 }
 
 impl Connection {
@@ -497,6 +523,84 @@ impl Connection {
     fn set_clean_session(_eventloop: &mut EventLoop, _clean: bool) {}
 }
 
+/// Renews the client certificate before it expires.
+#[derive(Debug)]
+struct CertRenewal {
+    client_id: ClientId,
+    timeout: Duration,
+    /// Set after a failed renewal, to not retry immediately.
+    retry_at: Option<SystemTime>,
+    task: Option<JoinHandle<Result<RenewedTransport, PairingError>>>,
+}
+
+impl CertRenewal {
+    fn new(client_id: ClientId, timeout: Duration) -> Self {
+        Self {
+            client_id,
+            timeout,
+            retry_at: None,
+            task: None,
+        }
+    }
+
+    /// Waits for the certificate of the provider to be renewed.
+    ///
+    /// The renewal runs in a separate task, so this is cancel safe and the task will be awaited on
+    /// the next call.
+    async fn renewed(&mut self, provider: &TransportProvider) -> RenewedTransport {
+        loop {
+            let task = match &mut self.task {
+                Some(task) => task,
+                None => {
+                    let Some(renew_at) = provider.renew_at() else {
+                        return std::future::pending().await;
+                    };
+
+                    let renew_at = self.retry_at.map_or(renew_at, |retry| retry.max(renew_at));
+                    let delay = renew_at
+                        .duration_since(SystemTime::now())
+                        .unwrap_or_default();
+
+                    trace!(?delay, "waiting to renew the certificate");
+
+                    tokio::time::sleep(delay).await;
+
+                    let renewal = provider.clone().renew(self.client_id.clone(), self.timeout);
+
+                    self.task.insert(tokio::spawn(renewal))
+                }
+            };
+
+            let res = task.await;
+            self.task = None;
+
+            match res {
+                Ok(Ok(renewed)) => {
+                    self.retry_at = None;
+
+                    return renewed;
+                }
+                Ok(Err(err)) => {
+                    error!(error = %Report::new(err), "couldn't renew the certificate");
+                }
+                Err(err) => {
+                    error!(error = %Report::new(err), "failed to join the certificate renewal task");
+                }
+            }
+
+            self.retry_at = SystemTime::now().checked_add(RENEWAL_RETRY);
+        }
+    }
+}
+
+impl Drop for CertRenewal {
+    fn drop(&mut self) {
+        if let Some(task) = &self.task {
+            task.abort();
+        }
+    }
+}
+
 /// This cannot be a type state machine, because any additional data cannot be moved out of the enum
 /// when polling. The only data that can be easily changed is the current state into the next one.
 #[derive(Debug)]
@@ -600,12 +704,15 @@ impl Disconnected {
     /// It recreates the credentials and reconnect to the broker, using the same
     /// session. If it fails, it returns an error so that the whole connection process can
     /// be retried.
+    ///
+    /// If Astarte rejects the credentials, the device is registered again. When that's not
+    /// possible a [`PollError::Unauthorized`] is returned, since retrying would never succeed.
     async fn reconnect(
         &mut self,
         conn: &mut Connection,
         client_id: ClientId<&str>,
         timeout: Duration,
-    ) -> Result<Next, PairingError> {
+    ) -> Result<Next, PollError> {
         let api = ApiClient::from_transport(
             &conn.provider,
             client_id.realm,
@@ -613,8 +720,21 @@ impl Disconnected {
             timeout,
         )?;
 
-        let transport = match conn.provider.validate_transport(&api).await {
+        let res = if conn.credentials_refused {
+            debug!("credentials refused by the broker, recreating the certificate");
+
+            conn.provider.recreate_transport(&api).await
+        } else {
+            conn.provider.validate_transport(&api).await
+        };
+
+        let transport = match res {
             Ok(transport) => transport,
+            Err(err) if err.is_unauthorized() => {
+                warn!(error = %Report::new(&err), "credentials rejected by Astarte");
+
+                return Self::register_again(conn, client_id, timeout, err).await;
+            }
             Err(err) => {
                 error!(error = %Report::new(err),"couldn't pair device");
 
@@ -624,11 +744,39 @@ impl Disconnected {
 
         debug!("created a new transport, reconnecting");
 
+        conn.credentials_refused = false;
+
         Self::set_transport(conn.eventloop_mut(), transport);
 
         Ok(Next::state(Connecting))
     }
 
+    /// Registers the device again after the credential secret was rejected.
+    async fn register_again(
+        conn: &mut Connection,
+        client_id: ClientId<&str>,
+        timeout: Duration,
+        rejected: PairingError,
+    ) -> Result<Next, PollError> {
+        match conn.provider.register_again(client_id, timeout).await {
+            Ok(true) => {
+                info!("device registered again, recreating the transport");
+
+                // The certificate was issued with the old secret
+                conn.credentials_refused = true;
+
+                Ok(Next::Same)
+            }
+            Ok(false) => Err(PollError::Unauthorized(rejected)),
+            Err(err) if err.is_unauthorized() => Err(PollError::Unauthorized(err)),
+            Err(err) => {
+                error!(error = %Report::new(err), "couldn't register the device again");
+
+                Ok(Next::Same)
+            }
+        }
+    }
+
     #[cfg(not(test))]
     fn set_transport(eventloop: &mut EventLoop, transport: Transport) {
         eventloop.mqtt_options.set_transport(transport);
@@ -652,7 +800,20 @@ impl Connecting {
     async fn wait_connack(&mut self, conn: &mut Connection) -> Next {
         let event = match conn.eventloop_mut().poll().await {
             Ok(event) => event,
-            Err(err) => return Next::handle_error(err),
+            Err(err) => {
+                if matches!(
+                    err,
+                    ConnectionError::ConnectionRefused(
+                        ConnectReturnCode::NotAuthorized | ConnectReturnCode::BadUserNamePassword
+                    )
+                ) {
+                    warn!("the broker refused the device credentials");
+
+                    conn.credentials_refused = true;
+                }
+
+                return Next::handle_error(err);
+            }
         };
 
         match event {
@@ -1057,9 +1218,25 @@ impl Connected {
     }
 
     async fn poll(&mut self, conn: &mut Connection) -> Next {
-        match conn.eventloop_mut().poll().await {
-            Ok(event) => Next::handle_event(event),
-            Err(err) => Next::handle_error(err),
+        tokio::select! {
+            res = conn.eventloop.get_mut().poll() => {
+                match res {
+                    Ok(event) => Next::handle_event(event),
+                    Err(err) => Next::handle_error(err),
+                }
+            }
+            renewed = conn.renewal.renewed(&conn.provider) => {
+                info!("certificate renewed, reconnecting");
+
+                conn.provider = renewed.provider;
+
+                let eventloop = conn.eventloop_mut();
+                Disconnected::set_transport(eventloop, renewed.transport);
+                // Drops the network but keeps the pending packets, to resend them once reconnected
+                eventloop.clean();
+
+                Next::state(Connecting)
+            }
         }
     }
 }
@@ -1091,6 +1268,7 @@ impl Next {
             ConnectionError::NetworkTimeout
             | ConnectionError::Io(_)
             | ConnectionError::FlushTimeout
+            | ConnectionError::Proxy(_)
             | ConnectionError::MqttState(StateError::Deserialization(mqttbytes::Error::Io(_))) => {
                 trace!("disconnected, wait for connack");
 
@@ -1112,6 +1290,15 @@ impl Next {
 
                 Next::state(Disconnected)
             }
+            #[cfg(feature = "websocket")]
+            ConnectionError::Websocket(_)
+            | ConnectionError::WsConnect(_)
+            | ConnectionError::InvalidUrl(_)
+            | ConnectionError::ResponseValidation(_) => {
+                trace!("recreate the websocket connection");
+
+                Next::state(Disconnected)
+            }
             ConnectionError::MqttState(StateError::Unsolicited(pkid)) => {
                 warn!("rumqtt reports unsolicited ack to pkid: {}", pkid);
                 Next::state(Connecting)
@@ -1186,7 +1373,10 @@ mod tests {
         session::{IntrospectionInterface, SessionError},
         store::{memory::MemoryStore, mock::MockStore, StoredProp},
         test::{DEVICE_OBJECT, DEVICE_PROPERTIES, DEVICE_PROPERTIES_NAME, SERVER_INDIVIDUAL},
-        transport::mqtt::test::notify_success,
+        transport::mqtt::{
+            pairing::tests::mock_create_certificate, registration::Registration,
+            test::notify_success,
+        },
         AstarteData,
     };
 
@@ -1432,6 +1622,7 @@ mod tests {
                 &interfaces,
                 &store,
                 Duration::from_secs(10),
+                Backoff::default(),
             ),
         )
         .await
@@ -1563,6 +1754,7 @@ mod tests {
                 &interfaces,
                 &mock_store,
                 Duration::from_secs(10),
+                Backoff::default(),
             ),
         )
         .await
@@ -1678,6 +1870,7 @@ mod tests {
                 &interfaces,
                 &mock_store,
                 Duration::from_secs(10),
+                Backoff::default(),
             ),
         )
         .await
@@ -1686,4 +1879,189 @@ mod tests {
 
         assert!(connection.link.is_link_connected());
     }
+
+    #[tokio::test]
+    async fn should_renew_certificate() {
+        let client_id = ClientId {
+            realm: "realm",
+            device_id: "device_id",
+        };
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = mock_create_certificate(&mut server)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mut provider = TransportProvider::configure(
+            server.url().parse().unwrap(),
+            "secret".to_string(),
+            None,
+            true,
+        )
+        .await
+        .expect("failed to configure transport provider");
+
+        let api = ApiClient::from_transport(
+            &provider,
+            client_id.realm,
+            client_id.device_id,
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        let _ = provider.transport(&api).await.unwrap();
+
+        assert!(provider.renew_at().is_some());
+        // Renew right away
+        provider.set_renew_at(SystemTime::now());
+
+        let mut eventl = EventLoop::new();
+        eventl
+            .expect_poll()
+            .returning(|| Box::pin(std::future::pending()));
+        // The pending packets are kept when reconnecting
+        eventl.expect_clean().once().return_const(());
+
+        let mut conn = Connection {
+            client: AsyncClient::default(),
+            eventloop: SyncWrapper::new(eventl),
+            provider,
+            renewal: CertRenewal::new(client_id.into(), Duration::from_secs(10)),
+            session_synced: true,
+            credentials_refused: false,
+        };
+
+        let next =
+            tokio::time::timeout(Duration::from_secs(3), Connected::new(true).poll(&mut conn))
+                .await
+                .expect("timeout reached");
+
+        assert!(matches!(next, Next::State(State::Connecting(Connecting))));
+
+        mock.assert_async().await;
+    }
+
+    async fn unauthorized_connection(server: &mockito::ServerGuard) -> Connection {
+        let provider = TransportProvider::configure(
+            server.url().parse().unwrap(),
+            "old".to_string(),
+            None,
+            true,
+        )
+        .await
+        .expect("failed to configure transport provider");
+
+        Connection {
+            client: AsyncClient::default(),
+            eventloop: SyncWrapper::new(EventLoop::new()),
+            provider,
+            renewal: CertRenewal::new(
+                ClientId {
+                    realm: "realm",
+                    device_id: "device_id",
+                }
+                .into(),
+                Duration::from_secs(10),
+            ),
+            session_synced: false,
+            credentials_refused: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_register_again_when_unauthorized() {
+        let client_id = ClientId {
+            realm: "realm",
+            device_id: "device_id",
+        };
+
+        let mut server = mockito::Server::new_async().await;
+        let unauthorized = server
+            .mock(
+                "POST",
+                "/v1/realm/devices/device_id/protocols/astarte_mqtt_v1/credentials",
+            )
+            .with_status(401)
+            .match_header("authorization", "Bearer old")
+            .create_async()
+            .await;
+        let register = server
+            .mock("POST", "/v1/realm/agent/devices")
+            .with_status(201)
+            .with_body(r#"{"data":{"credentials_secret":"secret"}}"#)
+            .match_header("authorization", "Bearer token")
+            .create_async()
+            .await;
+        let create = mock_create_certificate(&mut server).create_async().await;
+
+        let mut conn = unauthorized_connection(&server).await;
+        conn.provider
+            .set_registration(Some(Registration::PairingToken("token".to_string())));
+
+        let next = Disconnected
+            .reconnect(&mut conn, client_id, Duration::from_secs(10))
+            .await
+            .unwrap();
+        assert!(matches!(next, Next::Same));
+        assert!(conn.credentials_refused);
+        assert_eq!(conn.provider.credential_secret(), "secret");
+
+        let next = Disconnected
+            .reconnect(&mut conn, client_id, Duration::from_secs(10))
+            .await
+            .unwrap();
+        assert!(matches!(next, Next::State(State::Connecting(Connecting))));
+        assert!(!conn.credentials_refused);
+
+        unauthorized.assert_async().await;
+        register.assert_async().await;
+        create.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_fail_when_unauthorized_without_registration() {
+        let client_id = ClientId {
+            realm: "realm",
+            device_id: "device_id",
+        };
+
+        let mut server = mockito::Server::new_async().await;
+        let unauthorized = server
+            .mock(
+                "POST",
+                "/v1/realm/devices/device_id/protocols/astarte_mqtt_v1/credentials",
+            )
+            .with_status(403)
+            .create_async()
+            .await;
+
+        let mut conn = unauthorized_connection(&server).await;
+
+        let err = Disconnected
+            .reconnect(&mut conn, client_id, Duration::from_secs(10))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PollError::Unauthorized(ref err) if err.is_unauthorized()));
+
+        unauthorized.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_recreate_credentials_when_connack_refused() {
+        let server = mockito::Server::new_async().await;
+        let mut conn = unauthorized_connection(&server).await;
+
+        conn.eventloop_mut().expect_poll().once().returning(|| {
+            Box::pin(async {
+                Err(ConnectionError::ConnectionRefused(
+                    ConnectReturnCode::NotAuthorized,
+                ))
+            })
+        });
+
+        let next = Connecting.wait_connack(&mut conn).await;
+
+        assert!(matches!(next, Next::State(State::Disconnected(_))));
+        assert!(conn.credentials_refused);
+    }
 }
@@ -0,0 +1,472 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Storage for the credentials of the device.
+//!
+//! The credential secret, the private key and the certificate are stored through a
+//! [`CredentialStore`]. By default they are written as files in the writable directory, with the
+//! [`FileCredentialStore`], but they can be kept in any other storage by implementing the trait.
+//! The [`EncryptedCredentialStore`] encrypts them before writing them to another store.
+
+use std::{
+    fmt::{Debug, Display},
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use aws_lc_rs::aead::{Aad, Nonce, RandomizedNonceKey, AES_256_GCM, NONCE_LEN};
+use futures::future::BoxFuture;
+use tokio::fs;
+use tracing::{debug, info};
+
+use super::config::{CERTIFICATE_FILE, CREDENTIAL_FILE, PRIVATE_KEY_FILE};
+use crate::error::DynError;
+
+/// Error returned by a [`CredentialStore`].
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum CredentialStoreError {
+    /// Couldn't access the credential file.
+    #[error("couldn't access the credential file {path}")]
+    Io {
+        /// Path to the file.
+        path: PathBuf,
+        /// Reason why the file couldn't be accessed.
+        #[source]
+        backtrace: io::Error,
+    },
+    /// Couldn't encrypt the credential.
+    #[error("couldn't encrypt the {0}")]
+    Encrypt(CredentialKind),
+    /// Couldn't decrypt the credential, the key is wrong or the data is corrupted.
+    #[error("couldn't decrypt the {0}")]
+    Decrypt(CredentialKind),
+    /// Invalid key for the encryption.
+    #[error("invalid encryption key")]
+    Key,
+    /// Error returned by a custom store.
+    #[error("credential store error")]
+    Custom(#[source] DynError),
+}
+
+impl CredentialStoreError {
+    /// Creates an error for a custom [`CredentialStore`] implementation.
+    pub fn custom<E>(error: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Self::Custom(error.into())
+    }
+}
+
+/// Credential of the device to store.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CredentialKind {
+    /// Credential secret returned by the registration of the device.
+    Secret,
+    /// Private key of the client certificate, in PKCS#8 DER format.
+    PrivateKey,
+    /// Client certificate, in PEM format.
+    Certificate,
+}
+
+impl CredentialKind {
+    /// Returns the name of the credential.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CredentialKind::Secret => "credential secret",
+            CredentialKind::PrivateKey => "private key",
+            CredentialKind::Certificate => "certificate",
+        }
+    }
+}
+
+impl Display for CredentialKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Storage for the credentials of the device.
+///
+/// The credentials are opaque bytes, each identified by a [`CredentialKind`].
+pub trait CredentialStore: Debug + Send + Sync + 'static {
+    /// Loads the credential, returns [`None`] if it's not stored.
+    fn load(
+        &self,
+        kind: CredentialKind,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>, CredentialStoreError>> + Send;
+
+    /// Stores the credential, replacing the previous one.
+    fn store(
+        &self,
+        kind: CredentialKind,
+        value: &[u8],
+    ) -> impl Future<Output = Result<(), CredentialStoreError>> + Send;
+
+    /// Removes the credential, it's not an error if it's not stored.
+    fn remove(
+        &self,
+        kind: CredentialKind,
+    ) -> impl Future<Output = Result<(), CredentialStoreError>> + Send;
+}
+
+/// Object safe version of the [`CredentialStore`], to share it in the connection.
+pub(crate) trait DynCredentialStore: Debug + Send + Sync {
+    fn load_dyn(
+        &self,
+        kind: CredentialKind,
+    ) -> BoxFuture<'_, Result<Option<Vec<u8>>, CredentialStoreError>>;
+
+    fn store_dyn<'a>(
+        &'a self,
+        kind: CredentialKind,
+        value: &'a [u8],
+    ) -> BoxFuture<'a, Result<(), CredentialStoreError>>;
+
+    fn remove_dyn(&self, kind: CredentialKind) -> BoxFuture<'_, Result<(), CredentialStoreError>>;
+}
+
+impl<T> DynCredentialStore for T
+where
+    T: CredentialStore,
+{
+    fn load_dyn(
+        &self,
+        kind: CredentialKind,
+    ) -> BoxFuture<'_, Result<Option<Vec<u8>>, CredentialStoreError>> {
+        Box::pin(self.load(kind))
+    }
+
+    fn store_dyn<'a>(
+        &'a self,
+        kind: CredentialKind,
+        value: &'a [u8],
+    ) -> BoxFuture<'a, Result<(), CredentialStoreError>> {
+        Box::pin(self.store(kind, value))
+    }
+
+    fn remove_dyn(&self, kind: CredentialKind) -> BoxFuture<'_, Result<(), CredentialStoreError>> {
+        Box::pin(self.remove(kind))
+    }
+}
+
+/// Shared [`CredentialStore`].
+pub(crate) type SharedCredentialStore = Arc<dyn DynCredentialStore>;
+
+/// Stores the credentials as files in a directory.
+///
+/// This is the default store, used with the writable directory of the device.
+#[derive(Debug, Clone)]
+pub struct FileCredentialStore {
+    dir: PathBuf,
+}
+
+impl FileCredentialStore {
+    /// Store the credentials in the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the path of the file for the credential.
+    pub fn path(&self, kind: CredentialKind) -> PathBuf {
+        let file = match kind {
+            CredentialKind::Secret => CREDENTIAL_FILE,
+            CredentialKind::PrivateKey => PRIVATE_KEY_FILE,
+            CredentialKind::Certificate => CERTIFICATE_FILE,
+        };
+
+        self.dir.join(file)
+    }
+
+    fn io_error(path: &Path) -> impl FnOnce(io::Error) -> CredentialStoreError + '_ {
+        move |backtrace| CredentialStoreError::Io {
+            path: path.to_path_buf(),
+            backtrace,
+        }
+    }
+}
+
+impl CredentialStore for FileCredentialStore {
+    async fn load(&self, kind: CredentialKind) -> Result<Option<Vec<u8>>, CredentialStoreError> {
+        let path = self.path(kind);
+
+        match fs::read(&path).await {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                debug!(path = %path.display(), "missing {kind} file");
+
+                Ok(None)
+            }
+            Err(err) => Err(Self::io_error(&path)(err)),
+        }
+    }
+
+    async fn store(&self, kind: CredentialKind, value: &[u8]) -> Result<(), CredentialStoreError> {
+        let path = self.path(kind);
+
+        fs::write(&path, value).await.map_err(Self::io_error(&path))
+    }
+
+    async fn remove(&self, kind: CredentialKind) -> Result<(), CredentialStoreError> {
+        let path = self.path(kind);
+
+        match fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(Self::io_error(&path)(err)),
+        }
+    }
+}
+
+/// Header of the credentials encrypted by the [`EncryptedCredentialStore`].
+const ENCRYPTED_HEADER: &[u8] = b"\0astarte-aes-256-gcm\0";
+
+/// Encrypts the credentials before writing them to another store.
+///
+/// The credentials are encrypted with AES-256-GCM using the given key, which should be kept
+/// outside the store, for example in a secure element or in the keyring of the system.
+///
+/// The encrypted credentials are prefixed by a header, to tell them apart from the plaintext
+/// ones of a device that previously used the inner store directly. A plaintext credential is
+/// encrypted and written back to the inner store the first time it's loaded.
+///
+/// ```no_run
+/// use astarte_device_sdk::transport::mqtt::credentials::{
+///     EncryptedCredentialStore, FileCredentialStore,
+/// };
+/// use astarte_device_sdk::transport::mqtt::MqttConfig;
+///
+/// # fn read_key() -> [u8; 32] { [0; 32] }
+/// let key: [u8; 32] = read_key();
+/// let store = EncryptedCredentialStore::new(FileCredentialStore::new("/var/lib/device"), &key)
+///     .expect("valid key");
+///
+/// let mut config = MqttConfig::with_credential_secret("realm", "device_id", "secret", "url");
+/// config.credential_store(store);
+/// ```
+pub struct EncryptedCredentialStore<S = FileCredentialStore> {
+    inner: S,
+    key: RandomizedNonceKey,
+}
+
+impl<S> EncryptedCredentialStore<S> {
+    /// Encrypt the credentials stored in the inner store with the 256 bits key.
+    pub fn new(inner: S, key: &[u8; 32]) -> Result<Self, CredentialStoreError> {
+        let key =
+            RandomizedNonceKey::new(&AES_256_GCM, key).map_err(|_| CredentialStoreError::Key)?;
+
+        Ok(Self { inner, key })
+    }
+
+    fn encrypt(&self, kind: CredentialKind, value: &[u8]) -> Result<Vec<u8>, CredentialStoreError> {
+        let mut in_out = value.to_vec();
+
+        let nonce = self
+            .key
+            .seal_in_place_append_tag(Aad::from(kind.as_str()), &mut in_out)
+            .map_err(|_| CredentialStoreError::Encrypt(kind))?;
+
+        let mut encrypted = Vec::with_capacity(ENCRYPTED_HEADER.len() + NONCE_LEN + in_out.len());
+        encrypted.extend_from_slice(ENCRYPTED_HEADER);
+        encrypted.extend_from_slice(nonce.as_ref());
+        encrypted.append(&mut in_out);
+
+        Ok(encrypted)
+    }
+
+    fn decrypt(&self, kind: CredentialKind, value: &[u8]) -> Result<Vec<u8>, CredentialStoreError> {
+        if value.len() < NONCE_LEN {
+            return Err(CredentialStoreError::Decrypt(kind));
+        }
+
+        let (nonce, encrypted) = value.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| CredentialStoreError::Decrypt(kind))?;
+        let mut in_out = encrypted.to_vec();
+
+        let len = self
+            .key
+            .open_in_place(nonce, Aad::from(kind.as_str()), &mut in_out)
+            .map_err(|_| CredentialStoreError::Decrypt(kind))?
+            .len();
+
+        in_out.truncate(len);
+
+        Ok(in_out)
+    }
+}
+
+impl<S> Debug for EncryptedCredentialStore<S>
+where
+    S: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptedCredentialStore")
+            .field("inner", &self.inner)
+            .field("key", &"REDACTED")
+            .finish()
+    }
+}
+
+impl<S> CredentialStore for EncryptedCredentialStore<S>
+where
+    S: CredentialStore,
+{
+    async fn load(&self, kind: CredentialKind) -> Result<Option<Vec<u8>>, CredentialStoreError> {
+        let Some(value) = self.inner.load(kind).await? else {
+            return Ok(None);
+        };
+
+        if let Some(encrypted) = value.strip_prefix(ENCRYPTED_HEADER) {
+            return self.decrypt(kind, encrypted).map(Some);
+        }
+
+        info!("encrypting the plaintext {kind}");
+
+        self.store(kind, &value).await?;
+
+        Ok(Some(value))
+    }
+
+    async fn store(&self, kind: CredentialKind, value: &[u8]) -> Result<(), CredentialStoreError> {
+        let encrypted = self.encrypt(kind, value)?;
+
+        self.inner.store(kind, &encrypted).await
+    }
+
+    async fn remove(&self, kind: CredentialKind) -> Result<(), CredentialStoreError> {
+        self.inner.remove(kind).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn should_store_files() {
+        let dir = TempDir::new().unwrap();
+        let store = FileCredentialStore::new(dir.path());
+
+        assert_eq!(store.load(CredentialKind::Secret).await.unwrap(), None);
+
+        store
+            .store(CredentialKind::Secret, b"secret")
+            .await
+            .unwrap();
+        store
+            .store(CredentialKind::Certificate, b"cert")
+            .await
+            .unwrap();
+
+        // Same files used before the store
+        let secret = tokio::fs::read(dir.path().join(CREDENTIAL_FILE))
+            .await
+            .unwrap();
+        assert_eq!(secret, b"secret");
+        assert_eq!(
+            store.load(CredentialKind::Certificate).await.unwrap(),
+            Some(b"cert".to_vec())
+        );
+
+        store.remove(CredentialKind::Secret).await.unwrap();
+        store.remove(CredentialKind::Secret).await.unwrap();
+
+        assert_eq!(store.load(CredentialKind::Secret).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn should_encrypt_credentials() {
+        let dir = TempDir::new().unwrap();
+        let key = [42; 32];
+        let store =
+            EncryptedCredentialStore::new(FileCredentialStore::new(dir.path()), &key).unwrap();
+
+        store
+            .store(CredentialKind::PrivateKey, b"private key")
+            .await
+            .unwrap();
+
+        let raw = tokio::fs::read(dir.path().join(PRIVATE_KEY_FILE))
+            .await
+            .unwrap();
+        assert_ne!(raw, b"private key");
+        assert!(!raw.windows(11).any(|w| w == b"private key"));
+
+        assert_eq!(
+            store.load(CredentialKind::PrivateKey).await.unwrap(),
+            Some(b"private key".to_vec())
+        );
+        assert_eq!(store.load(CredentialKind::Secret).await.unwrap(), None);
+
+        // Wrong key
+        let other =
+            EncryptedCredentialStore::new(FileCredentialStore::new(dir.path()), &[1; 32]).unwrap();
+        assert!(matches!(
+            other.load(CredentialKind::PrivateKey).await,
+            Err(CredentialStoreError::Decrypt(CredentialKind::PrivateKey))
+        ));
+
+        // Moved to another credential
+        tokio::fs::write(dir.path().join(CREDENTIAL_FILE), raw)
+            .await
+            .unwrap();
+        assert!(matches!(
+            store.load(CredentialKind::Secret).await,
+            Err(CredentialStoreError::Decrypt(CredentialKind::Secret))
+        ));
+
+        let debug = format!("{store:?}");
+        assert!(debug.contains("REDACTED"));
+    }
+
+    #[tokio::test]
+    async fn should_encrypt_plaintext_credentials() {
+        let dir = TempDir::new().unwrap();
+        let inner = FileCredentialStore::new(dir.path());
+
+        // Stored by a previous version without the encryption
+        inner
+            .store(CredentialKind::Secret, b"secret")
+            .await
+            .unwrap();
+
+        let store = EncryptedCredentialStore::new(inner.clone(), &[42; 32]).unwrap();
+
+        assert_eq!(
+            store.load(CredentialKind::Secret).await.unwrap(),
+            Some(b"secret".to_vec())
+        );
+
+        let raw = inner.load(CredentialKind::Secret).await.unwrap().unwrap();
+        assert!(raw.starts_with(ENCRYPTED_HEADER));
+        assert!(!raw.windows(6).any(|w| w == b"secret"));
+
+        assert_eq!(
+            store.load(CredentialKind::Secret).await.unwrap(),
+            Some(b"secret".to_vec())
+        );
+    }
+}
@@ -20,8 +20,12 @@
 
 //! Crypto module to generate the CSR to authenticate the device to the Astarte.
 
-use rcgen::{CertificateParams, DistinguishedName, DnType, KeyPair, PKCS_ECDSA_P256_SHA256};
+use rcgen::{
+    CertificateParams, DistinguishedName, DnType, KeyPair, RsaKeySize, PKCS_ECDSA_P256_SHA256,
+    PKCS_ECDSA_P384_SHA384, PKCS_ED25519, PKCS_RSA_SHA256,
+};
 use rustls::pki_types::PrivatePkcs8KeyDer;
+use serde::{Deserialize, Serialize};
 
 /// Errors that can occur while generating the Certificate and CSR.
 #[non_exhaustive]
@@ -35,6 +39,39 @@ pub enum CryptoError {
     Utf8(#[from] std::string::FromUtf8Error),
 }
 
+/// Algorithm of the private key generated for the device certificate.
+///
+/// The key is stored in PKCS#8 DER format for every algorithm.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyAlgorithm {
+    /// ECDSA on the P-256 curve with SHA-256.
+    #[default]
+    EcdsaP256,
+    /// ECDSA on the P-384 curve with SHA-384.
+    EcdsaP384,
+    /// Ed25519 signatures.
+    Ed25519,
+    /// 2048 bits RSA key with PKCS#1 v1.5 SHA-256 signatures.
+    Rsa2048,
+    /// 4096 bits RSA key with PKCS#1 v1.5 SHA-256 signatures.
+    Rsa4096,
+}
+
+impl KeyAlgorithm {
+    /// Generate a random key pair for the algorithm.
+    fn generate(&self) -> Result<KeyPair, rcgen::Error> {
+        match self {
+            KeyAlgorithm::EcdsaP256 => KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256),
+            KeyAlgorithm::EcdsaP384 => KeyPair::generate_for(&PKCS_ECDSA_P384_SHA384),
+            KeyAlgorithm::Ed25519 => KeyPair::generate_for(&PKCS_ED25519),
+            KeyAlgorithm::Rsa2048 => KeyPair::generate_rsa_for(&PKCS_RSA_SHA256, RsaKeySize::_2048),
+            KeyAlgorithm::Rsa4096 => KeyPair::generate_rsa_for(&PKCS_RSA_SHA256, RsaKeySize::_4096),
+        }
+    }
+}
+
 /// Generate a Certificate and CSR bundle in PEM format.
 #[derive(Debug)]
 pub(crate) struct Bundle {
@@ -44,13 +81,17 @@ pub(crate) struct Bundle {
 }
 
 impl Bundle {
-    pub(crate) fn generate_key(realm: &str, device_id: &str) -> Result<Bundle, CryptoError> {
+    pub(crate) fn generate_key(
+        realm: &str,
+        device_id: &str,
+        algorithm: KeyAlgorithm,
+    ) -> Result<Bundle, CryptoError> {
         // The realm/device_id for the certificate
         let mut dn = DistinguishedName::new();
         dn.push(DnType::CommonName, format!("{realm}/{device_id}"));
 
         // Generate a random private key
-        let key_pair = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256)?;
+        let key_pair = algorithm.generate()?;
 
         let mut csr_param = CertificateParams::new([])?;
         csr_param.distinguished_name = dn;
@@ -70,7 +111,7 @@ mod tests {
 
     #[test]
     fn test_new_cert() {
-        let bundle = Bundle::generate_key("realm", "device_id");
+        let bundle = Bundle::generate_key("realm", "device_id", KeyAlgorithm::default());
 
         assert!(
             bundle.is_ok(),
@@ -90,7 +131,8 @@ mod tests {
 
     #[test]
     fn test_bundle() {
-        let Bundle { private_key, csr } = Bundle::generate_key("realm", "device_id").unwrap();
+        let Bundle { private_key, csr } =
+            Bundle::generate_key("realm", "device_id", KeyAlgorithm::default()).unwrap();
         assert!(!private_key.secret_pkcs8_der().is_empty());
         assert!(!csr.is_empty());
 
@@ -98,4 +140,32 @@ mod tests {
             .unwrap()
             .unwrap();
     }
+
+    #[test]
+    fn should_generate_with_algorithm() {
+        let cases = [
+            (KeyAlgorithm::EcdsaP256, &PKCS_ECDSA_P256_SHA256),
+            (KeyAlgorithm::EcdsaP384, &PKCS_ECDSA_P384_SHA384),
+            (KeyAlgorithm::Ed25519, &PKCS_ED25519),
+            (KeyAlgorithm::Rsa2048, &PKCS_RSA_SHA256),
+        ];
+
+        for (algorithm, exp) in cases {
+            let Bundle { private_key, csr } =
+                Bundle::generate_key("realm", "device_id", algorithm).unwrap();
+
+            let key = KeyPair::try_from(&private_key).unwrap();
+            assert!(key.is_compatible(exp), "{algorithm:?}");
+
+            let params = rcgen::CertificateSigningRequestParams::from_pem(&csr).unwrap();
+            assert_eq!(params.public_key.algorithm(), exp, "{algorithm:?}");
+        }
+    }
+
+    #[test]
+    fn should_deserialize_algorithm() {
+        let algorithm: KeyAlgorithm = serde_json::from_str(r#""ecdsa-p384""#).unwrap();
+
+        assert_eq!(algorithm, KeyAlgorithm::EcdsaP384);
+    }
 }
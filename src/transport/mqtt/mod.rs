@@ -26,10 +26,13 @@ pub(crate) mod client;
 mod components;
 mod config;
 mod connection;
+pub mod credentials;
 pub mod crypto;
 pub mod error;
 pub(crate) mod pairing;
 pub(crate) mod payload;
+mod pin;
+mod proxy;
 pub mod registration;
 mod retention;
 pub mod topic;
@@ -58,8 +61,11 @@ use super::{
 
 pub use self::config::Credential;
 pub use self::config::MqttConfig;
-pub use self::pairing::PairingError;
+pub use self::connection::PollError;
+pub use self::pairing::{DeviceInfo, PairingClient, PairingError, ProtocolInfo};
 pub use self::payload::PayloadError;
+pub use self::pin::{SpkiPin, SpkiPinError};
+pub use self::proxy::Proxy;
 use crate::{
     aggregate::AstarteObject,
     client::RecvError,
@@ -67,7 +73,7 @@ use crate::{
     interfaces::{self, DeviceIntrospection, Interfaces, MappingRef},
     properties,
     retention::{
-        mark_unsent_on_err, memory::VolatileStore, PublishInfo, RetentionId, StoredRetention,
+        mark_unsent_on_err, PublishInfo, RetentionId, StoredRetention, StoredRetentionExt,
     },
     session::{IntrospectionInterface, StoredSession},
     state::SharedState,
@@ -197,6 +203,8 @@ impl<S> MqttClient<S> {
             }
         }
 
+        self.state.receipts.received(id);
+
         Ok(())
     }
 
@@ -292,11 +300,6 @@ where
         id: RetentionId,
         validated: ValidatedIndividual,
     ) -> Result<(), crate::Error> {
-        debug_assert!(
-            !validated.retention.is_discard(),
-            "send stored called for retention discard"
-        );
-
         let buf = payload::serialize_individual(&validated.data, validated.timestamp)
             .map_err(MqttError::Payload)?;
 
@@ -323,11 +326,6 @@ where
         id: RetentionId,
         validated: ValidatedObject,
     ) -> Result<(), crate::Error> {
-        debug_assert!(
-            !validated.retention.is_discard(),
-            "send stored called for retention discard"
-        );
-
         let buf = payload::serialize_object(&validated.data, validated.timestamp)
             .map_err(MqttError::Payload)?;
 
@@ -612,28 +610,41 @@ impl<S> Mqtt<S> {
     }
 
     /// Marks the packets as received for the retention.
+    ///
+    /// If the token returned an error, the delivery receipt for the packet is resolved as dropped.
     async fn mark_packet_received(
-        volatile: &VolatileStore,
+        state: &SharedState,
         stored: &impl StoreCapabilities,
-        res_id: Result<RetentionId, TokenError>,
+        id: RetentionId,
+        res: Result<(), TokenError>,
     ) -> Result<(), RetentionError>
     where
         S: StoreCapabilities,
     {
-        let id = match res_id {
-            Ok(id) => id,
-            Err(err) => {
-                error!(error=%Report::new(err), "notice error while waiting for packet");
+        if let Err(err) = res {
+            error!(%id, error=%Report::new(err), "notice error while waiting for packet");
+
+            // The retained publishes are sent again, the receipt is resolved on the ack, the
+            // eviction or the expiry
+            let retained = match id {
+                RetentionId::Volatile(id) => {
+                    state.volatile_store.mark_sent(&id, false).await.is_some()
+                }
+                RetentionId::Stored(_) => stored.get_retention().is_some(),
+            };
 
-                return Ok(());
+            if !retained {
+                state.receipts.dropped(&id);
             }
-        };
+
+            return Ok(());
+        }
 
         trace!("received packet {id}");
 
         match id {
             RetentionId::Volatile(id) => {
-                volatile.mark_received(&id).await;
+                state.volatile_store.mark_received(&id).await;
             }
             RetentionId::Stored(id) => {
                 if let Some(retention) = stored.get_retention() {
@@ -642,6 +653,8 @@ impl<S> Mqtt<S> {
             }
         }
 
+        state.receipts.received(&id);
+
         debug!("marked {id} as received");
 
         Ok(())
@@ -659,8 +672,8 @@ impl<S> Mqtt<S> {
             let mut conn_future = std::pin::pin!(self.connection.next_publish());
 
             match futures::future::select(self.retention.into_future(), &mut conn_future).await {
-                Either::Left((res, _)) => {
-                    Self::mark_packet_received(&self.state.volatile_store, &self.store, res)
+                Either::Left(((id, res), _)) => {
+                    Self::mark_packet_received(&self.state, &self.store, id, res)
                         .await
                         .map_err(|err| TransportError::Transport(Error::Retention(err)))?;
                 }
@@ -773,7 +786,7 @@ where
             .connection
             .reconnect(self.client_id.as_ref(), interfaces, &self.store)
             .await
-            .map_err(Error::Mqtt);
+            .map_err(Error::from);
 
         // if we are connected but the session is not present we have to cleanup the retention data
         if result.as_ref().is_ok_and(|con| *con) && !self.connection.is_session_present() {
@@ -784,11 +797,12 @@ where
             let received = self.retention.discard();
 
             for id in received {
-                Self::mark_packet_received(&self.state.volatile_store, &self.store, Ok(id)).await?;
+                Self::mark_packet_received(&self.state, &self.store, id, Ok(())).await?;
             }
 
             if let Some(retention) = self.store.get_retention() {
                 retention.reset_all_publishes().await?;
+                retention.notify_removed(&self.state.receipts).await;
             }
 
             self.state.volatile_store.reset_sent().await;
@@ -961,14 +975,18 @@ pub(crate) mod test {
     };
 
     use crate::{
-        builder::{BuildConfig, ConnectionConfig, DeviceBuilder, DEFAULT_VOLATILE_CAPACITY},
-        retention::Context,
+        builder::{
+            BuildConfig, ConnectionConfig, DeviceBuilder, DEFAULT_CHANNEL_SIZE,
+            DEFAULT_VOLATILE_CAPACITY,
+        },
+        client::Subscriptions,
+        retention::{memory::VolatileStore, Context, DeliveryError},
         session::SessionError,
         store::{memory::MemoryStore, mock::MockStore, SqliteStore},
         test::{
             DEVICE_OBJECT, DEVICE_PROPERTIES, DEVICE_PROPERTIES_NAME, E2E_DEVICE_DATASTREAM,
             E2E_DEVICE_DATASTREAM_NAME, SERVER_INDIVIDUAL, SERVER_INDIVIDUAL_NAME,
-            SERVER_PROPERTIES,
+            SERVER_PROPERTIES, VOLATILE_DEVICE_DATASTREAM,
         },
         transport::mqtt::payload::Payload,
     };
@@ -1030,6 +1048,7 @@ pub(crate) mod test {
         let state = Arc::new(SharedState::new(
             Interfaces::from_iter(interfaces),
             VolatileStore::with_capacity(DEFAULT_VOLATILE_CAPACITY),
+            Subscriptions::with_capacity(DEFAULT_CHANNEL_SIZE),
         ));
 
         let mqtt = Mqtt::new(
@@ -1038,6 +1057,7 @@ pub(crate) mod test {
                 client.clone(),
                 eventloop,
                 transport_provider,
+                client_id.as_ref(),
                 self::connection::Connected::new(true),
                 Duration::from_secs(10),
             ),
@@ -1390,6 +1410,7 @@ pub(crate) mod test {
                 state: Arc::new(SharedState::new(
                     builder.interfaces,
                     VolatileStore::with_capacity(builder.volatile_retention),
+                    Subscriptions::with_capacity(builder.channel_size),
                 )),
                 connection_timeout: Duration::from_secs(10),
             }),
@@ -1519,6 +1540,7 @@ pub(crate) mod test {
                 state: Arc::new(SharedState::new(
                     builder.interfaces,
                     VolatileStore::with_capacity(builder.volatile_retention),
+                    Subscriptions::with_capacity(builder.channel_size),
                 )),
                 connection_timeout: Duration::from_secs(10),
             }),
@@ -2098,6 +2120,7 @@ pub(crate) mod test {
         )
         .await
         .unwrap()
+        .1
         .unwrap();
     }
 
@@ -2187,6 +2210,7 @@ pub(crate) mod test {
         )
         .await
         .unwrap()
+        .1
         .unwrap();
     }
 
@@ -2258,6 +2282,56 @@ pub(crate) mod test {
         )
         .await
         .unwrap()
+        .1
         .unwrap();
     }
+
+    #[tokio::test]
+    async fn should_keep_retained_receipt_on_token_error() {
+        let state = SharedState::new(
+            Interfaces::new(),
+            VolatileStore::with_capacity(DEFAULT_VOLATILE_CAPACITY),
+            Subscriptions::with_capacity(DEFAULT_CHANNEL_SIZE),
+        );
+        let store = StoreWrapper::new(MemoryStore::new());
+        let ctx = Context::new();
+
+        let path = MappingPath::try_from("/endpoint1").unwrap();
+        let interface = DatastreamIndividual::from_str(VOLATILE_DEVICE_DATASTREAM).unwrap();
+        let mapping = MappingRef::new(&interface, &path).unwrap();
+        let data =
+            ValidatedIndividual::validate(mapping, AstarteData::LongInteger(42), None).unwrap();
+
+        let retained = ctx.next();
+        state.volatile_store.push_sent(retained, data).await;
+        let retained = RetentionId::Volatile(retained);
+        let retained_receipt = state.receipts.register(retained, None);
+
+        let discarded = RetentionId::Volatile(ctx.next());
+        let discarded_receipt = state.receipts.register(discarded, None);
+
+        for id in [retained, discarded] {
+            Mqtt::<MemoryStore>::mark_packet_received(
+                &state,
+                &store,
+                id,
+                Err(TokenError::Disconnected),
+            )
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(discarded_receipt.await, Err(DeliveryError::Dropped));
+
+        // Marked to be sent again, with the receipt still pending
+        let mut unsent = Vec::new();
+        state.volatile_store.get_unsent(&mut unsent, 10).await;
+        assert_eq!(unsent.len(), 1);
+
+        Mqtt::<MemoryStore>::mark_packet_received(&state, &store, retained, Ok(()))
+            .await
+            .unwrap();
+
+        assert_eq!(retained_receipt.await, Ok(()));
+    }
 }
@@ -18,7 +18,7 @@
 
 //! Provides the functionalities to pair a device with the Astarte Cluster.
 
-use std::{io, path::PathBuf, time::Duration};
+use std::{collections::HashMap, io, path::PathBuf, time::Duration};
 
 use reqwest::{
     header::{HeaderMap, HeaderValue},
@@ -27,7 +27,14 @@ use reqwest::{
 use serde::{Deserialize, Serialize};
 use url::ParseError;
 
-use super::{config::transport::TransportProvider, crypto::CryptoError};
+use crate::error::DynError;
+
+use super::{
+    config::{load_secret, transport::TransportProvider},
+    credentials::CredentialStoreError,
+    crypto::CryptoError,
+    Credential, MqttConfig, Proxy,
+};
 
 /// Error returned during pairing.
 #[non_exhaustive]
@@ -69,6 +76,9 @@ pub enum PairingError {
     #[error("configuration error, {0}")]
     Config(String),
     /// Couldn't read the credentials secret from the file
+    #[deprecated(
+        note = "the credentials are read from the credential store, see `PairingError::CredentialStore`"
+    )]
     #[error("couldn't read credential secret from {path}")]
     ReadCredential {
         /// The path where the credential is stored.
@@ -87,6 +97,9 @@ pub enum PairingError {
         backtrace: io::Error,
     },
     /// Couldn't write the credentials secret to the file
+    #[deprecated(
+        note = "the credentials are written to the credential store, see `PairingError::CredentialStore`"
+    )]
     #[error("couldn't write credential secret to {path}")]
     WriteCredential {
         /// The path where the credential is stored.
@@ -98,6 +111,31 @@ pub enum PairingError {
     /// Couldn't read native certificates
     #[error("couldn't read native certificates")]
     ReadNativeCerts(#[source] tokio::task::JoinError),
+    /// Couldn't parse the PEM bundle of CA certificates
+    #[error("couldn't parse the CA certificates")]
+    CaCertificate(#[source] io::Error),
+    /// Couldn't access the credential store
+    #[error("couldn't access the credential store")]
+    CredentialStore(#[from] CredentialStoreError),
+    /// The registration callback failed
+    #[error("couldn't register the device again")]
+    Registration(#[source] DynError),
+    /// The device info is missing the broker URL of the Astarte MQTT v1 protocol
+    #[error("missing the broker URL for the astarte_mqtt_v1 protocol")]
+    MissingBrokerUrl,
+}
+
+impl PairingError {
+    /// Returns `true` if Astarte rejected the credentials of the device.
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(
+            self,
+            PairingError::Api {
+                status: StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN,
+                ..
+            }
+        )
+    }
 }
 
 impl From<reqwest::Error> for PairingError {
@@ -114,17 +152,30 @@ impl From<reqwest::Error> for PairingError {
 pub(crate) struct ApiClient<'a> {
     pub(crate) realm: &'a str,
     pub(crate) device_id: &'a str,
-    pairing_url: &'a Url,
+    pairing_url: Url,
     client: reqwest::Client,
 }
 
 impl<'a> ApiClient<'a> {
     pub(crate) fn from_transport(
-        provider: &'a TransportProvider,
+        provider: &TransportProvider,
         realm: &'a str,
         device_id: &'a str,
         timeout: Duration,
     ) -> Result<Self, PairingError> {
+        Ok(Self {
+            realm,
+            device_id,
+            pairing_url: provider.pairing_url().clone(),
+            client: Self::http_client(provider, timeout)?,
+        })
+    }
+
+    /// Creates the HTTP client authenticated with the credential secret.
+    fn http_client(
+        provider: &TransportProvider,
+        timeout: Duration,
+    ) -> Result<reqwest::Client, PairingError> {
         let tls_config = provider.api_tls_config()?;
 
         let mut headers = HeaderMap::new();
@@ -133,18 +184,19 @@ impl<'a> ApiClient<'a> {
         value.set_sensitive(true);
         headers.insert(reqwest::header::AUTHORIZATION, value);
 
-        let client = reqwest::Client::builder()
-            .use_preconfigured_tls(tls_config.clone())
+        let builder = Proxy::configure_http(
+            reqwest::Client::builder(),
+            provider.proxy(),
+            provider.pairing_url(),
+        )?;
+
+        let client = builder
+            .use_preconfigured_tls(tls_config)
             .default_headers(headers)
             .timeout(timeout)
             .build()?;
 
-        Ok(Self {
-            realm,
-            device_id,
-            pairing_url: provider.pairing_url(),
-            client,
-        })
+        Ok(client)
     }
 
     fn url<'i, I>(&self, segments: I) -> Result<Url, PairingError>
@@ -220,15 +272,23 @@ impl<'a> ApiClient<'a> {
     }
 
     pub async fn get_broker_url(&self) -> Result<Url, PairingError> {
+        self.device_info()
+            .await?
+            .broker_url()
+            .cloned()
+            .ok_or(PairingError::MissingBrokerUrl)
+    }
+
+    pub async fn device_info(&self) -> Result<DeviceInfo, PairingError> {
         let url = self.url([])?;
 
         let response = self.client.get(url).send().await?;
 
         match response.status() {
             StatusCode::OK => {
-                let res: ApiData<StatusInfo> = response.json().await?;
+                let res: ApiData<DeviceInfo> = response.json().await?;
 
-                Ok(res.data.protocols.astarte_mqtt_v1.broker_url)
+                Ok(res.data)
             }
             status_code => {
                 let raw_response = response.text().await?;
@@ -271,33 +331,160 @@ struct MqttV1ClientCrtValidity {
     valid: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct StatusInfo {
-    version: String,
-    status: String,
-    protocols: ProtocolsInfo,
+/// Client for the Astarte Pairing API of a device.
+///
+/// It can be used to check the registration of a device before connecting it with the
+/// [`DeviceBuilder`](crate::builder::DeviceBuilder).
+///
+/// ```no_run
+/// use astarte_device_sdk::transport::mqtt::{MqttConfig, PairingClient};
+///
+/// # async fn example() -> Result<(), astarte_device_sdk::transport::mqtt::PairingError> {
+/// let config = MqttConfig::with_credential_secret(
+///     "realm",
+///     "device_id",
+///     "credentials_secret",
+///     "https://api.astarte.localhost/pairing",
+/// );
+///
+/// let client = PairingClient::from_config(&config, std::time::Duration::from_secs(10)).await?;
+///
+/// client.health().await?;
+///
+/// let info = client.device_info().await?;
+/// println!("device status {}", info.status);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PairingClient {
+    realm: String,
+    device_id: String,
+    pairing_url: Url,
+    client: reqwest::Client,
+}
+
+impl PairingClient {
+    /// Creates the client with the pairing URL, TLS and proxy options of the configuration.
+    ///
+    /// The pairing token can only be used to register the device, so with a pairing token the
+    /// credentials secret is loaded from the configured
+    /// [`credential_store`](MqttConfig::credential_store). It returns a [`PairingError::Config`]
+    /// if no credentials secret is stored.
+    pub async fn from_config(config: &MqttConfig, timeout: Duration) -> Result<Self, PairingError> {
+        let secret = match &config.credential {
+            Credential::Secret { credentials_secret } => credentials_secret.clone(),
+            Credential::ParingToken { .. } => {
+                let stored = match &config.credential_store {
+                    Some(store) => load_secret(store).await?,
+                    None => None,
+                };
+
+                stored.ok_or_else(|| {
+                    PairingError::Config(
+                        "a credentials secret is required to use the pairing API".to_string(),
+                    )
+                })?
+            }
+        };
+
+        let provider = config.transport_provider(secret, None).await?;
+
+        Ok(Self {
+            realm: config.realm.clone(),
+            device_id: config.device_id.clone(),
+            pairing_url: provider.pairing_url().clone(),
+            client: ApiClient::http_client(&provider, timeout)?,
+        })
+    }
+
+    fn api(&self) -> ApiClient<'_> {
+        ApiClient {
+            realm: &self.realm,
+            device_id: &self.device_id,
+            pairing_url: self.pairing_url.clone(),
+            client: self.client.clone(),
+        }
+    }
+
+    /// Returns the status of the device and the information of the available protocols.
+    pub async fn device_info(&self) -> Result<DeviceInfo, PairingError> {
+        self.api().device_info().await
+    }
+
+    /// Returns the URL of the MQTT broker the device should connect to.
+    pub async fn broker_url(&self) -> Result<Url, PairingError> {
+        self.api().get_broker_url().await
+    }
+
+    /// Checks that the Pairing API is reachable and healthy.
+    ///
+    /// Returns an [`PairingError::Api`] error if the API replies with an unsuccessful status.
+    pub async fn health(&self) -> Result<(), PairingError> {
+        let mut url = self.pairing_url.clone();
+        url.path_segments_mut()
+            .map_err(|()| ParseError::RelativeUrlWithCannotBeABaseBase)?
+            .pop_if_empty()
+            .push("health");
+
+        let response = self.client.get(url).send().await?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let raw_response = response.text().await?;
+
+        Err(PairingError::Api {
+            status,
+            body: raw_response,
+        })
+    }
+}
+
+/// Information of a device registered in Astarte.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    /// Version of the Astarte cluster.
+    pub version: String,
+    /// Status of the device, e.g. `pending` before the first connection.
+    pub status: String,
+    /// Information of the protocols available to the device, by protocol name.
+    pub protocols: HashMap<String, ProtocolInfo>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct ProtocolsInfo {
-    astarte_mqtt_v1: AstarteMqttV1Info,
+impl DeviceInfo {
+    /// Name of the Astarte MQTT v1 protocol.
+    pub const ASTARTE_MQTT_V1: &'static str = "astarte_mqtt_v1";
+
+    /// Returns the broker URL of the Astarte MQTT v1 protocol.
+    pub fn broker_url(&self) -> Option<&Url> {
+        self.protocols
+            .get(Self::ASTARTE_MQTT_V1)
+            .and_then(|info| info.broker_url.as_ref())
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct AstarteMqttV1Info {
-    broker_url: Url,
+/// Information of a protocol available to the device.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolInfo {
+    /// URL of the broker to connect to, if the protocol uses one.
+    pub broker_url: Option<Url>,
 }
 
 #[cfg(test)]
 pub(crate) mod tests {
-    use crate::transport::mqtt::crypto::Bundle;
+    use crate::transport::mqtt::crypto::{Bundle, KeyAlgorithm};
 
     use super::*;
 
     use mockito::Server;
     use pretty_assertions::assert_eq;
 
-    const ASTARTE_CA_PEM: &str = include_str!("../../../tests/ca/astarte-ca.pem");
+    pub(crate) const ASTARTE_CA_PEM: &str = include_str!("../../../tests/ca/astarte-ca.pem");
     const ASTARTE_CA_KEY_PEM: &str = include_str!("../../../tests/ca/astarte-ca-key.pem");
 
     pub(crate) fn mock_get_broker_url(server: &mut mockito::ServerGuard) -> mockito::Mock {
@@ -390,7 +577,7 @@ pub(crate) mod tests {
             ApiClient::from_transport(&provider, "realm", "device_id", Duration::from_secs(10))
                 .expect("couldn't create api client");
 
-        let bundle = Bundle::generate_key("test", "device_id").unwrap();
+        let bundle = Bundle::generate_key("test", "device_id", KeyAlgorithm::default()).unwrap();
 
         let res = client.create_certificate(&bundle.csr).await.unwrap();
 
@@ -490,4 +677,114 @@ pub(crate) mod tests {
 
         mock.assert_async().await;
     }
+
+    #[tokio::test]
+    async fn should_get_device_info() {
+        let mut server = Server::new_async().await;
+
+        let mock = mock_get_broker_url(&mut server).create_async().await;
+
+        let config =
+            MqttConfig::with_credential_secret("realm", "device_id", "secret", server.url());
+        let client = PairingClient::from_config(&config, Duration::from_secs(10))
+            .await
+            .unwrap();
+
+        let info = client.device_info().await.unwrap();
+
+        assert_eq!(info.status, "pending");
+        assert_eq!(info.version, "1.1.1");
+        let expected = Url::parse("mqtts://broker.astarte.localhost:8883/").unwrap();
+        assert_eq!(info.broker_url(), Some(&expected));
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_probe_health() {
+        let mut server = Server::new_async().await;
+
+        let healthy = server
+            .mock("GET", "/pairing/health")
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let config = MqttConfig::with_credential_secret(
+            "realm",
+            "device_id",
+            "secret",
+            format!("{}/pairing/", server.url()),
+        );
+        let client = PairingClient::from_config(&config, Duration::from_secs(10))
+            .await
+            .unwrap();
+
+        client.health().await.unwrap();
+        healthy.assert_async().await;
+
+        let unhealthy = server
+            .mock("GET", "/pairing/health")
+            .with_status(503)
+            .create_async()
+            .await;
+
+        let err = client.health().await.unwrap_err();
+        assert!(matches!(err, PairingError::Api { status, .. } if status == 503));
+        unhealthy.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn should_require_credentials_secret() {
+        let config =
+            MqttConfig::with_pairing_token("realm", "device_id", "token", "http://localhost");
+
+        let err = PairingClient::from_config(&config, Duration::from_secs(10))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, PairingError::Config(_)));
+    }
+
+    #[tokio::test]
+    async fn should_use_stored_credentials_secret() {
+        use crate::transport::mqtt::credentials::{
+            CredentialKind, CredentialStore, FileCredentialStore,
+        };
+
+        let mut server = Server::new_async().await;
+
+        let mock = mock_get_broker_url(&mut server).create_async().await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileCredentialStore::new(dir.path());
+        store
+            .store(CredentialKind::Secret, b"secret")
+            .await
+            .unwrap();
+
+        let mut config =
+            MqttConfig::with_pairing_token("realm", "device_id", "token", server.url());
+        config.credential_store(store);
+
+        let client = PairingClient::from_config(&config, Duration::from_secs(10))
+            .await
+            .unwrap();
+
+        let info = client.device_info().await.unwrap();
+        assert_eq!(info.status, "pending");
+
+        mock.assert_async().await;
+
+        // Without the stored secret
+        let mut config =
+            MqttConfig::with_pairing_token("realm", "device_id", "token", server.url());
+        config.credential_store(FileCredentialStore::new(dir.path().join("missing")));
+
+        let err = PairingClient::from_config(&config, Duration::from_secs(10))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, PairingError::Config(_)));
+    }
 }
@@ -0,0 +1,242 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pinning of the public key of the Astarte server certificates.
+
+use std::{fmt::Display, str::FromStr};
+
+use aws_lc_rs::digest::{digest, SHA256, SHA256_OUTPUT_LEN};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use rustls::pki_types::{CertificateDer, TrustAnchor};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::Report;
+
+/// Prefix of the pin, optional when parsing.
+const PIN_PREFIX: &str = "sha256/";
+
+/// Error returned when parsing a [`SpkiPin`].
+#[non_exhaustive]
+#[derive(Debug, thiserror::Error)]
+pub enum SpkiPinError {
+    /// The pin is not valid base64.
+    #[error("invalid base64 encoded pin")]
+    Base64(#[from] base64::DecodeError),
+    /// The pin is not a SHA-256 digest.
+    #[error("invalid pin length {0}, expected a SHA-256 digest")]
+    Length(usize),
+}
+
+/// SHA-256 digest of the SubjectPublicKeyInfo of a certificate.
+///
+/// When pins are configured, a server certificate chain is accepted only if it's valid and one of
+/// its certificates has a pinned public key. The pin is the base64 encoded digest, optionally
+/// prefixed with `sha256/`, and can be computed with:
+///
+/// ```sh
+/// openssl x509 -in ca.pem -pubkey -noout \
+///     | openssl pkey -pubin -outform der \
+///     | openssl dgst -sha256 -binary \
+///     | base64
+/// ```
+///
+/// ```
+/// use astarte_device_sdk::transport::mqtt::SpkiPin;
+///
+/// let pin: SpkiPin = "sha256/47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=".parse().unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SpkiPin([u8; SHA256_OUTPUT_LEN]);
+
+impl SpkiPin {
+    /// Computes the pin of a DER encoded SubjectPublicKeyInfo.
+    pub fn from_spki_der(spki: &[u8]) -> Self {
+        let digest = digest(&SHA256, spki);
+
+        let mut pin = [0; SHA256_OUTPUT_LEN];
+        pin.copy_from_slice(digest.as_ref());
+
+        Self(pin)
+    }
+
+    /// Computes the pin of the public key in a DER encoded certificate.
+    pub(crate) fn from_certificate(cert: &CertificateDer<'_>) -> Option<Self> {
+        match x509_parser::parse_x509_certificate(cert) {
+            Ok((_, cert)) => Some(Self::from_spki_der(cert.public_key().raw)),
+            Err(err) => {
+                warn!(error = %Report::new(err), "couldn't parse the certificate to pin");
+
+                None
+            }
+        }
+    }
+
+    /// Computes the pin of the public key of a trust anchor.
+    pub(crate) fn from_trust_anchor(anchor: &TrustAnchor<'_>) -> Self {
+        // The trust anchor stores the content of the SubjectPublicKeyInfo sequence
+        Self::from_spki_der(&der_sequence(&anchor.subject_public_key_info))
+    }
+
+    /// Checks if one of the certificates in the chain, or the trust anchor that issued it, has a
+    /// pinned public key.
+    pub(crate) fn matches_chain(
+        pins: &[SpkiPin],
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        anchors: &[TrustAnchor<'_>],
+    ) -> bool {
+        let chain = std::iter::once(end_entity).chain(intermediates);
+
+        if chain
+            .filter_map(Self::from_certificate)
+            .any(|pin| pins.contains(&pin))
+        {
+            return true;
+        }
+
+        let last = intermediates.last().unwrap_or(end_entity);
+        let issuer = match x509_parser::parse_x509_certificate(last) {
+            Ok((_, cert)) => cert.issuer().as_raw().to_vec(),
+            Err(err) => {
+                warn!(error = %Report::new(err), "couldn't parse the certificate issuer");
+
+                return false;
+            }
+        };
+
+        anchors
+            .iter()
+            .filter(|anchor| der_sequence(&anchor.subject) == issuer)
+            .map(Self::from_trust_anchor)
+            .any(|pin| pins.contains(&pin))
+    }
+}
+
+/// Encodes the content as a DER sequence.
+fn der_sequence(content: &[u8]) -> Vec<u8> {
+    const SEQUENCE: u8 = 0x30;
+
+    let len = content.len();
+    let mut der = Vec::with_capacity(len + 6);
+    der.push(SEQUENCE);
+
+    if len < 0x80 {
+        // Short form, the length fits in 7 bits
+        #[allow(clippy::cast_possible_truncation)]
+        der.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        let len_bytes = &bytes[skip..];
+
+        // At most the size of an usize
+        #[allow(clippy::cast_possible_truncation)]
+        der.push(0x80 | len_bytes.len() as u8);
+        der.extend_from_slice(len_bytes);
+    }
+
+    der.extend_from_slice(content);
+
+    der
+}
+
+impl FromStr for SpkiPin {
+    type Err = SpkiPinError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        let value = value.strip_prefix(PIN_PREFIX).unwrap_or(value);
+
+        let bytes = BASE64_STANDARD.decode(value)?;
+        let pin = bytes
+            .try_into()
+            .map_err(|bytes: Vec<u8>| SpkiPinError::Length(bytes.len()))?;
+
+        Ok(Self(pin))
+    }
+}
+
+impl TryFrom<String> for SpkiPin {
+    type Error = SpkiPinError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<SpkiPin> for String {
+    fn from(value: SpkiPin) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for SpkiPin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{PIN_PREFIX}{}", BASE64_STANDARD.encode(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_pin() {
+        // SHA-256 of the empty string
+        let exp = SpkiPin::from_spki_der(&[]);
+
+        let pin: SpkiPin = "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+            .parse()
+            .unwrap();
+        assert_eq!(pin, exp);
+
+        let pin: SpkiPin = "sha256/47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+            .parse()
+            .unwrap();
+        assert_eq!(pin, exp);
+        assert_eq!(
+            pin.to_string(),
+            "sha256/47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+        );
+
+        let json = serde_json::to_string(&pin).unwrap();
+        assert_eq!(serde_json::from_str::<SpkiPin>(&json).unwrap(), pin);
+
+        assert!(matches!(
+            "AAAA".parse::<SpkiPin>(),
+            Err(SpkiPinError::Length(3))
+        ));
+        assert!(matches!(
+            "not base64!".parse::<SpkiPin>(),
+            Err(SpkiPinError::Base64(_))
+        ));
+    }
+
+    #[test]
+    fn should_encode_der_sequence() {
+        assert_eq!(der_sequence(&[1, 2]), [0x30, 2, 1, 2]);
+
+        let long = der_sequence(&[0; 300]);
+        assert_eq!(long[..4], [0x30, 0x82, 0x01, 0x2c]);
+        assert_eq!(long.len(), 304);
+    }
+}
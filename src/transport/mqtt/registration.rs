@@ -19,16 +19,18 @@
  */
 //! Provides static functions for registering a new device to an Astarte Cluster.
 
-use std::time::Duration;
+use std::{fmt::Debug, future::Future, sync::Arc, time::Duration};
 
 use base64::Engine;
+use futures::future::BoxFuture;
 use reqwest::{StatusCode, Url};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::transport::mqtt::PairingError;
+use crate::error::DynError;
+use crate::transport::mqtt::{ClientId, PairingError, Proxy};
 
-use super::pairing::ApiData;
+use super::{config::transport::TransportProvider, pairing::ApiData};
 
 #[derive(Debug, Serialize)]
 struct MqttV1HwId<'a> {
@@ -48,18 +50,34 @@ pub async fn register_device_with_timeout(
     device_id: &str,
     timeout: Duration,
 ) -> Result<String, PairingError> {
-    let mut url = Url::parse(pairing_url)?;
+    let url = registration_url(Url::parse(pairing_url)?, realm)?;
 
-    url.path_segments_mut()
+    let client = reqwest::Client::builder().timeout(timeout).build()?;
+
+    register_with_client(&client, url, token, device_id).await
+}
+
+/// Returns the URL of the agent API to register the device.
+fn registration_url(mut pairing_url: Url, realm: &str) -> Result<Url, PairingError> {
+    pairing_url
+        .path_segments_mut()
         .map_err(|_| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
         .push("v1")
         .push(realm)
         .push("agent")
         .push("devices");
 
+    Ok(pairing_url)
+}
+
+async fn register_with_client(
+    client: &reqwest::Client,
+    url: Url,
+    token: &str,
+    device_id: &str,
+) -> Result<String, PairingError> {
     let payload = ApiData::new(MqttV1HwId { hw_id: device_id });
 
-    let client = reqwest::Client::builder().timeout(timeout).build()?;
     let response = client
         .post(url)
         .bearer_auth(token)
@@ -100,6 +118,86 @@ pub async fn register_device(
     .await
 }
 
+/// Callback to register the device again, returning the new credential secret.
+#[derive(Clone)]
+pub(crate) struct RegistrationCallback(
+    Arc<dyn Fn() -> BoxFuture<'static, Result<String, DynError>> + Send + Sync>,
+);
+
+impl RegistrationCallback {
+    pub(crate) fn new<F, Fut, E>(callback: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, E>> + Send + 'static,
+        E: Into<DynError>,
+    {
+        Self(Arc::new(move || {
+            let fut = callback();
+
+            Box::pin(async move { fut.await.map_err(Into::into) })
+        }))
+    }
+}
+
+impl Debug for RegistrationCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RegistrationCallback(..)")
+    }
+}
+
+/// Registers the device again when its credentials are rejected by Astarte.
+#[derive(Clone)]
+pub(crate) enum Registration {
+    /// Register with the pairing token of the device.
+    PairingToken(String),
+    /// Register with a user provided callback.
+    Callback(RegistrationCallback),
+}
+
+impl Registration {
+    /// Returns the new credential secret for the device.
+    ///
+    /// The pairing token is sent with the same TLS configuration used for the pairing API.
+    pub(crate) async fn register(
+        &self,
+        provider: &TransportProvider,
+        client_id: ClientId<&str>,
+        timeout: Duration,
+    ) -> Result<String, PairingError> {
+        match self {
+            Registration::PairingToken(token) => {
+                let url = registration_url(provider.pairing_url().clone(), client_id.realm)?;
+
+                let client =
+                    Proxy::configure_http(reqwest::Client::builder(), provider.proxy(), &url)?
+                        .use_preconfigured_tls(provider.api_tls_config()?)
+                        .timeout(timeout)
+                        .build()?;
+
+                register_with_client(&client, url, token, client_id.device_id).await
+            }
+            Registration::Callback(callback) => {
+                (callback.0)().await.map_err(PairingError::Registration)
+            }
+        }
+    }
+}
+
+impl Debug for Registration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Registration::PairingToken(_) => f
+                .debug_tuple("Registration::PairingToken")
+                .field(&"REDACTED")
+                .finish(),
+            Registration::Callback(callback) => f
+                .debug_tuple("Registration::Callback")
+                .field(callback)
+                .finish(),
+        }
+    }
+}
+
 /// Generate a random device Id with UUIDv4.
 pub fn generate_random_uuid() -> String {
     let uuid = Uuid::new_v4();
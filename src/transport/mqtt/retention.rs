@@ -91,7 +91,7 @@ impl MqttRetention {
 }
 
 impl<'a> IntoFuture for &'a mut MqttRetention {
-    type Output = Result<RetentionId, TokenError>;
+    type Output = (RetentionId, Result<(), TokenError>);
 
     type IntoFuture = MqttRetentionFuture<'a>;
 
@@ -102,8 +102,9 @@ impl<'a> IntoFuture for &'a mut MqttRetention {
 
 pub(crate) struct MqttRetentionFuture<'a>(&'a mut MqttRetention);
 
+/// Resolves to the id of the first packet whose token completed, with the result of the token.
 impl std::future::Future for MqttRetentionFuture<'_> {
-    type Output = Result<RetentionId, TokenError>;
+    type Output = (RetentionId, Result<(), TokenError>);
 
     fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
         let this = &mut *self.get_mut().0;
@@ -115,7 +116,7 @@ impl std::future::Future for MqttRetentionFuture<'_> {
 
             match poll {
                 Poll::Pending => None,
-                Poll::Ready(Ok(_)) => Some((*id, Ok(*id))),
+                Poll::Ready(Ok(_)) => Some((*id, Ok(()))),
                 Poll::Ready(Err(TokenError::Waiting)) => {
                     warn!(%id, "future returned Ready(Waiting), this should not happen and it could lead to errors on the next poll");
 
@@ -136,7 +137,7 @@ impl std::future::Future for MqttRetentionFuture<'_> {
 
                 debug_assert!(pkt.is_some());
 
-                Poll::Ready(res)
+                Poll::Ready((id, res))
             }
             None => Poll::Pending,
         }
@@ -176,11 +177,13 @@ mod tests {
 
         t2.resolve(AckOfPub::None);
 
-        let n = retention.into_future().await.unwrap();
+        let (n, res) = retention.into_future().await;
         assert_eq!(n, RetentionId::Stored(i2));
+        res.unwrap();
 
         drop(t1);
-        let res = retention.into_future().await;
+        let (n, res) = retention.into_future().await;
+        assert_eq!(n, RetentionId::Stored(i1));
         assert!(res.is_err(), "expected error but got {:?}", res.unwrap());
     }
 }
@@ -23,9 +23,9 @@ use std::borrow::Borrow;
 use std::fmt::Display;
 use std::ops::Deref;
 
+use ::serde::Serialize;
 use astarte_interfaces::schema::MappingType;
 use bson::Bson;
-use serde::Serialize;
 
 use crate::Timestamp;
 
@@ -33,6 +33,7 @@ use self::de::{bson_array, ArrayType};
 
 pub(crate) mod de;
 mod display;
+pub mod serde;
 
 macro_rules! check_astype_match {
     ( $self:ident, $other:ident, {$( $variant:tt ,)*}) => {
@@ -0,0 +1,95 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serialize a [`DateTime`] as an [`AstarteData::DateTime`](crate::types::AstarteData::DateTime).
+//!
+//! The serde data model doesn't have a date time type, so [`chrono`] serializes it as a string.
+//! Use this module with `#[serde(with = "astarte_device_sdk::types::serde::datetime")]` on a
+//! field to send it on a `datetime` mapping.
+//!
+//! Other serializers receive the date time as an RFC 3339 string.
+//!
+//! ```
+//! use astarte_device_sdk::types::serde::to_astarte_object;
+//! use astarte_device_sdk::types::AstarteData;
+//! use chrono::{DateTime, Utc};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Reading {
+//!     #[serde(with = "astarte_device_sdk::types::serde::datetime")]
+//!     at: DateTime<Utc>,
+//! }
+//!
+//! let at = Utc::now();
+//! let object = to_astarte_object(&Reading { at }).unwrap();
+//!
+//! assert_eq!(object.get("at"), Some(&AstarteData::DateTime(at)));
+//! ```
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::de::Visitor;
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Name of the newtype struct recognized by the
+/// [`AstarteDataSerializer`](super::AstarteDataSerializer).
+pub(crate) const DATETIME_NEWTYPE: &str = "$astarte_device_sdk::DateTime";
+
+/// Serializes the date time as an Astarte date time.
+pub fn serialize<S>(value: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(DATETIME_NEWTYPE, &value.to_rfc3339())
+}
+
+/// Deserializes the date time from an Astarte date time or an RFC 3339 string.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_newtype_struct(DATETIME_NEWTYPE, DateTimeVisitor)
+}
+
+struct DateTimeVisitor;
+
+impl<'de> Visitor<'de> for DateTimeVisitor {
+    type Value = DateTime<Utc>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an RFC 3339 date time")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        DateTime::deserialize(deserializer)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        DateTime::parse_from_rfc3339(v)
+            .map(|date| date.with_timezone(&Utc))
+            .map_err(E::custom)
+    }
+}
@@ -0,0 +1,258 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Deserialize a value from the Astarte types.
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::aggregate::AstarteObject;
+use crate::event::Value;
+use crate::types::AstarteData;
+
+use super::SerdeError;
+
+impl<'de> serde::Deserializer<'de> for AstarteData {
+    type Error = SerdeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            AstarteData::Double(v) => visitor.visit_f64(v.into()),
+            AstarteData::Integer(v) => visitor.visit_i32(v),
+            AstarteData::Boolean(v) => visitor.visit_bool(v),
+            AstarteData::LongInteger(v) => visitor.visit_i64(v),
+            AstarteData::String(v) => visitor.visit_string(v),
+            AstarteData::BinaryBlob(v) => visitor.visit_byte_buf(v),
+            AstarteData::DateTime(v) => visitor.visit_string(v.to_rfc3339()),
+            AstarteData::DoubleArray(v) => visit_array(v.into_iter().map(f64::from), visitor),
+            AstarteData::IntegerArray(v) => visit_array(v, visitor),
+            AstarteData::BooleanArray(v) => visit_array(v, visitor),
+            AstarteData::LongIntegerArray(v) => visit_array(v, visitor),
+            AstarteData::StringArray(v) => visit_array(v, visitor),
+            AstarteData::BinaryBlobArray(v) => {
+                visit_array(v.into_iter().map(AstarteData::BinaryBlob), visitor)
+            }
+            AstarteData::DateTimeArray(v) => {
+                visit_array(v.into_iter().map(|v| v.to_rfc3339()), visitor)
+            }
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            // A Vec<u8> is deserialized as a sequence
+            AstarteData::BinaryBlob(v) => visit_array(v, visitor),
+            data => data.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            AstarteData::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            data => Err(SerdeError::Custom(format!(
+                "expected a string for an enum variant, got {}",
+                data.display_type()
+            ))),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, SerdeError> for AstarteData {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Visits the elements of an array as a sequence.
+fn visit_array<'de, I, V>(array: I, visitor: V) -> Result<V::Value, SerdeError>
+where
+    I: IntoIterator,
+    I::Item: IntoDeserializer<'de, SerdeError>,
+    V: Visitor<'de>,
+{
+    let mut seq = SeqDeserializer::new(array.into_iter());
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+
+    Ok(value)
+}
+
+impl<'de> serde::Deserializer<'de> for AstarteObject {
+    type Error = SerdeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut map = MapDeserializer::new(self.into_key_values());
+        let value = visitor.visit_map(&mut map)?;
+        map.end()?;
+
+        Ok(value)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, SerdeError> for AstarteObject {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Deserializes the data of the [`Value`], an unset property is deserialized as [`None`].
+impl<'de> serde::Deserializer<'de> for Value {
+    type Error = SerdeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Individual { data, .. } | Value::Property(Some(data)) => {
+                data.deserialize_any(visitor)
+            }
+            Value::Object { data, .. } => data.deserialize_any(visitor),
+            Value::Property(None) => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Individual { data, .. } | Value::Property(Some(data)) => {
+                visitor.visit_some(data)
+            }
+            Value::Object { data, .. } => visitor.visit_some(data),
+            Value::Property(None) => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Individual { data, .. } | Value::Property(Some(data)) => {
+                data.deserialize_seq(visitor)
+            }
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Individual { data, .. } | Value::Property(Some(data)) => {
+                data.deserialize_enum(name, variants, visitor)
+            }
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
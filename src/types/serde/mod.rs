@@ -0,0 +1,454 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Convert any [`Serialize`] and [`Deserialize`](serde::Deserialize) type from and into the Astarte types.
+//!
+//! This is an alternative to the `IntoAstarteObject` and `FromEvent` derive macros for the types
+//! that already implement the serde traits.
+//!
+//! The conversions follow the same rules of the [`AstarteData`]:
+//!
+//! - `i8`, `i16`, `i32`, `u8` and `u16` are serialized as [`AstarteData::Integer`];
+//! - `i64` and `u32` are serialized as [`AstarteData::LongInteger`], the bigger integers only if
+//!   they fit in an `i64`;
+//! - `f64` is serialized as [`AstarteData::Double`] and must be finite and not subnormal, while
+//!   `f32` is not supported since the conversion looses precision;
+//! - strings and chars are serialized as [`AstarteData::String`] and bytes (for example with
+//!   `serde_bytes`) as [`AstarteData::BinaryBlob`];
+//! - sequences are serialized as the array of their elements, which must have the same type, or
+//!   integers mixed with long integers or doubles. Empty sequences are not supported, since the
+//!   type of the array is unknown;
+//! - structs and maps are serialized as an [`AstarteObject`], omitting the fields that are
+//!   [`None`].
+//!
+//! When deserializing, an [`AstarteData::Integer`] can be read as any bigger integer or as a
+//! float, and an [`AstarteData::DateTime`] is deserialized as an RFC 3339 string.
+//!
+//! A [`chrono::DateTime`] is serialized as a string, use the [`datetime`] module to serialize it
+//! as an [`AstarteData::DateTime`].
+//!
+//! ```
+//! use astarte_device_sdk::types::serde::{from_astarte_object, to_astarte_object};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Sensor {
+//!     name: String,
+//!     value: f64,
+//!     unit: Option<String>,
+//! }
+//!
+//! let sensor = Sensor {
+//!     name: "temperature".to_string(),
+//!     value: 21.5,
+//!     unit: None,
+//! };
+//!
+//! let object = to_astarte_object(&sensor).unwrap();
+//! assert_eq!(object.len(), 2);
+//!
+//! let back: Sensor = from_astarte_object(object).unwrap();
+//! assert_eq!(back, sensor);
+//! ```
+
+use std::fmt::Display;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::aggregate::AstarteObject;
+use crate::event::Value;
+use crate::types::{AstarteData, TypeError};
+
+pub use self::ser::{
+    ArraySerializer, AstarteDataSerializer, AstarteObjectSerializer, ObjectSerializer,
+};
+
+pub mod datetime;
+
+mod de;
+mod ser;
+
+/// Errors while converting between the Astarte types and the serde data model.
+#[non_exhaustive]
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum SerdeError {
+    /// Error returned by the [`Serialize`] or [`Deserialize`](serde::Deserialize) implementation.
+    #[error("{0}")]
+    Custom(String),
+    /// Couldn't convert the value into an Astarte type.
+    #[error("couldn't convert the value")]
+    Type(#[from] TypeError),
+    /// The type is not supported by Astarte.
+    #[error("unsupported type {0}")]
+    Unsupported(&'static str),
+    /// The value is null, which can only be used to unset a property.
+    #[error("the value is null")]
+    Null,
+    /// The array is empty, so the type of its elements is unknown.
+    #[error("couldn't serialize an empty array")]
+    EmptyArray,
+    /// The elements of the array have different types.
+    #[error("the array elements have different types, expected {expected} but got {got}")]
+    MixedArray {
+        /// Type of the array elements.
+        expected: &'static str,
+        /// Type of the different element.
+        got: &'static str,
+    },
+    /// The key of an object is not a string.
+    #[error("the object keys must be strings")]
+    KeyMustBeString,
+}
+
+impl serde::ser::Error for SerdeError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Self::Custom(msg.to_string())
+    }
+}
+
+impl serde::de::Error for SerdeError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Self::Custom(msg.to_string())
+    }
+}
+
+/// Serializes a value into an [`AstarteData`].
+///
+/// A [`None`] or unit value returns [`SerdeError::Null`], use the [`AstarteDataSerializer`]
+/// directly to unset a property.
+pub fn to_astarte_data<T>(value: &T) -> Result<AstarteData, SerdeError>
+where
+    T: Serialize + ?Sized,
+{
+    value
+        .serialize(AstarteDataSerializer)?
+        .ok_or(SerdeError::Null)
+}
+
+/// Serializes a struct or a map into an [`AstarteObject`].
+pub fn to_astarte_object<T>(value: &T) -> Result<AstarteObject, SerdeError>
+where
+    T: Serialize + ?Sized,
+{
+    value.serialize(AstarteObjectSerializer)
+}
+
+/// Deserializes a value from an [`AstarteData`].
+pub fn from_astarte_data<T>(data: AstarteData) -> Result<T, SerdeError>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(data)
+}
+
+/// Deserializes a struct or a map from an [`AstarteObject`].
+pub fn from_astarte_object<T>(object: AstarteObject) -> Result<T, SerdeError>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(object)
+}
+
+/// Deserializes a value from the [`Value`] of an event.
+///
+/// An unset property is deserialized as [`None`].
+pub fn from_value<T>(value: Value) -> Result<T, SerdeError>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{TimeZone, Utc};
+    use pretty_assertions::assert_eq;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Sensor {
+        name: String,
+        enabled: bool,
+        sample_count: i32,
+        total: i64,
+        value: f64,
+        #[serde(with = "serde_bytes")]
+        raw: Vec<u8>,
+        readings: Vec<f64>,
+        unit: Option<String>,
+    }
+
+    fn sensor() -> Sensor {
+        Sensor {
+            name: "foo".to_string(),
+            enabled: true,
+            sample_count: 3,
+            total: 42,
+            value: 1.5,
+            raw: vec![1, 2, 3],
+            readings: vec![1.0, 2.5],
+            unit: None,
+        }
+    }
+
+    #[test]
+    fn should_serialize_struct_into_object() {
+        let object = to_astarte_object(&sensor()).unwrap();
+
+        let expected = AstarteObject::from_iter([
+            ("name".to_string(), AstarteData::from("foo")),
+            ("enabled".to_string(), AstarteData::Boolean(true)),
+            ("sampleCount".to_string(), AstarteData::Integer(3)),
+            ("total".to_string(), AstarteData::LongInteger(42)),
+            ("value".to_string(), AstarteData::try_from(1.5).unwrap()),
+            ("raw".to_string(), AstarteData::BinaryBlob(vec![1, 2, 3])),
+            (
+                "readings".to_string(),
+                AstarteData::try_from(vec![1.0, 2.5]).unwrap(),
+            ),
+        ]);
+
+        assert_eq!(object, expected);
+    }
+
+    #[test]
+    fn should_roundtrip_object() {
+        let mut exp = sensor();
+        exp.unit = Some("lux".to_string());
+
+        let object = to_astarte_object(&exp).unwrap();
+        let res: Sensor = from_astarte_object(object).unwrap();
+
+        assert_eq!(res, exp);
+
+        // The missing optional fields are none
+        let object = to_astarte_object(&sensor()).unwrap();
+        let res: Sensor = from_astarte_object(object).unwrap();
+
+        assert_eq!(res, sensor());
+    }
+
+    #[test]
+    fn should_serialize_map_into_object() {
+        let map = HashMap::from([("a", 1), ("b", 2)]);
+
+        let object = to_astarte_object(&map).unwrap();
+
+        assert_eq!(object.get("a"), Some(&AstarteData::Integer(1)));
+        assert_eq!(object.get("b"), Some(&AstarteData::Integer(2)));
+
+        let res = to_astarte_object(&HashMap::from([(1, 1)]));
+        assert!(matches!(res, Err(SerdeError::KeyMustBeString)));
+    }
+
+    #[test]
+    fn should_serialize_data() {
+        let cases = [
+            (to_astarte_data(&42u8), AstarteData::Integer(42)),
+            (to_astarte_data(&-42i32), AstarteData::Integer(-42)),
+            (to_astarte_data(&42u32), AstarteData::LongInteger(42)),
+            (to_astarte_data(&42u64), AstarteData::LongInteger(42)),
+            (to_astarte_data(&'c'), AstarteData::from("c")),
+            (
+                to_astarte_data(&vec![1i32, 2]),
+                AstarteData::IntegerArray(vec![1, 2]),
+            ),
+            (
+                to_astarte_data(&(1i32, 2i64)),
+                AstarteData::LongIntegerArray(vec![1, 2]),
+            ),
+            (
+                to_astarte_data(&[1.5f64, 2.0]),
+                AstarteData::try_from(vec![1.5, 2.0]).unwrap(),
+            ),
+            (
+                to_astarte_data(&vec!["a", "b"]),
+                AstarteData::StringArray(vec!["a".to_string(), "b".to_string()]),
+            ),
+        ];
+
+        for (res, exp) in cases {
+            assert_eq!(res.unwrap(), exp);
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_data() {
+        assert!(matches!(
+            to_astarte_data(&f64::NAN),
+            Err(SerdeError::Type(TypeError::Float))
+        ));
+        assert!(matches!(
+            to_astarte_data(&1.0f32),
+            Err(SerdeError::Unsupported("f32"))
+        ));
+        assert!(matches!(
+            to_astarte_data(&u64::MAX),
+            Err(SerdeError::Type(TypeError::Conversion { .. }))
+        ));
+        assert!(matches!(
+            to_astarte_data(&Vec::<i32>::new()),
+            Err(SerdeError::EmptyArray)
+        ));
+        assert!(matches!(
+            to_astarte_data(&(1i32, "a")),
+            Err(SerdeError::MixedArray { .. })
+        ));
+        assert!(matches!(
+            to_astarte_data(&None::<i32>),
+            Err(SerdeError::Null)
+        ));
+        assert!(matches!(
+            to_astarte_data(&sensor()),
+            Err(SerdeError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn should_unset_with_none() {
+        let res = None::<i32>.serialize(AstarteDataSerializer).unwrap();
+
+        assert_eq!(res, None);
+    }
+
+    #[test]
+    fn should_deserialize_data() {
+        let long: i64 = from_astarte_data(AstarteData::Integer(42)).unwrap();
+        assert_eq!(long, 42);
+
+        let double: f64 = from_astarte_data(AstarteData::Integer(42)).unwrap();
+        assert_eq!(double, 42.0);
+
+        let longs: Vec<i64> = from_astarte_data(AstarteData::IntegerArray(vec![1, 2])).unwrap();
+        assert_eq!(longs, [1, 2]);
+
+        let bytes: Vec<u8> = from_astarte_data(AstarteData::BinaryBlob(vec![1, 2])).unwrap();
+        assert_eq!(bytes, [1, 2]);
+
+        let blobs: Vec<Vec<u8>> =
+            from_astarte_data(AstarteData::BinaryBlobArray(vec![vec![1], vec![2]])).unwrap();
+        assert_eq!(blobs, [[1], [2]]);
+
+        let date = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let res: chrono::DateTime<Utc> = from_astarte_data(AstarteData::DateTime(date)).unwrap();
+        assert_eq!(res, date);
+
+        let opt: Option<String> = from_astarte_data(AstarteData::from("foo")).unwrap();
+        assert_eq!(opt.as_deref(), Some("foo"));
+
+        let res = from_astarte_data::<i32>(AstarteData::LongInteger(i64::MAX));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn should_deserialize_unit_enum() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Level {
+            Low,
+            High,
+        }
+
+        let data = to_astarte_data(&Level::High).unwrap();
+        assert_eq!(data, AstarteData::from("high"));
+
+        let res: Level = from_astarte_data(data).unwrap();
+        assert_eq!(res, Level::High);
+    }
+
+    #[test]
+    fn should_deserialize_value() {
+        let timestamp = Utc::now();
+
+        let value = Value::Object {
+            data: to_astarte_object(&sensor()).unwrap(),
+            timestamp,
+        };
+        let res: Sensor = from_value(value).unwrap();
+        assert_eq!(res, sensor());
+
+        let value = Value::Individual {
+            data: AstarteData::Integer(42),
+            timestamp,
+        };
+        let res: i64 = from_value(value).unwrap();
+        assert_eq!(res, 42);
+
+        let res: Option<bool> = from_value(Value::Property(None)).unwrap();
+        assert_eq!(res, None);
+
+        let res: Option<bool> = from_value(Value::Property(Some(true.into()))).unwrap();
+        assert_eq!(res, Some(true));
+
+        let res = from_value::<bool>(Value::Property(None));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn should_serialize_datetime_for_datetime_mapping() {
+        use std::str::FromStr;
+
+        use astarte_interfaces::{DatastreamIndividual, MappingPath};
+
+        use crate::interfaces::MappingRef;
+        use crate::validate::ValidatedIndividual;
+
+        const DEVICE_DATASTREAM: &str = include_str!(
+            "../../../e2e-test/interfaces/org.astarte-platform.rust.e2etest.DeviceDatastream.json"
+        );
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Reading {
+            #[serde(with = "datetime")]
+            at: chrono::DateTime<Utc>,
+        }
+
+        let at = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let reading = Reading { at };
+
+        let object = to_astarte_object(&reading).unwrap();
+        let data = object.get("at").cloned().unwrap();
+        assert_eq!(data, AstarteData::DateTime(at));
+
+        let interface = DatastreamIndividual::from_str(DEVICE_DATASTREAM).unwrap();
+        let path = MappingPath::try_from("/datetime_endpoint").unwrap();
+        let mapping = MappingRef::new(&interface, &path).unwrap();
+        ValidatedIndividual::validate(mapping, data, Some(Utc::now())).unwrap();
+
+        let res: Reading = from_astarte_object(object).unwrap();
+        assert_eq!(res, reading);
+
+        // Other formats get the RFC 3339 string
+        let json = serde_json::to_value(&reading).unwrap();
+        assert_eq!(json, serde_json::json!({ "at": at.to_rfc3339() }));
+        let res: Reading = serde_json::from_value(json).unwrap();
+        assert_eq!(res, reading);
+    }
+}
@@ -0,0 +1,634 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serialize a value into the Astarte types.
+
+use chrono::{DateTime, Utc};
+use serde::ser::{
+    Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple, SerializeTupleStruct,
+};
+use serde::Serialize;
+
+use crate::aggregate::AstarteObject;
+use crate::types::de::ArrayType;
+use crate::types::{AstarteData, Double, TypeError};
+
+use super::datetime::DATETIME_NEWTYPE;
+use super::SerdeError;
+
+// Returns an unsupported error for the serializer methods that take a value.
+macro_rules! unsupported {
+    ($($method:ident($ty:ty) => $name:literal,)*) => {
+        $(
+            fn $method(self, _v: $ty) -> Result<Self::Ok, Self::Error> {
+                Err(SerdeError::Unsupported($name))
+            }
+        )*
+    };
+}
+
+/// Serializer of a value into an [`AstarteData`].
+///
+/// The [`None`] and unit values are serialized as [`None`], which unsets a property.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AstarteDataSerializer;
+
+impl serde::Serializer for AstarteDataSerializer {
+    type Ok = Option<AstarteData>;
+    type Error = SerdeError;
+
+    type SerializeSeq = ArraySerializer;
+    type SerializeTuple = ArraySerializer;
+    type SerializeTupleStruct = ArraySerializer;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(AstarteData::Boolean(v)))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i32(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i32(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(AstarteData::Integer(v)))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(AstarteData::LongInteger(v)))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        long_integer(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i32(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i32(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        long_integer(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        long_integer(v)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
+        // Same as the conversion into Double, it's better handled by the final user
+        Err(SerdeError::Unsupported("f32"))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        Double::try_from(v)
+            .map(|v| Some(AstarteData::Double(v)))
+            .map_err(SerdeError::from)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(AstarteData::String(v.to_string())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(AstarteData::String(v.to_string())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(AstarteData::BinaryBlob(v.to_vec())))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        if name != DATETIME_NEWTYPE {
+            return value.serialize(self);
+        }
+
+        let Some(AstarteData::String(date)) = value.serialize(self)? else {
+            return Err(SerdeError::Custom(
+                "expected an RFC 3339 string for the date time".to_string(),
+            ));
+        };
+
+        DateTime::parse_from_rfc3339(&date)
+            .map(|date| Some(AstarteData::DateTime(date.with_timezone(&Utc))))
+            .map_err(|err| SerdeError::Custom(format!("invalid date time: {err}")))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(SerdeError::Unsupported("newtype variant"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(ArraySerializer::with_capacity(len.unwrap_or_default()))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(ArraySerializer::with_capacity(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(ArraySerializer::with_capacity(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(SerdeError::Unsupported("tuple variant"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(SerdeError::Unsupported("map, serialize it as an object"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(SerdeError::Unsupported("struct, serialize it as an object"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(SerdeError::Unsupported("struct variant"))
+    }
+}
+
+/// Converts an integer into a [`AstarteData::LongInteger`] if it fits in an `i64`.
+fn long_integer<T>(v: T) -> Result<Option<AstarteData>, SerdeError>
+where
+    T: TryInto<i64> + std::fmt::Display + Copy,
+{
+    v.try_into()
+        .map(|v| Some(AstarteData::LongInteger(v)))
+        .map_err(|_| TypeError::conversion(format!("from {v} into i64")).into())
+}
+
+/// Serializes the elements of a sequence into an Astarte array.
+#[derive(Debug)]
+pub struct ArraySerializer {
+    items: Vec<AstarteData>,
+}
+
+impl ArraySerializer {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<(), SerdeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        let item = value
+            .serialize(AstarteDataSerializer)?
+            .ok_or(SerdeError::Unsupported("null array element"))?;
+
+        self.items.push(item);
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<AstarteData>, SerdeError> {
+        // The integers are promoted to the type of the other numbers in the array
+        let item_type = self
+            .items
+            .iter()
+            .find(|item| !matches!(item, AstarteData::Integer(_)))
+            .or_else(|| self.items.first())
+            .ok_or(SerdeError::EmptyArray)?;
+
+        let item_type = match item_type {
+            AstarteData::Double(_) => ArrayType::Double,
+            AstarteData::Integer(_) => ArrayType::Integer,
+            AstarteData::Boolean(_) => ArrayType::Boolean,
+            AstarteData::LongInteger(_) => ArrayType::LongInteger,
+            AstarteData::String(_) => ArrayType::String,
+            AstarteData::BinaryBlob(_) => ArrayType::BinaryBlob,
+            AstarteData::DateTime(_) => ArrayType::DateTime,
+            AstarteData::DoubleArray(_)
+            | AstarteData::IntegerArray(_)
+            | AstarteData::BooleanArray(_)
+            | AstarteData::LongIntegerArray(_)
+            | AstarteData::StringArray(_)
+            | AstarteData::BinaryBlobArray(_)
+            | AstarteData::DateTimeArray(_) => {
+                return Err(SerdeError::Unsupported("nested array"));
+            }
+        };
+
+        let items = self.items.into_iter();
+
+        let array = match item_type {
+            ArrayType::Integer => {
+                AstarteData::IntegerArray(collect(items, "integer", |item| match item {
+                    AstarteData::Integer(v) => Some(v),
+                    _ => None,
+                })?)
+            }
+            ArrayType::LongInteger => {
+                AstarteData::LongIntegerArray(collect(items, "longinteger", |item| match item {
+                    AstarteData::LongInteger(v) => Some(v),
+                    AstarteData::Integer(v) => Some(v.into()),
+                    _ => None,
+                })?)
+            }
+            ArrayType::Double => {
+                AstarteData::DoubleArray(collect(items, "double", |item| match item {
+                    AstarteData::Double(v) => Some(v),
+                    AstarteData::Integer(v) => Double::try_from(f64::from(v)).ok(),
+                    _ => None,
+                })?)
+            }
+            ArrayType::Boolean => {
+                AstarteData::BooleanArray(collect(items, "boolean", |item| match item {
+                    AstarteData::Boolean(v) => Some(v),
+                    _ => None,
+                })?)
+            }
+            ArrayType::String => {
+                AstarteData::StringArray(collect(items, "string", |item| match item {
+                    AstarteData::String(v) => Some(v),
+                    _ => None,
+                })?)
+            }
+            ArrayType::BinaryBlob => {
+                AstarteData::BinaryBlobArray(collect(items, "binaryblob", |item| match item {
+                    AstarteData::BinaryBlob(v) => Some(v),
+                    _ => None,
+                })?)
+            }
+            ArrayType::DateTime => {
+                AstarteData::DateTimeArray(collect(items, "datetime", |item| match item {
+                    AstarteData::DateTime(v) => Some(v),
+                    _ => None,
+                })?)
+            }
+        };
+
+        Ok(Some(array))
+    }
+}
+
+/// Collects the elements of an array, converted into the given type.
+fn collect<T, F>(
+    items: impl Iterator<Item = AstarteData>,
+    expected: &'static str,
+    mut f: F,
+) -> Result<Vec<T>, SerdeError>
+where
+    F: FnMut(AstarteData) -> Option<T>,
+{
+    items
+        .map(|item| {
+            let got = item.display_type();
+
+            f(item).ok_or(SerdeError::MixedArray { expected, got })
+        })
+        .collect()
+}
+
+impl SerializeSeq for ArraySerializer {
+    type Ok = Option<AstarteData>;
+    type Error = SerdeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl SerializeTuple for ArraySerializer {
+    type Ok = Option<AstarteData>;
+    type Error = SerdeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl SerializeTupleStruct for ArraySerializer {
+    type Ok = Option<AstarteData>;
+    type Error = SerdeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+/// Serializer of a struct or a map into an [`AstarteObject`].
+///
+/// The fields with a [`None`] value are omitted from the object.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AstarteObjectSerializer;
+
+impl serde::Serializer for AstarteObjectSerializer {
+    type Ok = AstarteObject;
+    type Error = SerdeError;
+
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = ObjectSerializer;
+    type SerializeStruct = ObjectSerializer;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    unsupported! {
+        serialize_bool(bool) => "bool",
+        serialize_i8(i8) => "i8",
+        serialize_i16(i16) => "i16",
+        serialize_i32(i32) => "i32",
+        serialize_i64(i64) => "i64",
+        serialize_u8(u8) => "u8",
+        serialize_u16(u16) => "u16",
+        serialize_u32(u32) => "u32",
+        serialize_u64(u64) => "u64",
+        serialize_f32(f32) => "f32",
+        serialize_f64(f64) => "f64",
+        serialize_char(char) => "char",
+        serialize_str(&str) => "str",
+        serialize_bytes(&[u8]) => "bytes",
+        serialize_unit_struct(&'static str) => "unit struct",
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(SerdeError::Null)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(SerdeError::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(SerdeError::Unsupported("unit variant"))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(SerdeError::Unsupported("newtype variant"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(SerdeError::Unsupported("sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(SerdeError::Unsupported("tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(SerdeError::Unsupported("tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(SerdeError::Unsupported("tuple variant"))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(ObjectSerializer::with_capacity(len.unwrap_or_default()))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(ObjectSerializer::with_capacity(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(SerdeError::Unsupported("struct variant"))
+    }
+}
+
+/// Serializes the fields of a struct or the entries of a map into an [`AstarteObject`].
+#[derive(Debug)]
+pub struct ObjectSerializer {
+    object: AstarteObject,
+    key: Option<String>,
+}
+
+impl ObjectSerializer {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            object: AstarteObject::with_capacity(capacity),
+            key: None,
+        }
+    }
+
+    fn insert<T>(&mut self, key: String, value: &T) -> Result<(), SerdeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        if let Some(value) = value.serialize(AstarteDataSerializer)? {
+            self.object.insert(key, value);
+        }
+
+        Ok(())
+    }
+}
+
+impl SerializeMap for ObjectSerializer {
+    type Ok = AstarteObject;
+    type Error = SerdeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let Some(AstarteData::String(key)) = key.serialize(AstarteDataSerializer)? else {
+            return Err(SerdeError::KeyMustBeString);
+        };
+
+        self.key = Some(key);
+
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| SerdeError::Custom("value serialized before its key".to_string()))?;
+
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.object)
+    }
+}
+
+impl SerializeStruct for ObjectSerializer {
+    type Ok = AstarteObject;
+    type Error = SerdeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.object)
+    }
+}
@@ -0,0 +1,360 @@
+// This file is part of Astarte.
+//
+// Copyright 2025 SECO Mind Srl
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//    http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks the API generated by the `astarte_interface!` macro.
+
+use astarte_device_sdk::{
+    aggregate::AstarteObject, chrono::Utc, event::FromEventError, AstarteData, DeviceEvent, Error,
+    FromEvent, Value,
+};
+use astarte_device_sdk_derive::astarte_interface;
+use astarte_device_sdk_mock::fake::{Published, PublishedValue};
+use astarte_device_sdk_mock::FakeDeviceClient;
+use pretty_assertions::assert_eq;
+
+astarte_interface!("e2e-test/interfaces/org.astarte-platform.rust.e2etest.DeviceDatastream.json");
+astarte_interface!("e2e-test/interfaces/org.astarte-platform.rust.e2etest.DeviceAggregate.json");
+astarte_interface!(
+    "examples/retention/interfaces/org.astarte-platform.rust.examples.individual-datastream.VolatileDeviceDatastream.json"
+);
+astarte_interface!(
+    "examples/retention/interfaces/org.astarte-platform.rust.examples.individual-datastream.VolatileDeviceObject.json"
+);
+astarte_interface!(
+    "examples/individual_datastream/interfaces/org.astarte-platform.rust.examples.individual-datastream.ServerDatastream.json"
+);
+astarte_interface!(
+    server_object = "examples/object_datastream/interfaces/org.astarte-platform.rust.examples.object-datastream.ServerDatastream.json"
+);
+astarte_interface!(
+    "examples/individual_properties/interfaces/org.astarte-platform.rust.examples.individual-properties.DeviceProperties.json"
+);
+astarte_interface!(
+    "examples/individual_properties/interfaces/org.astarte-platform.rust.examples.individual-properties.ServerProperties.json"
+);
+
+/// Fake client with all the interfaces used by the tests.
+fn fake_client() -> FakeDeviceClient {
+    FakeDeviceClient::with_interfaces([
+        device_datastream::interface(),
+        device_aggregate::interface(),
+        volatile_device_datastream::interface(),
+        volatile_device_object::interface(),
+        device_properties::interface(),
+        server_properties::interface(),
+    ])
+    .unwrap()
+}
+
+fn published(interface: &str, path: &str, value: PublishedValue) -> Published {
+    Published {
+        interface: interface.to_string(),
+        path: path.to_string(),
+        value,
+    }
+}
+
+#[test]
+fn should_generate_the_interface_constants() {
+    assert_eq!(
+        device_datastream::INTERFACE_NAME,
+        "org.astarte-platform.rust.e2etest.DeviceDatastream"
+    );
+    assert_eq!(device_datastream::VERSION_MAJOR, 0);
+    assert_eq!(device_datastream::VERSION_MINOR, 1);
+
+    let interface = server_properties::interface();
+
+    assert_eq!(
+        interface.interface_name(),
+        server_properties::INTERFACE_NAME
+    );
+}
+
+#[tokio::test]
+async fn should_send_individual_datastreams() {
+    let client = fake_client();
+    let timestamp = Utc::now();
+
+    let mut sender = device_datastream::Sender::new(client.clone());
+    sender.send_double_endpoint(4.2, timestamp).await.unwrap();
+    sender
+        .send_string_endpoint("hello".to_string(), timestamp)
+        .await
+        .unwrap();
+
+    let err = sender.send_double_endpoint(f64::NAN, timestamp).await;
+    assert!(matches!(err, Err(Error::Types(_))), "{err:?}");
+
+    let mut volatile = volatile_device_datastream::Sender::new(client.clone());
+    volatile.send_endpoint2(true).await.unwrap();
+
+    assert_eq!(
+        client.published(),
+        [
+            published(
+                device_datastream::INTERFACE_NAME,
+                "/double_endpoint",
+                PublishedValue::Individual {
+                    data: AstarteData::try_from(4.2).unwrap(),
+                    timestamp: Some(timestamp),
+                },
+            ),
+            published(
+                device_datastream::INTERFACE_NAME,
+                "/string_endpoint",
+                PublishedValue::Individual {
+                    data: AstarteData::String("hello".to_string()),
+                    timestamp: Some(timestamp),
+                },
+            ),
+            published(
+                volatile_device_datastream::INTERFACE_NAME,
+                "/endpoint2",
+                PublishedValue::Individual {
+                    data: AstarteData::Boolean(true),
+                    timestamp: None,
+                },
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn should_send_objects() {
+    let client = fake_client();
+
+    let mut sender = volatile_device_object::Sender::new(client.clone());
+    sender
+        .send(volatile_device_object::Object {
+            longinteger: 42,
+            boolean: false,
+        })
+        .await
+        .unwrap();
+
+    let data = device_aggregate::Object {
+        double_endpoint: 1.5,
+        integer_endpoint: 1,
+        boolean_endpoint: true,
+        longinteger_endpoint: 2,
+        string_endpoint: "s".to_string(),
+        binaryblob_endpoint: vec![1],
+        datetime_endpoint: Utc::now(),
+        doublearray_endpoint: vec![1.0],
+        integerarray_endpoint: vec![1],
+        booleanarray_endpoint: vec![true],
+        longintegerarray_endpoint: vec![2],
+        stringarray_endpoint: vec!["s".to_string()],
+        binaryblobarray_endpoint: vec![vec![1]],
+        datetimearray_endpoint: vec![Utc::now()],
+    };
+    let object = AstarteObject::try_from(data.clone()).unwrap();
+    assert_eq!(object.len(), 14);
+
+    let timestamp = Utc::now();
+    let mut aggregate = device_aggregate::Sender::new(client.clone());
+    aggregate.send("sensor_1", data, timestamp).await.unwrap();
+
+    let exp_volatile = AstarteObject::from_iter([
+        ("longinteger".to_string(), AstarteData::LongInteger(42)),
+        ("boolean".to_string(), AstarteData::Boolean(false)),
+    ]);
+
+    assert_eq!(
+        client.published(),
+        [
+            published(
+                volatile_device_object::INTERFACE_NAME,
+                "/endpoint",
+                PublishedValue::Object {
+                    data: exp_volatile,
+                    timestamp: None,
+                },
+            ),
+            published(
+                device_aggregate::INTERFACE_NAME,
+                "/sensor_1",
+                PublishedValue::Object {
+                    data: object,
+                    timestamp: Some(timestamp),
+                },
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn should_set_and_get_properties() {
+    let client = fake_client();
+
+    let mut sender = device_properties::Sender::new(client.clone());
+    sender
+        .set_name("sensor_1", "kitchen".to_string())
+        .await
+        .unwrap();
+    sender.unset_name("sensor_1").await.unwrap();
+
+    assert_eq!(
+        client.published(),
+        [
+            published(
+                device_properties::INTERFACE_NAME,
+                "/sensor_1/name",
+                PublishedValue::Property(AstarteData::String("kitchen".to_string())),
+            ),
+            published(
+                device_properties::INTERFACE_NAME,
+                "/sensor_1/name",
+                PublishedValue::Unset,
+            ),
+        ]
+    );
+
+    client.inject(DeviceEvent {
+        interface: server_properties::INTERFACE_NAME.to_string(),
+        path: "/sensor_1/samplingPeriod".to_string(),
+        data: Value::Property(Some(AstarteData::Integer(5))),
+    });
+
+    let props = server_properties::Properties::new(client.clone());
+    assert_eq!(props.sampling_period("sensor_1").await.unwrap(), Some(5));
+    assert_eq!(props.sampling_period("sensor_2").await.unwrap(), None);
+    assert_eq!(props.enable("sensor_1").await.unwrap(), None);
+
+    let sender = device_properties::Sender::new(client);
+    assert_eq!(sender.name("sensor_1").await.unwrap(), None);
+}
+
+#[test]
+fn should_parse_individual_events() {
+    let event = DeviceEvent {
+        interface: server_datastream::INTERFACE_NAME.to_string(),
+        path: "/led_1/intensity".to_string(),
+        data: Value::Individual {
+            data: AstarteData::try_from(0.5).unwrap(),
+            timestamp: Utc::now(),
+        },
+    };
+
+    let event = server_datastream::Event::from_event(event).unwrap();
+
+    assert_eq!(
+        event,
+        server_datastream::Event::Intensity {
+            led_id: "led_1".to_string(),
+            value: 0.5,
+        }
+    );
+    assert_eq!(
+        server_datastream::Event::interface_name(),
+        Some(server_datastream::INTERFACE_NAME)
+    );
+
+    let event = DeviceEvent {
+        interface: server_datastream::INTERFACE_NAME.to_string(),
+        path: "/led_1/color".to_string(),
+        data: Value::Individual {
+            data: AstarteData::Boolean(true),
+            timestamp: Utc::now(),
+        },
+    };
+
+    let err = server_datastream::Event::from_event(event).unwrap_err();
+    assert!(matches!(err, FromEventError::Path { .. }), "{err:?}");
+
+    let event = DeviceEvent {
+        interface: "com.example.Other".to_string(),
+        path: "/led_1/enable".to_string(),
+        data: Value::Property(None),
+    };
+
+    let err = server_datastream::Event::from_event(event).unwrap_err();
+    assert!(matches!(err, FromEventError::Interface(_)), "{err:?}");
+}
+
+#[test]
+fn should_parse_object_events() {
+    let data = AstarteObject::from_iter([
+        ("endpoint1".to_string(), AstarteData::try_from(1.5).unwrap()),
+        (
+            "endpoint2".to_string(),
+            AstarteData::String("value".to_string()),
+        ),
+        (
+            "endpoint3".to_string(),
+            AstarteData::BooleanArray(vec![true, false]),
+        ),
+    ]);
+
+    let event = DeviceEvent {
+        interface: server_object::INTERFACE_NAME.to_string(),
+        path: "/sensor_1".to_string(),
+        data: Value::Object {
+            data,
+            timestamp: Utc::now(),
+        },
+    };
+
+    let event = server_object::Event::from_event(event).unwrap();
+
+    assert_eq!(
+        event,
+        server_object::Event {
+            sensor_id: "sensor_1".to_string(),
+            endpoint1: 1.5,
+            endpoint2: "value".to_string(),
+            endpoint3: vec![true, false],
+        }
+    );
+}
+
+#[test]
+fn should_parse_property_events() {
+    let event = DeviceEvent {
+        interface: server_properties::INTERFACE_NAME.to_string(),
+        path: "/sensor_1/samplingPeriod".to_string(),
+        data: Value::Property(Some(AstarteData::Integer(10))),
+    };
+
+    let event = server_properties::Event::from_event(event).unwrap();
+
+    assert_eq!(
+        event,
+        server_properties::Event::SamplingPeriod {
+            sensor_id: "sensor_1".to_string(),
+            value: Some(10),
+        }
+    );
+
+    let event = DeviceEvent {
+        interface: server_properties::INTERFACE_NAME.to_string(),
+        path: "/sensor_1/enable".to_string(),
+        data: Value::Property(None),
+    };
+
+    let event = server_properties::Event::from_event(event).unwrap();
+
+    assert_eq!(
+        event,
+        server_properties::Event::Enable {
+            sensor_id: "sensor_1".to_string(),
+            value: None,
+        }
+    );
+}